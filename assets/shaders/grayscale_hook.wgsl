@@ -0,0 +1,9 @@
+#define_import_path bevy_gaussian_splatting::color_hook
+
+
+// rec. 709 luminance, splat colors are linear at this point
+fn color_hook(color: vec4<f32>, frag_coord: vec4<f32>) -> vec4<f32> {
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+
+    return vec4<f32>(vec3<f32>(luminance), color.a);
+}
@@ -0,0 +1,125 @@
+use bevy::{
+    prelude::*,
+    render::{
+        ExtractSchedule,
+        RenderApp,
+    },
+    window::ExitCondition,
+};
+use criterion::{
+    BenchmarkId,
+    criterion_group,
+    criterion_main,
+    Criterion,
+    Throughput,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    random_gaussians,
+};
+
+
+const CLOUD_COUNTS: [usize; 3] = [
+    10,
+    100,
+    1000,
+];
+
+// a windowless app whose clouds share one prepared asset, requires a gpu adapter
+fn extract_app(count: usize) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        }),
+    );
+    app.add_plugins(GaussianSplattingPlugin);
+
+    let cloud = app.world_mut()
+        .resource_mut::<Assets<GaussianCloud>>()
+        .add(random_gaussians(1000));
+
+    for i in 0..count {
+        app.world_mut().spawn(GaussianSplattingBundle {
+            cloud: cloud.clone(),
+            settings: GaussianCloudSettings {
+                transform: Transform::from_xyz(i as f32, 0.0, 0.0)
+                    .with_rotation(Quat::from_rotation_y(i as f32))
+                    .with_scale(Vec3::splat(2.0)),
+                ..default()
+            },
+            ..default()
+        });
+    }
+
+    app.finish();
+    app.cleanup();
+
+    // prepares the cloud and sorted entries render assets
+    for _ in 0..4 {
+        app.update();
+    }
+
+    app
+}
+
+// runs the render app's extract schedule, applies its commands, and clears the render world like the end of a frame
+fn run_extract(app: &mut App, changed: bool) {
+    let main_world = app.world_mut();
+    main_world.increment_change_tick();
+
+    if changed {
+        let mut settings = main_world.query::<&mut GaussianCloudSettings>();
+        for mut settings in settings.iter_mut(main_world) {
+            settings.set_changed();
+        }
+    }
+
+    let mut main_world = std::mem::take(app.world_mut());
+
+    let render_app = app.sub_app_mut(RenderApp);
+    render_app.extract(&mut main_world);
+
+    let render_world = render_app.world_mut();
+    render_world.resource_scope(|render_world, mut schedules: Mut<Schedules>| {
+        schedules.get_mut(ExtractSchedule)
+            .unwrap()
+            .apply_deferred(render_world);
+    });
+    render_world.clear_entities();
+
+    *app.world_mut() = main_world;
+}
+
+fn gaussian_cloud_extract_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract gaussian clouds");
+    for count in CLOUD_COUNTS.iter() {
+        group.throughput(Throughput::Elements(*count as u64));
+
+        let mut app = extract_app(*count);
+
+        group.bench_function(
+            BenchmarkId::new("changed", count),
+            |b| b.iter(|| run_extract(&mut app, true)),
+        );
+
+        group.bench_function(
+            BenchmarkId::new("static", count),
+            |b| b.iter(|| run_extract(&mut app, false)),
+        );
+    }
+}
+
+criterion_group!{
+    name = extract_benches;
+    config = Criterion::default().sample_size(10);
+    targets = gaussian_cloud_extract_benchmark
+}
+criterion_main!(extract_benches);
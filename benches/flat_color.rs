@@ -0,0 +1,139 @@
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            Extent3d,
+            TextureDimension,
+            TextureFormat,
+            TextureUsages,
+        },
+        renderer::RenderDevice,
+        RenderApp,
+    },
+    window::ExitCondition,
+};
+use criterion::{
+    BenchmarkId,
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    material::spherical_harmonics::SH_COEFF_COUNT,
+    random_gaussians,
+};
+
+
+const GAUSSIAN_COUNT: usize = 200_000;
+
+const RESOLUTION: (u32, u32) = (1920, 1080);
+
+// the same random cloud with and without its higher bands, the degree 0 copy takes the flat color pipeline
+fn bench_cloud(flat: bool) -> GaussianCloud {
+    let mut cloud = random_gaussians(GAUSSIAN_COUNT);
+
+    if flat {
+        for index in 0..cloud.len() {
+            let spherical_harmonic = cloud.spherical_harmonic_mut(index);
+            for coefficient in 3..SH_COEFF_COUNT {
+                spherical_harmonic.set(coefficient, 0.0);
+            }
+        }
+
+        assert_eq!(cloud.sh_degree(), 0);
+    }
+
+    cloud
+}
+
+// a windowless app drawing one screen filling cloud into an offscreen target, requires a gpu adapter
+fn render_app(flat: bool) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        }),
+    );
+    app.add_plugins(GaussianSplattingPlugin);
+
+    let mut target = Image::new_fill(
+        Extent3d {
+            width: RESOLUTION.0,
+            height: RESOLUTION.1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    target.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT;
+    let target = app.world_mut().resource_mut::<Assets<Image>>().add(target);
+
+    let cloud = app.world_mut().resource_mut::<Assets<GaussianCloud>>().add(bench_cloud(flat));
+
+    app.world_mut().spawn(GaussianSplattingBundle {
+        cloud,
+        ..default()
+    });
+
+    app.world_mut().spawn(Camera3dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(target),
+            ..default()
+        },
+        tonemapping: Tonemapping::None,
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 30.0)).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    app.finish();
+    app.cleanup();
+
+    // uploads the cloud, compiles the pipelines and settles the first sort
+    for _ in 0..120 {
+        app.update();
+    }
+
+    app
+}
+
+// one frame, waiting for the gpu so the time includes the draw
+fn render_frame(app: &mut App) {
+    app.update();
+
+    app.sub_app(RenderApp)
+        .world()
+        .resource::<RenderDevice>()
+        .poll(wgpu::Maintain::Wait);
+}
+
+fn gaussian_cloud_flat_color_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flat color");
+
+    for (name, flat) in [("sh", false), ("flat", true)] {
+        let mut app = render_app(flat);
+
+        group.bench_function(
+            BenchmarkId::new(name, GAUSSIAN_COUNT),
+            |b| b.iter(|| render_frame(&mut app)),
+        );
+    }
+}
+
+criterion_group!{
+    name = flat_color_benches;
+    config = Criterion::default().sample_size(10);
+    targets = gaussian_cloud_flat_color_benchmark
+}
+criterion_main!(flat_color_benches);
@@ -0,0 +1,147 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            BufferInitDescriptor,
+            BufferUsages,
+            CommandEncoderDescriptor,
+            PipelineCache,
+        },
+        renderer::{
+            RenderDevice,
+            RenderQueue,
+        },
+        RenderApp,
+    },
+    window::ExitCondition,
+};
+use criterion::{
+    BenchmarkId,
+    criterion_group,
+    criterion_main,
+    Criterion,
+    Throughput,
+};
+use rand::Rng;
+
+use bevy_gaussian_splatting::{
+    GaussianSplattingPlugin,
+    sort::{
+        SortEntry,
+        SortKeyPrecision,
+        radix::{
+            GpuRadixSorter,
+            RadixSortPipeline,
+        },
+    },
+};
+
+
+const ENTRY_COUNTS: [usize; 3] = [
+    100_000,
+    1_000_000,
+    4_000_000,
+];
+
+// a windowless app with compiled radix pipelines, requires a gpu adapter
+fn radix_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        }),
+    );
+    app.add_plugins(GaussianSplattingPlugin);
+
+    app.finish();
+    app.cleanup();
+
+    app
+}
+
+// sorts a fresh copy of the same random entries each iteration and waits for the gpu, so both precisions sort
+// identical keys and the time covers the whole sort
+fn gaussian_cloud_radix_key_benchmark(c: &mut Criterion) {
+    let mut app = radix_app();
+    let mut rng = rand::thread_rng();
+
+    let mut group = c.benchmark_group("radix sort keys");
+    for count in ENTRY_COUNTS.iter() {
+        group.throughput(Throughput::Elements(*count as u64));
+
+        let entries = (0..*count)
+            .map(|index| SortEntry {
+                key: rng.gen(),
+                index: index as u32,
+            })
+            .collect::<Vec<_>>();
+
+        for precision in [SortKeyPrecision::Bits16, SortKeyPrecision::Bits32] {
+            let (render_device, render_queue, sorter, source, sorted) = {
+                let render_world = app.sub_app(RenderApp).world();
+                let render_device = render_world.resource::<RenderDevice>().clone();
+                let render_queue = render_world.resource::<RenderQueue>().clone();
+
+                let source = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("radix bench source entries"),
+                    contents: bytemuck::cast_slice(entries.as_slice()),
+                    usage: BufferUsages::COPY_SRC,
+                });
+                let sorted = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("radix bench entries"),
+                    contents: bytemuck::cast_slice(entries.as_slice()),
+                    usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                });
+
+                let sorter = GpuRadixSorter::new(
+                    &render_device,
+                    render_world.resource::<RadixSortPipeline>(),
+                    &sorted,
+                    *count,
+                    precision,
+                );
+
+                (render_device, render_queue, sorter, source, sorted)
+            };
+
+            let sort = |app: &App| {
+                let render_world = app.sub_app(RenderApp).world();
+                let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("radix bench encoder"),
+                });
+                encoder.copy_buffer_to_buffer(&source, 0, &sorted, 0, source.size());
+
+                let encoded = sorter.encode(
+                    &mut encoder,
+                    render_world.resource::<PipelineCache>(),
+                    render_world.resource::<RadixSortPipeline>(),
+                );
+
+                render_queue.submit([encoder.finish()]);
+                render_device.poll(wgpu::Maintain::Wait);
+
+                encoded
+            };
+
+            // the pipelines compile in the render schedule
+            while !sort(&app) {
+                app.update();
+            }
+
+            group.bench_function(
+                BenchmarkId::new(format!("{:?}", precision), count),
+                |b| b.iter(|| sort(&app)),
+            );
+        }
+    }
+}
+
+criterion_group!{
+    name = radix_key_benches;
+    config = Criterion::default().sample_size(10);
+    targets = gaussian_cloud_radix_key_benchmark
+}
+criterion_main!(radix_key_benches);
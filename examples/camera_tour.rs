@@ -0,0 +1,100 @@
+// tours the training viewpoints of a capture
+// cargo run --example camera_tour -- [cloud.ply] [colmap sparse directory containing cameras.txt and images.txt]
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    gaussian::settings::GaussianCloudCoordinateConvention,
+    io::colmap::CameraTrack,
+};
+
+
+#[derive(Resource)]
+struct Tour {
+    track: CameraTrack,
+    timer: Timer,
+    index: usize,
+}
+
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let cloud_path = args.get(1).cloned().unwrap_or("scenes/icecream.ply".to_string());
+    let colmap_path = args.get(2).cloned().unwrap_or("assets/scenes/sparse/0".to_string());
+
+    let cameras = std::fs::read_to_string(format!("{}/cameras.txt", colmap_path)).expect("failed to read cameras.txt");
+    let images = std::fs::read_to_string(format!("{}/images.txt", colmap_path)).expect("failed to read images.txt");
+
+    let settings = GaussianCloudSettings {
+        coordinate_convention: GaussianCloudCoordinateConvention::Colmap,
+        ..default()
+    };
+
+    let track = CameraTrack::from_colmap(&cameras, &images)
+        .expect("failed to parse colmap cameras")
+        .with_transform(settings.cloud_transform());
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(Tour {
+            track,
+            timer: Timer::from_seconds(2.0, TimerMode::Repeating),
+            index: 0,
+        })
+        .insert_resource(CloudSource {
+            path: cloud_path,
+            settings,
+        })
+        .add_systems(Startup, setup)
+        .add_systems(Update, tour)
+        .run();
+}
+
+
+#[derive(Resource)]
+struct CloudSource {
+    path: String,
+    settings: GaussianCloudSettings,
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    source: Res<CloudSource>,
+) {
+    commands.spawn(GaussianSplattingBundle {
+        cloud: asset_server.load(source.path.clone()),
+        settings: source.settings.clone(),
+        ..default()
+    });
+
+    commands.spawn(Camera3dBundle {
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+fn tour(
+    time: Res<Time>,
+    mut tour: ResMut<Tour>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<Camera3d>>,
+) {
+    if tour.track.poses.is_empty() || !tour.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let pose = tour.track.poses[tour.index].clone();
+    tour.index = (tour.index + 1) % tour.track.poses.len();
+
+    for (mut transform, mut projection) in cameras.iter_mut() {
+        *transform = pose.transform;
+        *projection = Projection::Perspective(pose.projection.clone());
+    }
+}
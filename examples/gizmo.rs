@@ -0,0 +1,72 @@
+// drag a cloud around with translate / rotate / scale handles, g r s switch modes
+// cargo run --example gizmo --features gizmo -- [cloud.ply]
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    gizmo::{
+        GaussianCloudGizmo,
+        GaussianCloudGizmoMode,
+    },
+};
+
+
+#[derive(Resource)]
+struct CloudPath(String);
+
+
+fn main() {
+    let cloud_path = std::env::args().nth(1).unwrap_or("scenes/icecream.ply".to_string());
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(CloudPath(cloud_path))
+        .add_systems(Startup, setup)
+        .add_systems(Update, switch_mode)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cloud_path: Res<CloudPath>,
+) {
+    commands.spawn((
+        GaussianSplattingBundle {
+            cloud: asset_server.load(cloud_path.0.clone()),
+            ..default()
+        },
+        GaussianCloudGizmo::default(),
+    ));
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 1.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+fn switch_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut gizmos: Query<&mut GaussianCloudGizmo>,
+) {
+    let mode = if keys.just_pressed(KeyCode::KeyG) {
+        GaussianCloudGizmoMode::Translate
+    } else if keys.just_pressed(KeyCode::KeyR) {
+        GaussianCloudGizmoMode::Rotate
+    } else if keys.just_pressed(KeyCode::KeyS) {
+        GaussianCloudGizmoMode::Scale
+    } else {
+        return;
+    };
+
+    for mut gizmo in gizmos.iter_mut() {
+        gizmo.mode = mode;
+    }
+}
@@ -0,0 +1,122 @@
+// measures the radix sort of a scene of many small clouds, b toggles RadixSortBatching and the window title shows the
+// smoothed frame time with the compute passes, dispatches and buffer clears encoded by the sort each frame
+// cargo run --example many_clouds --features="sort_radix" -- [cloud count] [gaussians per cloud]
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+    diagnostic::{
+        DiagnosticsStore,
+        FrameTimeDiagnosticsPlugin,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    random_gaussians,
+    sort::{
+        SortMode,
+        radix::{
+            RadixSortBatching,
+            RadixSortStats,
+        },
+    },
+};
+
+
+#[derive(Resource)]
+struct SceneSize {
+    clouds: usize,
+    gaussians: usize,
+}
+
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let clouds = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(200);
+    let gaussians = args.get(2).and_then(|arg| arg.parse().ok()).unwrap_or(2000);
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(SceneSize { clouds, gaussians })
+        .add_systems(Startup, setup)
+        .add_systems(Update, (
+            toggle_batching,
+            show_sort_work,
+        ))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+    scene_size: Res<SceneSize>,
+) {
+    let columns = (scene_size.clouds as f32).sqrt().ceil() as usize;
+
+    for index in 0..scene_size.clouds {
+        let (column, row) = (index % columns, index / columns);
+        let offset = (columns as f32 - 1.0) / 2.0;
+
+        commands.spawn(GaussianSplattingBundle {
+            cloud: gaussian_assets.add(random_gaussians(scene_size.gaussians)),
+            settings: GaussianCloudSettings {
+                sort_mode: SortMode::Radix,
+                transform: Transform::from_xyz(column as f32 - offset, 0.0, row as f32 - offset)
+                    .with_scale(Vec3::splat(0.1)),
+                ..default()
+            },
+            ..default()
+        });
+    }
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, columns as f32, columns as f32))
+            .looking_at(Vec3::ZERO, Vec3::Y),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+fn toggle_batching(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut batching: ResMut<RadixSortBatching>,
+) {
+    if keys.just_pressed(KeyCode::KeyB) {
+        batching.enabled = !batching.enabled;
+    }
+}
+
+fn show_sort_work(
+    diagnostics: Res<DiagnosticsStore>,
+    batching: Res<RadixSortBatching>,
+    stats: Res<RadixSortStats>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+    else {
+        return;
+    };
+
+    let counts = stats.last_frame();
+
+    for mut window in windows.iter_mut() {
+        window.title = format!(
+            "batching {}: {:.2} ms, {} sorts ({} batched), {} passes, {} dispatches, {} clears (b to toggle)",
+            if batching.enabled { "on" } else { "off" },
+            frame_time,
+            counts.sorts,
+            counts.batched_sorts,
+            counts.compute_passes,
+            counts.dispatches,
+            counts.buffer_clears,
+        );
+    }
+}
@@ -0,0 +1,86 @@
+// plays back a captured dynamic scene stored as one ply per frame
+// cargo run --example sequence -- [directory containing frame_0000.ply..frame_0100.ply] [fps]
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianSequence,
+    GaussianSequencePlayback,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+};
+
+
+const FRAME_COUNT: usize = 101;
+
+
+#[derive(Resource)]
+struct SequenceSource {
+    directory: String,
+    fps: f32,
+}
+
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let directory = args.get(1).cloned().unwrap_or("scenes/sequence".to_string());
+    let fps = args.get(2)
+        .and_then(|fps| fps.parse().ok())
+        .unwrap_or(30.0);
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(SequenceSource {
+            directory,
+            fps,
+        })
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_playback)
+        .run();
+}
+
+
+fn setup(
+    mut commands: Commands,
+    mut sequences: ResMut<Assets<GaussianSequence>>,
+    source: Res<SequenceSource>,
+) {
+    let sequence = GaussianSequence::new(
+        (0..FRAME_COUNT).map(|frame| format!("{}/frame_{:04}.ply", source.directory, frame)),
+    );
+
+    commands.spawn((
+        GaussianSplattingBundle::default(),
+        GaussianSequencePlayback {
+            sequence: sequences.add(sequence),
+            fps: source.fps,
+            ..default()
+        },
+    ));
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 1.5, 5.0)).looking_at(Vec3::ZERO, Vec3::Y),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+// space pauses and resumes, r restarts
+fn toggle_playback(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut playbacks: Query<&mut GaussianSequencePlayback>,
+) {
+    for mut playback in playbacks.iter_mut() {
+        if keys.just_pressed(KeyCode::Space) {
+            playback.playing = !playback.playing;
+        }
+
+        if keys.just_pressed(KeyCode::KeyR) {
+            playback.elapsed = 0.0;
+        }
+    }
+}
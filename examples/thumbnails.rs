@@ -0,0 +1,82 @@
+// renders a thumbnail of each cloud offscreen and saves them as png, then exits
+// cargo run --example thumbnails -- [cloud.ply ...]
+
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::{
+    GaussianCloudThumbnailReady,
+    GaussianCloudThumbnails,
+    GaussianCloudThumbnailSettings,
+    GaussianSplattingPlugin,
+};
+
+
+const OUTPUT_DIRECTORY: &str = "thumbnails";
+
+
+#[derive(Resource)]
+struct Gallery {
+    paths: Vec<String>,
+}
+
+
+fn main() {
+    let mut paths = std::env::args().skip(1).collect::<Vec<String>>();
+    if paths.is_empty() {
+        paths.push("scenes/icecream.ply".to_string());
+    }
+
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: None,
+            exit_condition: bevy::window::ExitCondition::DontExit,
+            close_when_requested: false,
+        }))
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(Gallery { paths })
+        .add_systems(Startup, request_thumbnails)
+        .add_systems(Update, save_thumbnails)
+        .run();
+}
+
+
+fn request_thumbnails(
+    asset_server: Res<AssetServer>,
+    gallery: Res<Gallery>,
+    mut thumbnails: ResMut<GaussianCloudThumbnails>,
+) {
+    thumbnails.settings = GaussianCloudThumbnailSettings {
+        width: 320,
+        height: 240,
+        ..default()
+    };
+    thumbnails.max_in_flight = 2;
+
+    thumbnails.request(gallery.paths.iter().map(|path| asset_server.load(path.clone())));
+}
+
+fn save_thumbnails(
+    asset_server: Res<AssetServer>,
+    thumbnails: Res<GaussianCloudThumbnails>,
+    mut ev_ready: EventReader<GaussianCloudThumbnailReady>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for ev in ev_ready.read() {
+        let name = asset_server.get_path(ev.cloud.id())
+            .and_then(|path| path.path().file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| format!("{:?}", ev.cloud.id()));
+
+        let image = ev.image.clone()
+            .try_into_dynamic()
+            .expect("thumbnail is rgba8")
+            .to_rgba8();
+
+        std::fs::create_dir_all(OUTPUT_DIRECTORY).unwrap();
+        image.save(format!("{}/{}.png", OUTPUT_DIRECTORY, name)).expect("failed to save thumbnail");
+        println!("saved {}/{}.png", OUTPUT_DIRECTORY, name);
+    }
+
+    if thumbnails.is_idle() {
+        app_exit.send(AppExit::Success);
+    }
+}
@@ -0,0 +1,95 @@
+// compares sorted blending with stochastic transparency on one cloud, space toggles the mode and the window title
+// shows the smoothed frame time of each
+// cargo run --example transparency -- [cloud.ply]
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+    diagnostic::{
+        DiagnosticsStore,
+        FrameTimeDiagnosticsPlugin,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    GaussianSplattingPlugin,
+    GaussianTransparencyMode,
+};
+
+
+#[derive(Resource)]
+struct CloudPath(String);
+
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    let cloud_path = args.get(1).cloned().unwrap_or("scenes/icecream.ply".to_string());
+
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(GaussianSplattingPlugin)
+        .insert_resource(CloudPath(cloud_path))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (
+            toggle_mode,
+            show_frame_time,
+        ))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cloud_path: Res<CloudPath>,
+) {
+    commands.spawn(GaussianSplattingBundle {
+        cloud: asset_server.load(cloud_path.0.clone()),
+        ..default()
+    });
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 1.5, 5.0)).looking_at(Vec3::ZERO, Vec3::Y),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+fn toggle_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut clouds: Query<&mut GaussianCloudSettings>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for mut settings in clouds.iter_mut() {
+        settings.transparency_mode = match settings.transparency_mode {
+            GaussianTransparencyMode::Sorted => GaussianTransparencyMode::Stochastic,
+            GaussianTransparencyMode::Stochastic => GaussianTransparencyMode::Sorted,
+        };
+    }
+}
+
+fn show_frame_time(
+    diagnostics: Res<DiagnosticsStore>,
+    clouds: Query<&GaussianCloudSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+    else {
+        return;
+    };
+
+    let Some(settings) = clouds.iter().next() else {
+        return;
+    };
+
+    for mut window in windows.iter_mut() {
+        window.title = format!("{:?} transparency: {:.2} ms (space to toggle)", settings.transparency_mode, frame_time);
+    }
+}
@@ -3,7 +3,16 @@ use rand::{
     Rng,
 };
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::primitives::Aabb,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use bevy::{
+    math::Affine3A,
+    render::primitives::Sphere,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -15,6 +24,11 @@ use rayon::prelude::*;
 #[allow(unused_imports)]
 use crate::{
     gaussian::{
+        covariance::{
+            compute_covariance_3d,
+            decompose_covariance_3d,
+            transform_covariance_3d,
+        },
         f32::{
             Covariance3dOpacity,
             Position,
@@ -22,15 +36,32 @@ use crate::{
             Rotation,
             ScaleOpacity,
         },
+        importance::GaussianCloudImportance,
         packed::Gaussian,
+        patch::{
+            GaussianCloudPatch,
+            GaussianCloudPatchError,
+        },
+        spatial::GaussianCloudSpatialIndex,
     },
     material::spherical_harmonics::{
         HALF_SH_COEFF_COUNT,
+        SH_CHANNELS,
         SH_COEFF_COUNT,
+        SH_DEGREE,
         SphericalHarmonicCoefficients,
+        num_sh_coefficients,
     },
 };
 
+
+// gaussians below this opacity are treated as floaters by weighted queries
+pub const WEIGHTED_CENTROID_OPACITY_FLOOR: f32 = 0.01;
+
+// relative tolerance when telling similarities apart from non-uniform scale or shear in `transform`
+#[cfg(not(feature = "precompute_covariance_3d"))]
+const SIMILARITY_EPSILON: f32 = 1e-5;
+
 #[allow(unused_imports)]
 #[cfg(feature = "f16")]
 use crate::gaussian::f16::{
@@ -61,6 +92,29 @@ pub struct GaussianCloud {
 
     #[cfg(feature = "precompute_covariance_3d")]
     pub covariance_3d_opacity_packed128: Vec<Covariance3dOpacityPacked128>,
+
+    // optional per-gaussian scalar for overlays, either empty or one value per gaussian
+    #[serde(default)]
+    pub channel: Vec<f32>,
+
+    // see set_importance_luminance_weight
+    #[serde(default)]
+    pub importance_luminance_weight: f32,
+
+    // optional position of each gaussian in the GaussianCloudSettings::reveal_fraction order, either empty (cloud
+    // order) or a permutation of 0..len, see set_reveal_order
+    #[serde(default)]
+    pub reveal_rank: Vec<u32>,
+
+    // built on first spatial query, mutating accessors reset it
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub spatial_index: GaussianCloudSpatialIndex,
+
+    // computed on first use, mutating accessors reset it
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub importance: GaussianCloudImportance,
 }
 
 #[cfg(feature = "f32")]
@@ -87,6 +141,29 @@ pub struct GaussianCloud {
     pub rotation: Vec<Rotation>,
     #[cfg(not(feature = "precompute_covariance_3d"))]
     pub scale_opacity: Vec<ScaleOpacity>,
+
+    // optional per-gaussian scalar for overlays, either empty or one value per gaussian
+    #[serde(default)]
+    pub channel: Vec<f32>,
+
+    // see set_importance_luminance_weight
+    #[serde(default)]
+    pub importance_luminance_weight: f32,
+
+    // optional position of each gaussian in the GaussianCloudSettings::reveal_fraction order, either empty (cloud
+    // order) or a permutation of 0..len, see set_reveal_order
+    #[serde(default)]
+    pub reveal_rank: Vec<u32>,
+
+    // built on first spatial query, mutating accessors reset it
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub spatial_index: GaussianCloudSpatialIndex,
+
+    // computed on first use, mutating accessors reset it
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub importance: GaussianCloudImportance,
 }
 
 impl GaussianCloud {
@@ -106,11 +183,31 @@ impl GaussianCloud {
         self.len_sqrt_ceil().pow(2)
     }
 
+    // highest band with a nonzero coefficient, planar storage buffers only upload bands up to it
+    pub fn sh_degree(&self) -> usize {
+        self.sh_degree_in(0..self.len())
+    }
+
+    pub fn sh_degree_in(&self, range: std::ops::Range<usize>) -> usize {
+        let coefficients = &self.spherical_harmonic[range];
+
+        (1..=SH_DEGREE)
+            .rev()
+            .find(|&degree| {
+                let band = num_sh_coefficients(degree - 1) * SH_CHANNELS..num_sh_coefficients(degree) * SH_CHANNELS;
+
+                coefficients.iter().any(|sh| band.clone().any(|index| sh.get(index) != 0.0))
+            })
+            .unwrap_or(0)
+    }
+
     pub fn position(&self, index: usize) -> &[f32; 3] {
         &self.position_visibility[index].position
     }
 
+    // resets the spatial index, the importance cache is kept since importance does not depend on position
     pub fn position_mut(&mut self, index: usize) -> &mut [f32; 3] {
+        self.invalidate_spatial_index();
         &mut self.position_visibility[index].position
     }
 
@@ -130,11 +227,45 @@ impl GaussianCloud {
         self.position_visibility[index].visibility
     }
 
+    // keeps the spatial index and the importance cache, neither reads visibility
     pub fn visibility_mut(&mut self, index: usize) -> &mut f32 {
         &mut self.position_visibility[index].visibility
     }
 
 
+    #[cfg(all(
+        not(feature = "precompute_covariance_3d"),
+        feature = "f16",
+    ))]
+    pub fn opacity(&self, index: usize) -> f32 {
+        self.rotation_scale_opacity_packed128[index].scale_opacity().opacity
+    }
+
+    #[cfg(all(
+        feature = "precompute_covariance_3d",
+        feature = "f16",
+    ))]
+    pub fn opacity(&self, index: usize) -> f32 {
+        self.covariance_3d_opacity_packed128[index].covariance_3d_opacity().opacity
+    }
+
+    #[cfg(all(
+        not(feature = "precompute_covariance_3d"),
+        feature = "f32",
+    ))]
+    pub fn opacity(&self, index: usize) -> f32 {
+        self.scale_opacity[index].opacity
+    }
+
+    #[cfg(all(
+        feature = "precompute_covariance_3d",
+        feature = "f32",
+    ))]
+    pub fn opacity(&self, index: usize) -> f32 {
+        self.covariance_3d[index].opacity
+    }
+
+
     // pub fn rotation(&self, index: usize) -> &[f32; 4] {
     //     #[cfg(feature = "f16")]
     //     return &self.rotation_scale_opacity_packed128[index].rotation;
@@ -238,10 +369,41 @@ impl GaussianCloud {
     }
 
     pub fn spherical_harmonic_mut(&mut self, index: usize) -> &mut SphericalHarmonicCoefficients {
+        self.invalidate_importance();
+
         &mut self.spherical_harmonic[index]
     }
 
+    pub fn set_channel(&mut self, channel: Vec<f32>) {
+        assert_eq!(channel.len(), self.len(), "channel must hold one value per gaussian");
+
+        self.channel = channel;
+    }
+
+    // reveals the gaussians in the given order, first to last, without moving them, so indices held by patches,
+    // selections, and snapshots stay valid. `order` lists every gaussian index once, e.g. morton_order() or
+    // importance_order()
+    pub fn set_reveal_order(&mut self, order: &[usize]) {
+        assert_eq!(order.len(), self.len(), "reveal order must list every gaussian");
+
+        let mut reveal_rank = vec![u32::MAX; self.len()];
+        for (rank, &index) in order.iter().enumerate() {
+            assert_eq!(reveal_rank[index], u32::MAX, "gaussian {} is listed twice in the reveal order", index);
+            reveal_rank[index] = rank as u32;
+        }
+
+        self.reveal_rank = reveal_rank;
+    }
+
+    // restores the cloud order
+    pub fn clear_reveal_order(&mut self) {
+        self.reveal_rank.clear();
+    }
+
     pub fn resize_to_square(&mut self) {
+        self.invalidate_spatial_index();
+        self.invalidate_importance();
+
         #[cfg(all(feature = "buffer_texture", feature = "f16"))]
         {
             self.position_visibility.resize(self.square_len(), PositionVisibility::default());
@@ -265,6 +427,323 @@ impl GaussianCloud {
 }
 
 
+impl GaussianCloud {
+    pub fn aabb(&self) -> Aabb {
+        if self.is_empty() {
+            return Aabb::default();
+        }
+
+        let (min, max) = self.position_iter()
+            .fold(
+                (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+                |(min, max), position| {
+                    let position = Vec3::from_slice(position.as_slice());
+                    (min.min(position), max.max(position))
+                },
+            );
+
+        Aabb::from_min_max(min, max)
+    }
+
+    // aabb of the centers between the `percentile`th and (100 - percentile)th percentile of each axis, so a few far
+    // floaters do not stretch the box, e.g. 1.0 for the 1st to 99th percentile. 0.0 matches aabb over the finite
+    // positions, and percentiles above 50.0 are clamped to the median. axes are trimmed independently, so the box
+    // bounds each coordinate's distribution rather than a joint fraction of the gaussians. costs a copy of one
+    // coordinate per gaussian and an O(n) selection per axis
+    pub fn robust_bounds(&self, percentile: f32) -> Aabb {
+        let fraction = (percentile / 100.0).clamp(0.0, 0.5);
+
+        let mut coordinates = Vec::with_capacity(self.len());
+        let mut min = Vec3::ZERO;
+        let mut max = Vec3::ZERO;
+
+        for axis in 0..3 {
+            coordinates.clear();
+            coordinates.extend(
+                self.position_iter()
+                    .map(|position| position[axis])
+                    .filter(|coordinate| coordinate.is_finite()),
+            );
+
+            if coordinates.is_empty() {
+                return Aabb::default();
+            }
+
+            let last = (coordinates.len() - 1) as f32;
+            let low = (fraction * last).round() as usize;
+            let high = ((1.0 - fraction) * last).round() as usize;
+
+            min[axis] = *coordinates.select_nth_unstable_by(low, f32::total_cmp).1;
+            max[axis] = *coordinates.select_nth_unstable_by(high, f32::total_cmp).1;
+        }
+
+        Aabb::from_min_max(min, max)
+    }
+
+    // largest axis scale over all gaussians, the splat extent is `sigma * max_scale` beyond each center
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn max_scale(&self) -> f32 {
+        self.gaussian_iter()
+            .map(|gaussian| {
+                let [x, y, z] = gaussian.scale_opacity.scale;
+                x.max(y).max(z)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    // sphere around the aabb center enclosing every gaussian inflated by `sigma` standard deviations,
+    // a sigma of 0.0 bounds the centers only and 3.0 bounds the visible 3 sigma footprint
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn bounding_sphere(&self, sigma: f32) -> Sphere {
+        let center = self.centroid();
+
+        let radius = self.gaussian_iter()
+            .map(|gaussian| {
+                let position = Vec3::from_array(gaussian.position_visibility.position);
+                let [x, y, z] = gaussian.scale_opacity.scale;
+
+                position.distance(center) + sigma * x.max(y).max(z)
+            })
+            .fold(0.0, f32::max);
+
+        Sphere {
+            center: center.into(),
+            radius,
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        self.aabb().center.into()
+    }
+
+    pub fn weighted_centroid(&self) -> Vec3 {
+        let (weighted_sum, total_weight) = (0..self.len())
+            .map(|index| (index, self.opacity(index)))
+            .filter(|(_, opacity)| *opacity >= WEIGHTED_CENTROID_OPACITY_FLOOR)
+            .fold(
+                (Vec3::ZERO, 0.0),
+                |(sum, weight), (index, opacity)| {
+                    let position = Vec3::from_slice(self.position(index).as_slice());
+                    (sum + position * opacity, weight + opacity)
+                },
+            );
+
+        if total_weight <= 0.0 {
+            return self.centroid();
+        }
+
+        weighted_sum / total_weight
+    }
+
+    #[cfg(all(
+        not(feature = "precompute_covariance_3d"),
+        feature = "f16",
+    ))]
+    pub fn set_gaussian(&mut self, index: usize, gaussian: Gaussian) {
+        self.invalidate_spatial_index();
+        self.invalidate_importance();
+        self.position_visibility[index] = gaussian.position_visibility;
+        self.spherical_harmonic[index] = gaussian.spherical_harmonic;
+        self.rotation_scale_opacity_packed128[index] = RotationScaleOpacityPacked128::from_gaussian(&gaussian);
+    }
+
+    #[cfg(all(
+        not(feature = "precompute_covariance_3d"),
+        feature = "f32",
+    ))]
+    pub fn set_gaussian(&mut self, index: usize, gaussian: Gaussian) {
+        self.invalidate_spatial_index();
+        self.invalidate_importance();
+        self.position_visibility[index] = gaussian.position_visibility;
+        self.spherical_harmonic[index] = gaussian.spherical_harmonic;
+        self.rotation[index] = gaussian.rotation;
+        self.scale_opacity[index] = gaussian.scale_opacity;
+    }
+
+    // out of range indices are rejected before anything is written
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn apply_patch(&mut self, patch: &GaussianCloudPatch) -> Result<(), GaussianCloudPatchError> {
+        patch.validate(self.len())?;
+
+        for (index, gaussian) in patch.updates.iter() {
+            self.set_gaussian(*index, *gaussian);
+        }
+
+        if patch.is_in_place() {
+            return Ok(());
+        }
+
+        let mut removed = vec![false; self.len()];
+        for index in patch.removals.iter() {
+            removed[*index] = true;
+        }
+
+        let gaussians = self.gaussian_iter()
+            .zip(removed)
+            .filter(|(_, removed)| !removed)
+            .map(|(gaussian, _)| gaussian)
+            .chain(patch.appends.iter().copied())
+            .collect::<Vec<Gaussian>>();
+
+        // the overlay channel and reveal order no longer line up once gaussians are added or removed, so they are
+        // dropped
+        self.channel.clear();
+        self.reveal_rank.clear();
+        self.replace_gaussians(gaussians);
+
+        Ok(())
+    }
+
+    // rebuilds the storage from `gaussians` and carries over everything from_gaussians resets: the overlay channel and
+    // reveal order, which must still line up with the new gaussians, and the importance luminance weight
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    fn replace_gaussians(&mut self, gaussians: Vec<Gaussian>) {
+        let channel = std::mem::take(&mut self.channel);
+        let reveal_rank = std::mem::take(&mut self.reveal_rank);
+        let importance_luminance_weight = self.importance_luminance_weight;
+
+        *self = Self::from_gaussians(gaussians);
+
+        self.channel = channel;
+        self.reveal_rank = reveal_rank;
+        self.importance_luminance_weight = importance_luminance_weight;
+    }
+
+    // bakes a rotation about the origin into positions, orientations, and view-dependent color
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn rotate(&mut self, rotation: Quat) {
+        let rotation_matrix = Mat3::from_quat(rotation);
+
+        let gaussians = self.gaussian_iter()
+            .map(|mut gaussian| {
+                let position = Vec3::from_array(gaussian.position_visibility.position);
+                gaussian.position_visibility.position = (rotation * position).to_array();
+
+                let [w, x, y, z] = gaussian.rotation.rotation;
+                let orientation = (rotation * Quat::from_xyzw(x, y, z, w)).normalize();
+                gaussian.rotation.rotation = [orientation.w, orientation.x, orientation.y, orientation.z];
+
+                gaussian.spherical_harmonic.rotate(rotation_matrix);
+
+                gaussian
+            })
+            .collect::<Vec<Gaussian>>();
+
+        self.replace_gaussians(gaussians);
+    }
+
+    // reflects the cloud through the plane orthogonal to `axis` (0, 1, 2 for x, y, z) at the origin. orientations
+    // are conjugated by the reflection (D R D) so each gaussian stays a proper rotation, and view dependent color is
+    // reflected with it, mirroring twice restores the cloud
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn mirror(&mut self, axis: usize) {
+        assert!(axis < 3, "mirror axis {} is not one of x, y, z", axis);
+
+        let gaussians = self.gaussian_iter()
+            .map(|mut gaussian| {
+                gaussian.position_visibility.position[axis] = -gaussian.position_visibility.position[axis];
+
+                // wxyz, the component along the mirror axis is kept and the other two imaginary parts flip
+                for component in (0..3).filter(|&component| component != axis) {
+                    gaussian.rotation.rotation[component + 1] = -gaussian.rotation.rotation[component + 1];
+                }
+
+                gaussian.spherical_harmonic.mirror(axis);
+
+                gaussian
+            })
+            .collect::<Vec<Gaussian>>();
+
+        self.replace_gaussians(gaussians);
+    }
+
+    // bakes an affine transform into the cloud. similarities (rotations, reflections, uniform scale) keep each
+    // gaussian's axes, other maps (non-uniform scale, shear) refit them to the transformed covariance M * Sigma * M^T.
+    // view dependent color follows the rotational part only
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn transform(&mut self, transform: Affine3A) {
+        let (scale, rotation, _) = transform.to_scale_rotation_translation();
+        let uniform_scale = scale.abs().dot(Vec3::ONE) / 3.0;
+
+        let linear = Mat3::from(transform.matrix3);
+        let uniform_gram = Mat3::IDENTITY * (uniform_scale * uniform_scale);
+        let similarity = (linear.transpose() * linear).abs_diff_eq(uniform_gram, SIMILARITY_EPSILON * uniform_scale * uniform_scale);
+
+        // glam folds a negative determinant into the x scale, i.e. linear = rotation * diag(-1, 1, 1) * scale
+        let mirrored = scale.x.is_sign_negative();
+        let orthonormal = if mirrored {
+            Mat3::from_quat(rotation) * Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0))
+        } else {
+            Mat3::from_quat(rotation)
+        };
+
+        let gaussians = self.gaussian_iter()
+            .map(|mut gaussian| {
+                let position = Vec3::from_array(gaussian.position_visibility.position);
+                gaussian.position_visibility.position = transform.transform_point3(position).to_array();
+
+                let [w, x, y, z] = gaussian.rotation.rotation;
+                if similarity {
+                    // conjugating by the x mirror keeps the covariance a proper rotation: D R D
+                    let local = if mirrored {
+                        Quat::from_xyzw(x, -y, -z, w)
+                    } else {
+                        Quat::from_xyzw(x, y, z, w)
+                    };
+                    let orientation = (rotation * local).normalize();
+                    gaussian.rotation.rotation = [orientation.w, orientation.x, orientation.y, orientation.z];
+
+                    gaussian.scale_opacity.scale = gaussian.scale_opacity.scale.map(|s| s * uniform_scale);
+                } else {
+                    let cov3d = compute_covariance_3d(
+                        Vec4::new(w, x, y, z).normalize(),
+                        Vec3::from_array(gaussian.scale_opacity.scale),
+                    );
+                    let (orientation, scale) = decompose_covariance_3d(transform_covariance_3d(cov3d, linear));
+
+                    gaussian.rotation.rotation = [orientation.w, orientation.x, orientation.y, orientation.z];
+                    gaussian.scale_opacity.scale = scale.to_array();
+                }
+
+                gaussian.spherical_harmonic.rotate(orthonormal);
+
+                gaussian
+            })
+            .collect::<Vec<Gaussian>>();
+
+        self.replace_gaussians(gaussians);
+    }
+
+    // moves the weighted centroid to the origin and fits the bounds into a unit box, returns the applied transform
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn recenter_and_normalize(&mut self) -> Affine3A {
+        if self.is_empty() {
+            return Affine3A::IDENTITY;
+        }
+
+        let centroid = self.weighted_centroid();
+        let (min, max) = self.position_iter()
+            .map(|position| Vec3::from_array(*position))
+            .fold(
+                (Vec3::INFINITY, Vec3::NEG_INFINITY),
+                |(min, max), position| (min.min(position), max.max(position)),
+            );
+
+        let extent = (max - min).max_element();
+        let scale = if extent > 0.0 { extent.recip() } else { 1.0 };
+
+        let transform = Affine3A::from_scale_rotation_translation(
+            Vec3::splat(scale),
+            Quat::IDENTITY,
+            -centroid * scale,
+        );
+        self.transform(transform);
+
+        transform
+    }
+}
+
+
 impl GaussianCloud {
     #[cfg(feature = "f16")]
     pub fn subset(&self, indicies: &[usize]) -> Self {
@@ -288,6 +767,9 @@ impl GaussianCloud {
             rotation_scale_opacity_packed128.push(self.rotation_scale_opacity_packed128[index]);
         }
 
+        let channel = self.channel_subset(indicies);
+        let reveal_rank = self.reveal_rank_subset(indicies);
+
         Self {
             position_visibility,
             spherical_harmonic,
@@ -296,6 +778,12 @@ impl GaussianCloud {
             covariance_3d_opacity_packed128,
             #[cfg(not(feature = "precompute_covariance_3d"))]
             rotation_scale_opacity_packed128,
+
+            channel,
+            importance_luminance_weight: self.importance_luminance_weight,
+            reveal_rank,
+            spatial_index: default(),
+            importance: default(),
         }
     }
 
@@ -313,14 +801,103 @@ impl GaussianCloud {
             scale_opacity.push(self.scale_opacity[index]);
         }
 
+        let channel = self.channel_subset(indicies);
+        let reveal_rank = self.reveal_rank_subset(indicies);
+
         Self {
             position_visibility,
             spherical_harmonic,
             rotation,
             scale_opacity,
+            channel,
+            importance_luminance_weight: self.importance_luminance_weight,
+            reveal_rank,
+            spatial_index: default(),
+            importance: default(),
         }
     }
 
+    // the gaussians whose centers lie inside `aabb` (boundary included), in cloud space. unlike the render time clip
+    // planes this drops the data, e.g. to cut sky and surroundings before export
+    pub fn crop(&self, aabb: &Aabb) -> Self {
+        let min = Vec3::from(aabb.min());
+        let max = Vec3::from(aabb.max());
+
+        self.retained(|position| position.cmpge(min).all() && position.cmple(max).all())
+    }
+
+    // the gaussians on the front side of `plane`, a * x + b * y + c * z + d >= 0 like GaussianCloudSettings::clip_planes,
+    // e.g. Vec4::new(0.0, 1.0, 0.0, -floor_height) drops everything below a y-up floor
+    pub fn crop_plane(&self, plane: Vec4) -> Self {
+        self.retained(|position| plane.dot(position.extend(1.0)) >= 0.0)
+    }
+
+    // drops the gaussians that edits have removed in place, i.e. collapsed to zero opacity (or zero scale), keeping
+    // the order of the rest and shrinking the storage to fit. returns the number of gaussians dropped. compacting through
+    // Assets::get_mut reallocates the gpu buffers, sort scratch, and sorted entries at the new count
+    pub fn compact(&mut self) -> usize {
+        let live = (0..self.len())
+            .filter(|&index| !self.is_collapsed(index))
+            .collect::<Vec<usize>>();
+
+        let removed = self.len() - live.len();
+        if removed > 0 {
+            *self = self.subset(&live);
+        }
+
+        removed
+    }
+
+    fn is_collapsed(&self, index: usize) -> bool {
+        if self.opacity(index) <= 0.0 {
+            return true;
+        }
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        if self.gaussian(index).scale_opacity.scale.iter().all(|scale| *scale == 0.0) {
+            return true;
+        }
+
+        false
+    }
+
+    fn retained(&self, keep: impl Fn(Vec3) -> bool) -> Self {
+        let indices = self.position_iter()
+            .enumerate()
+            .filter(|(_, position)| keep(Vec3::from_array(**position)))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+
+        self.subset(&indices)
+    }
+
+    // the kept gaussians reveal in the same relative order, ranked 0..indicies.len()
+    fn reveal_rank_subset(&self, indicies: &[usize]) -> Vec<u32> {
+        if self.reveal_rank.is_empty() {
+            return Vec::new();
+        }
+
+        let mut order = (0..indicies.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&position| self.reveal_rank[indicies[position]]);
+
+        let mut reveal_rank = vec![0; indicies.len()];
+        for (rank, position) in order.into_iter().enumerate() {
+            reveal_rank[position] = rank as u32;
+        }
+
+        reveal_rank
+    }
+
+    fn channel_subset(&self, indicies: &[usize]) -> Vec<f32> {
+        if self.channel.is_empty() {
+            return Vec::new();
+        }
+
+        indicies.iter()
+            .map(|&index| self.channel[index])
+            .collect()
+    }
+
     #[cfg(feature = "f32")]
     pub fn to_packed(&self) -> Vec<Gaussian> {
         let mut gaussians = Vec::with_capacity(self.len());
@@ -366,6 +943,12 @@ impl GaussianCloud {
             covariance_3d_opacity_packed128,
             #[cfg(not(feature = "precompute_covariance_3d"))]
             rotation_scale_opacity_packed128,
+
+            channel: Vec::new(),
+            importance_luminance_weight: 0.0,
+            reveal_rank: Vec::new(),
+            spatial_index: default(),
+            importance: default(),
         };
 
         cloud.resize_to_square();
@@ -393,6 +976,11 @@ impl GaussianCloud {
             spherical_harmonic,
             rotation,
             scale_opacity,
+            channel: Vec::new(),
+            importance_luminance_weight: 0.0,
+            reveal_rank: Vec::new(),
+            spatial_index: default(),
+            importance: default(),
         }
     }
 
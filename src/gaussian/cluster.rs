@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::GaussianCloud;
+
+
+const UNVISITED: usize = usize::MAX;
+const NOISE: usize = usize::MAX - 1;
+
+
+impl GaussianCloud {
+    // dbscan over gaussian centers in cloud space. a gaussian with at least `min_points` centers (itself included)
+    // within `eps` is a core gaussian, clusters are the core gaussians connected through those neighborhoods plus the
+    // non core gaussians they reach, and everything else is noise (None). clusters are numbered in the cloud order of
+    // their first core gaussian. `min_points` of 1 makes every gaussian a core gaussian, i.e. connected components
+    // at `eps`
+    //
+    // neighborhoods are looked up in the spatial index, so each is a query of the cells overlapping a 2 * eps box.
+    // the total cost is about n * (neighbors within eps + candidates in those cells): near linear when eps is on the
+    // order of the spacing between gaussians, approaching n^2 when eps spans a large part of the cloud
+    pub fn cluster_labels(&self, eps: f32, min_points: usize) -> Vec<Option<usize>> {
+        let grid = self.spatial_index();
+        let eps = eps.max(0.0);
+
+        let neighbors = |index: usize| {
+            let center = Vec3::from_array(*self.position(index));
+
+            grid.query_aabb(center - Vec3::splat(eps), center + Vec3::splat(eps))
+                .into_iter()
+                .filter(|&candidate| {
+                    Vec3::from_array(*self.position(candidate as usize)).distance_squared(center) <= eps * eps
+                })
+                .collect::<Vec<u32>>()
+        };
+
+        let mut labels = vec![UNVISITED; self.len()];
+        let mut clusters = 0;
+
+        for index in 0..self.len() {
+            if labels[index] != UNVISITED {
+                continue;
+            }
+
+            let seeds = neighbors(index);
+            if seeds.len() < min_points {
+                labels[index] = NOISE;
+                continue;
+            }
+
+            let cluster = clusters;
+            clusters += 1;
+            labels[index] = cluster;
+
+            let mut queue = VecDeque::from(seeds);
+            while let Some(neighbor) = queue.pop_front() {
+                let neighbor = neighbor as usize;
+
+                // reached from a core gaussian, so it borders this cluster but does not grow it
+                if labels[neighbor] == NOISE {
+                    labels[neighbor] = cluster;
+                    continue;
+                }
+
+                if labels[neighbor] != UNVISITED {
+                    continue;
+                }
+
+                labels[neighbor] = cluster;
+
+                let reachable = neighbors(neighbor);
+                if reachable.len() >= min_points {
+                    queue.extend(reachable);
+                }
+            }
+        }
+
+        labels.into_iter()
+            .map(|label| if label == NOISE { None } else { Some(label) })
+            .collect()
+    }
+
+    // splits the cloud into one cloud per cluster_labels cluster, dropping noise. each keeps the cloud order and
+    // channel of its gaussians
+    pub fn cluster(&self, eps: f32, min_points: usize) -> Vec<GaussianCloud> {
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+
+        for (index, label) in self.cluster_labels(eps, min_points).into_iter().enumerate() {
+            let Some(label) = label else {
+                continue;
+            };
+
+            // border gaussians can come before the first gaussian of an earlier cluster
+            if label >= clusters.len() {
+                clusters.resize_with(label + 1, Vec::new);
+            }
+
+            clusters[label].push(index);
+        }
+
+        clusters.iter()
+            .map(|indices| self.subset(indices))
+            .collect()
+    }
+}
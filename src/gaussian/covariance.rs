@@ -1,10 +1,14 @@
 use bevy::math::{
     Mat3,
+    Quat,
     Vec3,
     Vec4,
 };
 
 
+const JACOBI_SWEEPS: usize = 16;
+
+
 #[allow(non_snake_case)]
 pub fn compute_covariance_3d(
     rotation: Vec4,
@@ -47,3 +51,90 @@ pub fn compute_covariance_3d(
         Sigma.row(2).z,
     ]
 }
+
+
+fn covariance_matrix(cov3d: &[f32; 6]) -> Mat3 {
+    Mat3::from_cols(
+        Vec3::new(cov3d[0], cov3d[1], cov3d[2]),
+        Vec3::new(cov3d[1], cov3d[3], cov3d[4]),
+        Vec3::new(cov3d[2], cov3d[4], cov3d[5]),
+    )
+}
+
+// the covariance of a gaussian under a linear map is M * Sigma * M^T, exact for non-uniform scale and shear
+pub fn transform_covariance_3d(
+    cov3d: [f32; 6],
+    linear: Mat3,
+) -> [f32; 6] {
+    let transformed = linear * covariance_matrix(&cov3d) * linear.transpose();
+
+    [
+        transformed.x_axis.x,
+        transformed.y_axis.x,
+        transformed.z_axis.x,
+        transformed.y_axis.y,
+        transformed.z_axis.y,
+        transformed.z_axis.z,
+    ]
+}
+
+// splits a covariance back into a rotation and per axis scale with Sigma = R * S^2 * R^T (cyclic jacobi
+// eigendecomposition), the inverse of compute_covariance_3d up to axis order and sign
+#[allow(clippy::needless_range_loop)]
+pub fn decompose_covariance_3d(cov3d: [f32; 6]) -> (Quat, Vec3) {
+    let mut a = covariance_matrix(&cov3d).to_cols_array_2d();
+    let mut v = Mat3::IDENTITY.to_cols_array_2d();
+
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal = a[1][0].abs() + a[2][0].abs() + a[2][1].abs();
+        if off_diagonal <= f32::EPSILON * (a[0][0].abs() + a[1][1].abs() + a[2][2].abs()) {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[q][p] == 0.0 {
+                continue;
+            }
+
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[q][p]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            // a = J^T * a * J with J the (p, q) plane rotation, a stays symmetric
+            for k in 0..3 {
+                let akp = a[p][k];
+                let akq = a[q][k];
+                a[p][k] = c * akp - s * akq;
+                a[q][k] = s * akp + c * akq;
+            }
+            for k in 0..3 {
+                let apk = a[k][p];
+                let aqk = a[k][q];
+                a[k][p] = c * apk - s * aqk;
+                a[k][q] = s * apk + c * aqk;
+            }
+
+            for column in v.iter_mut() {
+                let vp = column[p];
+                let vq = column[q];
+                column[p] = c * vp - s * vq;
+                column[q] = s * vp + c * vq;
+            }
+        }
+    }
+
+    // v accumulates rows here, so its transpose holds the eigenvectors as columns
+    let mut eigenvectors = Mat3::from_cols_array_2d(&v).transpose();
+    if eigenvectors.determinant() < 0.0 {
+        eigenvectors.z_axis = -eigenvectors.z_axis;
+    }
+
+    let scale = Vec3::new(
+        a[0][0].max(0.0).sqrt(),
+        a[1][1].max(0.0).sqrt(),
+        a[2][2].max(0.0).sqrt(),
+    );
+
+    (Quat::from_mat3(&eigenvectors).normalize(), scale)
+}
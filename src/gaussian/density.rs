@@ -0,0 +1,82 @@
+use bevy::{
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{
+    Gaussian,
+    GaussianCloud,
+};
+
+
+// grid cells are this many typical footprint radii wide
+const CELL_RADII: f32 = 2.0;
+
+
+impl GaussianCloud {
+    // scales down opacities where gaussians pile up so dense regions of unevenly sampled captures do not blow out.
+    // the local density is estimated on a voxel grid:
+    //   1. the typical radius is the median of every gaussian's largest scale axis, and cells are CELL_RADII radii wide
+    //   2. each cell accumulates the opacity of the gaussians centered in it
+    //   3. a gaussian's density is the accumulated opacity of its cell and the 26 around it
+    // gaussians denser than the median density have their opacity scaled by (median / density)^strength, so 0.0
+    // leaves the cloud unchanged and 1.0 pulls every dense neighborhood down to the median. gaussians at or below the
+    // median are untouched
+    pub fn normalize_density(&mut self, strength: f32) {
+        if self.is_empty() || strength <= 0.0 {
+            return;
+        }
+
+        let gaussians = self.gaussian_iter().collect::<Vec<_>>();
+
+        let mut radii = gaussians.iter()
+            .map(|gaussian| Vec3::from_array(gaussian.scale_opacity.scale).max_element())
+            .collect::<Vec<f32>>();
+        let middle = radii.len() / 2;
+        let (_, radius, _) = radii.select_nth_unstable_by(middle, f32::total_cmp);
+
+        let cell_size = (*radius * CELL_RADII).max(f32::EPSILON);
+        let cell = |gaussian: &Gaussian| {
+            (Vec3::from_array(gaussian.position_visibility.position) / cell_size).floor().as_ivec3()
+        };
+
+        let mut grid: HashMap<IVec3, f32> = HashMap::new();
+        for gaussian in gaussians.iter() {
+            *grid.entry(cell(gaussian)).or_default() += gaussian.scale_opacity.opacity.max(0.0);
+        }
+
+        let densities = gaussians.iter()
+            .map(|gaussian| {
+                let center = cell(gaussian);
+
+                let mut density = 0.0;
+                for z in -1..=1 {
+                    for y in -1..=1 {
+                        for x in -1..=1 {
+                            density += grid.get(&(center + IVec3::new(x, y, z))).copied().unwrap_or_default();
+                        }
+                    }
+                }
+
+                density
+            })
+            .collect::<Vec<f32>>();
+
+        let mut sorted = densities.clone();
+        let (_, median, _) = sorted.select_nth_unstable_by(middle, f32::total_cmp);
+        let median = *median;
+
+        if median <= 0.0 {
+            return;
+        }
+
+        for (index, (mut gaussian, density)) in gaussians.into_iter().zip(densities).enumerate() {
+            if density <= median {
+                continue;
+            }
+
+            gaussian.scale_opacity.opacity *= (median / density).powf(strength);
+            self.set_gaussian(index, gaussian);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::GaussianCloud;
+
+
+// per gaussian quantity compared between two clouds
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudDiffMetric {
+    // cloud space distance between the two centers
+    #[default]
+    Position,
+    // absolute opacity change
+    Opacity,
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GaussianCloudDiffError {
+    // gaussians are compared by index, so both clouds must come from the same training run without densification
+    // or pruning in between
+    CountMismatch {
+        count: usize,
+        reference_count: usize,
+    },
+}
+
+impl fmt::Display for GaussianCloudDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CountMismatch { count, reference_count } => write!(
+                f,
+                "cannot diff a cloud of {} gaussians against a reference of {}, gaussians are compared by index",
+                count,
+                reference_count,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GaussianCloudDiffError {}
+
+
+impl GaussianCloud {
+    // delta of each gaussian to the gaussian at the same index of `reference`
+    pub fn diff(
+        &self,
+        reference: &GaussianCloud,
+        metric: GaussianCloudDiffMetric,
+    ) -> Result<Vec<f32>, GaussianCloudDiffError> {
+        if self.len() != reference.len() {
+            return Err(GaussianCloudDiffError::CountMismatch {
+                count: self.len(),
+                reference_count: reference.len(),
+            });
+        }
+
+        let deltas = (0..self.len())
+            .map(|index| match metric {
+                GaussianCloudDiffMetric::Position => Vec3::from_array(*self.position(index))
+                    .distance(Vec3::from_array(*reference.position(index))),
+                GaussianCloudDiffMetric::Opacity => (self.opacity(index) - reference.opacity(index)).abs(),
+            })
+            .collect();
+
+        Ok(deltas)
+    }
+
+    // stores the diff as the color channel and returns the channel range spanning it, render with
+    // GaussianCloudColorChannel::Channel and this as GaussianCloudSettings::channel_range to color splats by delta
+    pub fn set_diff_channel(
+        &mut self,
+        reference: &GaussianCloud,
+        metric: GaussianCloudDiffMetric,
+    ) -> Result<Vec2, GaussianCloudDiffError> {
+        let deltas = self.diff(reference, metric)?;
+
+        let max_delta = deltas.iter()
+            .copied()
+            .filter(|delta| delta.is_finite())
+            .fold(0.0, f32::max);
+
+        self.set_channel(deltas);
+
+        Ok(Vec2::new(0.0, max_delta.max(f32::EPSILON)))
+    }
+}
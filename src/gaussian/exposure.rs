@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    gaussian::fade::{
+        GaussianCloudLoaded,
+        send_loaded_events,
+    },
+    material::spherical_harmonics::{
+        SH_C0,
+        SH_CHANNELS,
+    },
+};
+
+
+// rec. 709 luma weights
+const LUMINANCE_WEIGHTS: Vec3 = Vec3::new(0.2126, 0.7152, 0.0722);
+
+// darker clouds keep an exposure of 1.0 rather than being amplified from noise
+const MIN_MEAN_LUMINANCE: f32 = 1e-4;
+
+
+impl GaussianCloud {
+    // luminance of a gaussian's view independent (dc) color, as the shader evaluates it before exposure. hdr
+    // captures are not clamped above 1.0
+    pub fn dc_luminance(&self, index: usize) -> f32 {
+        let spherical_harmonic = self.spherical_harmonic(index);
+
+        let mut rgb = Vec3::ZERO;
+        for channel in 0..SH_CHANNELS {
+            rgb[channel] = (0.5 + SH_C0 * spherical_harmonic.get(channel)).max(0.0);
+        }
+
+        rgb.dot(LUMINANCE_WEIGHTS)
+    }
+
+    // opacity weighted mean of dc_luminance. transparent gaussians barely contribute to the image, so they barely
+    // contribute here
+    pub fn mean_luminance(&self) -> f32 {
+        let (weighted_sum, total_weight) = (0..self.len())
+            .map(|index| (self.dc_luminance(index), self.opacity(index)))
+            .filter(|(luminance, opacity)| luminance.is_finite() && opacity.is_finite() && *opacity > 0.0)
+            .fold((0.0, 0.0), |(sum, weight), (luminance, opacity)| (sum + luminance * opacity, weight + opacity));
+
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        weighted_sum / total_weight
+    }
+
+    // exposure that scales the mean luminance to `target`, 1.0 for empty or black clouds
+    pub fn auto_exposure(&self, target: f32) -> f32 {
+        let mean_luminance = self.mean_luminance();
+        if mean_luminance < MIN_MEAN_LUMINANCE {
+            return 1.0;
+        }
+
+        target / mean_luminance
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianCloudExposurePlugin;
+
+impl Plugin for GaussianCloudExposurePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_auto_exposure.after(send_loaded_events),
+        );
+    }
+}
+
+
+// exposure is only set on load, so later writes to GaussianCloudSettings::exposure override it until the next reload
+fn apply_auto_exposure(
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut ev_loaded: EventReader<GaussianCloudLoaded>,
+    mut gaussian_clouds: Query<&mut GaussianCloudSettings>,
+) {
+    for ev in ev_loaded.read() {
+        let Ok(mut settings) = gaussian_clouds.get_mut(ev.entity) else {
+            continue;
+        };
+
+        let Some(target) = settings.auto_exposure_target else {
+            continue;
+        };
+
+        let Some(cloud) = gaussian_clouds_res.get(ev.cloud) else {
+            continue;
+        };
+
+        settings.exposure = cloud.auto_exposure(target);
+    }
+}
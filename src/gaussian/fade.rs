@@ -0,0 +1,145 @@
+use bevy::{
+    prelude::*,
+    utils::HashSet,
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+};
+
+
+// sent once a cloud entity's asset is available to render, again after the asset is reloaded or the handle changes
+#[derive(Event, Clone, Debug)]
+pub struct GaussianCloudLoaded {
+    pub entity: Entity,
+    pub cloud: AssetId<GaussianCloud>,
+}
+
+
+// ramps GaussianCloudSettings::global_opacity from 0 to 1 over `duration` seconds after each GaussianCloudLoaded,
+// a zero duration disables the fade
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct GaussianFadeIn {
+    pub duration: f32,
+}
+
+impl Default for GaussianFadeIn {
+    fn default() -> Self {
+        Self {
+            duration: 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct GaussianFadeInProgress {
+    elapsed: f32,
+}
+
+
+#[derive(Default)]
+pub struct GaussianCloudFadePlugin;
+
+impl Plugin for GaussianCloudFadePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianFadeIn>();
+        app.add_event::<GaussianCloudLoaded>();
+
+        app.add_systems(
+            Update,
+            (
+                send_loaded_events,
+                start_fade_in,
+                animate_fade_in,
+            ).chain(),
+        );
+    }
+}
+
+
+pub(crate) fn send_loaded_events(
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    mut ev_loaded: EventWriter<GaussianCloudLoaded>,
+    gaussian_clouds: Query<(Entity, Ref<Handle<GaussianCloud>>)>,
+    mut announced: Local<HashSet<Entity>>,
+) {
+    let modified = ev_asset.read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    announced.retain(|entity| gaussian_clouds.contains(*entity));
+
+    for (entity, handle) in gaussian_clouds.iter() {
+        if handle.is_changed() || modified.contains(&handle.id()) {
+            announced.remove(&entity);
+        }
+
+        if announced.contains(&entity) || !gaussian_clouds_res.contains(handle.id()) {
+            continue;
+        }
+
+        announced.insert(entity);
+        ev_loaded.send(GaussianCloudLoaded {
+            entity,
+            cloud: handle.id(),
+        });
+    }
+}
+
+fn start_fade_in(
+    mut commands: Commands,
+    mut ev_loaded: EventReader<GaussianCloudLoaded>,
+    mut gaussian_clouds: Query<(&GaussianFadeIn, &mut GaussianCloudSettings)>,
+) {
+    for ev in ev_loaded.read() {
+        let Ok((fade_in, mut settings)) = gaussian_clouds.get_mut(ev.entity) else {
+            continue;
+        };
+
+        if fade_in.duration <= 0.0 {
+            continue;
+        }
+
+        settings.global_opacity = 0.0;
+        commands.entity(ev.entity).insert(GaussianFadeInProgress {
+            elapsed: 0.0,
+        });
+    }
+}
+
+fn animate_fade_in(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut gaussian_clouds: Query<(
+        Entity,
+        &GaussianFadeIn,
+        &mut GaussianFadeInProgress,
+        &mut GaussianCloudSettings,
+    )>,
+) {
+    for (
+        entity,
+        fade_in,
+        mut progress,
+        mut settings,
+    ) in gaussian_clouds.iter_mut() {
+        progress.elapsed += time.delta_seconds();
+
+        let t = if fade_in.duration > 0.0 {
+            (progress.elapsed / fade_in.duration).min(1.0)
+        } else {
+            1.0
+        };
+        settings.global_opacity = t;
+
+        if t >= 1.0 {
+            commands.entity(entity).remove::<GaussianFadeInProgress>();
+        }
+    }
+}
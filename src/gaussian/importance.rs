@@ -0,0 +1,97 @@
+use std::sync::{
+    Arc,
+    OnceLock,
+};
+
+use crate::GaussianCloud;
+
+
+// lazily computed on first use and shared between clones
+#[derive(Clone, Debug, Default)]
+pub struct GaussianCloudImportance {
+    scores: OnceLock<Arc<Vec<f32>>>,
+    order: OnceLock<Arc<Vec<usize>>>,
+}
+
+// the scores are derived data, so they never affect cloud equality
+impl PartialEq for GaussianCloudImportance {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+
+impl GaussianCloud {
+    // view independent importance of each gaussian, opacity * max_scale^2 (proportional to the largest area it can
+    // cover on screen at a given distance, opacity alone when scales are precomputed away), times
+    // 1 + luminance_weight * dc_luminance so bright gaussians of hdr captures outrank dim ones of the same coverage.
+    // dividing by the squared view distance gives the view dependent importance the splat budget ranks by
+    pub fn compute_importance(&self) -> Vec<f32> {
+        let luminance_weight = self.importance_luminance_weight;
+
+        (0..self.len())
+            .map(|index| {
+                #[cfg(not(feature = "precompute_covariance_3d"))]
+                let footprint = {
+                    let scale = self.gaussian(index).scale_opacity.scale;
+                    let max_scale = scale[0].max(scale[1]).max(scale[2]);
+
+                    max_scale * max_scale
+                };
+
+                #[cfg(feature = "precompute_covariance_3d")]
+                let footprint = 1.0;
+
+                let brightness = if luminance_weight == 0.0 {
+                    1.0
+                } else {
+                    1.0 + luminance_weight * self.dc_luminance(index)
+                };
+
+                let importance = self.opacity(index) * footprint * brightness;
+                if importance.is_finite() { importance } else { 0.0 }
+            })
+            .collect()
+    }
+
+    // compute_importance, cached until the cloud is edited
+    pub fn importance(&self) -> &[f32] {
+        self.importance.scores.get_or_init(|| Arc::new(self.compute_importance()))
+    }
+
+    // gaussian indices from most to least important, ties keep cloud order
+    pub fn importance_order(&self) -> &[usize] {
+        self.importance.order.get_or_init(|| {
+            let importance = self.importance();
+
+            let mut order = (0..self.len()).collect::<Vec<_>>();
+            order.sort_by(|a, b| importance[*b].total_cmp(&importance[*a]));
+
+            Arc::new(order)
+        })
+    }
+
+    // GaussianCloudSettings::reveal_fraction grows the cloud from its dominant gaussians to the fine detail, the
+    // gaussians keep their indices
+    pub fn set_importance_reveal_order(&mut self) {
+        let order = self.importance_order().to_vec();
+        self.set_reveal_order(&order);
+    }
+
+    pub fn importance_luminance_weight(&self) -> f32 {
+        self.importance_luminance_weight
+    }
+
+    // 0.0 (default) ranks by opacity and coverage alone, 1.0 doubles the importance of a gaussian with unit dc
+    // luminance. negative weights are treated as 0.0. edits, subsets and serialization keep the weight
+    pub fn set_importance_luminance_weight(&mut self, weight: f32) {
+        self.importance_luminance_weight = weight.max(0.0);
+        self.invalidate_importance();
+    }
+
+    // required after writing gaussian fields directly, set_gaussian, spherical_harmonic_mut, apply_patch,
+    // resize_to_square and set_importance_luminance_weight call it
+    pub fn invalidate_importance(&mut self) {
+        self.importance = GaussianCloudImportance::default();
+    }
+}
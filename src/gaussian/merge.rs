@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+use crate::GaussianCloud;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use bevy::utils::HashMap;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use crate::{
+    Gaussian,
+    material::spherical_harmonics::SH_COEFF_COUNT,
+};
+
+
+// see GaussianCloud::merge_dedup
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+pub struct GaussianCloudMergeSettings {
+    // gaussians whose centers are closer than this (in cloud units) are candidates for collapsing
+    pub position_epsilon: f32,
+    // minimum |q1 . q2| of the unit rotations, 1.0 only collapses identical orientations. q and -q are the same
+    // rotation, so the sign is ignored
+    pub rotation_similarity: f32,
+}
+
+impl Default for GaussianCloudMergeSettings {
+    fn default() -> Self {
+        Self {
+            position_epsilon: 1e-3,
+            rotation_similarity: 0.99,
+        }
+    }
+}
+
+
+impl GaussianCloud {
+    // concatenates the clouds in order. the storage is appended as is, so both covariance layouts merge
+    pub fn merge<'a>(clouds: impl IntoIterator<Item = &'a GaussianCloud>) -> GaussianCloud {
+        let mut merged = GaussianCloud::default();
+
+        for cloud in clouds {
+            merged.position_visibility.extend_from_slice(&cloud.position_visibility);
+            merged.spherical_harmonic.extend_from_slice(&cloud.spherical_harmonic);
+
+            #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+            merged.covariance_3d_opacity_packed128.extend_from_slice(&cloud.covariance_3d_opacity_packed128);
+            #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+            merged.rotation_scale_opacity_packed128.extend_from_slice(&cloud.rotation_scale_opacity_packed128);
+
+            #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+            merged.covariance_3d.extend_from_slice(&cloud.covariance_3d);
+            #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+            {
+                merged.rotation.extend_from_slice(&cloud.rotation);
+                merged.scale_opacity.extend_from_slice(&cloud.scale_opacity);
+            }
+        }
+
+        merged.resize_to_square();
+        merged
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+struct Cluster {
+    // the first gaussian of the cluster, later ones are compared against it so clusters never drift
+    anchor: Gaussian,
+    count: usize,
+    position: Vec3,
+    scale: Vec3,
+    opacity: f32,
+    rotation: Vec4,
+    spherical_harmonic: [f32; SH_COEFF_COUNT],
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl Cluster {
+    fn new(gaussian: Gaussian) -> Self {
+        let mut cluster = Self {
+            anchor: gaussian,
+            count: 0,
+            position: Vec3::ZERO,
+            scale: Vec3::ZERO,
+            opacity: 0.0,
+            rotation: Vec4::ZERO,
+            spherical_harmonic: [0.0; SH_COEFF_COUNT],
+        };
+
+        cluster.add(&gaussian);
+        cluster
+    }
+
+    fn add(&mut self, gaussian: &Gaussian) {
+        let rotation = Vec4::from_array(gaussian.rotation.rotation);
+
+        // flip into the anchor's hemisphere so opposite signed quaternions do not cancel
+        let sign = if rotation.dot(Vec4::from_array(self.anchor.rotation.rotation)) < 0.0 { -1.0 } else { 1.0 };
+
+        self.count += 1;
+        self.position += Vec3::from_array(gaussian.position_visibility.position);
+        self.scale += Vec3::from_array(gaussian.scale_opacity.scale);
+        self.opacity += gaussian.scale_opacity.opacity;
+        self.rotation += rotation * sign;
+
+        for (index, coefficient) in self.spherical_harmonic.iter_mut().enumerate() {
+            *coefficient += gaussian.spherical_harmonic.get(index);
+        }
+    }
+
+    fn matches(&self, gaussian: &Gaussian, settings: &GaussianCloudMergeSettings) -> bool {
+        let anchor_position = Vec3::from_array(self.anchor.position_visibility.position);
+        let position = Vec3::from_array(gaussian.position_visibility.position);
+        if anchor_position.distance_squared(position) > settings.position_epsilon * settings.position_epsilon {
+            return false;
+        }
+
+        let anchor_rotation = Vec4::from_array(self.anchor.rotation.rotation).normalize_or_zero();
+        let rotation = Vec4::from_array(gaussian.rotation.rotation).normalize_or_zero();
+
+        anchor_rotation.dot(rotation).abs() >= settings.rotation_similarity
+    }
+
+    fn average(&self) -> Gaussian {
+        let weight = 1.0 / self.count as f32;
+        let mut gaussian = self.anchor;
+
+        gaussian.position_visibility.position = (self.position * weight).to_array();
+        gaussian.scale_opacity.scale = (self.scale * weight).to_array();
+        gaussian.scale_opacity.opacity = self.opacity * weight;
+
+        let rotation = self.rotation.normalize_or_zero();
+        if rotation != Vec4::ZERO {
+            gaussian.rotation.rotation = rotation.to_array();
+        }
+
+        for (index, coefficient) in self.spherical_harmonic.iter().enumerate() {
+            gaussian.spherical_harmonic.set(index, coefficient * weight);
+        }
+
+        gaussian
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GaussianCloud {
+    // concatenates the clouds, then collapses gaussians whose centers are within position_epsilon of each other and
+    // whose rotations are at least rotation_similarity alike into one gaussian with the averaged position, scale,
+    // rotation, opacity, and sh. averaging rather than summing the opacity keeps overlapping captures of the same
+    // surface from double counting it. gaussians are visited in order and join the first matching cluster, so the
+    // result is deterministic and keeps the order of each cluster's first gaussian. a non positive epsilon only
+    // concatenates
+    pub fn merge_dedup<'a>(
+        clouds: impl IntoIterator<Item = &'a GaussianCloud>,
+        settings: &GaussianCloudMergeSettings,
+    ) -> GaussianCloud {
+        let merged = GaussianCloud::merge(clouds);
+        if settings.position_epsilon <= 0.0 {
+            return merged;
+        }
+
+        let cell_size = settings.position_epsilon;
+        let cell = |position: Vec3| (position / cell_size).floor().as_ivec3();
+
+        let mut clusters: Vec<Cluster> = Vec::new();
+        let mut grid: HashMap<IVec3, Vec<usize>> = HashMap::new();
+
+        for gaussian in merged.gaussian_iter() {
+            let position = Vec3::from_array(gaussian.position_visibility.position);
+            let center = cell(position);
+
+            // cells are one epsilon wide, so every match lies in the surrounding 27
+            let mut matched: Option<usize> = None;
+            for z in -1..=1 {
+                for y in -1..=1 {
+                    for x in -1..=1 {
+                        let Some(candidates) = grid.get(&(center + IVec3::new(x, y, z))) else {
+                            continue;
+                        };
+
+                        for &candidate in candidates {
+                            if matched.is_none_or(|first| first >= candidate)
+                                && clusters[candidate].matches(&gaussian, settings)
+                            {
+                                matched = Some(candidate);
+                            }
+                        }
+                    }
+                }
+            }
+
+            match matched {
+                Some(cluster) => clusters[cluster].add(&gaussian),
+                None => {
+                    grid.entry(center).or_default().push(clusters.len());
+                    clusters.push(Cluster::new(gaussian));
+                }
+            }
+        }
+
+        clusters.iter()
+            .map(Cluster::average)
+            .collect()
+    }
+}
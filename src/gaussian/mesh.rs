@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use bevy::render::{
+    mesh::{
+        Indices,
+        PrimitiveTopology,
+    },
+    render_asset::RenderAssetUsages,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use crate::{
+    GaussianCloud,
+    gaussian::packed::Gaussian,
+    material::spherical_harmonics::{
+        SH_C0,
+        SH_CHANNELS,
+    },
+};
+
+
+// Ellipsoids closes each gaussian into a low poly ellipsoid, Billboards emits one quad per gaussian spanning its two
+// largest axes (far fewer triangles, but single sided, so draw it without backface culling)
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudMeshMode {
+    #[default]
+    Ellipsoids,
+    Billboards,
+}
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+pub struct GaussianCloudMeshSettings {
+    pub mode: GaussianCloudMeshMode,
+    // gaussians below this opacity are left out, higher values keep only the surface cores
+    pub opacity_threshold: f32,
+    // half extent of each ellipsoid or quad in standard deviations
+    pub sigma: f32,
+    // latitude rings per ellipsoid, with twice as many segments around, clamped to at least 2 (an octahedron)
+    pub rings: u32,
+    // keeps the most opaque gaussians when set, bounding the triangle count
+    pub max_gaussians: Option<usize>,
+}
+
+impl Default for GaussianCloudMeshSettings {
+    fn default() -> Self {
+        Self {
+            mode: GaussianCloudMeshMode::default(),
+            opacity_threshold: 0.5,
+            sigma: 1.0,
+            rings: 4,
+            max_gaussians: None,
+        }
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[derive(Default)]
+struct MeshBuffers {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GaussianCloud {
+    // rough triangle mesh for engines without a splat renderer. every kept gaussian becomes a solid shape with its
+    // view independent (dc) color and opacity as vertex colors, so view dependent color, soft falloff and the
+    // blending between overlapping gaussians are lost. the surface is a union of overlapping shapes rather than
+    // a watertight hull, and fidelity follows the capture density: dense captures read as a bumpy surface, sparse
+    // ones as separate blobs. each ellipsoid costs (rings + 1) * (2 * rings + 1) vertices and 4 * rings^2 triangles,
+    // each billboard 4 vertices and 2 triangles
+    pub fn to_mesh(&self, settings: &GaussianCloudMeshSettings) -> Mesh {
+        let mut kept = self.gaussian_iter()
+            .filter(|gaussian| gaussian.scale_opacity.opacity >= settings.opacity_threshold)
+            .collect::<Vec<_>>();
+
+        if let Some(max_gaussians) = settings.max_gaussians {
+            kept.sort_by(|a, b| b.scale_opacity.opacity.total_cmp(&a.scale_opacity.opacity));
+            kept.truncate(max_gaussians);
+        }
+
+        let mut buffers = MeshBuffers::default();
+        for gaussian in kept.iter() {
+            match settings.mode {
+                GaussianCloudMeshMode::Ellipsoids => push_ellipsoid(&mut buffers, gaussian, settings),
+                GaussianCloudMeshMode::Billboards => push_billboard(&mut buffers, gaussian, settings),
+            }
+        }
+
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, buffers.positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, buffers.normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, buffers.colors)
+            .with_inserted_indices(Indices::U32(buffers.indices))
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn frame(gaussian: &Gaussian) -> (Vec3, Quat, Vec3) {
+    let [w, x, y, z] = gaussian.rotation.rotation;
+
+    (
+        Vec3::from_array(gaussian.position_visibility.position),
+        Quat::from_xyzw(x, y, z, w).normalize(),
+        Vec3::from_array(gaussian.scale_opacity.scale).max(Vec3::splat(f32::EPSILON)),
+    )
+}
+
+// linear vertex color, the dc term evaluates to srgb like the splat shader
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn color(gaussian: &Gaussian) -> [f32; 4] {
+    let mut rgb = [0.0; SH_CHANNELS];
+    for (channel, value) in rgb.iter_mut().enumerate() {
+        *value = (0.5 + SH_C0 * gaussian.spherical_harmonic.get(channel)).clamp(0.0, 1.0);
+    }
+
+    let linear = Color::srgb(rgb[0], rgb[1], rgb[2]).to_linear();
+
+    [linear.red, linear.green, linear.blue, gaussian.scale_opacity.opacity]
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn push_ellipsoid(
+    buffers: &mut MeshBuffers,
+    gaussian: &Gaussian,
+    settings: &GaussianCloudMeshSettings,
+) {
+    let (position, rotation, scale) = frame(gaussian);
+    let radii = scale * settings.sigma;
+    let color = color(gaussian);
+
+    let rings = settings.rings.max(2);
+    let segments = rings * 2;
+    let first = buffers.positions.len() as u32;
+
+    for ring in 0..=rings {
+        let polar = std::f32::consts::PI * ring as f32 / rings as f32;
+
+        for segment in 0..=segments {
+            let azimuth = std::f32::consts::TAU * segment as f32 / segments as f32;
+            let unit = Vec3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            );
+
+            buffers.positions.push((position + rotation * (unit * radii)).to_array());
+            buffers.normals.push((rotation * (unit / radii)).normalize_or_zero().to_array());
+            buffers.colors.push(color);
+        }
+    }
+
+    let stride = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = first + ring * stride + segment;
+            let b = a + stride;
+
+            buffers.indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn push_billboard(
+    buffers: &mut MeshBuffers,
+    gaussian: &Gaussian,
+    settings: &GaussianCloudMeshSettings,
+) {
+    let (position, rotation, scale) = frame(gaussian);
+    let color = color(gaussian);
+
+    // the smallest axis is the normal, the other two span the quad
+    let axes = [Vec3::X, Vec3::Y, Vec3::Z];
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| scale[*b].total_cmp(&scale[*a]));
+
+    let u = rotation * axes[order[0]] * scale[order[0]] * settings.sigma;
+    let v = rotation * axes[order[1]] * scale[order[1]] * settings.sigma;
+    let normal = u.cross(v).normalize_or_zero();
+
+    let first = buffers.positions.len() as u32;
+    for corner in [-u - v, u - v, u + v, -u + v] {
+        buffers.positions.push((position + corner).to_array());
+        buffers.normals.push(normal.to_array());
+        buffers.colors.push(color);
+    }
+
+    buffers.indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+}
@@ -1,11 +1,34 @@
 use static_assertions::assert_cfg;
 
 pub mod cloud;
+pub mod cluster;
 pub mod covariance;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub mod density;
+
+pub mod diff;
+pub mod exposure;
+pub mod fade;
+pub mod importance;
+pub mod merge;
 pub mod f32;
+pub mod mesh;
+pub mod morton;
+pub mod normal;
 pub mod packed;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub mod paint;
+
+pub mod patch;
 pub mod rand;
+pub mod resample;
+pub mod sequence;
 pub mod settings;
+pub mod snapshot;
+pub mod spatial;
+pub mod stats;
 
 #[cfg(feature = "f16")]
 pub mod f16;
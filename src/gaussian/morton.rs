@@ -0,0 +1,70 @@
+use bevy::math::Vec3;
+
+use crate::GaussianCloud;
+
+
+// 21 bits per axis fill a 63 bit code
+const MORTON_BITS_PER_AXIS: u32 = 21;
+
+
+// spreads the low 21 bits of `value` two bits apart, so three spread axes interleave without overlap
+fn spread_bits(value: u32) -> u64 {
+    let mut x = value as u64 & 0x1f_ffff;
+    x = (x | x << 32) & 0x001f_0000_0000_ffff;
+    x = (x | x << 16) & 0x001f_0000_ff00_00ff;
+    x = (x | x << 8) & 0x100f_00f0_0f00_f00f;
+    x = (x | x << 4) & 0x10c3_0c30_c30c_30c3;
+    x = (x | x << 2) & 0x1249_2492_4924_9249;
+    x
+}
+
+// z-order code of `position` within the box starting at `min` with side lengths `extent`. positions outside the box
+// (or nan) clamp to its faces
+pub fn morton_code(position: Vec3, min: Vec3, extent: Vec3) -> u64 {
+    let max_cell = ((1u32 << MORTON_BITS_PER_AXIS) - 1) as f32;
+    let normalized = (position - min) / extent.max(Vec3::splat(f32::EPSILON));
+    let cell = (normalized * max_cell).clamp(Vec3::ZERO, Vec3::splat(max_cell));
+
+    spread_bits(cell.x as u32)
+        | spread_bits(cell.y as u32) << 1
+        | spread_bits(cell.z as u32) << 2
+}
+
+
+impl GaussianCloud {
+    // permutation sorting the gaussians by the morton code of their centers over the cloud aabb. the sort is stable,
+    // gaussians sharing a cell keep their relative order
+    pub fn morton_order(&self) -> Vec<usize> {
+        let aabb = self.aabb();
+        let min = Vec3::from(aabb.min());
+        let extent = Vec3::from(aabb.half_extents) * 2.0;
+
+        let codes = self.position_iter()
+            .map(|position| morton_code(Vec3::from_array(*position), min, extent))
+            .collect::<Vec<_>>();
+
+        let mut order = (0..self.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| codes[index]);
+
+        order
+    }
+
+    // reorders the gaussians along a z-order curve, so each contiguous chunk (e.g. of a streamed file written with
+    // io::ply::write_ply) covers one compact region and a partial load fills in the cloud region by region
+    pub fn morton_reorder(&mut self) {
+        let order = self.morton_order();
+        *self = self.subset(&order);
+    }
+
+    // splits the cloud into spatially compact clouds of at most `max_count` gaussians each, contiguous runs along
+    // the z-order curve. for clouds past a device's buffer limits, each chunk gets its own buffers and sort, and the
+    // transparent phase orders the chunk entities by their centers
+    pub fn morton_chunks(&self, max_count: usize) -> Vec<GaussianCloud> {
+        assert!(max_count > 0, "morton chunks need room for at least one gaussian");
+
+        self.morton_order()
+            .chunks(max_count)
+            .map(|indices| self.subset(indices))
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{
+    Gaussian,
+    GaussianCloud,
+};
+
+
+// a shortest axis at or above this fraction of the middle axis has no preferred direction
+const SPHERICAL_AXIS_RATIO: f32 = 0.9;
+
+
+// unit shortest axis of the gaussian flipped to point away from `outward_from`, zero for near spherical gaussians
+// (shortest axis within 10% of the middle one) and degenerate rotations, which have no meaningful surface direction
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+pub fn gaussian_normal(gaussian: &Gaussian, outward_from: Vec3) -> Vec3 {
+    let [w, x, y, z] = gaussian.rotation.rotation;
+    let rotation = Quat::from_xyzw(x, y, z, w).normalize();
+    let scale = gaussian.scale_opacity.scale;
+
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| scale[*a].total_cmp(&scale[*b]));
+
+    // negated so nan scales count as spherical
+    if !(scale[order[0]] < SPHERICAL_AXIS_RATIO * scale[order[1]]) {
+        return Vec3::ZERO;
+    }
+
+    let normal = (rotation * Vec3::AXES[order[0]]).normalize_or_zero();
+    let position = Vec3::from_array(gaussian.position_visibility.position);
+
+    if normal.dot(position - outward_from) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+
+impl GaussianCloud {
+    // per gaussian surface normal along the shortest ellipsoid axis, oriented away from the weighted centroid, which
+    // points outward for object centric captures. see gaussian_normal for the zero normals of near spherical gaussians
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    pub fn estimate_normals(&self) -> Vec<Vec3> {
+        let centroid = self.weighted_centroid();
+
+        self.gaussian_iter()
+            .map(|gaussian| gaussian_normal(&gaussian, centroid))
+            .collect()
+    }
+}
@@ -16,7 +16,10 @@ use crate::{
         Rotation,
         ScaleOpacity,
     },
-    material::spherical_harmonics::SphericalHarmonicCoefficients,
+    material::spherical_harmonics::{
+        SH_COEFF_COUNT,
+        SphericalHarmonicCoefficients,
+    },
 };
 
 #[derive(
@@ -38,3 +41,14 @@ pub struct Gaussian {
     pub scale_opacity: ScaleOpacity,
     pub spherical_harmonic: SphericalHarmonicCoefficients,
 }
+
+impl Gaussian {
+    pub fn is_finite(&self) -> bool {
+        self.rotation.rotation.iter()
+            .chain(self.position_visibility.position.iter())
+            .chain(self.scale_opacity.scale.iter())
+            .chain(std::iter::once(&self.scale_opacity.opacity))
+            .all(|value| value.is_finite())
+            && (0..SH_COEFF_COUNT).all(|index| self.spherical_harmonic.get(index).is_finite())
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::gaussian::{
+    cloud::GaussianCloud,
+    packed::Gaussian,
+    patch::{
+        GaussianCloudPatch,
+        GaussianCloudPatchError,
+        GaussianCloudPatches,
+    },
+    snapshot::CloudSnapshot,
+};
+
+#[cfg(feature = "f16")]
+use crate::gaussian::f16::RotationScaleOpacityPacked128;
+
+
+// one brush stroke. `patch` holds the painted gaussians, `undo` restores them
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaussianCloudPaintStroke {
+    pub patch: GaussianCloudPatch,
+    pub undo: CloudSnapshot,
+}
+
+impl GaussianCloudPaintStroke {
+    pub fn is_empty(&self) -> bool {
+        self.patch.is_empty()
+    }
+}
+
+
+impl GaussianCloud {
+    // adds `delta` to the opacity of each indexed gaussian (e.g. picked by GaussianCloud::pick_region), clamped to
+    // [0, 1], so a negative delta brushes floaters transparent. repeated indices are painted once. only opacities are
+    // written, so the spatial index survives and a drag can keep picking against it, the importance cache is reset
+    pub fn paint_opacity(&mut self, indices: &[u32], delta: f32) -> GaussianCloudPaintStroke {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut previous = Vec::with_capacity(indices.len());
+        let mut painted = Vec::with_capacity(indices.len());
+
+        for index in indices.into_iter().map(|index| index as usize) {
+            let mut gaussian = self.gaussian(index);
+            previous.push((index, gaussian));
+
+            gaussian.scale_opacity.opacity = (gaussian.scale_opacity.opacity + delta).clamp(0.0, 1.0);
+            self.write_opacity(index, &gaussian);
+            painted.push((index, gaussian));
+        }
+
+        if !painted.is_empty() {
+            self.invalidate_importance();
+        }
+
+        GaussianCloudPaintStroke {
+            patch: GaussianCloudPatch {
+                updates: painted,
+                ..Default::default()
+            },
+            undo: CloudSnapshot::Diff(GaussianCloudPatch {
+                updates: previous,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[cfg(feature = "f16")]
+    fn write_opacity(&mut self, index: usize, gaussian: &Gaussian) {
+        self.rotation_scale_opacity_packed128[index] = RotationScaleOpacityPacked128::from_gaussian(gaussian);
+    }
+
+    #[cfg(feature = "f32")]
+    fn write_opacity(&mut self, index: usize, gaussian: &Gaussian) {
+        self.scale_opacity[index].opacity = gaussian.scale_opacity.opacity;
+    }
+}
+
+
+impl GaussianCloudPatches {
+    // GaussianCloud::paint_opacity on a cloud asset, the render world then uploads only the painted ranges
+    pub fn paint_opacity(
+        &mut self,
+        clouds: &mut Assets<GaussianCloud>,
+        id: impl Into<AssetId<GaussianCloud>>,
+        indices: &[u32],
+        delta: f32,
+    ) -> Result<GaussianCloudPaintStroke, GaussianCloudPatchError> {
+        let id = id.into();
+        let cloud = clouds.get_mut(id).ok_or(GaussianCloudPatchError::MissingCloud)?;
+
+        let stroke = cloud.paint_opacity(indices, delta);
+        self.record(id, Some(&stroke.patch));
+
+        Ok(stroke)
+    }
+}
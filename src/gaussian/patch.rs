@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::Range,
+};
+
+use bevy::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::gaussian::{
+    cloud::GaussianCloud,
+    packed::Gaussian,
+};
+
+
+// indices in `updates` and `removals` refer to the cloud before the patch is applied,
+// updates are applied first, then removals, then appends
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+pub struct GaussianCloudPatch {
+    pub updates: Vec<(usize, Gaussian)>,
+    pub appends: Vec<Gaussian>,
+    pub removals: Vec<usize>,
+}
+
+impl GaussianCloudPatch {
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.appends.is_empty() && self.removals.is_empty()
+    }
+
+    // in-place patches keep the cloud length, so gpu buffers can be written without reallocation
+    pub fn is_in_place(&self) -> bool {
+        self.appends.is_empty() && self.removals.is_empty()
+    }
+
+    pub fn dirty_ranges(&self) -> Vec<Range<usize>> {
+        merge_dirty_ranges(self.updates.iter().map(|(index, _)| *index..*index + 1).collect())
+    }
+
+    // every update and removal must index a gaussian of the cloud the patch is applied to
+    pub fn validate(&self, count: usize) -> Result<(), GaussianCloudPatchError> {
+        let index = self.updates.iter()
+            .map(|(index, _)| *index)
+            .chain(self.removals.iter().copied())
+            .find(|index| *index >= count);
+
+        match index {
+            Some(index) => Err(GaussianCloudPatchError::IndexOutOfRange { index, count }),
+            None => Ok(()),
+        }
+    }
+}
+
+// sorted, with overlapping and adjacent ranges joined
+fn merge_dirty_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GaussianCloudPatchError {
+    IndexOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    MissingCloud,
+}
+
+impl fmt::Display for GaussianCloudPatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange { index, count } => write!(
+                f,
+                "patch indexes gaussian {} of a cloud of {}",
+                index,
+                count,
+            ),
+            Self::MissingCloud => write!(f, "patched cloud asset is not loaded"),
+        }
+    }
+}
+
+impl std::error::Error for GaussianCloudPatchError {}
+
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PendingCloudUploads {
+    // Assets::get_mut calls made by GaussianCloudPatches::apply, matched against the cloud's Modified events
+    pub modifications: usize,
+    pub dirty_ranges: Vec<Range<usize>>,
+    pub full_upload: bool,
+}
+
+// applies patches to cloud assets and remembers their dirty ranges, so the render world writes only those ranges
+// into the cloud's existing buffers instead of preparing it again. the ranges are dropped, and the cloud is uploaded
+// whole, when it is also modified another way before it is extracted or a patch changes its length
+#[derive(Debug, Default, Resource)]
+pub struct GaussianCloudPatches {
+    pub(crate) pending: HashMap<AssetId<GaussianCloud>, PendingCloudUploads>,
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GaussianCloudPatches {
+    pub fn apply(
+        &mut self,
+        clouds: &mut Assets<GaussianCloud>,
+        id: impl Into<AssetId<GaussianCloud>>,
+        patch: &GaussianCloudPatch,
+    ) -> Result<(), GaussianCloudPatchError> {
+        let id = id.into();
+        let cloud = clouds.get_mut(id).ok_or(GaussianCloudPatchError::MissingCloud)?;
+
+        // get_mut marked the cloud modified either way, a failed patch is uploaded whole
+        let result = cloud.apply_patch(patch);
+        self.record(id, result.is_ok().then_some(patch));
+
+        result
+    }
+
+    // counts one get_mut of the cloud, which uploads only the patch's dirty ranges if it is in place
+    pub(crate) fn record(&mut self, id: AssetId<GaussianCloud>, patch: Option<&GaussianCloudPatch>) {
+        let pending = self.pending.entry(id).or_default();
+        pending.modifications += 1;
+
+        match patch {
+            Some(patch) if patch.is_in_place() => {
+                pending.dirty_ranges.extend(patch.dirty_ranges());
+                pending.dirty_ranges = merge_dirty_ranges(std::mem::take(&mut pending.dirty_ranges));
+            },
+            _ => pending.full_upload = true,
+        }
+    }
+}
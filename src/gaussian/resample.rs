@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use bevy::utils::HashMap;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use crate::GaussianCloud;
+
+
+// bounds the work per gaussian, larger gaussians only cover the voxels this close to their center
+#[cfg(not(feature = "precompute_covariance_3d"))]
+const MAX_FOOTPRINT_CELLS: i32 = 8;
+
+// each gaussian covers voxels out to this many standard deviations of its largest axis
+#[cfg(not(feature = "precompute_covariance_3d"))]
+const FOOTPRINT_SIGMA: f32 = 2.0;
+
+
+// experimental hole filling, see GaussianCloud::resample
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+pub struct GaussianCloudResampleSettings {
+    // voxels along the longest axis of the cloud's bounds
+    pub resolution: u32,
+    // accumulated opacity a voxel should reach, voxels below it are topped up with a new gaussian
+    pub target_density: f32,
+    // gaussians below this opacity add no coverage and are never duplicated
+    pub opacity_threshold: f32,
+}
+
+impl Default for GaussianCloudResampleSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 128,
+            target_density: 0.5,
+            opacity_threshold: 0.1,
+        }
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+struct Coverage {
+    density: f32,
+    // strongest contributor, the new gaussian copies its color and rotation
+    source: usize,
+    source_weight: f32,
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GaussianCloud {
+    // returns a copy of the cloud with gaps filled:
+    //   1. every gaussian at or above opacity_threshold is splatted into a regular grid of `resolution` voxels along
+    //      the longest axis, adding opacity * exp(-d^2 / (2 sigma^2)) to each voxel center within FOOTPRINT_SIGMA
+    //      sigma of the gaussian, where sigma is its largest scale axis
+    //   2. voxels reached by some gaussian whose accumulated density is below target_density are thin, each gets a
+    //      new isotropic gaussian at its center sized to the voxel, with opacity target_density - density and the
+    //      color and rotation of the voxel's strongest contributor
+    // voxels no gaussian reaches stay empty, so empty space around the capture is never filled. the original
+    // gaussians are kept unchanged and come first
+    pub fn resample(&self, settings: &GaussianCloudResampleSettings) -> GaussianCloud {
+        let gaussians = self.gaussian_iter().collect::<Vec<_>>();
+        if gaussians.is_empty() || settings.resolution == 0 {
+            return self.clone();
+        }
+
+        let (min, max) = gaussians.iter()
+            .map(|gaussian| Vec3::from_array(gaussian.position_visibility.position))
+            .fold(
+                (Vec3::INFINITY, Vec3::NEG_INFINITY),
+                |(min, max), position| (min.min(position), max.max(position)),
+            );
+
+        let cell_size = ((max - min).max_element() / settings.resolution as f32).max(f32::EPSILON);
+        let cell_center = |cell: IVec3| min + (cell.as_vec3() + 0.5) * cell_size;
+
+        let mut coverage: HashMap<IVec3, Coverage> = HashMap::new();
+
+        for (index, gaussian) in gaussians.iter().enumerate() {
+            let opacity = gaussian.scale_opacity.opacity;
+            if opacity < settings.opacity_threshold {
+                continue;
+            }
+
+            let position = Vec3::from_array(gaussian.position_visibility.position);
+            let sigma = Vec3::from_array(gaussian.scale_opacity.scale).max_element().max(f32::EPSILON);
+
+            let center = ((position - min) / cell_size).floor().as_ivec3();
+            let radius = ((sigma * FOOTPRINT_SIGMA / cell_size).ceil() as i32).min(MAX_FOOTPRINT_CELLS);
+
+            for z in -radius..=radius {
+                for y in -radius..=radius {
+                    for x in -radius..=radius {
+                        let cell = center + IVec3::new(x, y, z);
+                        let distance_squared = cell_center(cell).distance_squared(position);
+
+                        if distance_squared > (sigma * FOOTPRINT_SIGMA).powi(2) && (x, y, z) != (0, 0, 0) {
+                            continue;
+                        }
+
+                        let weight = opacity * (-distance_squared / (2.0 * sigma * sigma)).exp();
+                        let voxel = coverage.entry(cell).or_insert(Coverage {
+                            density: 0.0,
+                            source: index,
+                            source_weight: 0.0,
+                        });
+
+                        voxel.density += weight;
+                        if weight > voxel.source_weight {
+                            voxel.source = index;
+                            voxel.source_weight = weight;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut fills = coverage.iter()
+            .filter(|(_, voxel)| voxel.density < settings.target_density)
+            .map(|(cell, voxel)| (*cell, voxel.source, settings.target_density - voxel.density))
+            .collect::<Vec<_>>();
+
+        // hash map order is arbitrary, keep the output deterministic
+        fills.sort_unstable_by_key(|(cell, _, _)| (cell.z, cell.y, cell.x));
+
+        let mut channel = self.channel.clone();
+        let mut resampled = gaussians;
+
+        for (cell, source, opacity) in fills {
+            let mut gaussian = resampled[source];
+
+            gaussian.position_visibility.position = cell_center(cell).to_array();
+            gaussian.scale_opacity.scale = [cell_size * 0.5; 3];
+            gaussian.scale_opacity.opacity = opacity.min(1.0);
+
+            resampled.push(gaussian);
+
+            if !channel.is_empty() {
+                channel.push(channel[source]);
+            }
+        }
+
+        let mut cloud = GaussianCloud::from_gaussians(resampled);
+        // the filled gaussians have no place in the reveal order, it is dropped like on appends
+        cloud.channel = channel;
+        cloud.importance_luminance_weight = self.importance_luminance_weight;
+
+        cloud
+    }
+}
@@ -0,0 +1,213 @@
+use bevy::{
+    asset::AssetPath,
+    prelude::*,
+};
+
+use crate::{
+    GaussianCloud,
+    gaussian::fade::send_loaded_events,
+    sort::{
+        SortedEntries,
+        status::ResortGaussianClouds,
+    },
+};
+
+
+// frames of a captured dynamic scene, one cloud file per frame. frames are streamed: a playing entity keeps only the
+// frame it shows and the one after it loaded, see GaussianSequencePlayback
+#[derive(
+    Asset,
+    Clone,
+    Debug,
+    Default,
+    Reflect,
+)]
+pub struct GaussianSequence {
+    pub frames: Vec<AssetPath<'static>>,
+}
+
+impl GaussianSequence {
+    pub fn new<'a>(
+        paths: impl IntoIterator<Item = impl Into<AssetPath<'a>>>,
+    ) -> Self {
+        Self {
+            frames: paths.into_iter()
+                .map(|path| path.into().into_owned())
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&AssetPath<'static>> {
+        self.frames.get(index)
+    }
+}
+
+
+// swaps the entity's Handle<GaussianCloud> through the sequence frames at `fps`
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct GaussianSequencePlayback {
+    pub sequence: Handle<GaussianSequence>,
+    pub fps: f32,
+    pub looping: bool,
+    pub playing: bool,
+    pub elapsed: f32,
+}
+
+impl Default for GaussianSequencePlayback {
+    fn default() -> Self {
+        Self {
+            sequence: Handle::default(),
+            fps: 30.0,
+            looping: true,
+            playing: true,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl GaussianSequencePlayback {
+    // frame shown after `elapsed` seconds of a sequence of `len` frames, the last frame holds once a non looping
+    // sequence ends
+    pub fn frame_index(&self, len: usize) -> usize {
+        if len == 0 || self.fps <= 0.0 || !self.elapsed.is_finite() {
+            return 0;
+        }
+
+        let frame = (self.elapsed.max(0.0) * self.fps) as usize;
+        if self.looping {
+            frame % len
+        } else {
+            frame.min(len - 1)
+        }
+    }
+
+    pub fn finished(&self, len: usize) -> bool {
+        !self.looping && self.elapsed * self.fps >= len as f32
+    }
+
+    // frame prefetched while `frame_index` is shown, none past the end of a non looping sequence
+    pub fn next_frame_index(&self, len: usize) -> Option<usize> {
+        let next = self.frame_index(len) + 1;
+
+        match (next < len, self.looping) {
+            (true, _) => Some(next),
+            (false, true) if len > 1 => Some(0),
+            _ => None,
+        }
+    }
+}
+
+
+// the frames an entity keeps resident: the one it shows and the one loading or prefetched after it. dropping a frame
+// handle unloads the cloud and its gpu buffers
+#[derive(Component, Default)]
+struct GaussianSequenceFrames {
+    shown: Option<usize>,
+    next: Option<(usize, Handle<GaussianCloud>)>,
+}
+
+
+#[derive(Default)]
+pub struct GaussianSequencePlugin;
+
+impl Plugin for GaussianSequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GaussianSequence>();
+        app.register_asset_reflect::<GaussianSequence>();
+        app.register_type::<GaussianSequencePlayback>();
+
+        app.add_systems(
+            Update,
+            advance_sequences.before(send_loaded_events),
+        );
+    }
+}
+
+
+// frames that are still loading hold the previous frame, seeks load the new frame on demand. swaps bypass change
+// detection, so GaussianCloudLoaded (and with it GaussianFadeIn and auto exposure) fires once when playback starts
+// rather than on every frame, and request a resort instead since the cpu sorts would keep the previous frame's order
+#[allow(clippy::type_complexity)]
+fn advance_sequences(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    sequences: Res<Assets<GaussianSequence>>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut resort: EventWriter<ResortGaussianClouds>,
+    mut playbacks: Query<(
+        Entity,
+        &mut GaussianSequencePlayback,
+        &mut Handle<GaussianCloud>,
+        Option<&mut GaussianSequenceFrames>,
+    )>,
+) {
+    for (entity, mut playback, mut cloud, frames) in playbacks.iter_mut() {
+        let Some(mut frames) = frames else {
+            commands.entity(entity).insert(GaussianSequenceFrames::default());
+            continue;
+        };
+
+        let Some(sequence) = sequences.get(&playback.sequence) else {
+            continue;
+        };
+
+        if sequence.is_empty() {
+            continue;
+        }
+
+        if playback.playing && !playback.finished(sequence.len()) {
+            playback.elapsed += time.delta_seconds();
+        }
+
+        let index = playback.frame_index(sequence.len());
+
+        if frames.shown != Some(index) {
+            let frame = match frames.next.take() {
+                Some((next_index, next)) if next_index == index => next,
+                _ => asset_server.load(sequence.frames[index].clone()),
+            };
+
+            let Some(next) = gaussian_clouds_res.get(&frame) else {
+                frames.next = Some((index, frame));
+                continue;
+            };
+
+            // the sorted entries are sized to the cloud, frames with another gaussian count get new ones
+            let same_len = gaussian_clouds_res.get(&*cloud)
+                .is_some_and(|current| current.len() == next.len());
+            if !same_len {
+                commands.entity(entity).remove::<Handle<SortedEntries>>();
+            }
+
+            // the first frame is announced like any other cloud
+            if frames.shown.is_none() {
+                *cloud = frame;
+            } else {
+                *cloud.bypass_change_detection() = frame;
+                resort.send(ResortGaussianClouds);
+            }
+
+            frames.shown = Some(index);
+        }
+
+        let Some(next_index) = playback.next_frame_index(sequence.len()) else {
+            continue;
+        };
+
+        if frames.next.as_ref().is_some_and(|(prefetched, _)| *prefetched == next_index) {
+            continue;
+        }
+
+        frames.next = Some((next_index, asset_server.load(sequence.frames[next_index].clone())));
+    }
+}
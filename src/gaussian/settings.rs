@@ -1,6 +1,27 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+    render::{
+        extract_resource::ExtractResource,
+        primitives::Aabb,
+    },
+};
 
-use crate::sort::SortMode;
+use crate::{
+    material::spherical_harmonics::SH_BAND_MASK_ALL,
+    sort::{
+        DEFAULT_TEMPORAL_SORT_WINDOW_SIZE,
+        SortKeyPrecision,
+        SortMode,
+    },
+};
+
+
+pub const GAUSSIAN_CLOUD_MAX_CLIP_PLANES: usize = 4;
+
+pub const GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES: usize = 16;
+
+pub const GAUSSIAN_CLOUD_MAX_SPLAT_TESSELLATION: u32 = 64;
 
 
 #[derive(
@@ -39,28 +60,609 @@ pub enum GaussianCloudRasterize {
 }
 
 
-#[derive(Component, Reflect, Clone)]
+// which bounds visualize_bounding_box draws. Splat outlines each splat's screen quad in the shader, Local draws the
+// cloud's tight aabb carried along by the cloud transform (an oriented box), World the axis aligned box enclosing it.
+// Local, World and Both are drawn with gizmos and require the gizmo feature
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudBoundingBoxMode {
+    #[default]
+    Splat,
+    Local,
+    World,
+    Both,
+}
+
+
+// alpha falloff across each splat. Flat and Cubic cover the same area as the gaussian at equal opacity, a disc of
+// radius sqrt(2) sigma and a smoothstep falling to zero at sqrt(20 / 3) sigma, so switching keeps overall coverage
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudKernel {
+    #[default]
+    Gaussian,
+    Flat,
+    Cubic,
+}
+
+
+// faces culled by the rasterizer. every splat footprint faces the camera and winds counter clockwise on screen, so
+// Back culling keeps splats and only drops back facing triangles of geometric render modes. lines never cull
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudCullMode {
+    #[default]
+    None,
+    Front,
+    Back,
+}
+
+impl GaussianCloudCullMode {
+    pub fn face(self) -> Option<bevy::render::render_resource::Face> {
+        use bevy::render::render_resource::Face;
+
+        match self {
+            Self::None => None,
+            Self::Front => Some(Face::Front),
+            Self::Back => Some(Face::Back),
+        }
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudFrontFace {
+    #[default]
+    Ccw,
+    Cw,
+}
+
+impl GaussianCloudFrontFace {
+    pub fn front_face(self) -> bevy::render::render_resource::FrontFace {
+        use bevy::render::render_resource::FrontFace;
+
+        match self {
+            Self::Ccw => FrontFace::Ccw,
+            Self::Cw => FrontFace::Cw,
+        }
+    }
+}
+
+
+// SortOrder colors each blended splat by its rank in the sorted entries, from the low end of the colormap (drawn
+// first) to the high end (drawn last). a correct back to front sort shows a smooth gradient toward the camera.
+// OpacityBoost(gamma) raises each blended gaussian's opacity to `gamma` before the kernel falloff, values below one
+// (e.g. 0.25) lift faint gaussians such as floaters into view without widening their footprints. Depth colors each
+// fragment by its view space depth over near..far, from the low end of the colormap at `near` to the high end at
+// `far`, in place of the gaussian's color
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianDebug {
+    #[default]
+    None,
+    SortOrder,
+    OpacityBoost(f32),
+    Depth {
+        near: f32,
+        far: f32,
+    },
+}
+
+// the GaussianDebug variant without its parameters, the part of it that keys the pipeline
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+)]
+pub enum GaussianDebugMode {
+    #[default]
+    None,
+    SortOrder,
+    OpacityBoost,
+    Depth,
+}
+
+impl GaussianDebug {
+    // the boost gamma and depth range are read from the cloud uniform, so every value shares one pipeline
+    pub fn mode(self) -> GaussianDebugMode {
+        match self {
+            GaussianDebug::None => GaussianDebugMode::None,
+            GaussianDebug::SortOrder => GaussianDebugMode::SortOrder,
+            GaussianDebug::OpacityBoost(_) => GaussianDebugMode::OpacityBoost,
+            GaussianDebug::Depth { .. } => GaussianDebugMode::Depth,
+        }
+    }
+
+    pub fn opacity_gamma(self) -> f32 {
+        match self {
+            GaussianDebug::OpacityBoost(gamma) => gamma.max(0.0),
+            _ => 1.0,
+        }
+    }
+
+    // the near..far view depth range of Depth, an empty range is widened so the mapping stays finite
+    pub fn depth_range(self) -> Vec2 {
+        match self {
+            GaussianDebug::Depth { near, far } => Vec2::new(near, far.max(near + f32::EPSILON)),
+            _ => Vec2::new(0.0, 1.0),
+        }
+    }
+}
+
+
+// Channel colors each splat by GaussianCloud::channel mapped over channel_range, storage buffers only
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudColorChannel {
+    #[default]
+    SphericalHarmonics,
+    Channel,
+}
+
+
+// Srgb luts (most .cube exports) are indexed and filled with srgb encoded colors, Linear luts with linear colors
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudColorLutDomain {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+
+// Sorted blends every splat back to front over the sorted entries. Stochastic skips the sort and draws in cloud
+// order, keeping each fragment with probability alpha (a per pixel dither, or alpha to coverage on multisampled views)
+// and writing depth for the kept ones, so the cloud intersects other geometry correctly at the cost of noise
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianTransparencyMode {
+    #[default]
+    Sorted,
+    Stochastic,
+}
+
+
+// Perspective projects each splat's covariance, so splats shrink with distance. Constant projects it as if the gaussian
+// were one world unit in front of the camera, keeping its screen size at any depth, e.g. for point cloud style
+// annotation overlays sized by global_scale. only affects the splat render mode
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum SplatSizeMode {
+    #[default]
+    Perspective,
+    Constant,
+}
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudRenderMode {
+    #[default]
+    Splats,
+    Ellipsoids,
+}
+
+impl GaussianCloudRenderMode {
+    pub fn entry_points(&self) -> GaussianCloudEntryPoints {
+        match self {
+            Self::Splats => GaussianCloudEntryPoints::SPLATS,
+            Self::Ellipsoids => GaussianCloudEntryPoints::ELLIPSOIDS,
+        }
+    }
+}
+
+
+// vertex and fragment entry points in gaussian.wgsl, replace them to draw a render mode with functions of a modified
+// shader (see render::GAUSSIAN_SHADER_HANDLE). the primitive topology still follows the render mode
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub struct GaussianCloudEntryPoints {
+    pub vertex: &'static str,
+    pub fragment: &'static str,
+}
+
+impl GaussianCloudEntryPoints {
+    pub const SPLATS: Self = Self {
+        vertex: "vs_points",
+        fragment: "fs_main",
+    };
+
+    pub const ELLIPSOIDS: Self = Self {
+        vertex: "vs_ellipsoid",
+        fragment: "fs_ellipsoid",
+    };
+}
+
+impl Default for GaussianCloudEntryPoints {
+    fn default() -> Self {
+        Self::SPLATS
+    }
+}
+
+
+// basis change from the capture's axes into bevy's right-handed y-up frame, applied before `transform`
+//   YUp:    identity
+//   Colmap: y-down z-forward, rotates 180 degrees about x   [[1, 0, 0], [0, -1, 0], [0, 0, -1]]
+//   ZUp:    right-handed z-up (e.g. blender), (x, y, z) -> (x, z, -y)   [[1, 0, 0], [0, 0, 1], [0, -1, 0]]
+//   FlipY:  mirrors y   [[1, 0, 0], [0, -1, 0], [0, 0, 1]]
+//   FlipZ:  mirrors z, converts left-handed y-up captures   [[1, 0, 0], [0, 1, 0], [0, 0, -1]]
+// mirrored conventions are handled by the renderer: covariances are transformed as T * sigma * T^T and
+// spherical harmonics are evaluated along the view direction mapped back into the cloud frame
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudCoordinateConvention {
+    #[default]
+    YUp,
+    Colmap,
+    ZUp,
+    FlipY,
+    FlipZ,
+}
+
+impl GaussianCloudCoordinateConvention {
+    pub fn matrix(&self) -> Mat3 {
+        match self {
+            Self::YUp => Mat3::IDENTITY,
+            Self::Colmap => Mat3::from_diagonal(Vec3::new(1.0, -1.0, -1.0)),
+            Self::ZUp => Mat3::from_cols(
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ),
+            Self::FlipY => Mat3::from_diagonal(Vec3::new(1.0, -1.0, 1.0)),
+            Self::FlipZ => Mat3::from_diagonal(Vec3::new(1.0, 1.0, -1.0)),
+        }
+    }
+}
+
+
+#[derive(Component, Reflect, Clone, PartialEq)]
 #[reflect(Component)]
 pub struct GaussianCloudSettings {
+    // false sizes each quad to the projected cutoff_sigma ellipse (obb), true draws a screen-aligned billboard (aabb)
     pub aabb: bool,
+    // standard deviations each splat extends to, fragments beyond it are discarded. smaller values shade fewer
+    // fragments but cut the tails off visibly
+    pub cutoff_sigma: f32,
+    pub kernel: GaussianCloudKernel,
+    // vertices around each splat footprint. 4 draws the quad, more draw a polygon hugging the cutoff ellipse
+    // (fewer wasted fragments for more vertices), clamped to GAUSSIAN_CLOUD_MAX_SPLAT_TESSELLATION. aabb splats
+    // always draw the quad
+    pub splat_tessellation: u32,
+    // scales each splat footprint without moving gaussian centers (the usual "scale modifier")
     pub global_scale: f32,
+    // clamps each gaussian's scale axes into [min, max] before global_scale, so floaters keep a bounded footprint
+    // instead of being culled. ignored with precompute_covariance_3d, which keeps no scales
+    pub scale_clamp: Option<(f32, f32)>,
+    // caps a splat's projected diameter to this fraction of the smaller viewport side, 0.0 disables the cap
+    pub max_screen_fraction: f32,
+    pub splat_size_mode: SplatSizeMode,
+    // multiplies every splat's opacity, below 1.0 the opaque pass and depth prepass are skipped so the whole cloud
+    // blends (see GaussianFadeIn)
+    pub global_opacity: f32,
+    // remaps each gaussian's stored opacity through evenly spaced samples over 0..=1, linearly interpolated, before
+    // focus, global_opacity, and the opaque threshold. None leaves opacities unchanged
+    pub opacity_curve: Option<[f32; GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES]>,
+    // draws only the first reveal_fraction of the gaussians in their reveal order, cloud order unless the asset sets
+    // one (see GaussianCloud::set_reveal_order), animating it from 0.0 to 1.0 grows the cloud in that order
+    pub reveal_fraction: f32,
+    // multiplies every splat color before the color lut and fog
+    pub exposure: f32,
+    // sets `exposure` on load so the cloud's mean luminance hits this value (see GaussianCloud::auto_exposure),
+    // None keeps `exposure` as is
+    pub auto_exposure_target: Option<f32>,
+    // gaussians farther than focus_radius from the world space focus_point fade out linearly over focus_falloff,
+    // the default f32::MAX radius disables the effect
+    pub focus_point: Vec3,
+    pub focus_radius: f32,
+    pub focus_falloff: f32,
+    // gaussians whose center lies on the negative side of any plane (dot(xyz, position) + w < 0) are dropped. planes
+    // are in the cloud's frame and move with `transform`, a zero plane never clips
+    pub clip_planes: [Vec4; GAUSSIAN_CLOUD_MAX_CLIP_PLANES],
     pub transform: Transform,
+    pub coordinate_convention: GaussianCloudCoordinateConvention,
     pub visualize_bounding_box: bool,
+    pub bounding_box_mode: GaussianCloudBoundingBoxMode,
     pub sort_mode: SortMode,
+    // ignored by always_on_top clouds, which draw Sorted
+    pub transparency_mode: GaussianTransparencyMode,
+    // added to the cloud's view distance in the transparent phase, positive values draw it later (as if closer to the
+    // camera) against other transparent meshes, negative values earlier. units are world units along the view axis
+    pub sort_bias: f32,
+    // radix sort only, see SortKeyPrecision
+    pub sort_key_precision: SortKeyPrecision,
+    pub temporal_sort_window_size: u32,
+    // gaussians at or above this opacity have their half opaque cores drawn first in an unsorted, depth writing pass,
+    // their soft fringes and every other gaussian are composited by the sorted blended pass
+    pub opaque_threshold: Option<f32>,
+    // writes depth for splat fragments at or above this alpha before the blended pass, so splats hidden behind
+    // near-opaque surfaces are rejected early. trades one extra unsorted draw for less overdraw in dense captures
+    pub depth_prepass_alpha: Option<f32>,
+    // on cameras with a DepthPrepass, rejects splats whose center and footprint corners all lie behind the prepass
+    // depth before rasterizing them, and discards fragments behind it
+    pub scene_depth_test: bool,
+    // draws the cloud after every other transparent item without depth testing, so it shows over the scene wherever
+    // it is, e.g. for annotation clouds. disables the opaque pass, depth prepass, and scene depth test
+    pub always_on_top: bool,
+    // draws at most this many gaussians per frame, chosen by view importance (see sort::apply_splat_budget), cpu sorts only
+    pub splat_budget: Option<usize>,
+    // false draws every entry with a direct draw instead of the instance count the radix sort writes after culling,
+    // identical for unculled clouds. the radix sort only writes the count for clouds that draw indirect, see
+    // draws_indirect. forced off on devices without indirect execution
+    pub use_indirect: bool,
     pub draw_mode: GaussianCloudDrawMode,
     pub rasterize_mode: GaussianCloudRasterize,
+    pub render_mode: GaussianCloudRenderMode,
+    pub cull_mode: GaussianCloudCullMode,
+    pub front_face: GaussianCloudFrontFace,
+    // rasterizer depth bias against coplanar geometry, depth_bias is rounded to whole depth units. depth is reversed, so
+    // positive values pull the cloud toward the camera. ignored by the ellipsoid wireframes
+    pub depth_bias: f32,
+    pub depth_bias_slope: f32,
+    // None uses render_mode.entry_points()
+    pub entry_points: Option<GaussianCloudEntryPoints>,
+    pub debug: GaussianDebug,
+    pub color_channel: GaussianCloudColorChannel,
+    // channel values at x map to the low end of the colormap, values at y to the high end
+    pub channel_range: Vec2,
+    // 3d lut applied to each splat color before fog (see io::cube::parse_cube), storage buffers only
+    pub color_lut: Option<Handle<Image>>,
+    pub color_lut_domain: GaussianCloudColorLutDomain,
+    // the input colors the lut covers, mapped to its first and last texels (see io::cube::CubeLut)
+    pub color_lut_input_min: Vec3,
+    pub color_lut_input_max: Vec3,
+    // splats fade linearly toward fog_color between fog_start and fog_end view depth, disabled when fog_end <= fog_start
+    pub fog_color: Color,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    // bit n enables spherical harmonic band n, e.g. 0b0001 renders the dc term only
+    pub sh_band_mask: u8,
 }
 
 impl Default for GaussianCloudSettings {
     fn default() -> Self {
         Self {
             aabb: false,
+            cutoff_sigma: 3.5,
+            kernel: GaussianCloudKernel::default(),
+            splat_tessellation: 4,
             global_scale: 1.0,
+            scale_clamp: None,
+            max_screen_fraction: 0.0,
+            splat_size_mode: SplatSizeMode::default(),
+            global_opacity: 1.0,
+            opacity_curve: None,
+            reveal_fraction: 1.0,
+            exposure: 1.0,
+            auto_exposure_target: None,
+            focus_point: Vec3::ZERO,
+            focus_radius: f32::MAX,
+            focus_falloff: 1.0,
+            clip_planes: [Vec4::ZERO; GAUSSIAN_CLOUD_MAX_CLIP_PLANES],
             transform: Transform::IDENTITY,
+            coordinate_convention: GaussianCloudCoordinateConvention::default(),
             visualize_bounding_box: false,
+            bounding_box_mode: GaussianCloudBoundingBoxMode::default(),
             sort_mode: SortMode::default(),
+            transparency_mode: GaussianTransparencyMode::default(),
+            sort_bias: 0.0,
+            sort_key_precision: SortKeyPrecision::default(),
+            temporal_sort_window_size: DEFAULT_TEMPORAL_SORT_WINDOW_SIZE,
+            splat_budget: None,
+            opaque_threshold: None,
+            depth_prepass_alpha: None,
+            scene_depth_test: false,
+            always_on_top: false,
+            use_indirect: true,
             draw_mode: GaussianCloudDrawMode::default(),
             rasterize_mode: GaussianCloudRasterize::default(),
+            render_mode: GaussianCloudRenderMode::default(),
+            cull_mode: GaussianCloudCullMode::default(),
+            front_face: GaussianCloudFrontFace::default(),
+            depth_bias: 0.0,
+            depth_bias_slope: 0.0,
+            entry_points: None,
+            debug: GaussianDebug::default(),
+            color_channel: GaussianCloudColorChannel::default(),
+            channel_range: Vec2::new(0.0, 1.0),
+            color_lut: None,
+            color_lut_domain: GaussianCloudColorLutDomain::default(),
+            color_lut_input_min: Vec3::ZERO,
+            color_lut_input_max: Vec3::ONE,
+            fog_color: Color::WHITE,
+            fog_start: 0.0,
+            fog_end: 0.0,
+            sh_band_mask: SH_BAND_MASK_ALL,
         }
     }
 }
+
+impl GaussianCloudSettings {
+    pub fn entry_points(&self) -> GaussianCloudEntryPoints {
+        self.entry_points.unwrap_or_else(|| self.render_mode.entry_points())
+    }
+
+    pub fn cloud_transform(&self) -> Affine3A {
+        self.transform.compute_affine() * Affine3A::from_mat3(self.coordinate_convention.matrix())
+    }
+
+    // axis aligned world bounds of a cloud space aabb under cloud_transform
+    pub fn world_aabb(&self, local: &Aabb) -> Aabb {
+        let transform = self.cloud_transform();
+        let linear = transform.matrix3;
+
+        Aabb {
+            center: transform.transform_point3a(local.center),
+            half_extents: linear.x_axis.abs() * local.half_extents.x
+                + linear.y_axis.abs() * local.half_extents.y
+                + linear.z_axis.abs() * local.half_extents.z,
+        }
+    }
+
+    // triangle strip vertices of one splat
+    pub fn splat_vertex_count(&self) -> u32 {
+        if self.aabb {
+            return 4;
+        }
+
+        self.splat_tessellation.clamp(4, GAUSSIAN_CLOUD_MAX_SPLAT_TESSELLATION)
+    }
+
+    // whether the splat draw reads the radix sort's instance count. budgeted, tessellated, and ellipsoid draws size
+    // themselves, so their sorts leave the indirect args alone like use_indirect = false does
+    pub fn draws_indirect(&self) -> bool {
+        let splat_budget = self.splat_budget
+            .filter(|_| self.sort_mode.supports_splat_budget());
+
+        self.use_indirect
+            && self.render_mode == GaussianCloudRenderMode::Splats
+            && splat_budget.is_none()
+            && self.splat_vertex_count() == 4
+            && cfg!(not(feature = "webgl2"))
+    }
+
+    // on top clouds have no depth test to resolve unsorted splats against, so they always draw sorted
+    pub fn effective_transparency_mode(&self) -> GaussianTransparencyMode {
+        if self.always_on_top {
+            return GaussianTransparencyMode::Sorted;
+        }
+
+        self.transparency_mode
+    }
+
+    // the sort that runs for the cloud, stochastic transparency draws in cloud order and never sorts
+    pub fn effective_sort_mode(&self) -> SortMode {
+        match self.effective_transparency_mode() {
+            GaussianTransparencyMode::Sorted => self.sort_mode.clone(),
+            GaussianTransparencyMode::Stochastic => SortMode::None,
+        }
+    }
+
+    // cloud space planes under cloud_transform, by the inverse transpose keeping the sign of every plane distance
+    pub fn world_clip_planes(&self) -> [Vec4; GAUSSIAN_CLOUD_MAX_CLIP_PLANES] {
+        let inverse_transpose = Mat4::from(self.cloud_transform()).inverse().transpose();
+
+        self.clip_planes.map(|plane| inverse_transpose * plane)
+    }
+
+    // the curve samples packed four per vec4 for the uniform, the identity ramp when there is no curve
+    pub fn opacity_curve_uniform(&self) -> [Vec4; GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES / 4] {
+        let samples = self.opacity_curve.unwrap_or_else(|| {
+            std::array::from_fn(|index| index as f32 / (GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES - 1) as f32)
+        });
+
+        std::array::from_fn(|index| Vec4::from_slice(&samples[index * 4..index * 4 + 4]))
+    }
+}
+
+
+// buffer creation options shared by every gaussian cloud, read when assets are prepared
+#[derive(
+    Resource,
+    Clone,
+    Debug,
+    Default,
+    ExtractResource,
+    Reflect,
+)]
+#[reflect(Resource)]
+pub struct GaussianCloudBufferSettings {
+    pub readback: bool,
+}
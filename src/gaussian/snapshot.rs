@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use crate::gaussian::{
+    cloud::GaussianCloud,
+    packed::Gaussian,
+    patch::GaussianCloudPatch,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use crate::gaussian::patch::GaussianCloudPatchError;
+
+
+// undo state for editors. Diff keeps the prior values of the gaussians an in-place patch overwrites, so it costs
+// one Gaussian (up to a few hundred bytes with degree 3 sh) per touched gaussian no matter the cloud size. Full
+// copies every buffer once and is shared by reference afterwards, clones of the snapshot are free
+#[derive(Clone, Debug, PartialEq)]
+pub enum CloudSnapshot {
+    Diff(GaussianCloudPatch),
+    Full(Arc<GaussianCloud>),
+}
+
+impl CloudSnapshot {
+    // approximate heap bytes held by the snapshot, a shared Full snapshot is counted by every holder
+    pub fn size_bytes(&self) -> usize {
+        match self {
+            CloudSnapshot::Diff(patch) => patch.updates.len() * std::mem::size_of::<(usize, Gaussian)>(),
+            CloudSnapshot::Full(cloud) => {
+                cloud.len() * std::mem::size_of::<Gaussian>() + cloud.channel.len() * std::mem::size_of::<f32>()
+            },
+        }
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GaussianCloud {
+    pub fn snapshot(&self) -> CloudSnapshot {
+        CloudSnapshot::Full(Arc::new(self.clone()))
+    }
+
+    // call before applying `patch`, restoring the result undoes it. in-place patches produce a Diff, patches that
+    // append or remove gaussians fall back to a Full snapshot
+    pub fn snapshot_patch(&self, patch: &GaussianCloudPatch) -> CloudSnapshot {
+        if !patch.is_in_place() {
+            return self.snapshot();
+        }
+
+        // out of range updates are rejected by apply_patch, so there is nothing to restore for them
+        let mut indices = patch.updates.iter()
+            .map(|(index, _)| *index)
+            .filter(|index| *index < self.len())
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        indices.dedup();
+
+        CloudSnapshot::Diff(GaussianCloudPatch {
+            updates: indices.into_iter()
+                .map(|index| (index, self.gaussian(index)))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    // fails when a Diff is restored onto a cloud that no longer has the gaussians it indexes
+    pub fn restore(&mut self, snapshot: &CloudSnapshot) -> Result<(), GaussianCloudPatchError> {
+        match snapshot {
+            CloudSnapshot::Diff(patch) => self.apply_patch(patch),
+            CloudSnapshot::Full(cloud) => {
+                *self = cloud.as_ref().clone();
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::sync::{
+    Arc,
+    OnceLock,
+};
+
+use bevy::{
+    prelude::*,
+    utils::{
+        HashMap,
+        HashSet,
+    },
+};
+
+use crate::GaussianCloud;
+
+
+// occupied cells hold about this many gaussians on average
+const TARGET_GAUSSIANS_PER_CELL: f32 = 8.0;
+
+// bounds the resolution of flat or degenerate clouds
+const MAX_CELLS_PER_AXIS: f32 = 1024.0;
+
+
+// uniform grid over gaussian centers in cloud space
+#[derive(Clone, Debug, Default)]
+pub struct GaussianCloudVoxelGrid {
+    pub min: Vec3,
+    pub cell_size: f32,
+    pub dimensions: IVec3,
+    cells: HashMap<IVec3, Vec<u32>>,
+}
+
+impl GaussianCloudVoxelGrid {
+    pub fn new(cloud: &GaussianCloud) -> Self {
+        if cloud.is_empty() {
+            return Self {
+                min: Vec3::ZERO,
+                cell_size: 1.0,
+                dimensions: IVec3::ZERO,
+                cells: HashMap::new(),
+            };
+        }
+
+        let (min, max) = cloud.position_iter()
+            .map(|position| Vec3::from_array(*position))
+            .fold(
+                (Vec3::INFINITY, Vec3::NEG_INFINITY),
+                |(min, max), position| (min.min(position), max.max(position)),
+            );
+
+        let extent = (max - min).max(Vec3::splat(f32::EPSILON));
+        let target_cells = (cloud.len() as f32 / TARGET_GAUSSIANS_PER_CELL).max(1.0);
+        let cell_size = (extent.x * extent.y * extent.z / target_cells).cbrt()
+            .max(extent.max_element() / MAX_CELLS_PER_AXIS);
+
+        let mut grid = Self {
+            min,
+            cell_size,
+            dimensions: (extent / cell_size).floor().as_ivec3() + IVec3::ONE,
+            cells: HashMap::new(),
+        };
+
+        for (index, position) in cloud.position_iter().enumerate() {
+            let cell = grid.cell(Vec3::from_array(*position));
+            grid.cells.entry(cell).or_default().push(index as u32);
+        }
+
+        grid
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // positions outside the grid map to the closest boundary cell
+    pub fn cell(&self, position: Vec3) -> IVec3 {
+        ((position - self.min) / self.cell_size)
+            .floor()
+            .as_ivec3()
+            .clamp(IVec3::ZERO, (self.dimensions - IVec3::ONE).max(IVec3::ZERO))
+    }
+
+    pub fn cell_bounds(&self, cell: IVec3) -> (Vec3, Vec3) {
+        let min = self.min + cell.as_vec3() * self.cell_size;
+
+        (min, min + Vec3::splat(self.cell_size))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (IVec3, &[u32])> + '_ {
+        self.cells.iter()
+            .map(|(cell, indices)| (*cell, indices.as_slice()))
+    }
+
+    pub fn cell_indices(&self, cell: IVec3) -> &[u32] {
+        self.cells.get(&cell)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    // candidates from every cell overlapping the box, callers still test exact positions
+    pub fn query_aabb(&self, min: Vec3, max: Vec3) -> Vec<u32> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let low = self.cell(min);
+        let high = self.cell(max);
+        let span = high - low + IVec3::ONE;
+
+        if span.x as usize * span.y as usize * span.z as usize > self.cells.len() {
+            return self.cells.iter()
+                .filter(|(cell, _)| cell.cmpge(low).all() && cell.cmple(high).all())
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect();
+        }
+
+        let mut candidates = Vec::new();
+        for z in low.z..=high.z {
+            for y in low.y..=high.y {
+                for x in low.x..=high.x {
+                    candidates.extend_from_slice(self.cell_indices(IVec3::new(x, y, z)));
+                }
+            }
+        }
+
+        candidates
+    }
+
+    // occupied cells at chebyshev distance `radius` from `center`
+    fn shell(&self, center: IVec3, radius: i32) -> impl Iterator<Item = IVec3> + '_ {
+        let low = (center - IVec3::splat(radius)).max(IVec3::ZERO);
+        let high = (center + IVec3::splat(radius)).min(self.dimensions - IVec3::ONE);
+
+        (low.z..=high.z)
+            .flat_map(move |z| (low.y..=high.y).map(move |y| (y, z)))
+            .flat_map(move |(y, z)| (low.x..=high.x).map(move |x| IVec3::new(x, y, z)))
+            .filter(move |cell| (*cell - center).abs().max_element() == radius)
+            .filter(|cell| self.cells.contains_key(cell))
+    }
+}
+
+
+// lazily built on first query and shared between clones
+#[derive(Clone, Debug, Default)]
+pub struct GaussianCloudSpatialIndex(OnceLock<Arc<GaussianCloudVoxelGrid>>);
+
+// the index is derived data, so it never affects cloud equality
+impl PartialEq for GaussianCloudSpatialIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+
+impl GaussianCloud {
+    pub fn spatial_index(&self) -> &GaussianCloudVoxelGrid {
+        self.spatial_index.0.get_or_init(|| Arc::new(GaussianCloudVoxelGrid::new(self)))
+    }
+
+    // builds the index ahead of time, e.g. after loading, so the first query doesn't stall
+    pub fn build_spatial_index(&self) {
+        self.spatial_index();
+    }
+
+    // required after writing gaussian fields directly, the mutating accessors already call it
+    pub fn invalidate_spatial_index(&mut self) {
+        self.spatial_index = GaussianCloudSpatialIndex::default();
+    }
+
+    // closest gaussian center to `point` in cloud space
+    pub fn nearest(&self, point: Vec3) -> Option<usize> {
+        let grid = self.spatial_index();
+        if grid.is_empty() {
+            return None;
+        }
+
+        let center = grid.cell(point);
+        let mut best: Option<(usize, f32)> = None;
+
+        for radius in 0..=grid.dimensions.max_element() {
+            // unvisited cells are at least this far from the point
+            if let Some((_, best_distance_squared)) = best {
+                let shell_distance = (radius - 1).max(0) as f32 * grid.cell_size;
+                if shell_distance * shell_distance > best_distance_squared {
+                    break;
+                }
+            }
+
+            for cell in grid.shell(center, radius) {
+                for &index in grid.cell_indices(cell) {
+                    let distance_squared = Vec3::from_array(*self.position(index as usize)).distance_squared(point);
+
+                    let closer = match best {
+                        Some((_, best_distance_squared)) => distance_squared < best_distance_squared,
+                        None => true,
+                    };
+
+                    if closer {
+                        best = Some((index as usize, distance_squared));
+                    }
+                }
+            }
+        }
+
+        best.map(|(index, _)| index)
+    }
+
+    // first gaussian center within `radius` of the ray in cloud space, with its distance along the ray
+    pub fn raycast(&self, ray: Ray3d, radius: f32) -> Option<(usize, f32)> {
+        let grid = self.spatial_index();
+        if grid.is_empty() {
+            return None;
+        }
+
+        let origin = ray.origin;
+        let direction = *ray.direction;
+
+        // grid bounds grown by the radius so centers just outside are still reachable
+        let bounds_min = grid.min - Vec3::splat(radius);
+        let bounds_max = grid.min + grid.dimensions.as_vec3() * grid.cell_size + Vec3::splat(radius);
+
+        let mut t_enter = 0.0_f32;
+        let mut t_exit = f32::INFINITY;
+        for axis in 0..3 {
+            if direction[axis].abs() < f32::EPSILON {
+                if origin[axis] < bounds_min[axis] || origin[axis] > bounds_max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let t0 = (bounds_min[axis] - origin[axis]) / direction[axis];
+            let t1 = (bounds_max[axis] - origin[axis]) / direction[axis];
+
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+        }
+
+        if t_enter > t_exit {
+            return None;
+        }
+
+        // a center within `radius` of the ray lies at most `reach` cells from a cell the ray crosses
+        let reach = (radius / grid.cell_size).ceil().max(0.0) as i32;
+
+        // cells are walked in the order the ray enters them, unclamped so the grown border is covered too
+        let entry = origin + direction * t_enter;
+        let mut cell = ((entry - grid.min) / grid.cell_size).floor().as_ivec3();
+
+        let mut step = IVec3::ZERO;
+        let mut t_next = Vec3::INFINITY;
+        let mut t_delta = Vec3::INFINITY;
+        for axis in 0..3 {
+            if direction[axis].abs() < f32::EPSILON {
+                continue;
+            }
+
+            step[axis] = if direction[axis] > 0.0 { 1 } else { -1 };
+
+            let boundary = grid.min[axis] + (cell[axis] + step[axis].max(0)) as f32 * grid.cell_size;
+            t_next[axis] = (boundary - origin[axis]) / direction[axis];
+            t_delta[axis] = grid.cell_size / direction[axis].abs();
+        }
+
+        let mut visited = HashSet::new();
+        let mut best: Option<(usize, f32)> = None;
+        let mut t = t_enter;
+
+        while t <= t_exit {
+            // every center not seen yet is closest to the ray past this cell's entry
+            if let Some((_, best_t)) = best {
+                if best_t < t {
+                    break;
+                }
+            }
+
+            for z in -reach..=reach {
+                for y in -reach..=reach {
+                    for x in -reach..=reach {
+                        let neighbor = cell + IVec3::new(x, y, z);
+                        if !visited.insert(neighbor) {
+                            continue;
+                        }
+
+                        for &index in grid.cell_indices(neighbor) {
+                            let offset = Vec3::from_array(*self.position(index as usize)) - origin;
+                            let along = offset.dot(direction);
+                            if along < 0.0 {
+                                continue;
+                            }
+
+                            let distance_squared = offset.length_squared() - along * along;
+                            if distance_squared > radius * radius {
+                                continue;
+                            }
+
+                            let closer = match best {
+                                Some((_, best_t)) => along < best_t,
+                                None => true,
+                            };
+
+                            if closer {
+                                best = Some((index as usize, along));
+                            }
+                        }
+                    }
+                }
+            }
+
+            let axis = if t_next.x <= t_next.y && t_next.x <= t_next.z {
+                0
+            } else if t_next.y <= t_next.z {
+                1
+            } else {
+                2
+            };
+
+            t = t_next[axis];
+            cell[axis] += step[axis];
+            t_next[axis] += t_delta[axis];
+        }
+
+        best
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    GaussianCloud,
+    material::spherical_harmonics::SH_DEGREE,
+};
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+pub struct GaussianCloudRange {
+    pub min: f32,
+    pub mean: f32,
+    pub max: f32,
+}
+
+// running min/max and a sum, so the stats pass needs no per-gaussian storage
+struct RangeAccumulator {
+    min: f32,
+    max: f32,
+    sum: f64,
+    count: usize,
+}
+
+impl RangeAccumulator {
+    fn new() -> Self {
+        Self {
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value as f64;
+        self.count += 1;
+    }
+
+    fn finish(&self) -> Option<GaussianCloudRange> {
+        (self.count > 0).then(|| GaussianCloudRange {
+            min: self.min,
+            mean: (self.sum / self.count as f64) as f32,
+            max: self.max,
+        })
+    }
+}
+
+
+// bounds, centroid, and ranges only cover finite gaussians
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+pub struct GaussianCloudStats {
+    pub count: usize,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+    pub centroid: [f32; 3],
+    pub opacity: Option<GaussianCloudRange>,
+    // per-axis scales, unavailable when covariances are precomputed
+    pub scale: Option<GaussianCloudRange>,
+    pub sh_degree: usize,
+    pub non_finite_fraction: f32,
+}
+
+
+impl GaussianCloud {
+    pub fn stats(&self) -> GaussianCloudStats {
+        let mut bounds_min = Vec3::INFINITY;
+        let mut bounds_max = Vec3::NEG_INFINITY;
+        let mut position_sum = bevy::math::DVec3::ZERO;
+
+        let mut opacity = RangeAccumulator::new();
+        #[allow(unused_mut)]
+        let mut scale = RangeAccumulator::new();
+
+        let mut finite = 0;
+
+        for index in 0..self.len() {
+            let position = Vec3::from_array(*self.position(index));
+            let gaussian_opacity = self.opacity(index);
+
+            #[cfg(not(feature = "precompute_covariance_3d"))]
+            let gaussian = self.gaussian(index);
+
+            #[cfg(not(feature = "precompute_covariance_3d"))]
+            let is_finite = gaussian.is_finite();
+            #[cfg(feature = "precompute_covariance_3d")]
+            let is_finite = position.is_finite() && gaussian_opacity.is_finite();
+
+            if !is_finite {
+                continue;
+            }
+            finite += 1;
+
+            bounds_min = bounds_min.min(position);
+            bounds_max = bounds_max.max(position);
+            position_sum += position.as_dvec3();
+
+            opacity.push(gaussian_opacity);
+
+            #[cfg(not(feature = "precompute_covariance_3d"))]
+            for axis_scale in gaussian.scale_opacity.scale {
+                scale.push(axis_scale);
+            }
+        }
+
+        let (bounds_min, bounds_max, centroid) = if finite > 0 {
+            (bounds_min, bounds_max, (position_sum / finite as f64).as_vec3())
+        } else {
+            (Vec3::ZERO, Vec3::ZERO, Vec3::ZERO)
+        };
+
+        GaussianCloudStats {
+            count: self.len(),
+            bounds_min: bounds_min.to_array(),
+            bounds_max: bounds_max.to_array(),
+            centroid: centroid.to_array(),
+            opacity: opacity.finish(),
+            scale: scale.finish(),
+            sh_degree: SH_DEGREE,
+            non_finite_fraction: if self.is_empty() {
+                0.0
+            } else {
+                (self.len() - finite) as f32 / self.len() as f32
+            },
+        }
+    }
+}
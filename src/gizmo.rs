@@ -0,0 +1,307 @@
+use bevy::{
+    math::Affine3A,
+    prelude::*,
+    render::primitives::Aabb,
+    utils::HashMap,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    gaussian::settings::GaussianCloudBoundingBoxMode,
+};
+
+
+const AXES: [(Vec3, Color); 3] = [
+    (Vec3::X, Color::srgb(0.9, 0.2, 0.2)),
+    (Vec3::Y, Color::srgb(0.2, 0.9, 0.2)),
+    (Vec3::Z, Color::srgb(0.2, 0.4, 0.9)),
+];
+
+const ACTIVE_COLOR: Color = Color::srgb(1.0, 0.9, 0.1);
+
+const LOCAL_BOUNDS_COLOR: Color = Color::srgb(1.0, 0.5, 0.1);
+const WORLD_BOUNDS_COLOR: Color = Color::srgb(0.1, 0.8, 1.0);
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudGizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+
+// draws handles at the cloud's weighted centroid, dragging them with the left mouse button edits
+// GaussianCloudSettings::transform. every delta is a world space operation about that pivot
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct GaussianCloudGizmo {
+    pub mode: GaussianCloudGizmoMode,
+    // handle length in world units
+    pub size: f32,
+    // cursor distance in logical pixels at which a handle is grabbed
+    pub grab_distance: f32,
+}
+
+impl Default for GaussianCloudGizmo {
+    fn default() -> Self {
+        Self {
+            mode: GaussianCloudGizmoMode::default(),
+            size: 1.0,
+            grab_distance: 12.0,
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianCloudGizmoPlugin;
+
+impl Plugin for GaussianCloudGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianCloudGizmo>();
+        app.add_systems(Update, (
+            update_cloud_gizmos,
+            draw_bounding_boxes,
+        ));
+    }
+}
+
+
+// world space pivot of the gizmo, the weighted centroid keeps floaters from dragging it off the subject
+pub fn gizmo_pivot(cloud: &GaussianCloud, settings: &GaussianCloudSettings) -> Vec3 {
+    settings.cloud_transform().transform_point3(cloud.weighted_centroid())
+}
+
+
+struct GizmoDrag {
+    entity: Entity,
+    axis: usize,
+    cursor: Vec2,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_cloud_gizmos(
+    mut gizmos: Gizmos,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    mut gaussian_clouds: Query<(
+        Entity,
+        &Handle<GaussianCloud>,
+        &mut GaussianCloudSettings,
+        &GaussianCloudGizmo,
+    )>,
+    mut centroids: Local<HashMap<AssetId<GaussianCloud>, Vec3>>,
+    mut drag: Local<Option<GizmoDrag>>,
+) {
+    for ev in ev_asset.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = ev {
+            centroids.remove(id);
+        }
+    }
+
+    let cursor = windows.get_single().ok().and_then(Window::cursor_position);
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+
+    if !mouse.pressed(MouseButton::Left) {
+        *drag = None;
+    }
+
+    for (
+        entity,
+        cloud_handle,
+        mut settings,
+        gizmo,
+    ) in gaussian_clouds.iter_mut() {
+        let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+            continue;
+        };
+
+        let local_centroid = *centroids.entry(cloud_handle.id())
+            .or_insert_with(|| cloud.weighted_centroid());
+        let pivot = settings.cloud_transform().transform_point3(local_centroid);
+
+        if let (Some(cursor), Some((camera, camera_transform))) = (cursor, camera) {
+            let to_screen = |point: Vec3| camera.world_to_viewport(camera_transform, point);
+
+            if mouse.just_pressed(MouseButton::Left) && drag.is_none() {
+                let grabbed = AXES.iter()
+                    .enumerate()
+                    .filter_map(|(axis, (direction, _))| {
+                        let start = to_screen(pivot)?;
+                        let end = to_screen(pivot + *direction * gizmo.size)?;
+
+                        Some((axis, distance_to_segment(cursor, start, end)))
+                    })
+                    .filter(|(_, distance)| *distance <= gizmo.grab_distance)
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+
+                if let Some((axis, _)) = grabbed {
+                    *drag = Some(GizmoDrag {
+                        entity,
+                        axis,
+                        cursor,
+                    });
+                }
+            }
+
+            if let Some(active) = drag.as_mut().filter(|active| active.entity == entity) {
+                let direction = AXES[active.axis].0;
+
+                if let (Some(pivot_screen), Some(axis_screen)) = (
+                    to_screen(pivot),
+                    to_screen(pivot + direction * gizmo.size),
+                ) {
+                    let screen_axis = axis_screen - pivot_screen;
+                    let cursor_delta = cursor - active.cursor;
+
+                    // fraction of the handle length the cursor moved along the projected axis
+                    let along = if screen_axis.length_squared() > f32::EPSILON {
+                        cursor_delta.dot(screen_axis) / screen_axis.length_squared()
+                    } else {
+                        0.0
+                    };
+
+                    match gizmo.mode {
+                        GaussianCloudGizmoMode::Translate => {
+                            settings.transform.translation += direction * along * gizmo.size;
+                        },
+                        GaussianCloudGizmoMode::Rotate => {
+                            let previous = active.cursor - pivot_screen;
+                            let current = cursor - pivot_screen;
+
+                            // viewport y points down, so a counter clockwise drag on screen has a negative angle
+                            let angle = -previous.angle_between(current);
+                            let facing = direction.dot(camera_transform.translation() - pivot).signum();
+
+                            if angle.is_finite() {
+                                settings.transform.rotate_around(pivot, Quat::from_axis_angle(direction, angle * facing));
+                            }
+                        },
+                        GaussianCloudGizmoMode::Scale => {
+                            let factor = (1.0 + along).max(0.01);
+
+                            settings.transform.translation = pivot + (settings.transform.translation - pivot) * factor;
+                            settings.transform.scale *= factor;
+                        },
+                    }
+                }
+
+                active.cursor = cursor;
+            }
+        }
+
+        let active_axis = drag.as_ref()
+            .filter(|active| active.entity == entity)
+            .map(|active| active.axis);
+
+        for (axis, (direction, color)) in AXES.iter().enumerate() {
+            let color = if active_axis == Some(axis) { ACTIVE_COLOR } else { *color };
+            let end = pivot + *direction * gizmo.size;
+
+            match gizmo.mode {
+                GaussianCloudGizmoMode::Translate => {
+                    gizmos.arrow(pivot, end, color);
+                },
+                GaussianCloudGizmoMode::Rotate => {
+                    gizmos.line(pivot, end, color);
+                    gizmos.circle(pivot, Dir3::new_unchecked(*direction), gizmo.size, color);
+                },
+                GaussianCloudGizmoMode::Scale => {
+                    gizmos.line(pivot, end, color);
+                    gizmos.cuboid(
+                        Transform::from_translation(end).with_scale(Vec3::splat(gizmo.size * 0.1)),
+                        color,
+                    );
+                },
+            }
+        }
+    }
+}
+
+// GaussianCloudSettings::bounding_box_mode other than Splat, for every cloud with visualize_bounding_box
+fn draw_bounding_boxes(
+    mut gizmos: Gizmos,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    gaussian_clouds: Query<(
+        &Handle<GaussianCloud>,
+        &GaussianCloudSettings,
+    )>,
+    mut local_bounds: Local<HashMap<AssetId<GaussianCloud>, Aabb>>,
+) {
+    for ev in ev_asset.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = ev {
+            local_bounds.remove(id);
+        }
+    }
+
+    for (cloud_handle, settings) in gaussian_clouds.iter() {
+        if !settings.visualize_bounding_box {
+            continue;
+        }
+
+        let (local, world) = match settings.bounding_box_mode {
+            GaussianCloudBoundingBoxMode::Splat => continue,
+            GaussianCloudBoundingBoxMode::Local => (true, false),
+            GaussianCloudBoundingBoxMode::World => (false, true),
+            GaussianCloudBoundingBoxMode::Both => (true, true),
+        };
+
+        let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+            continue;
+        };
+
+        let aabb = *local_bounds.entry(cloud_handle.id())
+            .or_insert_with(|| cloud.aabb());
+
+        if local {
+            let unit_box = Affine3A::from_scale_rotation_translation(
+                (2.0 * aabb.half_extents).into(),
+                Quat::IDENTITY,
+                aabb.center.into(),
+            );
+
+            gizmos.cuboid(
+                GlobalTransform::from(Mat4::from(settings.cloud_transform() * unit_box)),
+                LOCAL_BOUNDS_COLOR,
+            );
+        }
+
+        if world {
+            let world_aabb = settings.world_aabb(&aabb);
+
+            gizmos.cuboid(
+                Transform::from_translation(world_aabb.center.into())
+                    .with_scale((2.0 * world_aabb.half_extents).into()),
+                WORLD_BOUNDS_COLOR,
+            );
+        }
+    }
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_squared() > f32::EPSILON {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    point.distance(start + segment * t)
+}
@@ -1,6 +1,11 @@
 
+use crate::io::error::GaussianLoadError;
+
+
 // TODO: support streamed codecs
-pub trait GaussianCloudCodec {
+pub trait GaussianCloudCodec: Sized {
     fn encode(&self) -> Vec<u8>;
-    fn decode(data: &[u8]) -> Self;
+
+    // files of an unsupported version, truncated or otherwise corrupt fail instead of panicking
+    fn decode(data: &[u8]) -> Result<Self, GaussianLoadError>;
 }
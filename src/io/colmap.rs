@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    io::{
+        Error,
+        ErrorKind,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+};
+
+
+#[derive(Clone, Debug)]
+pub struct CameraPose {
+    pub name: String,
+    pub transform: Transform,
+    pub projection: PerspectiveProjection,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CameraTrack {
+    pub poses: Vec<CameraPose>,
+}
+
+impl CameraTrack {
+    // poses are in the capture's world frame, pass `GaussianCloudSettings::cloud_transform` to follow the cloud
+    pub fn with_transform(mut self, transform: Affine3A) -> Self {
+        for pose in self.poses.iter_mut() {
+            let camera = transform * pose.transform.compute_affine();
+            pose.transform = Transform::from_matrix(camera.into());
+        }
+
+        self
+    }
+
+    pub fn from_colmap(
+        cameras_txt: &str,
+        images_txt: &str,
+    ) -> Result<Self, Error> {
+        let cameras = parse_cameras(cameras_txt)?;
+
+        let mut lines = images_txt.lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<&str>>();
+
+        // blank lines are only padding past the last record, inside the list they are empty 2d points lines
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+
+        // each image is a pose line followed by a (possibly empty) 2d points line
+        let mut poses = Vec::new();
+        for record in lines.chunks(2) {
+            let line = record[0];
+
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.len() < 10 {
+                return Err(invalid(format!("malformed images.txt line: {}", line)));
+            }
+
+            let values = fields[1..8].iter()
+                .map(|field| parse_f32(field))
+                .collect::<Result<Vec<f32>, Error>>()?;
+            let camera_id = fields[8];
+            let name = fields[9..].join(" ");
+
+            let projection = cameras.get(camera_id)
+                .cloned()
+                .ok_or_else(|| invalid(format!("image {} references unknown camera {}", name, camera_id)))?;
+
+            // colmap stores world-to-camera with +y down and +z forward, bevy cameras look down -z with +y up
+            let world_to_camera = Quat::from_xyzw(values[1], values[2], values[3], values[0]).normalize();
+            let translation = Vec3::new(values[4], values[5], values[6]);
+
+            let camera_to_world = world_to_camera.inverse();
+            let center = -(camera_to_world * translation);
+            let rotation = camera_to_world * Quat::from_rotation_x(std::f32::consts::PI);
+
+            poses.push(CameraPose {
+                name,
+                transform: Transform::from_translation(center).with_rotation(rotation),
+                projection,
+            });
+        }
+
+        poses.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            poses,
+        })
+    }
+}
+
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_f32(field: &str) -> Result<f32, Error> {
+    field.parse::<f32>()
+        .map_err(|_| invalid(format!("expected a number, found {}", field)))
+}
+
+fn parse_cameras(cameras_txt: &str) -> Result<HashMap<String, PerspectiveProjection>, Error> {
+    let mut cameras = HashMap::new();
+
+    for line in cameras_txt.lines() {
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        if fields.is_empty() || fields[0].starts_with('#') {
+            continue;
+        }
+
+        if fields.len() < 5 {
+            return Err(invalid(format!("malformed cameras.txt line: {}", line)));
+        }
+
+        let width = parse_f32(fields[2])?;
+        let height = parse_f32(fields[3])?;
+        let params = fields[4..].iter()
+            .map(|field| parse_f32(field))
+            .collect::<Result<Vec<f32>, Error>>()?;
+
+        // single focal models list f first, the rest list fx then fy
+        let focal_y = match fields[1] {
+            "SIMPLE_PINHOLE" | "SIMPLE_RADIAL" | "RADIAL" | "SIMPLE_RADIAL_FISHEYE" | "RADIAL_FISHEYE" => params[0],
+            _ => *params.get(1).ok_or_else(|| invalid(format!("camera {} is missing fy", fields[0])))?,
+        };
+
+        cameras.insert(
+            fields[0].to_string(),
+            PerspectiveProjection {
+                fov: 2.0 * (height / (2.0 * focal_y)).atan(),
+                aspect_ratio: width / height,
+                ..default()
+            },
+        );
+    }
+
+    Ok(cameras)
+}
@@ -0,0 +1,129 @@
+use std::io::{
+    Error,
+    ErrorKind,
+};
+
+use half::f16;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            Extent3d,
+            TextureDimension,
+            TextureFormat,
+        },
+    },
+};
+
+use crate::GaussianCloudSettings;
+
+
+// a parsed .cube lut. DOMAIN_MIN and DOMAIN_MAX bound the input colors the table covers, its outputs are kept as
+// written
+#[derive(Clone, Debug)]
+pub struct CubeLut {
+    pub image: Image,
+    pub domain_min: Vec3,
+    pub domain_max: Vec3,
+}
+
+impl CubeLut {
+    // grades the cloud with this lut, input colors are remapped from the domain to the table before sampling
+    pub fn apply(self, images: &mut Assets<Image>, settings: &mut GaussianCloudSettings) {
+        settings.color_lut = Some(images.add(self.image));
+        settings.color_lut_input_min = self.domain_min;
+        settings.color_lut_input_max = self.domain_max;
+    }
+}
+
+
+// resolve's .cube format, 3d luts only. the table is stored red fastest, which is also the texel order of a 3d texture.
+// half floats keep smooth gradients from banding, 8 bit texels quantize each interpolated channel to 1/255 steps
+pub fn parse_cube(cube: &str) -> Result<CubeLut, Error> {
+    let mut size = None;
+    let mut domain_min = Vec3::ZERO;
+    let mut domain_max = Vec3::ONE;
+    let mut table = Vec::new();
+
+    for line in cube.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let keyword = fields.next().unwrap();
+
+        match keyword {
+            "TITLE" => {},
+            "LUT_1D_SIZE" => return Err(invalid("1d luts are not supported".to_string())),
+            "LUT_3D_SIZE" => {
+                let value = fields.next().unwrap_or_default();
+                let parsed = value.parse::<u32>()
+                    .map_err(|_| invalid(format!("invalid LUT_3D_SIZE: {}", value)))?;
+
+                if parsed < 2 {
+                    return Err(invalid(format!("LUT_3D_SIZE must be at least 2, found {}", parsed)));
+                }
+
+                size = Some(parsed);
+            },
+            "DOMAIN_MIN" => domain_min = parse_vec3(fields)?,
+            "DOMAIN_MAX" => domain_max = parse_vec3(fields)?,
+            _ => table.push(parse_vec3(line.split_whitespace())?),
+        }
+    }
+
+    let size = size.ok_or_else(|| invalid("missing LUT_3D_SIZE".to_string()))?;
+
+    let expected = (size * size * size) as usize;
+    if table.len() != expected {
+        return Err(invalid(format!("expected {} lut entries, found {}", expected, table.len())));
+    }
+
+    if domain_min.cmpge(domain_max).any() {
+        return Err(invalid(format!("DOMAIN_MIN {} must be below DOMAIN_MAX {}", domain_min, domain_max)));
+    }
+
+    let data = table.iter()
+        .flat_map(|color| [color.x, color.y, color.z, 1.0])
+        .flat_map(|channel| f16::from_f32(channel).to_le_bytes())
+        .collect::<Vec<u8>>();
+
+    let image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: size,
+        },
+        TextureDimension::D3,
+        data,
+        TextureFormat::Rgba16Float,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    Ok(CubeLut {
+        image,
+        domain_min,
+        domain_max,
+    })
+}
+
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_vec3<'a>(fields: impl Iterator<Item = &'a str>) -> Result<Vec3, Error> {
+    let values = fields
+        .map(|field| field.parse::<f32>()
+            .map_err(|_| invalid(format!("expected a number, found {}", field))))
+        .collect::<Result<Vec<f32>, Error>>()?;
+
+    match values.as_slice() {
+        [r, g, b] => Ok(Vec3::new(*r, *g, *b)),
+        _ => Err(invalid(format!("expected 3 values, found {}", values.len()))),
+    }
+}
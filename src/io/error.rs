@@ -0,0 +1,145 @@
+use std::fmt;
+
+
+// returned by the asset loader and the parsers, each variant names what to look for in the source file
+#[derive(Debug)]
+pub enum GaussianLoadError {
+    Io(std::io::Error),
+    // the file does not start with the `ply` magic line
+    NotPly,
+    // the `format` line names an encoding other than ascii, binary_little_endian or binary_big_endian
+    UnsupportedFormat {
+        format: String,
+        byte_offset: u64,
+    },
+    InvalidHeader {
+        message: String,
+    },
+    MissingElement {
+        element: &'static str,
+        found: Vec<String>,
+    },
+    MissingProperty {
+        element: String,
+        property: &'static str,
+    },
+    // required properties must be float or double scalars
+    UnsupportedPropertyType {
+        element: String,
+        property: String,
+        found: String,
+    },
+    // the payload ended before `expected` elements were read
+    Truncated {
+        element: String,
+        expected: usize,
+        byte_offset: u64,
+    },
+    InvalidPayload {
+        element: String,
+        byte_offset: u64,
+        message: String,
+    },
+    UnsupportedExtension {
+        extension: Option<String>,
+    },
+    // the file names a version of its format this build cannot read
+    UnsupportedVersion {
+        format: &'static str,
+        version: u32,
+        expected: u32,
+    },
+    // raised by bevy_gltf while loading the rest of a gltf with gaussian primitives
+    #[cfg(feature = "io_gltf")]
+    Gltf(Box<bevy::gltf::GltfError>),
+    FeatureDisabled {
+        extension: &'static str,
+        feature: &'static str,
+    },
+}
+
+impl fmt::Display for GaussianLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::NotPly => write!(f, "not a ply file, the first line must be `ply`"),
+            Self::UnsupportedFormat { format, byte_offset } => write!(
+                f,
+                "unsupported ply format `{}` at byte {}, expected ascii, binary_little_endian or binary_big_endian",
+                format,
+                byte_offset,
+            ),
+            Self::InvalidHeader { message } => write!(f, "invalid ply header: {}", message),
+            Self::MissingElement { element, found } => write!(
+                f,
+                "ply has no `{}` element, found [{}]",
+                element,
+                found.join(", "),
+            ),
+            Self::MissingProperty { element, property } => write!(
+                f,
+                "ply element `{}` has no `{}` property",
+                element,
+                property,
+            ),
+            Self::UnsupportedPropertyType { element, property, found } => write!(
+                f,
+                "ply property `{}.{}` is {}, expected float or double",
+                element,
+                property,
+                found,
+            ),
+            Self::Truncated { element, expected, byte_offset } => write!(
+                f,
+                "ply truncated at byte {} while reading {} `{}` elements",
+                byte_offset,
+                expected,
+                element,
+            ),
+            Self::InvalidPayload { element, byte_offset, message } => write!(
+                f,
+                "invalid ply `{}` data near byte {}: {}",
+                element,
+                byte_offset,
+                message,
+            ),
+            Self::UnsupportedExtension { extension } => write!(
+                f,
+                "unsupported extension {:?}, only .ply, .spz, .splat and .gcloud are supported",
+                extension,
+            ),
+            Self::UnsupportedVersion { format, version, expected } => write!(
+                f,
+                "unsupported {} version {}, expected {}",
+                format,
+                version,
+                expected,
+            ),
+            #[cfg(feature = "io_gltf")]
+            Self::Gltf(error) => write!(f, "gltf error: {}", error),
+            Self::FeatureDisabled { extension, feature } => write!(
+                f,
+                "{} support not enabled, enable with the {} feature",
+                extension,
+                feature,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GaussianLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            #[cfg(feature = "io_gltf")]
+            Self::Gltf(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for GaussianLoadError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -1,3 +1,10 @@
+use std::io::{
+    Error,
+    ErrorKind,
+    Read,
+    Write,
+};
+
 use bincode2::{
     deserialize_from,
     serialize_into,
@@ -7,12 +14,83 @@ use flate2::{
     read::GzDecoder,
     write::GzEncoder,
 };
+use serde::Deserialize;
 
+#[allow(unused_imports)]
 use crate::{
-    gaussian::GaussianCloud,
-    io::codec::GaussianCloudCodec,
+    GaussianCloud,
+    gaussian::f32::PositionVisibility,
+    io::{
+        codec::GaussianCloudCodec,
+        error::GaussianLoadError,
+    },
+    material::spherical_harmonics::SphericalHarmonicCoefficients,
+};
+
+#[allow(unused_imports)]
+#[cfg(feature = "f16")]
+use crate::gaussian::f16::{
+    Covariance3dOpacityPacked128,
+    RotationScaleOpacityPacked128,
 };
 
+#[allow(unused_imports)]
+#[cfg(feature = "f32")]
+use crate::gaussian::f32::{
+    Covariance3dOpacity,
+    Rotation,
+    ScaleOpacity,
+};
+
+
+// bincode is positional, so appending a field to GaussianCloud breaks every file written before it. versioned files
+// start with the magic and a little endian u32 version, files without it are the unversioned original layout
+const GCLOUD_MAGIC: [u8; 4] = *b"GCLD";
+const GCLOUD_VERSION: u32 = 1;
+
+
+// the GaussianCloud fields written before versioning, frozen
+#[derive(Deserialize)]
+struct GaussianCloudV0 {
+    position_visibility: Vec<PositionVisibility>,
+    spherical_harmonic: Vec<SphericalHarmonicCoefficients>,
+
+    #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+    rotation_scale_opacity_packed128: Vec<RotationScaleOpacityPacked128>,
+    #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+    covariance_3d_opacity_packed128: Vec<Covariance3dOpacityPacked128>,
+
+    #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+    covariance_3d: Vec<Covariance3dOpacity>,
+    #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+    rotation: Vec<Rotation>,
+    #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+    scale_opacity: Vec<ScaleOpacity>,
+}
+
+impl From<GaussianCloudV0> for GaussianCloud {
+    fn from(cloud: GaussianCloudV0) -> Self {
+        GaussianCloud {
+            position_visibility: cloud.position_visibility,
+            spherical_harmonic: cloud.spherical_harmonic,
+
+            #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+            rotation_scale_opacity_packed128: cloud.rotation_scale_opacity_packed128,
+            #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+            covariance_3d_opacity_packed128: cloud.covariance_3d_opacity_packed128,
+
+            #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+            covariance_3d: cloud.covariance_3d,
+            #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+            rotation: cloud.rotation,
+            #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+            scale_opacity: cloud.scale_opacity,
+
+            ..Default::default()
+        }
+    }
+}
+
 
 impl GaussianCloudCodec for GaussianCloud {
     fn encode(&self) -> Vec<u8> {
@@ -20,16 +98,43 @@ impl GaussianCloudCodec for GaussianCloud {
 
         {
             let mut gz_encoder = GzEncoder::new(&mut output, Compression::default());
+            gz_encoder.write_all(&GCLOUD_MAGIC).expect("failed to encode cloud");
+            gz_encoder.write_all(&GCLOUD_VERSION.to_le_bytes()).expect("failed to encode cloud");
             serialize_into(&mut gz_encoder, &self).expect("failed to encode cloud");
         }
 
         output
     }
 
-    fn decode(data: &[u8]) -> Self {
-        let decompressed = GzDecoder::new(data);
-        let cloud: GaussianCloud = deserialize_from(decompressed).expect("failed to decode cloud");
+    fn decode(data: &[u8]) -> Result<Self, GaussianLoadError> {
+        let invalid = |message: String| Error::new(ErrorKind::InvalidData, message);
+
+        let mut decompressed = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+
+        let Some(versioned) = decompressed.strip_prefix(&GCLOUD_MAGIC) else {
+            let cloud: GaussianCloudV0 = deserialize_from(decompressed.as_slice())
+                .map_err(|error| invalid(format!("unversioned gcloud: {}", error)))?;
+
+            return Ok(cloud.into());
+        };
+
+        let (version, payload) = versioned.split_at(versioned.len().min(4));
+        let version = <[u8; 4]>::try_from(version)
+            .map(u32::from_le_bytes)
+            .map_err(|_| invalid("gcloud version truncated".to_string()))?;
+
+        if version != GCLOUD_VERSION {
+            return Err(GaussianLoadError::UnsupportedVersion {
+                format: "gcloud",
+                version,
+                expected: GCLOUD_VERSION,
+            });
+        }
+
+        let cloud = deserialize_from(payload)
+            .map_err(|error| invalid(format!("gcloud version {}: {}", version, error)))?;
 
-        cloud
+        Ok(cloud)
     }
 }
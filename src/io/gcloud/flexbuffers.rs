@@ -1,3 +1,8 @@
+use std::io::{
+    Error,
+    ErrorKind,
+};
+
 use flexbuffers::{
     FlexbufferSerializer,
     Reader,
@@ -9,7 +14,10 @@ use serde::{
 
 use crate::{
     GaussianCloud,
-    io::codec::GaussianCloudCodec,
+    io::{
+        codec::GaussianCloudCodec,
+        error::GaussianLoadError,
+    },
 };
 
 
@@ -21,10 +29,12 @@ impl GaussianCloudCodec for GaussianCloud {
         serializer.view().to_vec()
     }
 
-    fn decode(data: &[u8]) -> Self {
-        let reader = Reader::get_root(data).expect("failed to read flexbuffer");
-        let cloud = GaussianCloud::deserialize(reader).expect("deserialization failed");
+    fn decode(data: &[u8]) -> Result<Self, GaussianLoadError> {
+        let invalid = |error: &dyn std::fmt::Display| Error::new(ErrorKind::InvalidData, format!("gcloud flexbuffer: {}", error));
+
+        let reader = Reader::get_root(data).map_err(|error| invalid(&error))?;
+        let cloud = GaussianCloud::deserialize(reader).map_err(|error| invalid(&error))?;
 
-        cloud
+        Ok(cloud)
     }
 }
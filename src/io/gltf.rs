@@ -0,0 +1,770 @@
+use std::io::{
+    Error,
+    ErrorKind,
+};
+
+use base64::Engine;
+use bevy::{
+    prelude::*,
+    asset::{
+        AssetLoader,
+        AsyncReadExt,
+        LoadContext,
+        io::{
+            Reader,
+            VecReader,
+        },
+    },
+    gltf::{
+        Gltf,
+        GltfAssetLabel,
+        GltfLoader,
+        GltfLoaderSettings,
+        GltfPlugin,
+    },
+    math::Affine3A,
+    render::{
+        renderer::RenderDevice,
+        texture::CompressedImageFormats,
+    },
+    tasks::AsyncComputeTaskPool,
+    transform::TransformSystem,
+    utils::HashMap,
+};
+use serde_json::Value;
+
+use crate::{
+    Gaussian,
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    io::error::GaussianLoadError,
+    material::spherical_harmonics::{
+        SH_C0,
+        SH_CHANNELS,
+        SH_DEGREE,
+    },
+};
+
+
+pub const GLTF_GAUSSIAN_EXTENSION: &str = "KHR_gaussian_splatting";
+
+const GLB_MAGIC: u32 = 0x46546c67;
+const GLB_CHUNK_JSON: u32 = 0x4e4f534a;
+const GLB_CHUNK_BIN: u32 = 0x004e4942;
+
+
+// registers GaussianGltfLoader in place of bevy_gltf's loader once GltfPlugin has registered its own
+#[derive(Default)]
+pub struct GaussianGltfPlugin;
+
+impl Plugin for GaussianGltfPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GltfGaussianCloud>();
+        app.add_systems(
+            PostUpdate,
+            follow_gltf_node_transforms.after(TransformSystem::TransformPropagate),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // the wrapped loader fills the gltf asset types GltfPlugin initializes
+        if !app.is_plugin_added::<GltfPlugin>() {
+            return;
+        }
+
+        let supported_compressed_formats = match app.world().get_resource::<RenderDevice>() {
+            Some(render_device) => CompressedImageFormats::from_features(render_device.features()),
+            None => CompressedImageFormats::NONE,
+        };
+
+        app.register_asset_loader(GaussianGltfLoader {
+            gltf: GltfLoader {
+                supported_compressed_formats,
+                custom_vertex_attributes: default(),
+            },
+        });
+    }
+}
+
+
+// the clouds of gltf scenes, their settings follow the node hierarchy's GlobalTransform
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct GltfGaussianCloud;
+
+type MovedGltfGaussianClouds = (With<GltfGaussianCloud>, Changed<GlobalTransform>);
+
+fn follow_gltf_node_transforms(
+    mut clouds: Query<(&GlobalTransform, &mut GaussianCloudSettings), MovedGltfGaussianClouds>,
+) {
+    for (global_transform, mut settings) in clouds.iter_mut() {
+        settings.transform = global_transform.compute_transform();
+    }
+}
+
+
+// one primitive carrying the extension, clouds are labeled by gaussian_cloud_label(mesh, primitive)
+#[derive(Clone, Debug)]
+pub struct GltfGaussianPrimitive {
+    pub mesh: usize,
+    pub primitive: usize,
+    pub gaussians: Vec<Gaussian>,
+}
+
+// e.g. `scene.glb#Mesh0/Primitive0/GaussianCloud`, under the label bevy_gltf would give the primitive's mesh
+pub fn gaussian_cloud_label(mesh: usize, primitive: usize) -> String {
+    format!("{}/GaussianCloud", GltfAssetLabel::Primitive { mesh, primitive })
+}
+
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn get_usize(value: &Value, key: &str) -> Option<usize> {
+    value.get(key).and_then(Value::as_u64).map(|value| value as usize)
+}
+
+fn get_f32s<const N: usize>(value: &Value, key: &str) -> Option<[f32; N]> {
+    let array = value.get(key)?.as_array()?;
+    if array.len() != N {
+        return None;
+    }
+
+    let mut values = [0.0; N];
+    for (value, element) in values.iter_mut().zip(array) {
+        *value = element.as_f64()? as f32;
+    }
+
+    Some(values)
+}
+
+
+// splits a glb into its json document and binary chunk
+fn split_glb(bytes: &[u8]) -> Result<(Value, &[u8]), Error> {
+    if bytes.len() < 12 || read_u32(bytes, 0) != GLB_MAGIC {
+        return Err(invalid("not a glb file".to_string()));
+    }
+
+    if read_u32(bytes, 4) != 2 {
+        return Err(invalid(format!("unsupported glb version {}, expected 2", read_u32(bytes, 4))));
+    }
+
+    let mut json = None;
+    let mut bin: &[u8] = &[];
+
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let length = read_u32(bytes, offset) as usize;
+        let chunk_type = read_u32(bytes, offset + 4);
+        let data = bytes.get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| invalid(format!("glb chunk at byte {} truncated", offset)))?;
+
+        match chunk_type {
+            GLB_CHUNK_JSON => {
+                let document = serde_json::from_slice(data)
+                    .map_err(|error| invalid(format!("glb json: {}", error)))?;
+                json = Some(document);
+            },
+            GLB_CHUNK_BIN if bin.is_empty() => bin = data,
+            _ => {},
+        }
+
+        // chunks are 4 byte aligned
+        offset += 8 + ((length + 3) & !3);
+    }
+
+    let json = json.ok_or_else(|| invalid("glb has no json chunk".to_string()))?;
+
+    Ok((json, bin))
+}
+
+
+// flattened accessor values, normalized integers map to [0, 1] (or [-1, 1] when signed). the file's counts and
+// offsets are checked against `expected_count` and the buffer view before anything is allocated
+fn read_accessor(
+    json: &Value,
+    buffers: &[Vec<u8>],
+    index: usize,
+    expected_count: Option<usize>,
+) -> Result<(Vec<f32>, usize), Error> {
+    let accessor = json["accessors"].get(index)
+        .ok_or_else(|| invalid(format!("accessor {} does not exist", index)))?;
+
+    let components = match accessor["type"].as_str() {
+        Some("SCALAR") => 1,
+        Some("VEC2") => 2,
+        Some("VEC3") => 3,
+        Some("VEC4") => 4,
+        other => return Err(invalid(format!("accessor {} has unsupported type {:?}", index, other))),
+    };
+
+    let component_type = get_usize(accessor, "componentType").unwrap_or(0);
+    let component_size = match component_type {
+        5120 | 5121 => 1,
+        5122 | 5123 => 2,
+        5125 | 5126 => 4,
+        _ => return Err(invalid(format!("accessor {} has unsupported component type {}", index, component_type))),
+    };
+
+    let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+    let count = get_usize(accessor, "count").unwrap_or(0);
+
+    if accessor.get("sparse").is_some() {
+        return Err(invalid(format!("accessor {} is sparse, sparse accessors are not supported", index)));
+    }
+
+    if expected_count.is_some_and(|expected_count| expected_count != count) {
+        return Err(invalid(format!("accessor {} holds {} elements, expected {:?}", index, count, expected_count)));
+    }
+
+    let value_count = count.checked_mul(components)
+        .ok_or_else(|| invalid(format!("accessor {} count {} overflows", index, count)))?;
+
+    // accessors without a buffer view are all zeros, only accepted where the count is known from another accessor
+    let Some(view_index) = get_usize(accessor, "bufferView") else {
+        if expected_count.is_none() {
+            return Err(invalid(format!("accessor {} has no buffer view", index)));
+        }
+
+        return Ok((vec![0.0; value_count], components));
+    };
+
+    let view = json["bufferViews"].get(view_index)
+        .ok_or_else(|| invalid(format!("buffer view {} does not exist", view_index)))?;
+
+    let bin = get_usize(view, "buffer")
+        .and_then(|buffer| buffers.get(buffer))
+        .ok_or_else(|| invalid(format!("buffer view {} has no buffer", view_index)))?;
+
+    let element_size = components * component_size;
+    let stride = get_usize(view, "byteStride").unwrap_or(element_size);
+    let view_start = get_usize(view, "byteOffset").unwrap_or(0);
+    let view_end = view_start.checked_add(get_usize(view, "byteLength").unwrap_or(0))
+        .filter(|view_end| *view_end <= bin.len())
+        .ok_or_else(|| invalid(format!("buffer view {} reads past the end of its buffer", view_index)))?;
+    let start = view_start.checked_add(get_usize(accessor, "byteOffset").unwrap_or(0));
+
+    let end = match count {
+        0 => start,
+        _ => start.and_then(|start| {
+            (count - 1).checked_mul(stride)?
+                .checked_add(element_size)?
+                .checked_add(start)
+        }),
+    };
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(invalid(format!("accessor {} offsets overflow", index)));
+    };
+
+    if stride < element_size || end > view_end {
+        return Err(invalid(format!("accessor {} reads past the end of buffer view {}", index, view_index)));
+    }
+
+    let mut values = Vec::with_capacity(value_count);
+    for element in 0..count {
+        for component in 0..components {
+            let offset = start + element * stride + component * component_size;
+            let bytes = &bin[offset..offset + component_size];
+
+            let value = match (component_type, normalized) {
+                (5126, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                (5121, true) => bytes[0] as f32 / 255.0,
+                (5123, true) => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 65535.0,
+                (5120, true) => (bytes[0] as i8 as f32 / 127.0).max(-1.0),
+                (5122, true) => (i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32767.0).max(-1.0),
+                (5121, false) => bytes[0] as f32,
+                (5123, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                (5120, false) => bytes[0] as i8 as f32,
+                (5122, false) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                _ => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+            };
+
+            values.push(value);
+        }
+    }
+
+    Ok((values, components))
+}
+
+
+fn node_transform(node: &Value) -> Affine3A {
+    if let Some(matrix) = get_f32s::<16>(node, "matrix") {
+        return Affine3A::from_mat4(Mat4::from_cols_array(&matrix));
+    }
+
+    let translation = get_f32s::<3>(node, "translation").map_or(Vec3::ZERO, Vec3::from_array);
+    let rotation = get_f32s::<4>(node, "rotation").map_or(Quat::IDENTITY, Quat::from_array);
+    let scale = get_f32s::<3>(node, "scale").map_or(Vec3::ONE, Vec3::from_array);
+
+    Affine3A::from_scale_rotation_translation(scale, rotation, translation)
+}
+
+
+// attributes are looked up under the extension's prefix first, then as `_` prefixed custom attributes
+fn attribute(attributes: &Value, name: &str) -> Option<usize> {
+    get_usize(attributes, &format!("{}:{}", GLTF_GAUSSIAN_EXTENSION, name))
+        .or_else(|| get_usize(attributes, &format!("_{}", name)))
+}
+
+
+fn parse_primitive(
+    json: &Value,
+    buffers: &[Vec<u8>],
+    attributes: &Value,
+) -> Result<Vec<Gaussian>, Error> {
+    let required = |name: &str, index: Option<usize>| index
+        .ok_or_else(|| invalid(format!("gaussian primitive has no {} attribute", name)));
+
+    let position_index = required("POSITION", get_usize(attributes, "POSITION"))?;
+    let (positions, components) = read_accessor(json, buffers, position_index, None)?;
+    if components != 3 {
+        return Err(invalid("POSITION must be a vec3".to_string()));
+    }
+    let count = positions.len() / 3;
+
+    let read = |index: usize, components: usize, name: &str| -> Result<Vec<f32>, Error> {
+        let (values, found) = read_accessor(json, buffers, index, Some(count))?;
+        if found != components || values.len() != count * components {
+            return Err(invalid(format!("{} must hold one vec{} per position", name, components)));
+        }
+
+        Ok(values)
+    };
+
+    let rotations = read(required("ROTATION", attribute(attributes, "ROTATION"))?, 4, "ROTATION")?;
+    let scales = read(required("SCALE", attribute(attributes, "SCALE"))?, 3, "SCALE")?;
+    let colors = read(required("COLOR_0", get_usize(attributes, "COLOR_0"))?, 4, "COLOR_0")?;
+    let opacities = attribute(attributes, "OPACITY")
+        .map(|index| read(index, 1, "OPACITY"))
+        .transpose()?;
+
+    let mut rest = Vec::new();
+    for degree in 1..=SH_DEGREE {
+        for coefficient in 0..2 * degree + 1 {
+            let name = format!("SH_DEGREE_{}_COEF_{}", degree, coefficient);
+            if let Some(index) = attribute(attributes, &name) {
+                rest.push((degree * degree + coefficient, read(index, 3, &name)?));
+            }
+        }
+    }
+
+    let gaussians = (0..count)
+        .map(|i| {
+            let mut gaussian = Gaussian::default();
+
+            gaussian.position_visibility.position = [positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]];
+            gaussian.position_visibility.visibility = 1.0;
+
+            // gltf quaternions are xyzw, gaussians store wxyz
+            let [x, y, z, w] = [rotations[i * 4], rotations[i * 4 + 1], rotations[i * 4 + 2], rotations[i * 4 + 3]];
+            gaussian.rotation.rotation = [w, x, y, z];
+
+            gaussian.scale_opacity.scale = [scales[i * 3], scales[i * 3 + 1], scales[i * 3 + 2]];
+            gaussian.scale_opacity.opacity = opacities.as_ref().map_or(colors[i * 4 + 3], |opacities| opacities[i]);
+
+            for channel in 0..SH_CHANNELS {
+                gaussian.spherical_harmonic.set(channel, (colors[i * 4 + channel] - 0.5) / SH_C0);
+            }
+
+            for (coefficient, values) in rest.iter() {
+                for channel in 0..SH_CHANNELS {
+                    gaussian.spherical_harmonic.set(coefficient * SH_CHANNELS + channel, values[i * 3 + channel]);
+                }
+            }
+
+            gaussian
+        })
+        .collect();
+
+    Ok(gaussians)
+}
+
+
+fn is_gaussian(primitive: &Value) -> bool {
+    primitive["extensions"].get(GLTF_GAUSSIAN_EXTENSION).is_some()
+}
+
+fn has_gaussian_primitives(json: &Value) -> bool {
+    json["meshes"].as_array().into_iter().flatten()
+        .flat_map(|mesh| mesh["primitives"].as_array().into_iter().flatten())
+        .any(is_gaussian)
+}
+
+
+// the json document of a .gltf or .glb file, with the binary chunk of a glb
+fn split_gltf(bytes: &[u8]) -> Result<(Value, Option<&[u8]>), Error> {
+    if bytes.starts_with(b"glTF") {
+        let (json, bin) = split_glb(bytes)?;
+        return Ok((json, Some(bin)));
+    }
+
+    let json = serde_json::from_slice(bytes)
+        .map_err(|error| invalid(format!("gltf json: {}", error)))?;
+
+    Ok((json, None))
+}
+
+// the glb binary chunk or a base64 data uri, None for buffers in external files
+fn embedded_buffer(buffer: &Value, index: usize, bin: Option<&[u8]>) -> Result<Option<Vec<u8>>, Error> {
+    let Some(uri) = buffer["uri"].as_str() else {
+        return match bin {
+            Some(bin) if index == 0 => Ok(Some(bin.to_vec())),
+            _ => Err(invalid(format!("buffer {} has no uri and is not the glb binary chunk", index))),
+        };
+    };
+
+    let Some(data) = uri.strip_prefix("data:") else {
+        return Ok(None);
+    };
+
+    let encoded = data.split_once(";base64,")
+        .map(|(_, encoded)| encoded)
+        .ok_or_else(|| invalid(format!("buffer {} data uri is not base64", index)))?;
+
+    base64::engine::general_purpose::STANDARD.decode(encoded)
+        .map(Some)
+        .map_err(|error| invalid(format!("buffer {} data uri: {}", index, error)))
+}
+
+// every buffer of the document, external files are read relative to the gltf
+async fn load_buffers(
+    json: &Value,
+    bin: Option<&[u8]>,
+    load_context: &mut LoadContext<'_>,
+) -> Result<Vec<Vec<u8>>, GaussianLoadError> {
+    let mut buffers = Vec::new();
+
+    for (index, buffer) in json["buffers"].as_array().into_iter().flatten().enumerate() {
+        if let Some(data) = embedded_buffer(buffer, index, bin)? {
+            buffers.push(data);
+            continue;
+        }
+
+        let uri = buffer["uri"].as_str().unwrap_or_default();
+        let path = load_context.asset_path().resolve_embed(uri)
+            .map_err(|error| invalid(format!("buffer {} uri {}: {}", index, uri, error)))?;
+        let data = load_context.read_asset_bytes(path).await
+            .map_err(|error| invalid(format!("buffer {} uri {}: {}", index, uri, error)))?;
+
+        buffers.push(data);
+    }
+
+    Ok(buffers)
+}
+
+
+// every primitive with the gaussian splatting extension, in mesh and primitive order
+pub fn parse_gaussian_primitives(
+    json: &Value,
+    buffers: &[Vec<u8>],
+) -> Result<Vec<GltfGaussianPrimitive>, Error> {
+    let mut primitives = Vec::new();
+
+    for (mesh, value) in json["meshes"].as_array().into_iter().flatten().enumerate() {
+        for (primitive, value) in value["primitives"].as_array().into_iter().flatten().enumerate() {
+            if !is_gaussian(value) {
+                continue;
+            }
+
+            primitives.push(GltfGaussianPrimitive {
+                mesh,
+                primitive,
+                gaussians: parse_primitive(json, buffers, &value["attributes"])?,
+            });
+        }
+    }
+
+    Ok(primitives)
+}
+
+// the gaussian primitives of a .gltf or .glb file whose buffers are all embedded
+pub fn parse_gltf(bytes: &[u8]) -> Result<Vec<GltfGaussianPrimitive>, GaussianLoadError> {
+    let (json, bin) = split_gltf(bytes)?;
+
+    let buffers = json["buffers"].as_array().into_iter().flatten()
+        .enumerate()
+        .map(|(index, buffer)| embedded_buffer(buffer, index, bin)?
+            .ok_or_else(|| invalid(format!("buffer {} is an external file", index))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(parse_gaussian_primitives(&json, &buffers)?)
+}
+
+
+// the document bevy_gltf loads for a file with gaussian primitives: without them, so their attributes pass its
+// validation, and without scenes, which are built by gaussian_gltf_scene instead
+fn strip_gaussian_primitives(json: &Value) -> Value {
+    let mut json = json.clone();
+
+    // get_mut, indexing a missing key mutably would insert a null the gltf schema rejects
+    for mesh in json.get_mut("meshes").and_then(Value::as_array_mut).into_iter().flatten() {
+        if let Some(primitives) = mesh.get_mut("primitives").and_then(Value::as_array_mut) {
+            primitives.retain(|primitive| !is_gaussian(primitive));
+        }
+    }
+
+    for key in ["extensionsUsed", "extensionsRequired"] {
+        if let Some(extensions) = json.get_mut(key).and_then(Value::as_array_mut) {
+            extensions.retain(|extension| extension.as_str() != Some(GLTF_GAUSSIAN_EXTENSION));
+        }
+    }
+
+    if let Some(root) = json.as_object_mut() {
+        root.remove("scene");
+        root.remove("scenes");
+    }
+
+    json
+}
+
+fn encode_gltf(json: &Value, bin: Option<&[u8]>) -> Vec<u8> {
+    let mut json = serde_json::to_vec(json).unwrap();
+
+    let Some(bin) = bin else {
+        return json;
+    };
+
+    // a glb without a binary chunk stays without one
+    let chunks = if bin.is_empty() { 1 } else { 2 };
+
+    // chunks are 4 byte aligned, json is padded with spaces and the binary chunk with zeros
+    json.resize((json.len() + 3) & !3, b' ');
+    let bin_length = (bin.len() + 3) & !3;
+    let length = 12 + 8 + json.len() + (chunks - 1) * (8 + bin_length);
+
+    let mut bytes = Vec::with_capacity(length);
+    bytes.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(length as u32).to_le_bytes());
+
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+    bytes.extend_from_slice(&json);
+
+    if chunks == 1 {
+        return bytes;
+    }
+
+    bytes.extend_from_slice(&(bin_length as u32).to_le_bytes());
+    bytes.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+    bytes.extend_from_slice(bin);
+    bytes.resize(bytes.len() + bin_length - bin.len(), 0);
+
+    bytes
+}
+
+
+enum ScenePrimitive {
+    Cloud(Handle<GaussianCloud>),
+    Mesh(Handle<Mesh>, Handle<StandardMaterial>),
+}
+
+fn spawn_node(
+    parent: &mut WorldChildBuilder,
+    nodes: &[Value],
+    primitives: &[Vec<(String, ScenePrimitive)>],
+    index: usize,
+    depth: usize,
+) -> Result<(), Error> {
+    let node = nodes.get(index)
+        .ok_or_else(|| invalid(format!("node {} does not exist", index)))?;
+
+    if depth > nodes.len() {
+        return Err(invalid("gltf node hierarchy has a cycle".to_string()));
+    }
+
+    let name = node["name"].as_str()
+        .map_or_else(|| format!("GltfNode{}", index), str::to_string);
+    let transform = Transform::from_matrix(Mat4::from(node_transform(node)));
+
+    let mut result = Ok(());
+    parent.spawn((SpatialBundle::from_transform(transform), Name::new(name)))
+        .with_children(|parent| {
+            let mesh = get_usize(node, "mesh").and_then(|mesh| primitives.get(mesh));
+
+            for (name, primitive) in mesh.into_iter().flatten() {
+                match primitive {
+                    ScenePrimitive::Cloud(cloud) => parent.spawn((
+                        GaussianSplattingBundle {
+                            cloud: cloud.clone(),
+                            ..default()
+                        },
+                        TransformBundle::default(),
+                        GltfGaussianCloud,
+                        Name::new(name.clone()),
+                    )),
+                    ScenePrimitive::Mesh(mesh, material) => parent.spawn((
+                        PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            ..default()
+                        },
+                        Name::new(name.clone()),
+                    )),
+                };
+            }
+
+            for child in node["children"].as_array().into_iter().flatten().filter_map(Value::as_u64) {
+                if result.is_ok() {
+                    result = spawn_node(parent, nodes, primitives, child as usize, depth + 1);
+                }
+            }
+        });
+
+    result
+}
+
+// the nodes of a gltf scene with their names and transforms, gaussian primitives spawn a GaussianSplattingBundle
+// under their node, other primitives the mesh and material bevy_gltf loaded for them
+fn gaussian_gltf_scene(
+    json: &Value,
+    scene: &Value,
+    primitives: &[Vec<(String, ScenePrimitive)>],
+) -> Result<Scene, Error> {
+    let nodes = json["nodes"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+    let mut world = World::default();
+    let mut result = Ok(());
+
+    world.spawn(SpatialBundle::INHERITED_IDENTITY)
+        .with_children(|parent| {
+            for root in scene["nodes"].as_array().into_iter().flatten().filter_map(Value::as_u64) {
+                if result.is_ok() {
+                    result = spawn_node(parent, nodes, primitives, root as usize, 0);
+                }
+            }
+        });
+
+    result.map(|_| Scene::new(world))
+}
+
+
+// loads .gltf and .glb files in place of bevy_gltf's loader. files without gaussian primitives are handed to it
+// unchanged. otherwise it loads everything but the gaussian primitives, each of those becomes a GaussianCloud labeled
+// by gaussian_cloud_label, and the scenes are rebuilt with the clouds under their nodes
+pub struct GaussianGltfLoader {
+    pub gltf: GltfLoader,
+}
+
+impl AssetLoader for GaussianGltfLoader {
+    type Asset = Gltf;
+    type Settings = GltfLoaderSettings;
+    type Error = GaussianLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let (json, bin) = split_gltf(&bytes)?;
+
+        if !has_gaussian_primitives(&json) {
+            return self.gltf.load(&mut VecReader::new(bytes), settings, load_context).await
+                .map_err(|error| GaussianLoadError::Gltf(Box::new(error)));
+        }
+
+        let buffers = load_buffers(&json, bin, load_context).await?;
+        let stripped = encode_gltf(&strip_gaussian_primitives(&json), bin);
+
+        // parsing is cpu bound, run it on the compute pool so asset io threads stay responsive
+        let (json, gaussian_primitives) = AsyncComputeTaskPool::get().spawn(async move {
+            let primitives = parse_gaussian_primitives(&json, &buffers);
+            (json, primitives)
+        }).await;
+        let gaussian_primitives = gaussian_primitives?;
+
+        let mut gltf = self.gltf.load(&mut VecReader::new(stripped), settings, load_context).await
+            .map_err(|error| GaussianLoadError::Gltf(Box::new(error)))?;
+
+        let mut clouds = gaussian_primitives.into_iter()
+            .map(|primitive| {
+                let label = gaussian_cloud_label(primitive.mesh, primitive.primitive);
+                let cloud = load_context.add_labeled_asset(label, GaussianCloud::from_gaussians(primitive.gaussians));
+
+                ((primitive.mesh, primitive.primitive), cloud)
+            })
+            .collect::<HashMap<_, _>>();
+
+        // bevy_gltf only adds the default material for the scene nodes it spawns, a glTF default material
+        let default_material = GltfAssetLabel::DefaultMaterial.to_string();
+        let mut default_material_added = false;
+
+        let mut primitives = Vec::new();
+        for (mesh_index, mesh) in json["meshes"].as_array().into_iter().flatten().enumerate() {
+            let mesh_primitives = mesh["primitives"].as_array().map(Vec::as_slice).unwrap_or_default();
+            let mesh_name = mesh["name"].as_str().unwrap_or("Mesh");
+
+            let mut loaded = 0;
+            let mut spawned = Vec::new();
+
+            for (primitive_index, primitive) in mesh_primitives.iter().enumerate() {
+                let name = match mesh_primitives.len() {
+                    1 => mesh_name.to_string(),
+                    _ => format!("{}.{}", mesh_name, primitive_index),
+                };
+
+                if let Some(cloud) = clouds.remove(&(mesh_index, primitive_index)) {
+                    spawned.push((name, ScenePrimitive::Cloud(cloud)));
+                    continue;
+                }
+
+                let mesh_label = GltfAssetLabel::Primitive { mesh: mesh_index, primitive: loaded };
+                loaded += 1;
+
+                let material_label = match get_usize(primitive, "material") {
+                    Some(index) => GltfAssetLabel::Material { index, is_scale_inverted: false }.to_string(),
+                    None => {
+                        if !default_material_added && !settings.load_materials.is_empty() {
+                            load_context.add_labeled_asset(default_material.clone(), StandardMaterial {
+                                metallic: 1.0,
+                                perceptual_roughness: 1.0,
+                                ..default()
+                            });
+                            default_material_added = true;
+                        }
+
+                        default_material.clone()
+                    },
+                };
+
+                spawned.push((name, ScenePrimitive::Mesh(
+                    load_context.get_label_handle(mesh_label.to_string()),
+                    load_context.get_label_handle(material_label),
+                )));
+            }
+
+            primitives.push(spawned);
+        }
+
+        for (index, scene) in json["scenes"].as_array().into_iter().flatten().enumerate() {
+            let handle = load_context.add_labeled_asset(
+                GltfAssetLabel::Scene(index).to_string(),
+                gaussian_gltf_scene(&json, scene, &primitives)?,
+            );
+
+            if let Some(name) = scene["name"].as_str() {
+                gltf.named_scenes.insert(name.into(), handle.clone());
+            }
+
+            gltf.scenes.push(handle);
+        }
+
+        gltf.default_scene = gltf.scenes.get(get_usize(&json, "scene").unwrap_or(0)).cloned();
+
+        Ok(gltf)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+}
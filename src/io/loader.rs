@@ -1,67 +1,326 @@
 #[allow(unused_imports)]
-use std::io::{
-    BufReader,
-    Cursor,
-    ErrorKind,
+use std::{
+    collections::HashMap,
+    io::{
+        BufRead,
+        BufReader,
+        Cursor,
+        Read,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
 };
 
-use bevy::asset::{
-    AssetLoader,
-    AsyncReadExt,
-    LoadContext,
-    io::Reader,
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+    asset::{
+        AssetLoader,
+        AssetPath,
+        AsyncReadExt,
+        LoadContext,
+        io::Reader,
+    },
+    tasks::AsyncComputeTaskPool,
+};
+use serde::{
+    Deserialize,
+    Serialize,
 };
 
 use crate::{
     GaussianCloud,
-    io::codec::GaussianCloudCodec,
+    io::{
+        codec::GaussianCloudCodec,
+        error::GaussianLoadError,
+    },
 };
 
 
+// parse progress in [0, 1] for clouds currently or previously decoded by the loader
+#[derive(Clone, Debug, Default, Resource)]
+pub struct GaussianCloudLoadProgress {
+    progress: Arc<Mutex<HashMap<AssetPath<'static>, f32>>>,
+}
+
+impl GaussianCloudLoadProgress {
+    pub fn get(&self, path: &AssetPath) -> Option<f32> {
+        self.progress.lock().unwrap().get(path).copied()
+    }
+
+    #[cfg(any(feature = "io_ply", feature = "io_spz"))]
+    fn set(&self, path: &AssetPath<'static>, progress: f32) {
+        self.progress.lock().unwrap().insert(path.clone(), progress);
+    }
+}
+
+
+// transforms baked in by `recenter_and_normalize`, the inverse maps a loaded cloud back to source coordinates
+#[derive(Clone, Debug, Default, Resource)]
+pub struct GaussianCloudLoadTransforms {
+    transforms: Arc<Mutex<HashMap<AssetPath<'static>, Affine3A>>>,
+}
+
+impl GaussianCloudLoadTransforms {
+    pub fn get(&self, path: &AssetPath) -> Option<Affine3A> {
+        self.transforms.lock().unwrap().get(path).copied()
+    }
+
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    fn set(&self, path: &AssetPath<'static>, transform: Affine3A) {
+        self.transforms.lock().unwrap().insert(path.clone(), transform);
+    }
+}
+
+
+#[cfg(feature = "io_ply")]
+struct ProgressReader<R, F> {
+    inner: R,
+    read: usize,
+    reported: usize,
+    report: F,
+}
+
+#[cfg(feature = "io_ply")]
+impl<R, F: FnMut(usize)> ProgressReader<R, F> {
+    const REPORT_INTERVAL: usize = 1 << 20;
+
+    fn new(inner: R, report: F) -> Self {
+        Self {
+            inner,
+            read: 0,
+            reported: 0,
+            report,
+        }
+    }
+
+    fn advance(&mut self, amount: usize) {
+        self.read += amount;
+
+        if self.read - self.reported >= Self::REPORT_INTERVAL {
+            self.reported = self.read;
+            (self.report)(self.read);
+        }
+    }
+}
+
+#[cfg(feature = "io_ply")]
+impl<R: Read, F: FnMut(usize)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.advance(read);
+
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "io_ply")]
+impl<R: BufRead, F: FnMut(usize)> BufRead for ProgressReader<R, F> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.advance(amount);
+    }
+}
+
+
+// Activate applies sigmoid to ply opacities and exp to scales (reference 3dgs exports store logits and log scales),
+// Raw keeps already activated values, Auto picks per attribute: logits once any opacity leaves [0, 1], log scales
+// once any scale is negative
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum ActivationMode {
+    Raw,
+    Activate,
+    #[default]
+    Auto,
+}
+
+
+// order of the ply f_rest_* coefficients. ChannelMajor is the reference 3dgs layout (all red coefficients, then
+// green, then blue), CoefficientMajor interleaves rgb per coefficient as some exporters do. property order in the
+// header never matters, coefficients are matched by name
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum ShCoefficientLayout {
+    #[default]
+    ChannelMajor,
+    CoefficientMajor,
+}
+
+
+#[derive(
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
+pub struct GaussianCloudLoaderSettings {
+    // skip ply gaussians with nan or infinite attributes, the dropped count is logged
+    pub sanitize_on_load: bool,
+    pub activation: ActivationMode,
+    pub sh_layout: ShCoefficientLayout,
+    // recenters on the weighted centroid and scales to a unit bounding box, see GaussianCloudLoadTransforms.
+    // ignored with precompute_covariance_3d
+    pub recenter_and_normalize: bool,
+    // scales down opacities in dense regions by density_normalize_strength, see GaussianCloud::normalize_density.
+    // ignored with precompute_covariance_3d
+    pub density_normalize: bool,
+    pub density_normalize_strength: f32,
+    // overrides the cloud's importance luminance weight, see GaussianCloud::set_importance_luminance_weight. None
+    // keeps the loaded weight, 0.0 for everything but gcloud files
+    pub importance_luminance_weight: Option<f32>,
+}
+
+impl Default for GaussianCloudLoaderSettings {
+    fn default() -> Self {
+        Self {
+            sanitize_on_load: false,
+            activation: ActivationMode::default(),
+            sh_layout: ShCoefficientLayout::default(),
+            recenter_and_normalize: false,
+            density_normalize: false,
+            density_normalize_strength: 1.0,
+            importance_luminance_weight: None,
+        }
+    }
+}
+
+
 #[derive(Default)]
-pub struct GaussianCloudLoader;
+pub struct GaussianCloudLoader {
+    pub progress: GaussianCloudLoadProgress,
+    pub transforms: GaussianCloudLoadTransforms,
+}
 
 impl AssetLoader for GaussianCloudLoader {
     type Asset = GaussianCloud;
-    type Settings = ();
-    type Error = std::io::Error;
+    type Settings = GaussianCloudLoaderSettings;
+    type Error = GaussianLoadError;
 
+    #[allow(unused_variables)]
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
-        _settings: &'a Self::Settings,
+        settings: &'a Self::Settings,
         load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
-        match load_context.path().extension() {
+        #[allow(unused_mut)]
+        let mut cloud = match load_context.path().extension() {
             Some(ext) if ext == "ply" => {
                 #[cfg(feature = "io_ply")]
                 {
-                    let cursor = Cursor::new(bytes);
-                    let mut f = BufReader::new(cursor);
+                    let progress = self.progress.clone();
+                    let path = load_context.asset_path().clone_owned();
+                    let settings = settings.clone();
+
+                    // parsing is cpu bound, run it on the compute pool so asset io threads stay responsive
+                    AsyncComputeTaskPool::get().spawn(async move {
+                        let total = bytes.len().max(1);
+                        progress.set(&path, 0.0);
+
+                        let cursor = Cursor::new(bytes);
+                        let mut f = ProgressReader::new(
+                            BufReader::new(cursor),
+                            |read| progress.set(&path, read as f32 / total as f32),
+                        );
+
+                        let mut gaussians = crate::io::ply::parse_ply_with_settings(&mut f, &settings)?;
+                        progress.set(&path, 1.0);
 
-                    let gaussians = crate::io::ply::parse_ply(&mut f)?;
+                        if settings.sanitize_on_load {
+                            let dropped = crate::io::ply::sanitize_gaussians(&mut gaussians);
+                            if dropped > 0 {
+                                warn!("dropped {} non-finite gaussians from {}", dropped, path);
+                            }
+                        }
 
-                    Ok(GaussianCloud::from_gaussians(gaussians))
+                        Ok::<_, GaussianLoadError>(GaussianCloud::from_gaussians(gaussians))
+                    }).await
                 }
 
                 #[cfg(not(feature = "io_ply"))]
                 {
-                    Err(std::io::Error::new(ErrorKind::Other, "ply support not enabled, enable with io_ply feature"))
+                    Err(GaussianLoadError::FeatureDisabled { extension: "ply", feature: "io_ply" })
                 }
             },
-            Some(ext) if ext == "gcloud" => {
-                let cloud = GaussianCloud::decode(bytes.as_slice());
+            Some(ext) if ext == "spz" => {
+                #[cfg(feature = "io_spz")]
+                {
+                    let progress = self.progress.clone();
+                    let path = load_context.asset_path().clone_owned();
+
+                    AsyncComputeTaskPool::get().spawn(async move {
+                        progress.set(&path, 0.0);
+
+                        let gaussians = crate::io::spz::parse_spz(bytes.as_slice())?;
+                        progress.set(&path, 1.0);
+
+                        Ok::<_, GaussianLoadError>(GaussianCloud::from_gaussians(gaussians))
+                    }).await
+                }
+
+                #[cfg(not(feature = "io_spz"))]
+                {
+                    Err(GaussianLoadError::FeatureDisabled { extension: "spz", feature: "io_spz" })
+                }
+            },
+            Some(ext) if ext == "splat" => {
+                let gaussians = crate::io::splat::parse_splat(bytes.as_slice())?;
 
-                Ok(cloud)
+                Ok(GaussianCloud::from_gaussians(gaussians))
+            },
+            Some(ext) if ext == "gcloud" => {
+                GaussianCloud::decode(bytes.as_slice())
             },
-            _ => Err(std::io::Error::new(ErrorKind::Other, "only .ply and .gcloud supported")),
+            extension => Err(GaussianLoadError::UnsupportedExtension {
+                extension: extension.map(|extension| extension.to_string_lossy().into_owned()),
+            }),
+        }?;
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        if settings.recenter_and_normalize {
+            let transform = cloud.recenter_and_normalize();
+            self.transforms.set(&load_context.asset_path().clone_owned(), transform);
         }
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        if settings.density_normalize {
+            cloud.normalize_density(settings.density_normalize_strength);
+        }
+
+        if let Some(weight) = settings.importance_luminance_weight {
+            cloud.set_importance_luminance_weight(weight);
+        }
+
+        Ok(cloud)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["ply", "gcloud"]
+        &["ply", "spz", "splat", "gcloud"]
     }
 }
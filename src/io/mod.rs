@@ -1,7 +1,17 @@
 pub mod codec;
+pub mod colmap;
+pub mod cube;
+pub mod error;
 pub mod gcloud;
 pub mod loader;
+pub mod splat;
 pub mod writer;
 
+#[cfg(feature = "io_gltf")]
+pub mod gltf;
+
 #[cfg(feature = "io_ply")]
 pub mod ply;
+
+#[cfg(feature = "io_spz")]
+pub mod spz;
@@ -1,25 +1,146 @@
-use std::io::BufRead;
+use std::io::{
+    BufRead,
+    Cursor,
+    ErrorKind,
+    Read,
+};
+
+use bevy::{
+    math::Vec3,
+    utils::HashMap,
+};
 
 use ply_rs::{
     ply::{
+        DefaultElement,
+        ElementDef,
+        Encoding,
+        Header,
         Property,
         PropertyAccess,
+        PropertyType,
+        ScalarType,
     },
     parser::Parser,
 };
 
 use crate::{
     material::spherical_harmonics::{
+        SH_C0,
         SH_CHANNELS,
-        SH_COEFF_COUNT,
         SH_COEFF_COUNT_PER_CHANNEL,
     },
     gaussian::packed::Gaussian,
+    io::{
+        error::GaussianLoadError,
+        loader::{
+            ActivationMode,
+            GaussianCloudLoaderSettings,
+            ShCoefficientLayout,
+        },
+    },
 };
 
 
 pub const MAX_SIZE_VARIANCE: f32 = 5.0;
 
+// f_rest_* properties of a degree 3 capture, higher degrees are truncated on read
+pub const MAX_PLY_SH_REST: usize = 45;
+
+const PLY_FORMATS: [&str; 3] = ["ascii", "binary_little_endian", "binary_big_endian"];
+
+const REQUIRED_PROPERTIES: [&str; 3] = ["x", "y", "z"];
+
+const DC_PROPERTIES: [&str; 3] = ["f_dc_0", "f_dc_1", "f_dc_2"];
+
+// plain point clouds carry a vertex color instead of dc terms, read when all three f_dc_* are missing
+const RGB_PROPERTIES: [&str; 3] = ["red", "green", "blue"];
+
+// missing ones load as zero, which is fine for plain point clouds but usually means a mislabeled export
+const GAUSSIAN_PROPERTIES: [&str; 8] = [
+    "scale_0",
+    "scale_1",
+    "scale_2",
+    "opacity",
+    "rot_0",
+    "rot_1",
+    "rot_2",
+    "rot_3",
+];
+
+
+// per vertex values of the scalar vertex properties the loader does not use (e.g. confidence, timestamp, nx), by
+// property name, one value per loaded gaussian in file order. integer properties are converted to f32
+pub type PlyExtraProperties = HashMap<String, Vec<f32>>;
+
+
+fn is_gaussian_property(name: &str) -> bool {
+    REQUIRED_PROPERTIES.contains(&name)
+        || DC_PROPERTIES.contains(&name)
+        || GAUSSIAN_PROPERTIES.contains(&name)
+        || name.starts_with("f_rest_")
+}
+
+fn scalar_value(property: &Property) -> Option<f32> {
+    match *property {
+        Property::Char(v) => Some(v as f32),
+        Property::UChar(v) => Some(v as f32),
+        Property::Short(v) => Some(v as f32),
+        Property::UShort(v) => Some(v as f32),
+        Property::Int(v) => Some(v as f32),
+        Property::UInt(v) => Some(v as f32),
+        Property::Float(v) => Some(v),
+        Property::Double(v) => Some(v as f32),
+        _ => None,
+    }
+}
+
+
+// f_rest_* values are kept by name until the header tells how many coefficients each channel has. unknown scalars
+// are kept in header order, properties arrive in that order for every vertex
+struct PlyVertex {
+    gaussian: Gaussian,
+    rest: [f32; MAX_PLY_SH_REST],
+    extras: Vec<f32>,
+}
+
+impl PropertyAccess for PlyVertex {
+    fn new() -> Self {
+        PlyVertex {
+            gaussian: Gaussian::default(),
+            rest: [0.0; MAX_PLY_SH_REST],
+            extras: Vec::new(),
+        }
+    }
+
+    fn set_property(&mut self, key: String, property: Property) {
+        if !is_gaussian_property(&key) {
+            if let Some(value) = scalar_value(&property) {
+                self.extras.push(value);
+            }
+
+            return;
+        }
+
+        // double precision exports are narrowed, every other scalar type is rejected by validate_vertex_element
+        let property = match property {
+            Property::Double(v) => Property::Float(v as f32),
+            property => property,
+        };
+
+        match (key.strip_prefix("f_rest_"), property) {
+            (Some(index), Property::Float(v)) => {
+                if let Ok(index) = index.parse::<usize>() {
+                    if index < MAX_PLY_SH_REST {
+                        self.rest[index] = v;
+                    }
+                }
+            },
+            (_, property) => self.gaussian.set_property(key, property),
+        }
+    }
+}
+
 impl PropertyAccess for Gaussian {
     fn new() -> Self {
         Gaussian::default()
@@ -36,61 +157,181 @@ impl PropertyAccess for Gaussian {
             ("scale_0", Property::Float(v))     => self.scale_opacity.scale[0] = v,
             ("scale_1", Property::Float(v))     => self.scale_opacity.scale[1] = v,
             ("scale_2", Property::Float(v))     => self.scale_opacity.scale[2] = v,
-            ("opacity", Property::Float(v))     => self.scale_opacity.opacity = 1.0 / (1.0 + (-v).exp()),
+            ("opacity", Property::Float(v))     => self.scale_opacity.opacity = v,
             ("rot_0", Property::Float(v))       => self.rotation.rotation[0] = v,
             ("rot_1", Property::Float(v))       => self.rotation.rotation[1] = v,
             ("rot_2", Property::Float(v))       => self.rotation.rotation[2] = v,
             ("rot_3", Property::Float(v))       => self.rotation.rotation[3] = v,
-            (_, Property::Float(v)) if key.starts_with("f_rest_") => {
-                let i = key[7..].parse::<usize>().unwrap();
-
-                // interleaved
-                // if (i + 3) < SH_COEFF_COUNT {
-                //     self.spherical_harmonic.coefficients[i + 3] = v;
-                // }
-
-                // planar
-                let channel = i / SH_COEFF_COUNT_PER_CHANNEL;
-                let coefficient = if SH_COEFF_COUNT_PER_CHANNEL == 1 {
-                    1
-                } else {
-                    (i % (SH_COEFF_COUNT_PER_CHANNEL - 1)) + 1
-                };
-
-                let interleaved_idx = coefficient * SH_CHANNELS + channel;
-
-                if interleaved_idx < SH_COEFF_COUNT {
-                    self.spherical_harmonic.set(interleaved_idx, v);
-                } else {
-                    // TODO: convert higher degree SH to lower degree SH
-                }
-            }
             (_, _) => {},
         }
     }
 }
 
-pub fn parse_ply(mut reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, std::io::Error> {
-    let gaussian_parser = Parser::<Gaussian>::new();
-    let header = gaussian_parser.read_header(&mut reader)?;
+pub fn parse_ply(reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, GaussianLoadError> {
+    parse_ply_with_activation(reader, ActivationMode::default())
+}
 
-    let mut cloud = Vec::new();
+pub fn parse_ply_with_activation(
+    reader: &mut dyn BufRead,
+    activation: ActivationMode,
+) -> Result<Vec<Gaussian>, GaussianLoadError> {
+    parse_ply_with_settings(
+        reader,
+        &GaussianCloudLoaderSettings {
+            activation,
+            ..Default::default()
+        },
+    )
+}
+
+pub fn parse_ply_with_settings(
+    reader: &mut dyn BufRead,
+    settings: &GaussianCloudLoaderSettings,
+) -> Result<Vec<Gaussian>, GaussianLoadError> {
+    parse_ply_with_extras(reader, settings).map(|(gaussians, _)| gaussians)
+}
 
+// parse_ply_with_settings, also returning the vertex properties it skips. every extra lines up with the returned
+// gaussians, filtering only the gaussians (e.g. with sanitize_gaussians) misaligns them
+pub fn parse_ply_with_extras(
+    reader: &mut dyn BufRead,
+    settings: &GaussianCloudLoaderSettings,
+) -> Result<(Vec<Gaussian>, PlyExtraProperties), GaussianLoadError> {
+    let header_bytes = read_header_bytes(reader)?;
+
+    let vertex_parser = Parser::<PlyVertex>::new();
+    let header = vertex_parser.read_header(&mut Cursor::new(header_bytes.as_slice()))
+        .map_err(|error| GaussianLoadError::InvalidHeader { message: error.to_string() })?;
+
+    if !header.elements.contains_key("vertex") {
+        return Err(GaussianLoadError::MissingElement {
+            element: "vertex",
+            found: header.elements.keys().cloned().collect(),
+        });
+    }
+
+    let mut payload = CountingReader {
+        inner: reader,
+        read: header_bytes.len() as u64,
+    };
+    let mut vertices: Vec<PlyVertex> = Vec::new();
+    let mut rest_count = 0;
+    let mut extra_names = Vec::new();
+    let mut rgb_scale = None;
+
+    // elements are stored in header order, so any element ahead of the vertices is read and dropped
+    let element_parser = Parser::<DefaultElement>::new();
     for (_ignore_key, element) in &header.elements {
         if element.name == "vertex" {
-            cloud = gaussian_parser.read_payload_for_element(&mut reader, element, &header)?;
+            rgb_scale = validate_vertex_element(element)?;
+
+            rest_count = element.properties.keys()
+                .filter(|name| name.starts_with("f_rest_"))
+                .count();
+
+            extra_names = element.properties.iter()
+                .filter(|(name, property)| {
+                    !is_gaussian_property(name) && matches!(property.data_type, PropertyType::Scalar(_))
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<String>>();
+
+            vertices = read_element(&vertex_parser, &mut payload, element, &header)?;
+            break;
         }
+
+        read_element(&element_parser, &mut payload, element, &header)?;
+    }
+
+    if rest_count % SH_CHANNELS != 0 || rest_count > MAX_PLY_SH_REST {
+        bevy::log::warn!("unexpected ply f_rest property count {}, view dependent color may be wrong", rest_count);
     }
 
+    let rest_per_channel = rest_count.min(MAX_PLY_SH_REST) / SH_CHANNELS;
+    if rest_per_channel + 1 > SH_COEFF_COUNT_PER_CHANNEL {
+        bevy::log::debug!(
+            "ply has {} sh coefficients per channel, truncating to {}",
+            rest_per_channel + 1,
+            SH_COEFF_COUNT_PER_CHANNEL,
+        );
+    }
+
+    let mut extras = extra_names.iter()
+        .map(|name| (name.clone(), Vec::with_capacity(vertices.len())))
+        .collect::<Vec<(String, Vec<f32>)>>();
+    for vertex in &vertices {
+        for ((_, values), value) in extras.iter_mut().zip(vertex.extras.iter()) {
+            values.push(*value);
+        }
+    }
+
+    // the vertex colors become dc terms and are no longer extras
+    let rgb = rgb_scale.map(|scale| {
+        let mut channels = RGB_PROPERTIES.map(|name| {
+            let index = extras.iter().position(|(extra, _)| extra == name).unwrap();
+            extras.remove(index).1
+        });
+
+        for (values, scale) in channels.iter_mut().zip(scale) {
+            for value in values.iter_mut() {
+                *value = (*value * scale - 0.5) / SH_C0;
+            }
+        }
+
+        channels
+    });
+
+    let mut cloud = vertices.into_iter()
+        .enumerate()
+        .map(|(index, vertex)| {
+            let mut gaussian = vertex.gaussian;
+
+            if let Some(rgb) = &rgb {
+                for (channel, values) in rgb.iter().enumerate() {
+                    gaussian.spherical_harmonic.set(channel, values[index]);
+                }
+            }
+
+            for channel in 0..SH_CHANNELS {
+                for coefficient in 1..=rest_per_channel.min(SH_COEFF_COUNT_PER_CHANNEL - 1) {
+                    let interleaved_idx = coefficient * SH_CHANNELS + channel;
+                    let source_idx = match settings.sh_layout {
+                        ShCoefficientLayout::ChannelMajor => channel * rest_per_channel + coefficient - 1,
+                        ShCoefficientLayout::CoefficientMajor => (coefficient - 1) * SH_CHANNELS + channel,
+                    };
+
+                    gaussian.spherical_harmonic.set(interleaved_idx, vertex.rest[source_idx]);
+                }
+            }
+
+            gaussian
+        })
+        .collect::<Vec<Gaussian>>();
+
+    let (logit_opacity, log_scale) = match settings.activation {
+        ActivationMode::Raw => (false, false),
+        ActivationMode::Activate => (true, true),
+        ActivationMode::Auto => (
+            cloud.iter().any(|gaussian| !(0.0..=1.0).contains(&gaussian.scale_opacity.opacity)),
+            cloud.iter().any(|gaussian| gaussian.scale_opacity.scale.iter().any(|scale| *scale < 0.0)),
+        ),
+    };
+
     for gaussian in &mut cloud {
         gaussian.position_visibility.visibility = 1.0;
 
-        let mean_scale = (gaussian.scale_opacity.scale[0] + gaussian.scale_opacity.scale[1] + gaussian.scale_opacity.scale[2]) / 3.0;
-        for i in 0..3 {
-            gaussian.scale_opacity.scale[i] = gaussian.scale_opacity.scale[i]
-                .max(mean_scale - MAX_SIZE_VARIANCE)
-                .min(mean_scale + MAX_SIZE_VARIANCE)
-                .exp();
+        if logit_opacity {
+            gaussian.scale_opacity.opacity = 1.0 / (1.0 + (-gaussian.scale_opacity.opacity).exp());
+        }
+
+        if log_scale {
+            let mean_scale = (gaussian.scale_opacity.scale[0] + gaussian.scale_opacity.scale[1] + gaussian.scale_opacity.scale[2]) / 3.0;
+            for i in 0..3 {
+                gaussian.scale_opacity.scale[i] = gaussian.scale_opacity.scale[i]
+                    .max(mean_scale - MAX_SIZE_VARIANCE)
+                    .min(mean_scale + MAX_SIZE_VARIANCE)
+                    .exp();
+            }
         }
 
         let norm = (0..4).map(|i| gaussian.rotation.rotation[i].powf(2.0)).sum::<f32>().sqrt();
@@ -99,5 +340,283 @@ pub fn parse_ply(mut reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, std::io:
         }
     }
 
-    Ok(cloud)
+    Ok((cloud, extras.into_iter().collect()))
+}
+
+
+// reads up to and including `end_header`, checking the magic and format lines ply_rs only reports as generic errors
+fn read_header_bytes(reader: &mut dyn BufRead) -> Result<Vec<u8>, GaussianLoadError> {
+    let mut header = Vec::new();
+
+    loop {
+        let line_start = header.len();
+        if reader.read_until(b'\n', &mut header)? == 0 {
+            return Err(GaussianLoadError::InvalidHeader {
+                message: "missing end_header".to_string(),
+            });
+        }
+
+        let line = String::from_utf8_lossy(&header[line_start..]);
+        let line = line.trim();
+
+        if line_start == 0 && line != "ply" {
+            return Err(GaussianLoadError::NotPly);
+        }
+
+        if let Some(format) = line.strip_prefix("format ") {
+            let format = format.split_whitespace().next().unwrap_or_default();
+            if !PLY_FORMATS.contains(&format) {
+                return Err(GaussianLoadError::UnsupportedFormat {
+                    format: format.to_string(),
+                    byte_offset: line_start as u64,
+                });
+            }
+        }
+
+        if line == "end_header" {
+            return Ok(header);
+        }
+    }
+}
+
+// returns the per channel scale of the red, green, blue fallback, none when the vertices have dc terms
+fn validate_vertex_element(element: &ElementDef) -> Result<Option<[f32; 3]>, GaussianLoadError> {
+    for property in REQUIRED_PROPERTIES {
+        if !element.properties.contains_key(property) {
+            return Err(GaussianLoadError::MissingProperty {
+                element: element.name.clone(),
+                property,
+            });
+        }
+    }
+
+    for (name, property) in &element.properties {
+        let known = is_gaussian_property(name);
+
+        match property.data_type {
+            PropertyType::Scalar(ScalarType::Float) | PropertyType::Scalar(ScalarType::Double) => {},
+            _ if !known => {},
+            ref data_type => return Err(GaussianLoadError::UnsupportedPropertyType {
+                element: element.name.clone(),
+                property: name.clone(),
+                found: format!("{:?}", data_type),
+            }),
+        }
+    }
+
+    let missing = GAUSSIAN_PROPERTIES.iter()
+        .filter(|property| !element.properties.contains_key(**property))
+        .copied()
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        bevy::log::warn!("ply {} element has no {}, loading them as zero", element.name, missing.join(", "));
+    }
+
+    if DC_PROPERTIES.iter().all(|property| element.properties.contains_key(*property)) {
+        return Ok(None);
+    }
+
+    // a partial set of dc terms is a broken export, without any a black cloud would load silently
+    let has_dc = DC_PROPERTIES.iter().any(|property| element.properties.contains_key(*property));
+    let rgb_scale = RGB_PROPERTIES.map(|property| match element.properties.get(property).map(|p| &p.data_type) {
+        Some(PropertyType::Scalar(ScalarType::UChar)) => Some(1.0 / u8::MAX as f32),
+        Some(PropertyType::Scalar(ScalarType::UShort)) => Some(1.0 / u16::MAX as f32),
+        Some(PropertyType::Scalar(ScalarType::Float)) | Some(PropertyType::Scalar(ScalarType::Double)) => Some(1.0),
+        _ => None,
+    });
+
+    match rgb_scale {
+        [Some(red), Some(green), Some(blue)] if !has_dc => {
+            bevy::log::debug!("ply {} element has no f_dc_*, loading colors from red, green, blue", element.name);
+            Ok(Some([red, green, blue]))
+        },
+        _ => Err(GaussianLoadError::MissingProperty {
+            element: element.name.clone(),
+            property: DC_PROPERTIES.into_iter()
+                .find(|property| !element.properties.contains_key(*property))
+                .unwrap(),
+        }),
+    }
+}
+
+// binary payloads go through ply_rs, which swaps big endian values. ascii payloads are tokenized here so every
+// token rust's float parsing accepts (1e-5, .5, nan, inf) loads and errors point at the offending line
+fn read_element<T: PropertyAccess>(
+    parser: &Parser<T>,
+    reader: &mut CountingReader,
+    element: &ElementDef,
+    header: &Header,
+) -> Result<Vec<T>, GaussianLoadError> {
+    if !matches!(header.encoding, Encoding::Ascii) {
+        return parser.read_payload_for_element(reader, element, header)
+            .map_err(|error| payload_error(error, element, reader.read));
+    }
+
+    let mut elements = Vec::with_capacity(element.count);
+    let mut line = String::new();
+
+    while elements.len() < element.count {
+        let line_offset = reader.read;
+
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(GaussianLoadError::Truncated {
+                element: element.name.clone(),
+                expected: element.count,
+                byte_offset: reader.read,
+            });
+        }
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let mut value = T::new();
+
+        for (name, property) in &element.properties {
+            match property.data_type {
+                PropertyType::Scalar(_) => {
+                    let scalar = parse_ascii_token(tokens.next(), element, line_offset)?;
+                    value.set_property(name.clone(), Property::Double(scalar));
+                },
+                PropertyType::List(..) => {
+                    let count = parse_ascii_token(tokens.next(), element, line_offset)? as usize;
+                    for _ in 0..count {
+                        parse_ascii_token(tokens.next(), element, line_offset)?;
+                    }
+                },
+            }
+        }
+
+        elements.push(value);
+    }
+
+    Ok(elements)
+}
+
+fn parse_ascii_token(
+    token: Option<&str>,
+    element: &ElementDef,
+    byte_offset: u64,
+) -> Result<f64, GaussianLoadError> {
+    let invalid = |message: String| GaussianLoadError::InvalidPayload {
+        element: element.name.clone(),
+        byte_offset,
+        message,
+    };
+
+    let token = token.ok_or_else(|| invalid(format!("line has fewer values than the {} properties", element.properties.len())))?;
+
+    token.parse::<f64>()
+        .map_err(|_| invalid(format!("`{}` is not a number", token)))
+}
+
+fn payload_error(error: std::io::Error, element: &ElementDef, byte_offset: u64) -> GaussianLoadError {
+    match error.kind() {
+        ErrorKind::UnexpectedEof => GaussianLoadError::Truncated {
+            element: element.name.clone(),
+            expected: element.count,
+            byte_offset,
+        },
+        _ => GaussianLoadError::InvalidPayload {
+            element: element.name.clone(),
+            byte_offset,
+            message: error.to_string(),
+        },
+    }
+}
+
+// tracks the absolute byte offset for payload errors
+struct CountingReader<'a> {
+    inner: &'a mut dyn BufRead,
+    read: u64,
+}
+
+impl Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl BufRead for CountingReader<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.read += amount as u64;
+    }
+}
+
+
+// writes binary_little_endian in the reference 3dgs layout (logit opacity, log scale, channel major f_rest_*) in
+// slice order, so a morton_reorder'd cloud streams spatially coherent chunks. opacities and scales are expected
+// activated as loaded, read the file back with ActivationMode::Activate. `normals` (e.g. estimate_normals) adds
+// nx, ny, nz after the position like the reference exporter, missing entries are written as zero
+pub fn write_ply(gaussians: &[Gaussian], normals: Option<&[Vec3]>) -> Vec<u8> {
+    let rest_per_channel = (SH_COEFF_COUNT_PER_CHANNEL - 1).min(MAX_PLY_SH_REST / SH_CHANNELS);
+    let normal_properties: &[&str] = if normals.is_some() { &["nx", "ny", "nz"] } else { &[] };
+
+    let mut header = format!("ply\nformat binary_little_endian 1.0\nelement vertex {}\n", gaussians.len());
+    let properties = ["x", "y", "z"].into_iter()
+        .chain(normal_properties.iter().copied())
+        .chain(["f_dc_0", "f_dc_1", "f_dc_2"])
+        .map(str::to_string)
+        .chain((0..rest_per_channel * SH_CHANNELS).map(|index| format!("f_rest_{}", index)))
+        .chain(["opacity", "scale_0", "scale_1", "scale_2", "rot_0", "rot_1", "rot_2", "rot_3"].map(str::to_string));
+    for property in properties {
+        header += &format!("property float {}\n", property);
+    }
+    header += "end_header\n";
+
+    let vertex_floats = 11 + normal_properties.len() + SH_CHANNELS * (rest_per_channel + 1);
+    let vertex_size = vertex_floats * std::mem::size_of::<f32>();
+
+    let mut ply = header.into_bytes();
+    ply.reserve(gaussians.len() * vertex_size);
+
+    for (index, gaussian) in gaussians.iter().enumerate() {
+        let mut push = |value: f32| ply.extend_from_slice(&value.to_le_bytes());
+
+        gaussian.position_visibility.position.into_iter().for_each(&mut push);
+
+        if let Some(normals) = normals {
+            normals.get(index).copied().unwrap_or(Vec3::ZERO).to_array().into_iter().for_each(&mut push);
+        }
+
+        for channel in 0..SH_CHANNELS {
+            push(gaussian.spherical_harmonic.get(channel));
+        }
+
+        for channel in 0..SH_CHANNELS {
+            for coefficient in 1..=rest_per_channel {
+                push(gaussian.spherical_harmonic.get(coefficient * SH_CHANNELS + channel));
+            }
+        }
+
+        let opacity = gaussian.scale_opacity.opacity.clamp(f32::EPSILON, 1.0 - f32::EPSILON);
+        push((opacity / (1.0 - opacity)).ln());
+
+        for scale in gaussian.scale_opacity.scale {
+            push(scale.max(f32::MIN_POSITIVE).ln());
+        }
+
+        gaussian.rotation.rotation.into_iter().for_each(&mut push);
+    }
+
+    ply
+}
+
+
+// drops gaussians with any non-finite attribute, a single nan position poisons every depth key of the sort
+pub fn sanitize_gaussians(gaussians: &mut Vec<Gaussian>) -> usize {
+    let count = gaussians.len();
+    gaussians.retain(Gaussian::is_finite);
+
+    count - gaussians.len()
 }
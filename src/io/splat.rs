@@ -0,0 +1,102 @@
+use std::io::{
+    Error,
+    ErrorKind,
+};
+
+use crate::{
+    material::spherical_harmonics::SH_C0,
+    gaussian::packed::Gaussian,
+};
+
+
+// position and scale as f32x3, rgba and a wxyz rotation as u8x4
+pub const SPLAT_POINT_SIZE: usize = 32;
+
+
+fn quantize(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+
+// the headerless `.splat` format of antimatter15's web viewer, flat rgb per gaussian with no higher sh bands, so
+// these clouds always take the degree 0 flat color pipeline. values are stored activated and positions as exported,
+// like spz the loader's activation setting does not apply
+pub fn parse_splat(bytes: &[u8]) -> Result<Vec<Gaussian>, Error> {
+    if !bytes.len().is_multiple_of(SPLAT_POINT_SIZE) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("splat size {} is not a multiple of {} bytes", bytes.len(), SPLAT_POINT_SIZE),
+        ));
+    }
+
+    let read_f32 = |point: &[u8], offset: usize| f32::from_le_bytes([
+        point[offset],
+        point[offset + 1],
+        point[offset + 2],
+        point[offset + 3],
+    ]);
+
+    let gaussians = bytes.chunks_exact(SPLAT_POINT_SIZE)
+        .map(|point| {
+            let mut gaussian = Gaussian::default();
+
+            for axis in 0..3 {
+                gaussian.position_visibility.position[axis] = read_f32(point, axis * 4);
+                gaussian.scale_opacity.scale[axis] = read_f32(point, 12 + axis * 4);
+            }
+            gaussian.position_visibility.visibility = 1.0;
+
+            for channel in 0..3 {
+                let color = point[24 + channel] as f32 / 255.0;
+                gaussian.spherical_harmonic.set(channel, (color - 0.5) / SH_C0);
+            }
+            gaussian.scale_opacity.opacity = point[27] as f32 / 255.0;
+
+            let mut rotation = [0.0; 4];
+            for (value, byte) in rotation.iter_mut().zip(&point[28..32]) {
+                *value = (*byte as f32 - 128.0) / 128.0;
+            }
+
+            let norm = rotation.iter().map(|value| value * value).sum::<f32>().sqrt();
+            gaussian.rotation.rotation = if norm > f32::EPSILON {
+                rotation.map(|value| value / norm)
+            } else {
+                [1.0, 0.0, 0.0, 0.0]
+            };
+
+            gaussian
+        })
+        .collect();
+
+    Ok(gaussians)
+}
+
+
+// higher sh bands are dropped, the dc term is written as the flat color
+pub fn write_splat(gaussians: &[Gaussian]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(gaussians.len() * SPLAT_POINT_SIZE);
+
+    for gaussian in gaussians {
+        for position in gaussian.position_visibility.position {
+            bytes.extend_from_slice(&position.to_le_bytes());
+        }
+
+        for scale in gaussian.scale_opacity.scale {
+            bytes.extend_from_slice(&scale.to_le_bytes());
+        }
+
+        for channel in 0..3 {
+            let color = 0.5 + SH_C0 * gaussian.spherical_harmonic.get(channel);
+            bytes.push(quantize(color * 255.0));
+        }
+        bytes.push(quantize(gaussian.scale_opacity.opacity * 255.0));
+
+        let rotation = gaussian.rotation.rotation;
+        let norm = rotation.iter().map(|value| value * value).sum::<f32>().sqrt().max(f32::EPSILON);
+        for value in rotation {
+            bytes.push(quantize(value / norm * 128.0 + 128.0));
+        }
+    }
+
+    bytes
+}
@@ -0,0 +1,309 @@
+use std::io::{
+    Error,
+    ErrorKind,
+    Read,
+    Write,
+};
+
+use flate2::{
+    Compression,
+    read::GzDecoder,
+    write::GzEncoder,
+};
+
+use crate::{
+    material::spherical_harmonics::{
+        SH_CHANNELS,
+        SH_COEFF_COUNT_PER_CHANNEL,
+        SH_DEGREE,
+    },
+    gaussian::packed::Gaussian,
+};
+
+
+// "NGSP", little endian
+pub const SPZ_MAGIC: u32 = 0x5053474e;
+pub const SPZ_HEADER_SIZE: usize = 16;
+
+// version 2 stores xyz of the rotation quaternion, version 3 the smallest three components. only version 2 is written
+pub const SPZ_MIN_VERSION: u32 = 2;
+pub const SPZ_MAX_VERSION: u32 = 3;
+
+// fixed point bits of the 24 bit positions written by `write_spz`, 12 covers +-2048 units at 0.25mm
+pub const SPZ_FRACTIONAL_BITS: u8 = 12;
+
+const COLOR_SCALE: f32 = 0.15;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpzHeader {
+    pub version: u32,
+    pub num_points: u32,
+    pub sh_degree: u8,
+    pub fractional_bits: u8,
+    pub flags: u8,
+}
+
+impl SpzHeader {
+    fn rotation_bytes(&self) -> usize {
+        if self.version >= 3 { 4 } else { 3 }
+    }
+
+    fn payload_size(&self) -> usize {
+        let sh_bytes = sh_rest_per_channel(self.sh_degree) * SH_CHANNELS;
+        let per_point = 9 + 1 + 3 + 3 + self.rotation_bytes() + sh_bytes;
+
+        self.num_points as usize * per_point
+    }
+}
+
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+// higher order coefficients per channel, the dc term is stored separately as color
+fn sh_rest_per_channel(sh_degree: u8) -> usize {
+    match sh_degree {
+        0 => 0,
+        1 => 3,
+        2 => 8,
+        _ => 15,
+    }
+}
+
+fn quantize(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+
+// spz stores right-up-back axes, which is bevy's y-up convention, so unlike most ply exports these clouds need
+// no coordinate conversion. positions are 24 bit fixed point, every other attribute 8 bit, and values are stored
+// activated, so the loader's activation setting does not apply
+pub fn parse_spz(bytes: &[u8]) -> Result<Vec<Gaussian>, Error> {
+    let mut data = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut data)?;
+
+    if data.len() < SPZ_HEADER_SIZE {
+        return Err(invalid("spz header truncated".to_string()));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+
+    if read_u32(0) != SPZ_MAGIC {
+        return Err(invalid("not an spz file".to_string()));
+    }
+
+    let header = SpzHeader {
+        version: read_u32(4),
+        num_points: read_u32(8),
+        sh_degree: data[12],
+        fractional_bits: data[13],
+        flags: data[14],
+    };
+
+    if !(SPZ_MIN_VERSION..=SPZ_MAX_VERSION).contains(&header.version) {
+        return Err(invalid(format!(
+            "unsupported spz version {}, supported versions are {} to {}",
+            header.version,
+            SPZ_MIN_VERSION,
+            SPZ_MAX_VERSION,
+        )));
+    }
+
+    if header.sh_degree > 3 {
+        return Err(invalid(format!("unsupported spz sh degree {}", header.sh_degree)));
+    }
+
+    let payload = &data[SPZ_HEADER_SIZE..];
+    if payload.len() < header.payload_size() {
+        return Err(invalid(format!(
+            "spz payload truncated, expected {} bytes for {} points, found {}",
+            header.payload_size(),
+            header.num_points,
+            payload.len(),
+        )));
+    }
+
+    let rest_per_channel = sh_rest_per_channel(header.sh_degree);
+    if rest_per_channel + 1 > SH_COEFF_COUNT_PER_CHANNEL {
+        bevy::log::debug!(
+            "spz has {} sh coefficients per channel, truncating to {}",
+            rest_per_channel + 1,
+            SH_COEFF_COUNT_PER_CHANNEL,
+        );
+    }
+
+    let count = header.num_points as usize;
+    let rotation_bytes = header.rotation_bytes();
+
+    let (positions, payload) = payload.split_at(count * 9);
+    let (alphas, payload) = payload.split_at(count);
+    let (colors, payload) = payload.split_at(count * 3);
+    let (scales, payload) = payload.split_at(count * 3);
+    let (rotations, payload) = payload.split_at(count * rotation_bytes);
+    let sh = &payload[..count * rest_per_channel * SH_CHANNELS];
+
+    let position_scale = 1.0 / (1u32 << header.fractional_bits) as f32;
+
+    let gaussians = (0..count)
+        .map(|i| {
+            let mut gaussian = Gaussian::default();
+
+            for axis in 0..3 {
+                let bytes = &positions[i * 9 + axis * 3..i * 9 + axis * 3 + 3];
+
+                // sign extend the 24 bit value through the top byte
+                let fixed = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                gaussian.position_visibility.position[axis] = fixed as f32 * position_scale;
+            }
+            gaussian.position_visibility.visibility = 1.0;
+
+            gaussian.scale_opacity.opacity = alphas[i] as f32 / 255.0;
+
+            for axis in 0..3 {
+                gaussian.scale_opacity.scale[axis] = (scales[i * 3 + axis] as f32 / 16.0 - 10.0).exp();
+            }
+
+            // spz quaternions are xyzw, gaussians store wxyz
+            let xyzw = decode_rotation(&rotations[i * rotation_bytes..(i + 1) * rotation_bytes]);
+            gaussian.rotation.rotation = [xyzw[3], xyzw[0], xyzw[1], xyzw[2]];
+
+            for channel in 0..SH_CHANNELS {
+                let color = colors[i * 3 + channel] as f32 / 255.0;
+                gaussian.spherical_harmonic.set(channel, (color - 0.5) / COLOR_SCALE);
+            }
+
+            // rest coefficients interleave rgb per coefficient, the same as gaussian storage
+            for coefficient in 0..rest_per_channel.min(SH_COEFF_COUNT_PER_CHANNEL - 1) {
+                for channel in 0..SH_CHANNELS {
+                    let value = sh[(i * rest_per_channel + coefficient) * SH_CHANNELS + channel];
+
+                    gaussian.spherical_harmonic.set(
+                        (coefficient + 1) * SH_CHANNELS + channel,
+                        (value as f32 - 128.0) / 128.0,
+                    );
+                }
+            }
+
+            gaussian
+        })
+        .collect();
+
+    Ok(gaussians)
+}
+
+fn decode_rotation(bytes: &[u8]) -> [f32; 4] {
+    let mut xyzw = [0.0; 4];
+
+    if bytes.len() == 3 {
+        for (value, byte) in xyzw.iter_mut().zip(bytes) {
+            *value = *byte as f32 / 127.5 - 1.0;
+        }
+
+        let squared = xyzw[0] * xyzw[0] + xyzw[1] * xyzw[1] + xyzw[2] * xyzw[2];
+        xyzw[3] = (1.0 - squared).max(0.0).sqrt();
+
+        return xyzw;
+    }
+
+    // two bits select the largest component, every other one is a sign bit and 9 bits of magnitude in
+    // [0, sqrt(1/2)], packed from the last component down
+    const MASK: u32 = (1 << 9) - 1;
+
+    let mut packed = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let largest = (packed >> 30) as usize;
+    let mut squared = 0.0;
+
+    for axis in (0..4).rev() {
+        if axis == largest {
+            continue;
+        }
+
+        let magnitude = std::f32::consts::FRAC_1_SQRT_2 * (packed & MASK) as f32 / MASK as f32;
+        let negative = (packed >> 9) & 1 == 1;
+        packed >>= 10;
+
+        xyzw[axis] = if negative { -magnitude } else { magnitude };
+        squared += xyzw[axis] * xyzw[axis];
+    }
+
+    xyzw[largest] = (1.0 - squared).max(0.0).sqrt();
+
+    xyzw
+}
+
+
+// writes version 2 at this build's sh degree, opacities and scales are expected activated as loaded
+pub fn write_spz(gaussians: &[Gaussian], fractional_bits: u8) -> Result<Vec<u8>, Error> {
+    let header = SpzHeader {
+        version: SPZ_MIN_VERSION,
+        num_points: gaussians.len() as u32,
+        sh_degree: SH_DEGREE.min(3) as u8,
+        fractional_bits,
+        flags: 0,
+    };
+
+    let count = gaussians.len();
+    let rest_per_channel = sh_rest_per_channel(header.sh_degree);
+
+    let mut positions = Vec::with_capacity(count * 9);
+    let mut alphas = Vec::with_capacity(count);
+    let mut colors = Vec::with_capacity(count * 3);
+    let mut scales = Vec::with_capacity(count * 3);
+    let mut rotations = Vec::with_capacity(count * 3);
+    let mut sh = Vec::with_capacity(count * rest_per_channel * SH_CHANNELS);
+
+    let position_scale = (1u32 << fractional_bits) as f32;
+
+    for gaussian in gaussians {
+        for position in gaussian.position_visibility.position {
+            let fixed = (position * position_scale)
+                .round()
+                .clamp(-8388608.0, 8388607.0) as i32;
+            positions.extend_from_slice(&fixed.to_le_bytes()[..3]);
+        }
+
+        alphas.push(quantize(gaussian.scale_opacity.opacity * 255.0));
+
+        for channel in 0..SH_CHANNELS {
+            let color = gaussian.spherical_harmonic.get(channel) * COLOR_SCALE + 0.5;
+            colors.push(quantize(color * 255.0));
+        }
+
+        for scale in gaussian.scale_opacity.scale {
+            let log_scale = scale.max(f32::MIN_POSITIVE).ln();
+            scales.push(quantize((log_scale + 10.0) * 16.0));
+        }
+
+        let [w, x, y, z] = gaussian.rotation.rotation;
+        let norm = (w * w + x * x + y * y + z * z).sqrt().max(f32::EPSILON);
+
+        // w is implied non-negative
+        let sign = (if w < 0.0 { -1.0 } else { 1.0 }) / norm;
+        for value in [x, y, z] {
+            rotations.push(quantize((value * sign + 1.0) * 127.5));
+        }
+
+        for coefficient in 0..rest_per_channel {
+            for channel in 0..SH_CHANNELS {
+                let value = gaussian.spherical_harmonic.get((coefficient + 1) * SH_CHANNELS + channel);
+                sh.push(quantize(value * 128.0 + 128.0));
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(SPZ_HEADER_SIZE + header.payload_size());
+    data.extend_from_slice(&SPZ_MAGIC.to_le_bytes());
+    data.extend_from_slice(&header.version.to_le_bytes());
+    data.extend_from_slice(&header.num_points.to_le_bytes());
+    data.extend_from_slice(&[header.sh_degree, header.fractional_bits, header.flags, 0]);
+
+    for section in [positions, alphas, colors, scales, rotations, sh] {
+        data.extend_from_slice(&section);
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&data)?;
+    encoder.finish()
+}
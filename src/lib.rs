@@ -2,13 +2,78 @@ use bevy::prelude::*;
 
 pub use gaussian::{
     packed::Gaussian,
+    diff::{
+        GaussianCloudDiffError,
+        GaussianCloudDiffMetric,
+    },
+    fade::{
+        GaussianCloudLoaded,
+        GaussianFadeIn,
+    },
     cloud::GaussianCloud,
+    mesh::{
+        GaussianCloudMeshMode,
+        GaussianCloudMeshSettings,
+    },
+    patch::{
+        GaussianCloudPatch,
+        GaussianCloudPatchError,
+        GaussianCloudPatches,
+    },
     rand::random_gaussians,
-    settings::GaussianCloudSettings,
+    sequence::{
+        GaussianSequence,
+        GaussianSequencePlayback,
+    },
+    settings::{
+        GaussianCloudBufferSettings,
+        GaussianCloudColorLutDomain,
+        GaussianCloudCullMode,
+        GaussianCloudEntryPoints,
+        GaussianCloudFrontFace,
+        GaussianCloudSettings,
+        GaussianDebug,
+        GaussianDebugMode,
+        GaussianTransparencyMode,
+        SplatSizeMode,
+    },
+    snapshot::CloudSnapshot,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub use gaussian::paint::GaussianCloudPaintStroke;
+
+pub use io::error::GaussianLoadError;
+
+pub use io::loader::{
+    GaussianCloudLoadProgress,
+    GaussianCloudLoadTransforms,
 };
 
 pub use material::spherical_harmonics::SphericalHarmonicCoefficients;
 
+pub use render::{
+    GaussianCloudUniformExtension,
+    occlusion::{
+        GaussianCloudOcclusion,
+        GaussianOcclusionCulling,
+    },
+    outline::GaussianOutline,
+    scissor::GaussianScissor,
+    thumbnail::{
+        GaussianCloudThumbnailReady,
+        GaussianCloudThumbnails,
+        GaussianCloudThumbnailSettings,
+    },
+};
+
+pub use sort::cache::GaussianCloudSortCache;
+
+pub use sort::status::{
+    GaussianCloudSortStatus,
+    ResortGaussianClouds,
+};
+
 use io::loader::GaussianCloudLoader;
 
 use render::RenderPipelinePlugin;
@@ -22,6 +87,9 @@ pub mod render;
 pub mod sort;
 pub mod utils;
 
+#[cfg(feature = "gizmo")]
+pub mod gizmo;
+
 #[cfg(feature = "noise")]
 pub mod noise;
 
@@ -31,6 +99,7 @@ pub struct GaussianSplattingBundle {
     pub settings: GaussianCloudSettings,
     pub cloud: Handle<GaussianCloud>,
     pub visibility: Visibility,
+    pub inherited_visibility: InheritedVisibility,
 }
 
 
@@ -48,7 +117,14 @@ impl Plugin for GaussianSplattingPlugin {
         app.init_asset::<GaussianCloud>();
         app.register_asset_reflect::<GaussianCloud>();
 
-        app.init_asset_loader::<GaussianCloudLoader>();
+        let progress = GaussianCloudLoadProgress::default();
+        let transforms = GaussianCloudLoadTransforms::default();
+        app.insert_resource(progress.clone());
+        app.insert_resource(transforms.clone());
+        app.register_asset_loader(GaussianCloudLoader {
+            progress,
+            transforms,
+        });
 
         app.register_type::<GaussianCloudSettings>();
         app.register_type::<GaussianSplattingBundle>();
@@ -57,9 +133,18 @@ impl Plugin for GaussianSplattingPlugin {
             RenderPipelinePlugin,
             material::MaterialPlugin,
             query::QueryPlugin,
+            gaussian::fade::GaussianCloudFadePlugin,
+            gaussian::exposure::GaussianCloudExposurePlugin,
+            gaussian::sequence::GaussianSequencePlugin,
         ));
 
+        #[cfg(feature = "gizmo")]
+        app.add_plugins(gizmo::GaussianCloudGizmoPlugin);
+
         #[cfg(feature = "noise")]
         app.add_plugins(noise::NoisePlugin);
+
+        #[cfg(feature = "io_gltf")]
+        app.add_plugins(io::gltf::GaussianGltfPlugin);
     }
 }
@@ -37,7 +37,7 @@ impl Plugin for SphericalHarmonicCoefficientsPlugin {
 }
 
 
-const fn num_sh_coefficients(degree: usize) -> usize {
+pub const fn num_sh_coefficients(degree: usize) -> usize {
     if degree == 0 {
         1
     } else {
@@ -47,15 +47,34 @@ const fn num_sh_coefficients(degree: usize) -> usize {
 
 
 #[cfg(feature = "web")]
-const SH_DEGREE: usize = 0;
+pub const SH_DEGREE: usize = 0;
 
 #[cfg(not(feature = "web"))]
-const SH_DEGREE: usize = 3;
+pub const SH_DEGREE: usize = 3;
 
 pub const SH_CHANNELS: usize = 3;
+pub const SH_BAND_MASK_ALL: u8 = 0b1111;
+// degree 0 basis, a dc coefficient c evaluates to 0.5 + SH_C0 * c
+pub const SH_C0: f32 = 0.282_094_8;
 pub const SH_COEFF_COUNT_PER_CHANNEL: usize = num_sh_coefficients(SH_DEGREE);
 pub const SH_COEFF_COUNT: usize = (SH_COEFF_COUNT_PER_CHANNEL * SH_CHANNELS + 3) & !3;
 
+// padded interleaved coefficient count of a degree, the first sh_coeff_count(d) coefficients of any
+// SphericalHarmonicCoefficients hold bands 0 to d
+pub const fn sh_coeff_count(degree: usize) -> usize {
+    (num_sh_coefficients(degree) * SH_CHANNELS + 3) & !3
+}
+
+// 32 bit words per gaussian when only bands 0 to `degree` are stored
+#[cfg(feature = "f16")]
+pub const fn sh_word_count(degree: usize) -> usize {
+    sh_coeff_count(degree) / 2
+}
+#[cfg(feature = "f32")]
+pub const fn sh_word_count(degree: usize) -> usize {
+    sh_coeff_count(degree)
+}
+
 pub const HALF_SH_COEFF_COUNT: usize = SH_COEFF_COUNT / 2;
 pub const PADDED_HALF_SH_COEFF_COUNT: usize = (HALF_SH_COEFF_COUNT + 3) & !3;
 
@@ -140,6 +159,177 @@ impl SphericalHarmonicCoefficients {
     pub fn set(&mut self, index: usize, value: f32) {
         self.coefficients[index] = value;
     }
+
+    #[cfg(feature = "f16")]
+    pub fn get(&self, index: usize) -> f32 {
+        let packed = self.coefficients[index / 2];
+        let bits = match index % 2 {
+            0 => packed & 0x0000ffff,
+            1 => packed >> 16,
+            _ => unreachable!(),
+        };
+
+        f16::from_bits(bits as u16).to_f32()
+    }
+
+    #[cfg(feature = "f32")]
+    pub fn get(&self, index: usize) -> f32 {
+        self.coefficients[index]
+    }
+
+    // rotates the radiance lobes so that the color seen along `rotation * d` matches the color previously seen along `d`
+    pub fn rotate(&mut self, rotation: Mat3) {
+        let inverse = rotation.transpose();
+
+        for band in 1..=SH_DEGREE {
+            let offset = band * band;
+            let count = 2 * band + 1;
+
+            let mut basis = [[0.0; 7]; 7];
+            let mut rotated = [[0.0; 7]; 7];
+            for (i, direction) in SH_ROTATION_SAMPLES.iter().take(count).enumerate() {
+                let direction = Vec3::from_array(*direction).normalize();
+                basis[i] = sh_band_basis(band, direction);
+                rotated[i] = sh_band_basis(band, inverse * direction);
+            }
+
+            let band_rotation = solve_band(basis, rotated, count);
+
+            for channel in 0..SH_CHANNELS {
+                let index = |coefficient: usize| (offset + coefficient) * SH_CHANNELS + channel;
+
+                let mut source = [0.0; 7];
+                for (coefficient, value) in source.iter_mut().take(count).enumerate() {
+                    *value = self.get(index(coefficient)) as f64;
+                }
+
+                for (row, weights) in band_rotation.iter().take(count).enumerate() {
+                    let value = weights.iter()
+                        .zip(source.iter())
+                        .take(count)
+                        .map(|(weight, value)| weight * value)
+                        .sum::<f64>();
+
+                    self.set(index(row), value as f32);
+                }
+            }
+        }
+    }
+
+    // reflects the radiance lobes through the plane orthogonal to `axis` (0, 1, 2 for x, y, z). every basis function
+    // is either even or odd along an axis, so the reflection is an exact sign flip per coefficient
+    pub fn mirror(&mut self, axis: usize) {
+        let direction = Vec3::from_array(SH_ROTATION_SAMPLES[0]).normalize();
+        let mut mirrored = direction;
+        mirrored[axis] = -mirrored[axis];
+
+        for band in 1..=SH_DEGREE {
+            let offset = band * band;
+            let count = 2 * band + 1;
+
+            let basis = sh_band_basis(band, direction);
+            let reflected = sh_band_basis(band, mirrored);
+
+            for coefficient in 0..count {
+                if basis[coefficient] * reflected[coefficient] >= 0.0 {
+                    continue;
+                }
+
+                for channel in 0..SH_CHANNELS {
+                    let index = (offset + coefficient) * SH_CHANNELS + channel;
+                    self.set(index, -self.get(index));
+                }
+            }
+        }
+    }
+}
+
+
+// any 2l + 1 directions with a non-singular basis matrix reconstruct the band rotation exactly
+const SH_ROTATION_SAMPLES: [[f32; 3]; 7] = [
+    [0.8, 0.3, 0.52],
+    [-0.2, 0.9, 0.38],
+    [0.1, -0.4, 0.91],
+    [-0.7, -0.5, 0.5],
+    [0.6, -0.7, -0.39],
+    [-0.3, 0.2, -0.93],
+    [0.45, 0.85, -0.27],
+];
+
+// matches the basis ordering and signs of spherical_harmonics.wgsl
+fn sh_band_basis(band: usize, direction: Vec3) -> [f64; 7] {
+    let x = direction.x as f64;
+    let y = direction.y as f64;
+    let z = direction.z as f64;
+    let xx = x * x;
+    let yy = y * y;
+    let zz = z * z;
+
+    match band {
+        1 => [
+            -0.4886025119029199 * y,
+            0.4886025119029199 * z,
+            -0.4886025119029199 * x,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+        ],
+        2 => [
+            1.0925484305920792 * x * y,
+            -1.0925484305920792 * y * z,
+            0.31539156525252005 * (2.0 * zz - xx - yy),
+            -1.0925484305920792 * x * z,
+            0.5462742152960396 * (xx - yy),
+            0.0,
+            0.0,
+        ],
+        3 => [
+            -0.5900435899266435 * y * (3.0 * xx - yy),
+            2.890611442640554 * x * y * z,
+            -0.4570457994644658 * y * (4.0 * zz - xx - yy),
+            0.3731763325901154 * z * (2.0 * zz - 3.0 * xx - 3.0 * yy),
+            -0.4570457994644658 * x * (4.0 * zz - xx - yy),
+            1.445305721320277 * z * (xx - yy),
+            -0.5900435899266435 * x * (xx - 3.0 * yy),
+        ],
+        _ => unreachable!(),
+    }
+}
+
+// gauss-jordan solve of `basis * x = rotated`, the result maps source band coefficients to rotated coefficients
+fn solve_band(
+    mut basis: [[f64; 7]; 7],
+    mut rotated: [[f64; 7]; 7],
+    count: usize,
+) -> [[f64; 7]; 7] {
+    for column in 0..count {
+        let pivot = (column..count)
+            .max_by(|&a, &b| basis[a][column].abs().total_cmp(&basis[b][column].abs()))
+            .unwrap();
+        basis.swap(column, pivot);
+        rotated.swap(column, pivot);
+
+        let scale = 1.0 / basis[column][column];
+        basis[column].iter_mut().for_each(|value| *value *= scale);
+        rotated[column].iter_mut().for_each(|value| *value *= scale);
+
+        let pivot_basis = basis[column];
+        let pivot_rotated = rotated[column];
+
+        for row in (0..count).filter(|&row| row != column) {
+            let factor = basis[row][column];
+
+            basis[row].iter_mut()
+                .zip(pivot_basis.iter())
+                .for_each(|(value, pivot)| *value -= factor * pivot);
+            rotated[row].iter_mut()
+                .zip(pivot_rotated.iter())
+                .for_each(|(value, pivot)| *value -= factor * pivot);
+        }
+    }
+
+    rotated
 }
 
 
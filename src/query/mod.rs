@@ -3,6 +3,9 @@ use bevy::prelude::*;
 #[cfg(feature = "query_raycast")]
 pub mod raycast;
 
+#[cfg(feature = "query_select")]
+pub mod region;
+
 #[cfg(feature = "query_select")]
 pub mod select;
 
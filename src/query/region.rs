@@ -0,0 +1,127 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+};
+
+use crate::GaussianCloud;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum PickRegion {
+    Rect(Rect),
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+}
+
+impl PickRegion {
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Self::Rect(rect) => *rect,
+            Self::Circle { center, radius } => Rect::from_center_half_size(*center, Vec2::splat(*radius)),
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Self::Rect(rect) => rect.contains(point),
+            Self::Circle { center, radius } => center.distance_squared(point) <= radius * radius,
+        }
+    }
+}
+
+
+// viewport coordinates match the camera's, i.e. cursor positions can be used directly
+pub struct GaussianCloudPickView<'a> {
+    pub camera: &'a Camera,
+    pub camera_transform: &'a GlobalTransform,
+    pub cloud_transform: Affine3A,
+    pub depth_range: Option<Range<f32>>,
+}
+
+
+impl GaussianCloud {
+    // selects gaussians by projected center, depth is view space distance along the camera's forward axis
+    pub fn pick_region(
+        &self,
+        view: &GaussianCloudPickView,
+        region: PickRegion,
+    ) -> Vec<u32> {
+        let view_from_world = view.camera_transform.affine().inverse();
+        let region_bounds = region.bounds();
+
+        let grid = self.spatial_index();
+        let mut picked = Vec::new();
+
+        for (cell, indices) in grid.cells() {
+            if !self.cell_may_overlap(view, &view_from_world, region_bounds, cell) {
+                continue;
+            }
+
+            for &index in indices {
+                let position = Vec3::from_array(*self.position(index as usize));
+                let world_position = view.cloud_transform.transform_point3(position);
+
+                if let Some(depth_range) = &view.depth_range {
+                    let depth = -view_from_world.transform_point3(world_position).z;
+                    if !depth_range.contains(&depth) {
+                        continue;
+                    }
+                }
+
+                let Some(viewport_position) = view.camera.world_to_viewport(view.camera_transform, world_position) else {
+                    continue;
+                };
+
+                if region.contains(viewport_position) {
+                    picked.push(index);
+                }
+            }
+        }
+
+        picked.sort_unstable();
+        picked
+    }
+
+    // conservative, cells are only rejected when all eight corners project outside the region bounds or depth range
+    fn cell_may_overlap(
+        &self,
+        view: &GaussianCloudPickView,
+        view_from_world: &Affine3A,
+        region_bounds: Rect,
+        cell: IVec3,
+    ) -> bool {
+        let (cell_min, cell_max) = self.spatial_index().cell_bounds(cell);
+
+        let mut viewport_bounds = Rect::EMPTY;
+        let mut depth_bounds = (f32::INFINITY, f32::NEG_INFINITY);
+
+        for corner in 0..8 {
+            let corner = Vec3::select(
+                BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                cell_max,
+                cell_min,
+            );
+            let world_corner = view.cloud_transform.transform_point3(corner);
+
+            let depth = -view_from_world.transform_point3(world_corner).z;
+            depth_bounds = (depth_bounds.0.min(depth), depth_bounds.1.max(depth));
+
+            match view.camera.world_to_viewport(view.camera_transform, world_corner) {
+                Some(viewport_corner) => viewport_bounds = viewport_bounds.union_point(viewport_corner),
+                None => return true,
+            }
+        }
+
+        if let Some(depth_range) = &view.depth_range {
+            if depth_bounds.1 < depth_range.start || depth_bounds.0 >= depth_range.end {
+                return false;
+            }
+        }
+
+        viewport_bounds.min.cmple(region_bounds.max).all() && viewport_bounds.max.cmpge(region_bounds.min).all()
+    }
+}
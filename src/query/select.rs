@@ -39,6 +39,7 @@ pub struct SelectPlugin;
 impl Plugin for SelectPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Select>();
+        app.register_type::<crate::query::region::PickRegion>();
 
         app.add_event::<InvertSelectionEvent>();
         app.add_event::<SaveSelectionEvent>();
@@ -1,17 +1,53 @@
 #define_import_path bevy_gaussian_splatting::bindings
 
+#ifdef VIEW_GLOBALS
 #import bevy_render::globals::Globals
+#endif
 #import bevy_render::view::View
 
 
 @group(0) @binding(0) var<uniform> view: View;
+#ifdef VIEW_GLOBALS
 @group(0) @binding(1) var<uniform> globals: Globals;
+#endif
 
+// mirrors GaussianCloudUniform field for field, extensions read their per cloud data from `extension`
 struct GaussianUniforms {
     transform: mat4x4<f32>,
     global_scale: f32,
     count: u32,
     count_root_ceil: u32,
+    fog_color: vec4<f32>,
+    fog_start: f32,
+    fog_end: f32,
+    channel_min: f32,
+    channel_max: f32,
+    opaque_threshold: f32,
+    max_screen_fraction: f32,
+    depth_prepass_alpha: f32,
+    outline_thickness: f32,
+    outline_color: vec4<f32>,
+    global_opacity: f32,
+    focus_point: vec3<f32>,
+    focus_radius: f32,
+    focus_falloff: f32,
+    scale_clamp_min: f32,
+    scale_clamp_max: f32,
+    sort_center: vec3<f32>,
+    sort_radius: f32,
+    clip_planes: array<vec4<f32>, 4>,
+    exposure: f32,
+    splat_vertex_count: u32,
+    cutoff_sigma: f32,
+    debug_opacity_gamma: f32,
+    debug_depth_near: f32,
+    debug_depth_far: f32,
+    opacity_curve: array<vec4<f32>, 4>,
+    reveal_fraction: f32,
+    draws_indirect: u32,
+    color_lut_input_min: vec3<f32>,
+    color_lut_input_max: vec3<f32>,
+    extension: array<vec4<f32>, 4>,
 };
 @group(1) @binding(0) var<uniform> gaussian_uniforms: GaussianUniforms;
 
@@ -1,6 +1,5 @@
 #import bevy_gaussian_splatting::bindings::{
     view,
-    globals,
     gaussian_uniforms,
     sorting_pass_index,
     sorting,
@@ -12,9 +11,18 @@
 #import bevy_gaussian_splatting::depth::{
     depth_to_rgb,
 }
+#import bevy_gaussian_splatting::spherical_harmonics::{
+    linear_to_srgb,
+    srgb_to_linear,
+}
+#ifdef COLOR_HOOK
+#import bevy_gaussian_splatting::color_hook::color_hook
+#endif
 #import bevy_gaussian_splatting::transform::{
     world_to_clip,
     in_frustum,
+    transform_cov3d,
+    world_to_local_direction,
 }
 
 #ifdef PACKED
@@ -86,8 +94,63 @@
 #endif
 
 
+#ifdef SCENE_DEPTH_TEST
+#ifdef SCENE_DEPTH_MULTISAMPLED
+@group(0) @binding(2) var scene_depth: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(2) var scene_depth: texture_depth_2d;
+#endif
+
+// prepass depth at a framebuffer position, reverse z so larger is nearer
+fn scene_depth_at(frag_coord: vec2<f32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(scene_depth));
+    let texel = clamp(vec2<i32>(frag_coord), vec2<i32>(0), size - 1);
+
+    // the last argument is the sample index when multisampled, the mip level otherwise
+    return textureLoad(scene_depth, texel, 0);
+}
+
+fn ndc_to_frag_coord(ndc: vec2<f32>) -> vec2<f32> {
+    return view.viewport.xy + (ndc * vec2<f32>(0.5, -0.5) + 0.5) * view.viewport.zw;
+}
+#endif
+
 #ifdef BUFFER_STORAGE
 @group(3) @binding(0) var<storage, read> sorted_entries: array<Entry>;
+@group(3) @binding(4) var<storage, read> reveal_rank: array<u32>;
+
+#ifdef COLOR_CHANNEL
+@group(3) @binding(1) var<storage, read> color_channel: array<f32>;
+#endif
+
+#ifdef COLOR_LUT
+@group(3) @binding(2) var color_lut: texture_3d<f32>;
+@group(3) @binding(3) var color_lut_sampler: sampler;
+
+fn apply_color_lut(color: vec3<f32>) -> vec3<f32> {
+#ifdef COLOR_LUT_SRGB
+    let encoded = linear_to_srgb(max(color, vec3<f32>(0.0)));
+#else
+    let encoded = color;
+#endif
+
+    // the lut's input domain spans its texels
+    let input_min = gaussian_uniforms.color_lut_input_min;
+    let input_extent = max(gaussian_uniforms.color_lut_input_max - input_min, vec3<f32>(1e-6));
+    let lut_color = clamp((encoded - input_min) / input_extent, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    // sample texel centers so the lut edges map exactly to 0 and 1
+    let size = vec3<f32>(textureDimensions(color_lut));
+    let uvw = lut_color * (size - 1.0) / size + 0.5 / size;
+    let graded = textureSampleLevel(color_lut, color_lut_sampler, uvw, 0.0).rgb;
+
+#ifdef COLOR_LUT_SRGB
+    return srgb_to_linear(graded);
+#else
+    return graded;
+#endif
+}
+#endif
 
 fn get_entry(index: u32) -> Entry {
     return sorted_entries[index];
@@ -153,6 +216,14 @@ fn get_rotation_matrix(
     );
 }
 
+fn clamp_scale(scale: vec3<f32>) -> vec3<f32> {
+    return clamp(
+        scale,
+        vec3<f32>(gaussian_uniforms.scale_clamp_min),
+        vec3<f32>(gaussian_uniforms.scale_clamp_max),
+    );
+}
+
 fn get_scale_matrix(
     scale: vec3<f32>,
 ) -> mat3x3<f32> {
@@ -166,28 +237,21 @@ fn get_scale_matrix(
 
 // https://github.com/cvlab-epfl/gaussian-splatting-web/blob/905b3c0fb8961e42c79ef97e64609e82383ca1c2/src/shaders.ts#L185
 // TODO: precompute
+// cloud space covariance, see transform_cov3d for the world space one
 fn compute_cov3d(scale: vec3<f32>, rotation: vec4<f32>) -> array<f32, 6> {
     let S = get_scale_matrix(scale);
-
-    let T = mat3x3<f32>(
-        gaussian_uniforms.transform[0].xyz,
-        gaussian_uniforms.transform[1].xyz,
-        gaussian_uniforms.transform[2].xyz,
-    );
-
     let R = get_rotation_matrix(rotation);
 
     let M = S * R;
     let Sigma = transpose(M) * M;
-    let TS = T * Sigma * transpose(T);
 
     return array<f32, 6>(
-        TS[0][0],
-        TS[0][1],
-        TS[0][2],
-        TS[1][1],
-        TS[1][2],
-        TS[2][2],
+        Sigma[0][0],
+        Sigma[0][1],
+        Sigma[0][2],
+        Sigma[1][1],
+        Sigma[1][2],
+        Sigma[2][2],
     );
 }
 
@@ -196,14 +260,22 @@ fn compute_cov2d(
     index: u32,
 ) -> vec3<f32> {
 #ifdef PRECOMPUTE_COVARIANCE_3D
-    let cov3d = get_cov3d(index);
+    // global_scale multiplies each axis, so the covariance scales by its square
+    var cov3d = get_cov3d(index);
+    let scale_squared = gaussian_uniforms.global_scale * gaussian_uniforms.global_scale;
+    for (var i = 0u; i < 6u; i += 1u) {
+        cov3d[i] *= scale_squared;
+    }
 #else
     let rotation = get_rotation(index);
-    let scale = get_scale(index);
+    let scale = clamp_scale(get_scale(index));
 
-    let cov3d = compute_cov3d(scale, rotation);
+    var cov3d = compute_cov3d(scale, rotation);
 #endif
 
+    // precomputed and computed covariances are both in cloud space, the full upper 3x3 of the transform applies
+    cov3d = transform_cov3d(cov3d);
+
     let Vrk = mat3x3(
         cov3d[0], cov3d[1], cov3d[2],
         cov3d[1], cov3d[3], cov3d[4],
@@ -235,12 +307,39 @@ fn compute_cov2d(
     let T = W * J;
 
     var cov = transpose(T) * transpose(Vrk) * T;
+
+#ifdef SPLAT_SIZE_CONSTANT
+    // J scales with 1 / t, so this is the footprint the gaussian would have at one unit of view depth
+    cov *= t.z * t.z;
+#endif
+
     cov[0][0] += 0.3f;
     cov[1][1] += 0.3f;
 
     return vec3<f32>(cov[0][0], cov[0][1], cov[1][1]);
 }
 
+// limits the cutoff_sigma radius to a fraction of the smaller viewport side, shrinking the whole footprint rather than clipping it
+fn clamp_cov2d(cov2d: vec3<f32>) -> vec3<f32> {
+    if (gaussian_uniforms.max_screen_fraction <= 0.0) {
+        return cov2d;
+    }
+
+    let det = cov2d.x * cov2d.z - cov2d.y * cov2d.y;
+    let mid = 0.5 * (cov2d.x + cov2d.z);
+    let lambda1 = mid + sqrt(max(0.0, mid * mid - det));
+
+    let radius = gaussian_uniforms.cutoff_sigma * sqrt(lambda1);
+    let max_radius = 0.5 * gaussian_uniforms.max_screen_fraction * min(view.viewport.z, view.viewport.w);
+
+    if (radius <= max_radius) {
+        return cov2d;
+    }
+
+    let factor = max_radius / radius;
+    return cov2d * factor * factor;
+}
+
 fn get_bounding_box(
     cov2d: vec3<f32>,
     direction: vec2<f32>,
@@ -262,7 +361,7 @@ fn get_bounding_box(
 
 
 #ifdef USE_AABB
-    let radius_px = 3.5 * max(x_axis_length, y_axis_length);
+    let radius_px = gaussian_uniforms.cutoff_sigma * max(x_axis_length, y_axis_length);
     let radius_ndc = vec2<f32>(
         radius_px / view.viewport.zw,
     );
@@ -280,7 +379,7 @@ fn get_bounding_box(
     let major_radius = sqrt((cov2d.x + cov2d.z + b) * 0.5);
     let minor_radius = sqrt((cov2d.x + cov2d.z - b) * 0.5);
 
-    let bounds = 3.5 * vec2<f32>(
+    let bounds = gaussian_uniforms.cutoff_sigma * vec2<f32>(
         major_radius,
         minor_radius,
     );
@@ -289,9 +388,10 @@ fn get_bounding_box(
         -cov2d.y,
         lambda1 - cov2d.x,
     ));
+    // a quarter turn rather than a reflection of the major axis, which keeps the footprint's winding
     let eigvec2 = vec2<f32>(
-        eigvec1.y,
-        -eigvec1.x
+        -eigvec1.y,
+        eigvec1.x
     );
 
     let rotation_matrix = transpose(
@@ -323,6 +423,78 @@ fn get_bounding_box(
 //     // TODO: compute cov2d, color (any non-quad gaussian property)
 // }
 
+// 1 within focus_radius of focus_point, fading linearly to 0 over focus_falloff beyond it
+fn focus_factor(world_position: vec3<f32>) -> f32 {
+    let distance = length(world_position - gaussian_uniforms.focus_point);
+    let falloff = max(gaussian_uniforms.focus_falloff, 1e-6);
+
+    return 1.0 - clamp((distance - gaussian_uniforms.focus_radius) / falloff, 0.0, 1.0);
+}
+
+// alpha at `power` = -0.5 * (distance in sigmas)^2 from the splat center
+fn splat_kernel(power: f32) -> f32 {
+#ifdef KERNEL_FLAT
+    // disc of radius sqrt(2) sigma
+    return select(0.0, 1.0, power >= -1.0);
+#else ifdef KERNEL_CUBIC
+    // smoothstep reaching zero at sqrt(20 / 3) sigma
+    let t = sqrt(-2.0 * power) / 2.5819889;
+    return 1.0 - smoothstep(0.0, 1.0, t);
+#else
+    return exp(power);
+#endif
+}
+
+#ifdef OPACITY_CURVE
+// piecewise linear through the 16 evenly spaced samples of GaussianCloudSettings::opacity_curve
+fn apply_opacity_curve(opacity: f32) -> f32 {
+    let t = clamp(opacity, 0.0, 1.0) * 15.0;
+    let i = min(u32(t), 14u);
+
+    let a = gaussian_uniforms.opacity_curve[i / 4u][i % 4u];
+    let b = gaussian_uniforms.opacity_curve[(i + 1u) / 4u][(i + 1u) % 4u];
+
+    return mix(a, b, t - f32(i));
+}
+#endif
+
+// false on the negative side of any clip plane, zero planes always pass
+fn inside_clip_planes(world_position: vec3<f32>) -> bool {
+    var inside = true;
+    for (var i = 0u; i < 4u; i += 1u) {
+        let plane = gaussian_uniforms.clip_planes[i];
+        inside &= dot(plane.xyz, world_position) + plane.w >= 0.0;
+    }
+
+    return inside;
+}
+
+// the first reveal_fraction of the cloud is drawn, in the asset's reveal order when it has one and cloud order
+// otherwise
+fn revealed(splat_index: u32) -> bool {
+    var rank = splat_index;
+#ifdef BUFFER_STORAGE
+    if (arrayLength(&reveal_rank) == gaussian_uniforms.count) {
+        rank = reveal_rank[splat_index];
+    }
+#endif
+    return f32(rank) < gaussian_uniforms.reveal_fraction * f32(gaussian_uniforms.count);
+}
+
+// strip vertex `index` of a regular polygon circumscribing the unit circle, zigzagging 0, 1, n - 1, 2, n - 2, ... so
+// each vertex closes a triangle with the previous two
+fn splat_polygon_offset(index: u32, sides: u32) -> vec2<f32> {
+    var corner = 0u;
+    if (index % 2u == 1u) {
+        corner = (index + 1u) / 2u;
+    } else if (index > 0u) {
+        corner = sides - index / 2u;
+    }
+
+    let angle = 6.283185307 * (f32(corner) + 0.5) / f32(sides);
+    return vec2<f32>(cos(angle), sin(angle)) / cos(3.141592654 / f32(sides));
+}
+
 fn inverted_infinity_norm(v: vec3<f32>) -> vec3<f32> {
     let min_value = min(v.x, min(v.y, v.z));
     let min_vec = vec3<f32>(min_value);
@@ -342,19 +514,54 @@ fn vs_points(
 ) -> GaussianVertexOutput {
     var output: GaussianVertexOutput;
 
+#ifdef OPAQUE_PASS
+    // opaque splats are drawn unsorted, straight from cloud order
+    let splat_index = instance_index;
+
+    var discard_quad = false;
+#else ifdef DEPTH_PREPASS
+    let splat_index = instance_index;
+
+    var discard_quad = false;
+#else ifdef OUTLINE
+    // flat colored, so cloud order is as good as sorted
+    let splat_index = instance_index;
+
+    var discard_quad = false;
+#else ifdef STOCHASTIC_TRANSPARENCY
+    // kept fragments write depth, so the depth test resolves the order instead of the sort
+    let splat_index = instance_index;
+
+    var discard_quad = false;
+#else
     let entry = get_entry(instance_index);
     let splat_index = entry.value;
 
     var discard_quad = false;
 
     discard_quad |= entry.key == 0xFFFFFFFFu; // || splat_index == 0u;
+#endif
 
     let position = vec4<f32>(get_position(splat_index), 1.0);
 
     let transformed_position = (gaussian_uniforms.transform * position).xyz;
     let projected_position = world_to_clip(transformed_position);
 
+#ifdef OPACITY_CURVE
+    let opacity = apply_opacity_curve(get_opacity(splat_index)) * focus_factor(transformed_position);
+#else
+    let opacity = get_opacity(splat_index) * focus_factor(transformed_position);
+#endif
+
+    // gaussians at or above the threshold are opaque: the opaque pass draws their cores with depth, and they stay in
+    // the sorted blended pass for their soft fringe, see opaque_core in fs_main
+#ifdef OPAQUE_PASS
+    discard_quad |= opacity < gaussian_uniforms.opaque_threshold;
+#endif
+
     discard_quad |= !in_frustum(projected_position.xyz);
+    discard_quad |= !inside_clip_planes(transformed_position);
+    discard_quad |= !revealed(splat_index);
 
 #ifdef DRAW_SELECTED
     discard_quad |= get_visibility(splat_index) < 0.5;
@@ -366,17 +573,25 @@ fn vs_points(
         return output;
     }
 
+    // counter clockwise in ndc like the tessellated polygon, so splats are front faces for any cull mode
     var quad_vertices = array<vec2<f32>, 4>(
         vec2<f32>(-1.0, -1.0),
-        vec2<f32>(-1.0,  1.0),
         vec2<f32>( 1.0, -1.0),
+        vec2<f32>(-1.0,  1.0),
         vec2<f32>( 1.0,  1.0),
     );
 
-    let quad_index = vertex_index % 4u;
-    let quad_offset = quad_vertices[quad_index];
+    var quad_offset = quad_vertices[vertex_index % 4u];
+    if (gaussian_uniforms.splat_vertex_count > 4u) {
+        quad_offset = splat_polygon_offset(vertex_index, gaussian_uniforms.splat_vertex_count);
+    }
 
-    let ray_direction = normalize(transformed_position - view.world_position);
+#ifdef FLAT_COLOR
+    // flat color is view independent
+    let ray_direction = vec3<f32>(0.0, 0.0, 1.0);
+#else
+    let ray_direction = world_to_local_direction(normalize(transformed_position - view.world_position));
+#endif
 
     var rgb = vec3<f32>(0.0);
 
@@ -428,14 +643,30 @@ fn vs_points(
         0.5 * (t.y + 1.0),
         0.5 * (t.z + 1.0)
     );
+#else ifdef COLOR_CHANNEL
+    // the channel is constant per gaussian, so it is mapped once per vertex and interpolated unchanged
+    rgb = depth_to_rgb(
+        color_channel[splat_index],
+        gaussian_uniforms.channel_min,
+        gaussian_uniforms.channel_max,
+    );
 #else
-    rgb = get_color(splat_index, ray_direction);
+    rgb = get_color(splat_index, ray_direction) * gaussian_uniforms.exposure;
+#endif
+
+#ifdef DEBUG_SORT_ORDER
+    // instance_index is the rank in the sorted entries
+    rgb = depth_to_rgb(
+        f32(instance_index),
+        0.0,
+        f32(max(gaussian_uniforms.count, 2u) - 1u),
+    );
 #endif
 
     // TODO: verify color benefit for ray_direction computed at quad verticies instead of gaussian center (same as current complexity)
     output.color = vec4<f32>(
         rgb,
-        get_opacity(splat_index),
+        opacity * gaussian_uniforms.global_opacity,
     );
 
 #ifdef HIGHLIGHT_SELECTED
@@ -444,7 +675,7 @@ fn vs_points(
     }
 #endif
 
-    let cov2d = compute_cov2d(transformed_position, splat_index);
+    let cov2d = clamp_cov2d(compute_cov2d(transformed_position, splat_index));
 
 #ifdef USE_AABB
     let det = cov2d.x * cov2d.z - cov2d.y * cov2d.y;
@@ -469,51 +700,282 @@ fn vs_points(
         projected_position.zw
     );
 
+#ifdef SCENE_DEPTH_TEST
+    // premature depth reject, the quad is dropped before rasterization when its center and all four corners are
+    // behind the scene. every vertex of the quad evaluates the same samples, so the whole quad is dropped or kept
+    var occluded = projected_position.z < scene_depth_at(ndc_to_frag_coord(projected_position.xy));
+    for (var corner = 0u; corner < 4u; corner += 1u) {
+        let corner_ndc = projected_position.xy + get_bounding_box(cov2d, quad_vertices[corner]).xy;
+        occluded &= projected_position.z < scene_depth_at(ndc_to_frag_coord(corner_ndc));
+    }
+
+    if (occluded) {
+        output.position = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+        return output;
+    }
+#endif
+
+#ifdef OUTLINE
+    // push each corner outwards by the outline thickness, uv and major_minor extrapolate past the footprint so
+    // fs_main can shrink them back
+    let corner_px = length(bb.zw);
+    let dilation = (corner_px + gaussian_uniforms.outline_thickness) / max(corner_px, 1e-6);
+
+    output.uv = quad_offset * dilation;
+    output.major_minor = bb.zw * dilation;
+    output.position = vec4<f32>(
+        projected_position.xy + bb.xy * dilation,
+        projected_position.zw
+    );
+#endif
+
     return output;
 }
 
+#ifdef STOCHASTIC_TRANSPARENCY
+// uniform in [0, 1) per pixel and splat, a pcg hash of the pixel and the fragment depth bits
+fn stochastic_threshold(frag_coord: vec4<f32>) -> f32 {
+    var state = u32(frag_coord.x) * 1973u + u32(frag_coord.y) * 9277u + bitcast<u32>(frag_coord.z) * 26699u;
+    state = state * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+
+    return f32(((word >> 22u) ^ word) >> 8u) / 16777216.0;
+}
+#endif
+
 @fragment
 fn fs_main(input: GaussianVertexOutput) -> @location(0) vec4<f32> {
+#ifdef SCENE_DEPTH_TEST
+    if (input.position.z < scene_depth_at(input.position.xy)) {
+        discard;
+    }
+#endif
+
+#ifdef OUTLINE
+    // every fragment within outline_thickness pixels of the footprint samples the nearest footprint point
+    let offset_px = length(input.major_minor);
+    let shrink = max(offset_px - gaussian_uniforms.outline_thickness, 0.0) / max(offset_px, 1e-6);
+    let major_minor = input.major_minor * shrink;
+    let uv = input.uv * shrink;
+#else
+    let major_minor = input.major_minor;
+    let uv = input.uv;
+#endif
+
 #ifdef USE_AABB
-    let d = -input.major_minor;
+    let d = -major_minor;
     let conic = input.conic;
     let power = -0.5 * (conic.x * d.x * d.x + conic.z * d.y * d.y) + conic.y * d.x * d.y;
 
-    if (power > 0.0) {
+    let cutoff = gaussian_uniforms.cutoff_sigma;
+    if (power > 0.0 || power < -0.5 * cutoff * cutoff) {
         discard;
     }
 #endif
 
 #ifdef USE_OBB
-    let sigma = 1.0 / 3.5;
+    // uv spans the quad, so the unit circle is the cutoff_sigma ellipse
+    let sigma = 1.0 / gaussian_uniforms.cutoff_sigma;
     let sigma_squared = 2.0 * sigma * sigma;
-    let distance_squared = dot(input.uv, input.uv);
+    let distance_squared = dot(uv, uv);
 
     let power = -distance_squared / sigma_squared;
 
-    if (distance_squared > 3.5 * 3.5) {
+    if (distance_squared > 1.0) {
         discard;
     }
 #endif
 
 #ifdef VISUALIZE_BOUNDING_BOX
-    let uv = (input.uv + 1.0) / 2.0;
+    let box_uv = (input.uv + 1.0) / 2.0;
     let edge_width = 0.08;
     if (
-        (uv.x < edge_width || uv.x > 1.0 - edge_width) ||
-        (uv.y < edge_width || uv.y > 1.0 - edge_width)
+        (box_uv.x < edge_width || box_uv.x > 1.0 - edge_width) ||
+        (box_uv.y < edge_width || box_uv.y > 1.0 - edge_width)
     ) {
         return vec4<f32>(0.3, 1.0, 0.1, 1.0);
     }
 #endif
 
-    let alpha = exp(power);
-    let final_alpha = alpha * input.color.a;
+    let alpha = splat_kernel(power);
+#ifdef DEBUG_OPACITY_BOOST
+    // boosts the gaussian's opacity, not the kernel, so faint splats show at their usual size
+    var final_alpha = alpha * pow(input.color.a, gaussian_uniforms.debug_opacity_gamma);
+#else
+    var final_alpha = alpha * input.color.a;
+#endif
 
     // TODO: round final_alpha to terminate depth test?
 
+    // the half opaque core of an opaque gaussian, drawn (with depth) by the opaque pass and skipped by the blended
+    // pass, which composites the rest of the footprint. tested before hooks so both passes split at the same edge
+    let opaque_core = input.color.a >= gaussian_uniforms.opaque_threshold && alpha * input.color.a >= 0.5;
+
+    var rgb = input.color.rgb;
+#ifdef COLOR_LUT
+    rgb = apply_color_lut(rgb);
+#endif
+    if (gaussian_uniforms.fog_end > gaussian_uniforms.fog_start) {
+        let view_position = view.view_from_clip * vec4<f32>(0.0, 0.0, input.position.z, 1.0);
+        let depth = -view_position.z / view_position.w;
+        let fog = clamp(
+            (depth - gaussian_uniforms.fog_start) / (gaussian_uniforms.fog_end - gaussian_uniforms.fog_start),
+            0.0,
+            1.0,
+        );
+        rgb = mix(rgb, gaussian_uniforms.fog_color.rgb, fog);
+    }
+
+#ifdef DEBUG_DEPTH
+    // the fragment's own depth rather than the gaussian center, so bent or misplaced geometry shows as a gradient break
+    let debug_view_position = view.view_from_clip * vec4<f32>(0.0, 0.0, input.position.z, 1.0);
+    rgb = depth_to_rgb(
+        -debug_view_position.z / debug_view_position.w,
+        gaussian_uniforms.debug_depth_near,
+        gaussian_uniforms.debug_depth_far,
+    );
+#endif
+
+#ifdef COLOR_HOOK
+    let hooked = color_hook(vec4<f32>(rgb, final_alpha), input.position);
+    rgb = hooked.rgb;
+    final_alpha = hooked.a;
+#endif
+
+#ifdef DEPTH_PREPASS
+    if (final_alpha < gaussian_uniforms.depth_prepass_alpha) {
+        discard;
+    }
+
+    return vec4<f32>(0.0);
+#else ifdef OUTLINE
+    // hard edged coverage, overlapping dilated splats must not accumulate into a gradient
+    if (final_alpha < 0.1) {
+        discard;
+    }
+
+    let outline_color = gaussian_uniforms.outline_color;
+    return vec4<f32>(outline_color.rgb * outline_color.a, outline_color.a);
+#else ifdef OPAQUE_PASS
+    // alpha tested so the depth write only covers the splat core
+    if (!opaque_core) {
+        discard;
+    }
+
+    return vec4<f32>(rgb, 1.0);
+#else ifdef ALPHA_TO_COVERAGE
+    // the rasterizer turns alpha into a sample mask, the fragment's depth is written for the covered samples
+    if (final_alpha < 1.0 / 255.0) {
+        discard;
+    }
+
+    return vec4<f32>(rgb, final_alpha);
+#else ifdef STOCHASTIC_TRANSPARENCY
+    // kept with probability alpha, the noise is decorrelated between overlapping splats through their depth
+    if (final_alpha <= stochastic_threshold(input.position)) {
+        discard;
+    }
+
+    return vec4<f32>(rgb, 1.0);
+#else
+    if (opaque_core) {
+        discard;
+    }
+
     return vec4<f32>(
-        input.color.rgb * final_alpha,
+        rgb * final_alpha,
         final_alpha,
     );
+#endif
+}
+
+
+@vertex
+fn vs_ellipsoid(
+    @builtin(instance_index) instance_index: u32,
+    @builtin(vertex_index) vertex_index: u32,
+) -> GaussianVertexOutput {
+    var output: GaussianVertexOutput;
+
+    let splat_index = instance_index;
+
+    var discard_line = false;
+
+    let position = get_position(splat_index);
+    let transformed_position = (gaussian_uniforms.transform * vec4<f32>(position, 1.0)).xyz;
+
+    discard_line |= !in_frustum(world_to_clip(transformed_position).xyz);
+    discard_line |= !inside_clip_planes(transformed_position);
+    discard_line |= !revealed(splat_index);
+
+#ifdef DRAW_SELECTED
+    discard_line |= get_visibility(splat_index) < 0.5;
+#endif
+
+    if (discard_line) {
+        output.color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+        output.position = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+        return output;
+    }
+
+    // each great circle is a line list of ELLIPSOID_SEGMENTS segments
+    let vertices_per_circle = #{ELLIPSOID_SEGMENTS}u * 2u;
+    let circle = vertex_index / vertices_per_circle;
+    let circle_vertex = vertex_index % vertices_per_circle;
+    let segment = circle_vertex / 2u + circle_vertex % 2u;
+
+    let theta = 6.283185307 * f32(segment) / f32(#{ELLIPSOID_SEGMENTS}u);
+    let c = cos(theta);
+    let s = sin(theta);
+
+    var unit = vec3<f32>(c, s, 0.0);
+    if (circle == 1u) {
+        unit = vec3<f32>(0.0, c, s);
+    } else if (circle == 2u) {
+        unit = vec3<f32>(c, 0.0, s);
+    }
+
+#ifdef PRECOMPUTE_COVARIANCE_3D
+    // rotation is not retained with precomputed covariance, approximate with a sphere
+    let cov3d = get_cov3d(splat_index);
+    let radius = sqrt(max((cov3d[0] + cov3d[3] + cov3d[5]) / 3.0, 0.0));
+    let offset = unit * radius * gaussian_uniforms.global_scale;
+#else
+    let R = get_rotation_matrix(get_rotation(splat_index));
+    let S = get_scale_matrix(clamp_scale(get_scale(splat_index)));
+
+    // Sigma = transpose(R) * S * S * R, so local axes are rotated by transpose(R)
+    let offset = transpose(R) * (S * unit);
+#endif
+
+    let vertex_position = (gaussian_uniforms.transform * vec4<f32>(position + offset, 1.0)).xyz;
+
+#ifdef FLAT_COLOR
+    // flat color is view independent
+    let ray_direction = vec3<f32>(0.0, 0.0, 1.0);
+#else
+    let ray_direction = world_to_local_direction(normalize(transformed_position - view.world_position));
+#endif
+    output.color = vec4<f32>(
+        get_color(splat_index, ray_direction) * gaussian_uniforms.exposure,
+        focus_factor(transformed_position) * gaussian_uniforms.global_opacity,
+    );
+
+#ifdef HIGHLIGHT_SELECTED
+    if (get_visibility(splat_index) > 0.5) {
+        output.color = vec4<f32>(0.3, 1.0, 0.1, 1.0);
+    }
+#endif
+
+    output.position = world_to_clip(vertex_position);
+
+    return output;
+}
+
+@fragment
+fn fs_ellipsoid(input: GaussianVertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(
+        input.color.rgb * input.color.a,
+        input.color.a,
+    );
 }
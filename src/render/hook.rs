@@ -0,0 +1,35 @@
+use bevy::{
+    prelude::*,
+    render::extract_resource::ExtractResource,
+};
+
+
+// user wgsl spliced into fs_main. a color hook declares `#define_import_path bevy_gaussian_splatting::color_hook`
+// and `fn color_hook(color: vec4<f32>, frag_coord: vec4<f32>) -> vec4<f32>`, it receives the straight (not
+// premultiplied) splat color after lut and fog, before blending. see assets/shaders/grayscale_hook.wgsl
+#[derive(
+    Resource,
+    Clone,
+    Debug,
+    Default,
+    ExtractResource,
+)]
+pub struct GaussianCloudShaderHooks {
+    pub color: Option<Handle<Shader>>,
+}
+
+
+// registers a color hook loaded from an asset path, e.g. "shaders/grayscale_hook.wgsl"
+pub struct GaussianCloudColorHookPlugin {
+    pub path: String,
+}
+
+impl Plugin for GaussianCloudColorHookPlugin {
+    fn build(&self, app: &mut App) {
+        let shader = app.world().resource::<AssetServer>().load(self.path.clone());
+
+        app.insert_resource(GaussianCloudShaderHooks {
+            color: Some(shader),
+        });
+    }
+}
@@ -1,29 +1,51 @@
-use std::hash::Hash;
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    hash::Hash,
+    ops::Range,
+    sync::mpsc::{
+        Receiver,
+        TryRecvError,
+    },
+};
 
 use bevy::{
     asset::{
         load_internal_asset,
         LoadState,
     },
-    core_pipeline::core_3d::Transparent3d,
+    core_pipeline::{
+        core_3d::Transparent3d,
+        prepass::{
+            DepthPrepass,
+            ViewPrepassTextures,
+        },
+    },
     ecs::{
+        event::ManualEventReader,
         query::ROQueryItem,
         system::{
             lifetimeless::*,
             SystemParamItem,
         }
     },
+    math::Affine3A,
     prelude::*,
     render::{
         Extract,
+        extract_resource::ExtractResourcePlugin,
         extract_component::{
             ComponentUniforms,
             DynamicUniformIndex,
+            ExtractComponentPlugin,
             UniformComponentPlugin,
         },
-        globals::{
-            GlobalsBuffer,
-            GlobalsUniform,
+        primitives::{
+            Aabb,
+            Frustum,
+            Sphere,
         },
         render_asset::{
             PrepareAssetError,
@@ -31,6 +53,7 @@ use bevy::{
             RenderAssetPlugin,
             RenderAssetUsages,
             RenderAssets,
+            prepare_assets,
         },
         render_phase::{
             AddRenderCommand,
@@ -41,44 +64,100 @@ use bevy::{
             RenderCommandResult,
             SetItemPipeline,
             TrackedRenderPass,
+            ViewRangefinder3d,
             ViewSortedRenderPhases,
         },
         render_resource::*,
-        renderer::RenderDevice,
+        renderer::{
+            RenderAdapter,
+            RenderDevice,
+            RenderQueue,
+        },
+        texture::{
+            BevyDefault,
+            GpuImage,
+        },
         view::{
             ExtractedView,
+            RenderLayers,
+            ViewTarget,
             ViewUniform,
             ViewUniformOffset,
             ViewUniforms,
         },
+        MainWorld,
         Render,
         RenderApp,
         RenderSet,
     }
 };
 
+#[cfg(feature = "view_globals")]
+use bevy::render::globals::{
+    GlobalsBuffer,
+    GlobalsUniform,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use bevy::{
+    ecs::system::StaticSystemParam,
+    render::render_asset::ExtractedAssets,
+};
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+use crate::gaussian::patch::GaussianCloudPatch;
+
+#[cfg(all(
+    not(feature = "precompute_covariance_3d"),
+    any(feature = "packed", feature = "buffer_storage"),
+))]
+use crate::gaussian::packed::Gaussian;
+
 use crate::{
     gaussian::{
         cloud::GaussianCloud,
+        patch::GaussianCloudPatches,
         settings::{
+            GAUSSIAN_CLOUD_MAX_CLIP_PLANES,
+            GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES,
+            GaussianCloudBoundingBoxMode,
+            GaussianCloudBufferSettings,
+            GaussianCloudColorChannel,
+            GaussianCloudColorLutDomain,
             GaussianCloudDrawMode,
+            GaussianCloudEntryPoints,
+            GaussianCloudCullMode,
+            GaussianCloudFrontFace,
+            GaussianCloudKernel,
             GaussianCloudRasterize,
+            GaussianCloudRenderMode,
             GaussianCloudSettings,
+            GaussianDebugMode,
+            GaussianTransparencyMode,
+            SplatSizeMode,
         },
     },
     material::spherical_harmonics::{
-        HALF_SH_COEFF_COUNT,
-        SH_COEFF_COUNT,
+        SH_BAND_MASK_ALL,
+        SH_DEGREE,
         SH_VEC4_PLANES,
+        sh_coeff_count,
     },
     morph::MorphPlugin,
     sort::{
+        DEFAULT_TEMPORAL_SORT_WINDOW_SIZE,
         GpuSortedEntry,
         SortPlugin,
         SortedEntries,
     },
 };
 
+pub mod hook;
+pub mod occlusion;
+pub mod outline;
+pub mod scissor;
+pub mod thumbnail;
+
 #[cfg(feature = "packed")]
 mod packed;
 
@@ -90,12 +169,15 @@ mod texture;
 
 
 const BINDINGS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(675257236);
-const GAUSSIAN_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(68294581);
+// insert a modified gaussian.wgsl at this handle to replace the render shader, see GaussianCloudEntryPoints
+pub const GAUSSIAN_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(68294581);
 const PACKED_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(123623514);
 const PLANAR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(72345231);
 const TEXTURE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(26345735);
 const TRANSFORM_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(734523534);
 
+pub const MAX_ELLIPSOID_COUNT: usize = 1 << 16;
+
 
 #[derive(Default)]
 pub struct RenderPipelinePlugin;
@@ -144,12 +226,29 @@ impl Plugin for RenderPipelinePlugin {
             Shader::from_wgsl
         );
 
+        app.register_type::<GaussianCloudBufferSettings>();
+        app.init_resource::<GaussianCloudBufferSettings>();
+        app.add_plugins(ExtractResourcePlugin::<GaussianCloudBufferSettings>::default());
+
+        app.init_resource::<hook::GaussianCloudShaderHooks>();
+        app.add_plugins(ExtractResourcePlugin::<hook::GaussianCloudShaderHooks>::default());
+
+        app.register_type::<GaussianCloudUniformExtension>();
+        app.register_type::<outline::GaussianOutline>();
+        app.add_plugins(ExtractComponentPlugin::<outline::GaussianOutline>::default());
+
+        app.register_type::<scissor::GaussianScissor>();
+        app.add_plugins(ExtractComponentPlugin::<scissor::GaussianScissor>::default());
+
         app.add_plugins(RenderAssetPlugin::<GpuGaussianCloud>::default());
+        app.init_resource::<GaussianCloudPatches>();
         app.add_plugins(UniformComponentPlugin::<GaussianCloudUniform>::default());
 
         app.add_plugins((
             MorphPlugin,
             SortPlugin,
+            occlusion::GaussianOcclusionPlugin,
+            thumbnail::GaussianCloudThumbnailPlugin,
         ));
 
         #[cfg(feature = "buffer_texture")]
@@ -158,14 +257,23 @@ impl Plugin for RenderPipelinePlugin {
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
                 .add_render_command::<Transparent3d, DrawGaussians>()
+                .add_render_command::<Transparent3d, DrawGaussiansOpaque>()
                 .init_resource::<GaussianUniformBindGroups>()
-                .add_systems(ExtractSchedule, extract_gaussians)
+                .init_resource::<ExtractedGaussianCloudPatches>()
+                .add_systems(ExtractSchedule, (
+                    extract_gaussians,
+                    extract_gaussian_cloud_patches,
+                ))
                 .add_systems(
                     Render,
                     (
+                        write_gaussian_cloud_patches
+                            .in_set(RenderSet::PrepareAssets)
+                            .before(prepare_assets::<GpuGaussianCloud>),
                         queue_gaussian_bind_group.in_set(RenderSet::Queue),
-                        queue_gaussian_view_bind_groups.in_set(RenderSet::Queue),
                         queue_gaussians.in_set(RenderSet::Queue),
+                        // the view bind group samples the prepass depth, which is prepared after queueing
+                        queue_gaussian_view_bind_groups.in_set(RenderSet::PrepareBindGroups),
                     ),
                 );
         }
@@ -181,7 +289,7 @@ impl Plugin for RenderPipelinePlugin {
 }
 
 
-#[derive(Bundle)]
+#[derive(Bundle, Clone)]
 pub struct GpuGaussianSplattingBundle {
     pub settings: GaussianCloudSettings,
     pub settings_uniform: GaussianCloudUniform,
@@ -189,29 +297,101 @@ pub struct GpuGaussianSplattingBundle {
     pub cloud_handle: Handle<GaussianCloud>,
 }
 
+// per gaussian data uploaded for a cloud, which attributes exist depends on the enabled storage features
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+pub enum GaussianCloudAttribute {
+    // PositionVisibility, 4 x f32
+    PositionVisibility,
+    // sh_word_count(sh_degree) u32 words per gaussian, bands lowest first
+    SphericalHarmonics,
+    // RotationScaleOpacityPacked128, a wxyz rotation then scale and opacity as f16 pairs in 4 x u32 (f16)
+    RotationScaleOpacity,
+    // Rotation, a wxyz quaternion as 4 x f32 (f32)
+    Rotation,
+    // ScaleOpacity, 4 x f32 (f32)
+    ScaleOpacity,
+    // Covariance3dOpacityPacked128 (f16) or Covariance3dOpacity (f32), with precompute_covariance_3d
+    Covariance3dOpacity,
+    // every attribute of a gaussian in one packed::Gaussian (packed)
+    Packed,
+}
+
+// buffers hold one element per gaussian in cloud order, textures one texel per gaussian and layer in row major order
+#[derive(Clone, Copy, Debug)]
+pub enum GaussianCloudAttributeStorage<'a> {
+    Buffer(&'a Buffer),
+    Texture(&'a Texture),
+}
+
+// the buffers are owned by the render asset and replaced whenever the cloud asset changes, so hold handles no longer
+// than a frame and look the cloud up again in RenderAssets<GpuGaussianCloud>. they are read only for other wgpu code:
+// writing them out of band is unsupported and is overwritten or raced by patches, morphs, and the sort
 #[derive(Debug, Clone)]
 pub struct GpuGaussianCloud {
     #[cfg(feature = "packed")]
     pub packed: packed::PackedBuffers,
     #[cfg(feature = "buffer_storage")]
     pub planar: planar::PlanarBuffers,
+    #[cfg(feature = "buffer_texture")]
+    pub textures: texture::TextureBuffers,
 
     pub count: usize,
 
+    // degree the render pipeline is specialized on, below SH_DEGREE when planar storage buffers dropped empty bands
+    pub sh_degree: usize,
+
     pub draw_indirect_buffer: Buffer,
 
+    // cloud-space bounds of the 3 sigma footprint, none when scales are unavailable
+    pub bounds: Option<(Sphere, Aabb)>,
+
+    #[cfg(feature = "buffer_storage")]
+    pub channel_buffer: Buffer,
+
+    #[cfg(feature = "buffer_storage")]
+    pub reveal_rank_buffer: Buffer,
+
     #[cfg(feature = "debug_gpu")]
     pub debug_gpu: GaussianCloud,
 }
+
+impl GpuGaussianCloud {
+    // read-only handle to where an attribute is stored with the enabled features, none when the storage mode does not
+    // keep it on its own, e.g. every attribute but Packed with `packed`
+    pub fn attribute(&self, attribute: GaussianCloudAttribute) -> Option<GaussianCloudAttributeStorage<'_>> {
+        #[cfg(feature = "packed")]
+        let storage = self.packed.attribute(attribute).map(GaussianCloudAttributeStorage::Buffer);
+        #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+        let storage = self.planar.attribute(attribute).map(GaussianCloudAttributeStorage::Buffer);
+        #[cfg(feature = "buffer_texture")]
+        let storage = self.textures.attribute(attribute).map(GaussianCloudAttributeStorage::Texture);
+
+        storage
+    }
+}
+
 impl RenderAsset for GpuGaussianCloud {
     type SourceAsset = GaussianCloud;
-    type Param = SRes<RenderDevice>;
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<RenderQueue>,
+        Option<SRes<GaussianCloudBufferSettings>>,
+    );
 
+    #[allow(unused_variables)]
     fn prepare_asset(
         source: Self::SourceAsset,
-        render_device: &mut SystemParamItem<Self::Param>,
+        (render_device, render_queue, buffer_settings): &mut SystemParamItem<Self::Param>,
     ) -> Result<Self, PrepareAssetError<Self::SourceAsset>> {
         let count = source.len();
+        let usage = cloud_buffer_usage(buffer_settings.as_deref());
 
         let draw_indirect_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
             label: Some("draw indirect buffer"),
@@ -224,19 +404,61 @@ impl RenderAsset for GpuGaussianCloud {
             usage: BufferUsages::INDIRECT | BufferUsages::COPY_DST | BufferUsages::STORAGE | BufferUsages::COPY_SRC,
         });
 
-        // TODO: (extract GaussianCloud, TextureBuffers) when feature buffer_texture is enabled
+        let bounds = cloud_bounds(&source);
+
+        // a single placeholder value keeps the binding valid for clouds without a channel
+        #[cfg(feature = "buffer_storage")]
+        let channel_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("channel buffer"),
+            contents: bytemuck::cast_slice(if source.channel.is_empty() {
+                &[0.0_f32]
+            } else {
+                source.channel.as_slice()
+            }),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        });
+
+        // the shader only reads ranks from a buffer holding one per gaussian, the placeholder keeps cloud order
+        #[cfg(feature = "buffer_storage")]
+        let reveal_rank_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("reveal rank buffer"),
+            contents: bytemuck::cast_slice(if source.reveal_rank.len() == count {
+                source.reveal_rank.as_slice()
+            } else {
+                &[0_u32]
+            }),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        });
+
+        #[cfg(feature = "buffer_storage")]
+        let planar = planar::prepare_cloud(render_device, &source, usage);
+
+        // packed buffers and textures always hold every band
+        #[cfg(any(feature = "packed", not(feature = "buffer_storage")))]
+        let sh_degree = SH_DEGREE;
+        #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+        let sh_degree = planar.sh_degree;
 
         Ok(GpuGaussianCloud {
             count,
+            sh_degree,
             draw_indirect_buffer,
+            bounds,
+
+            #[cfg(feature = "buffer_storage")]
+            channel_buffer,
+            #[cfg(feature = "buffer_storage")]
+            reveal_rank_buffer,
 
             #[cfg(feature = "packed")]
-            packed: packed::prepare_cloud(render_device, &source),
+            packed: packed::prepare_cloud(render_device, &source, usage),
             #[cfg(feature = "buffer_storage")]
-            planar: planar::prepare_cloud(render_device, &source),
+            planar,
+            #[cfg(feature = "buffer_texture")]
+            textures: texture::prepare_cloud(render_device, render_queue, &source),
 
             #[cfg(feature = "debug_gpu")]
-            debug_gpu: gaussian_cloud,
+            debug_gpu: source,
         })
     }
 
@@ -245,6 +467,432 @@ impl RenderAsset for GpuGaussianCloud {
     }
 }
 
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn cloud_bounds(cloud: &GaussianCloud) -> Option<(Sphere, Aabb)> {
+    (!cloud.is_empty()).then(|| {
+        let margin = 3.0 * cloud.max_scale();
+        let aabb = cloud.aabb();
+
+        (
+            cloud.bounding_sphere(3.0),
+            Aabb {
+                center: aabb.center,
+                half_extents: aabb.half_extents + margin,
+            },
+        )
+    })
+}
+
+#[cfg(feature = "precompute_covariance_3d")]
+fn cloud_bounds(_cloud: &GaussianCloud) -> Option<(Sphere, Aabb)> {
+    None
+}
+
+pub fn cloud_buffer_usage(settings: Option<&GaussianCloudBufferSettings>) -> BufferUsages {
+    let usage = BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE;
+
+    match settings {
+        Some(settings) if settings.readback => usage | BufferUsages::COPY_SRC,
+        _ => usage,
+    }
+}
+
+// each cloud buffer is bound whole, a buffer past the device limits can not be drawn at all. split such clouds with
+// GaussianCloud::morton_chunks, see "large clouds" in the readme
+pub fn assert_storage_binding_fits(
+    render_device: &RenderDevice,
+    label: &str,
+    size: usize,
+) {
+    let limits = render_device.limits();
+    let limit = (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size);
+
+    assert!(
+        size as u64 <= limit,
+        "{} needs {} bytes, the device binds at most {} (max_storage_buffer_binding_size / max_buffer_size), split the \
+        cloud with GaussianCloud::morton_chunks",
+        label,
+        size,
+        limit,
+    );
+}
+
+pub(crate) fn create_cloud_buffer(
+    render_device: &RenderDevice,
+    label: &str,
+    contents: &[u8],
+    usage: BufferUsages,
+) -> Buffer {
+    assert_storage_binding_fits(render_device, label, contents.len());
+
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some(label),
+        contents,
+        usage,
+    })
+}
+
+// a copy of a buffer on its way to the cpu. nothing blocks: the copy is submitted on creation and map_async signals
+// the channel once the gpu is done, which `is_mapped` checks without waiting. call it (or the try_read of the
+// readbacks built on it) once a frame until it returns data or an error, then drop the readback
+pub struct BufferReadback {
+    staging_buffer: Buffer,
+    receiver: Receiver<Result<(), wgpu::BufferAsyncError>>,
+    // the map result once the callback reported it, a failed map (e.g. device loss) is returned by every later poll
+    map_result: Option<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl BufferReadback {
+    pub fn new(
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+        buffer: &Buffer,
+    ) -> Self {
+        assert!(
+            buffer.usage().contains(BufferUsages::COPY_SRC),
+            "gaussian cloud readback requires GaussianCloudBufferSettings::readback",
+        );
+
+        let staging_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("gaussian_cloud_readback_buffer"),
+            size: buffer.size(),
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("gaussian_cloud_readback_encoder"),
+        });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging_buffer, 0, buffer.size());
+        render_queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        staging_buffer.slice(..).map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        Self {
+            staging_buffer,
+            receiver,
+            map_result: None,
+        }
+    }
+
+    // the map callback only runs during a device poll, see poll_readbacks. a callback dropped without reporting
+    // (e.g. the buffer was destroyed mid-map) counts as a failed map
+    pub fn is_mapped(&mut self) -> Result<bool, wgpu::BufferAsyncError> {
+        if self.map_result.is_none() {
+            self.map_result = match self.receiver.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err(wgpu::BufferAsyncError)),
+            };
+        }
+
+        match &self.map_result {
+            Some(result) => result.clone().map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn try_read<T: bytemuck::Pod>(
+        &mut self,
+        render_device: &RenderDevice,
+    ) -> Result<Option<Vec<T>>, wgpu::BufferAsyncError> {
+        poll_readbacks(render_device);
+
+        Ok(if self.is_mapped()? { self.read() } else { None })
+    }
+
+    // none unless is_mapped returned true
+    pub fn read<T: bytemuck::Pod>(&self) -> Option<Vec<T>> {
+        matches!(self.map_result, Some(Ok(()))).then(|| {
+            bytemuck::cast_slice::<u8, T>(&self.staging_buffer.slice(..).get_mapped_range()).to_vec()
+        })
+    }
+}
+
+// runs the callbacks of finished maps without waiting for the gpu
+pub fn poll_readbacks(render_device: &RenderDevice) {
+    render_device.poll(wgpu::Maintain::Poll);
+}
+
+// the gaussians of a GpuGaussianCloud in flight to the cpu, see GpuGaussianCloud::read_gaussians. planar clouds in
+// textures have no buffers to copy
+#[cfg(all(
+    not(feature = "precompute_covariance_3d"),
+    any(feature = "packed", feature = "buffer_storage"),
+))]
+pub struct GaussianCloudReadback {
+    count: usize,
+
+    #[cfg(feature = "packed")]
+    packed: BufferReadback,
+    #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+    planar: planar::PlanarReadback,
+}
+
+#[cfg(all(
+    not(feature = "precompute_covariance_3d"),
+    any(feature = "packed", feature = "buffer_storage"),
+))]
+impl GaussianCloudReadback {
+    // none until every buffer of the cloud has been copied and mapped, an error if any of the maps failed
+    pub fn try_read(&mut self, render_device: &RenderDevice) -> Result<Option<Vec<Gaussian>>, wgpu::BufferAsyncError> {
+        poll_readbacks(render_device);
+
+        #[cfg(feature = "packed")]
+        let gaussians = if self.packed.is_mapped()? {
+            self.packed.read::<Gaussian>()
+        } else {
+            None
+        };
+
+        #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+        let gaussians = if self.planar.is_mapped()? {
+            self.planar.read().map(|cloud| cloud.gaussian_iter().collect::<Vec<Gaussian>>())
+        } else {
+            None
+        };
+
+        Ok(gaussians.map(|gaussians| {
+            gaussians.into_iter()
+                .take(self.count)
+                .collect()
+        }))
+    }
+}
+
+#[cfg(all(
+    not(feature = "precompute_covariance_3d"),
+    any(feature = "packed", feature = "buffer_storage"),
+))]
+impl GpuGaussianCloud {
+    // copies the cloud's buffers for the cpu, e.g. to save gaussians edited by a compute pass. requires
+    // GaussianCloudBufferSettings::readback, poll the result with try_read
+    pub fn read_gaussians(
+        &self,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+    ) -> GaussianCloudReadback {
+        GaussianCloudReadback {
+            count: self.count,
+
+            #[cfg(feature = "packed")]
+            packed: packed::readback(render_device, render_queue, &self.packed),
+            #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+            planar: planar::PlanarReadback::new(render_device, render_queue, &self.planar),
+        }
+    }
+}
+
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl GpuGaussianCloud {
+    // uploads only the ranges touched by an in-place patch, `cloud` must already have the patch applied.
+    // returns false when the patch changes the cloud length and the buffers must be prepared again
+    pub fn write_patch(
+        &self,
+        render_queue: &RenderQueue,
+        cloud: &GaussianCloud,
+        patch: &GaussianCloudPatch,
+    ) -> bool {
+        patch.is_in_place() && self.write_ranges(render_queue, cloud, &patch.dirty_ranges())
+    }
+
+    // the cloud bounds are left as prepared
+    pub fn write_ranges(
+        &self,
+        render_queue: &RenderQueue,
+        cloud: &GaussianCloud,
+        ranges: &[Range<usize>],
+    ) -> bool {
+        if cloud.len() != self.count || ranges.iter().any(|range| range.end > self.count) {
+            return false;
+        }
+
+        // the planar buffers have no room for bands above their degree
+        #[cfg(feature = "buffer_storage")]
+        if ranges.iter().any(|range| cloud.sh_degree_in(range.clone()) > self.planar.sh_degree) {
+            return false;
+        }
+
+        for range in ranges.iter() {
+            #[cfg(feature = "packed")]
+            packed::write_range(render_queue, &self.packed, cloud, range.clone());
+
+            #[cfg(feature = "buffer_storage")]
+            planar::write_range(render_queue, &self.planar, cloud, range.clone());
+        }
+
+        true
+    }
+}
+
+
+// the clouds extracted alongside in-place GaussianCloudPatches. ExtractedAssets can not be edited, so on frames with
+// patches every changed cloud is extracted here too and prepared by write_gaussian_cloud_patches instead
+#[derive(Default, Resource)]
+pub struct ExtractedGaussianCloudPatches {
+    patched: Vec<(AssetId<GaussianCloud>, GaussianCloud, Vec<Range<usize>>)>,
+    changed: Vec<(AssetId<GaussianCloud>, GaussianCloud)>,
+    removed: Vec<AssetId<GaussianCloud>>,
+}
+
+// RenderAssetPlugin extracts every modified cloud, a cloud whose Modified events all came from
+// GaussianCloudPatches::apply keeps its ranges, any other get_mut of it uploads it whole
+fn extract_gaussian_cloud_patches(
+    mut commands: Commands,
+    mut main_world: ResMut<MainWorld>,
+    mut asset_events: Local<ManualEventReader<AssetEvent<GaussianCloud>>>,
+) {
+    let mut modifications = HashMap::<AssetId<GaussianCloud>, usize>::new();
+    let mut added = HashSet::new();
+    let mut removed = Vec::new();
+
+    for event in asset_events.read(main_world.resource::<Events<AssetEvent<GaussianCloud>>>()) {
+        match event {
+            AssetEvent::Added { id } => {
+                added.insert(*id);
+            },
+            AssetEvent::Modified { id } => *modifications.entry(*id).or_default() += 1,
+            AssetEvent::Unused { id } => removed.push(*id),
+            _ => {},
+        }
+    }
+
+    let mut patches = main_world.resource_mut::<GaussianCloudPatches>();
+    let ranges = modifications.iter()
+        .filter_map(|(&id, &count)| {
+            let pending = patches.pending.remove(&id)?;
+            let in_place = pending.modifications == count && !pending.full_upload && !added.contains(&id);
+
+            in_place.then_some((id, pending.dirty_ranges))
+        })
+        .collect::<HashMap<_, _>>();
+
+    if ranges.is_empty() {
+        commands.insert_resource(ExtractedGaussianCloudPatches::default());
+        return;
+    }
+
+    let clouds = main_world.resource::<Assets<GaussianCloud>>();
+    let mut extracted = ExtractedGaussianCloudPatches::default();
+
+    for id in added.into_iter().chain(modifications.into_keys()).collect::<HashSet<_>>() {
+        if removed.contains(&id) {
+            continue;
+        }
+
+        let Some(cloud) = clouds.get(id) else {
+            continue;
+        };
+
+        match ranges.get(&id) {
+            Some(ranges) => extracted.patched.push((id, cloud.clone(), ranges.clone())),
+            None => extracted.changed.push((id, cloud.clone())),
+        }
+    }
+
+    extracted.removed = removed;
+    commands.insert_resource(extracted);
+}
+
+// writes the patched ranges into the buffers the cloud already has. on those frames the extracted assets are
+// cleared so prepare_assets does not upload the patched clouds again, and the other changed clouds, as well as clouds
+// the ranges can not be written into, are prepared here instead
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn write_gaussian_cloud_patches(
+    mut patches: ResMut<ExtractedGaussianCloudPatches>,
+    render_queue: Res<RenderQueue>,
+    mut extracted_assets: ResMut<ExtractedAssets<GpuGaussianCloud>>,
+    mut gaussian_clouds: ResMut<RenderAssets<GpuGaussianCloud>>,
+    param: StaticSystemParam<<GpuGaussianCloud as RenderAsset>::Param>,
+) {
+    if patches.patched.is_empty() {
+        return;
+    }
+
+    *extracted_assets = ExtractedAssets::default();
+
+    let mut param = param.into_inner();
+    let patches = std::mem::take(&mut *patches);
+
+    for id in patches.removed {
+        gaussian_clouds.remove(id);
+    }
+
+    let mut changed = patches.changed;
+    for (id, cloud, ranges) in patches.patched {
+        let Some(gpu_cloud) = gaussian_clouds.get_mut(id) else {
+            changed.push((id, cloud));
+            continue;
+        };
+
+        if !gpu_cloud.write_ranges(&render_queue, &cloud, &ranges) {
+            changed.push((id, cloud));
+            continue;
+        }
+
+        gpu_cloud.bounds = cloud_bounds(&cloud);
+    }
+
+    for (id, cloud) in changed {
+        gaussian_clouds.remove(id);
+
+        if let Ok(gpu_cloud) = GpuGaussianCloud::prepare_asset(cloud, &mut param) {
+            gaussian_clouds.insert(id, gpu_cloud);
+        }
+    }
+}
+
+#[cfg(feature = "precompute_covariance_3d")]
+fn write_gaussian_cloud_patches() {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawIndirectStats {
+    pub count: u32,
+    pub instance_count: u32,
+    pub culled: bool,
+}
+
+// the draw indirect args of a GpuGaussianCloud in flight to the cpu
+pub struct DrawIndirectStatsReadback {
+    count: u32,
+    args: BufferReadback,
+}
+
+impl DrawIndirectStatsReadback {
+    pub fn try_read(&mut self, render_device: &RenderDevice) -> Result<Option<DrawIndirectStats>, wgpu::BufferAsyncError> {
+        let Some(args) = self.args.try_read::<u32>(render_device)? else {
+            return Ok(None);
+        };
+        let instance_count = args[1];
+
+        Ok(Some(DrawIndirectStats {
+            count: self.count,
+            instance_count,
+            culled: instance_count < self.count,
+        }))
+    }
+}
+
+impl GpuGaussianCloud {
+    // the radix sort compacts culled entries behind the survivors and writes the survivor count into
+    // `draw_indirect_buffer` of clouds that draw indirect, cpu sorts and direct draws leave the args as they are (the
+    // initial `count` unless the cloud drew indirect before). poll the result with try_read
+    pub fn read_draw_indirect_stats(
+        &self,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+    ) -> DrawIndirectStatsReadback {
+        DrawIndirectStatsReadback {
+            count: self.count as u32,
+            args: BufferReadback::new(render_device, render_queue, &self.draw_indirect_buffer),
+        }
+    }
+}
+
 #[cfg(feature = "buffer_storage")]
 type GpuGaussianBundleQuery = (
     Entity,
@@ -263,7 +911,97 @@ type GpuGaussianBundleQuery = (
     &'static texture::GpuTextureBuffers,
 );
 
+// clouds and cameras without RenderLayers are on layer 0, matched the same way as meshes
+pub fn view_renders_cloud(view: Option<&RenderLayers>, cloud: Option<&RenderLayers>) -> bool {
+    let default_layers = RenderLayers::default();
+
+    view.unwrap_or(&default_layers).intersects(cloud.unwrap_or(&default_layers))
+}
+
+// entities with InheritedVisibility follow the hierarchy like meshes, so a hidden ancestor hides the cloud. entities
+// spawned without it only honor their own Visibility
+pub fn cloud_visible(visibility: &Visibility, inherited: Option<&InheritedVisibility>) -> bool {
+    match inherited {
+        Some(inherited) => inherited.get(),
+        None => *visibility != Visibility::Hidden,
+    }
+}
+
+// depth writing passes would keep a faded cloud's core opaque
+fn opaque_passes(settings: &GaussianCloudSettings) -> bool {
+    settings.render_mode == GaussianCloudRenderMode::Splats
+        && settings.global_opacity >= 1.0
+        && !settings.always_on_top
+        && settings.effective_transparency_mode() == GaussianTransparencyMode::Sorted
+}
+
+// the rotation-invariant sphere test rejects most off-screen clouds before the tighter obb test
+fn cloud_in_frustum(
+    frustum: &Frustum,
+    (sphere, aabb): &(Sphere, Aabb),
+    transform: &Affine3A,
+) -> bool {
+    let max_axis_scale = transform.matrix3.x_axis.length()
+        .max(transform.matrix3.y_axis.length())
+        .max(transform.matrix3.z_axis.length());
+
+    let world_sphere = Sphere {
+        center: transform.transform_point3a(sphere.center),
+        radius: sphere.radius * max_axis_scale,
+    };
+
+    if !frustum.intersects_sphere(&world_sphere, true) {
+        return false;
+    }
+
+    frustum.intersects_obb(aabb, transform, true, true)
+}
+
+// one transparent item per cloud at its world bounding sphere center (the transform origin until bounds are known),
+// the splats themselves are sorted within the cloud
+fn cloud_sort_distance(
+    rangefinder: &ViewRangefinder3d,
+    settings: &GaussianCloudSettings,
+    bounds: Option<&(Sphere, Aabb)>,
+) -> f32 {
+    let center = bounds
+        .map(|(sphere, _)| Vec3::from(sphere.center))
+        .unwrap_or(Vec3::ZERO);
+    let world_center = settings.cloud_transform().transform_point3(center);
+    let distance = rangefinder.distance_translation(&world_center) + settings.sort_bias;
+
+    if settings.always_on_top {
+        return on_top_distance(distance);
+    }
+
+    distance
+}
+
+// lowest transparent phase key of an always_on_top cloud, far past any view depth
+const ON_TOP_DISTANCE_BASE: f32 = 1e30;
+
+// maps a phase key into a band above every other item that keeps the order of on top clouds among each other, so
+// their view depth and sort_bias still apply. the key's order preserving bits are quantized to 26 bits and spaced
+// two apart above the base, leaving the float just below each key free for the cloud's outline
+pub fn on_top_distance(distance: f32) -> f32 {
+    let bits = distance.to_bits();
+    let ordered = if bits >> 31 == 1 { !bits } else { bits | 0x8000_0000 };
+
+    f32::from_bits(ON_TOP_DISTANCE_BASE.to_bits() + ((ordered >> 6) << 1) + 1)
+}
+
+// the lut is only sampled once its image is on the gpu, untouched color until then
+fn color_lut_domain(
+    settings: &GaussianCloudSettings,
+    gpu_images: &RenderAssets<GpuImage>,
+) -> Option<GaussianCloudColorLutDomain> {
+    settings.color_lut.as_ref()
+        .filter(|lut| gpu_images.get(*lut).is_some())
+        .map(|_| settings.color_lut_domain)
+}
+
 #[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
 fn queue_gaussians(
     gaussian_cloud_uniform: Res<ComponentUniforms<GaussianCloudUniform>>,
     transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
@@ -273,9 +1011,14 @@ fn queue_gaussians(
     gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
     sorted_entries: Res<RenderAssets<GpuSortedEntry>>,
     mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
-    mut views: Query<(Entity, &ExtractedView)>,
+    mut views: Query<(Entity, &ExtractedView, Option<&Frustum>, Option<&RenderLayers>, Has<DepthPrepass>)>,
     msaa: Res<Msaa>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    shader_hooks: Res<hook::GaussianCloudShaderHooks>,
     gaussian_splatting_bundles: Query<GpuGaussianBundleQuery>,
+    outlines: Query<&outline::GaussianOutline>,
+    cloud_render_layers: Query<&RenderLayers>,
+    occlusion: Res<occlusion::GaussianCloudOcclusion>,
 ) {
     // TODO: condition this system based on GaussianCloudBindGroup attachment
     if gaussian_cloud_uniform.buffer().is_none() {
@@ -283,8 +1026,9 @@ fn queue_gaussians(
     };
 
     let draw_custom = transparent_3d_draw_functions.read().id::<DrawGaussians>();
+    let draw_opaque = transparent_3d_draw_functions.read().id::<DrawGaussiansOpaque>();
 
-    for (view_entity, _view) in &mut views {
+    for (view_entity, view, frustum, view_render_layers, scene_depth) in &mut views {
         let Some(transparent_phase) = transparent_render_phases.get_mut(&view_entity) else {
             continue;
         };
@@ -296,33 +1040,150 @@ fn queue_gaussians(
             settings,
             _,
         ) in &gaussian_splatting_bundles {
-            if gaussian_clouds.get(cloud_handle).is_none() {
-                return;
-            }
+            // a cloud still streaming in (e.g. a sequence frame or a hot reload) skips only itself
+            let Some(cloud) = gaussian_clouds.get(cloud_handle) else {
+                continue;
+            };
 
             if sorted_entries.get(sorted_entries_handle).is_none() {
-                return;
+                continue;
+            }
+
+            if !view_renders_cloud(view_render_layers, cloud_render_layers.get(entity).ok()) {
+                continue;
+            }
+
+            if let (Some(frustum), Some(bounds)) = (frustum, cloud.bounds.as_ref()) {
+                if !cloud_in_frustum(frustum, bounds, &settings.cloud_transform()) {
+                    continue;
+                }
+            }
+
+            if occlusion.is_occluded(view_entity, entity) {
+                continue;
             }
 
             let key = GaussianCloudPipelineKey {
                 aabb: settings.aabb,
-                visualize_bounding_box: settings.visualize_bounding_box,
+                visualize_bounding_box: settings.visualize_bounding_box
+                    && settings.bounding_box_mode == GaussianCloudBoundingBoxMode::Splat,
                 draw_mode: settings.draw_mode,
                 rasterize_mode: settings.rasterize_mode,
+                render_mode: settings.render_mode,
+                kernel: settings.kernel,
+                cull_mode: settings.cull_mode,
+                front_face: settings.front_face,
+                depth_bias: settings.depth_bias.round() as i32,
+                depth_bias_slope: settings.depth_bias_slope.to_bits(),
+                entry_points: settings.entry_points(),
+                debug: settings.debug.mode(),
                 sample_count: msaa.samples(),
+                hdr: view.hdr,
+                sh_band_mask: settings.sh_band_mask,
+                sh_degree: cloud.sh_degree,
+                color_channel: settings.color_channel,
+                opaque_pass: false,
+                depth_prepass: false,
+                color_lut: color_lut_domain(settings, &gpu_images),
+                color_hook: shader_hooks.color.is_some(),
+                outline: false,
+                scene_depth,
+                scene_depth_test: scene_depth && settings.scene_depth_test && !settings.always_on_top,
+                always_on_top: settings.always_on_top,
+                opacity_curve: settings.opacity_curve.is_some(),
+                transparency_mode: settings.effective_transparency_mode(),
+                splat_size_mode: settings.splat_size_mode,
             };
 
             let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key);
 
-            // // TODO: distance to gaussian cloud centroid
-            // let rangefinder = view.rangefinder3d();
+            if settings.depth_prepass_alpha.is_some() && opaque_passes(settings) {
+                let prepass_pipeline = pipelines.specialize(
+                    &pipeline_cache,
+                    &custom_pipeline,
+                    GaussianCloudPipelineKey {
+                        depth_prepass: true,
+                        ..key
+                    },
+                );
+
+                // depth only, lets the blended pass early-z reject splats behind the front surface
+                transparent_phase.add(Transparent3d {
+                    entity,
+                    draw_function: draw_opaque,
+                    distance: f32::NEG_INFINITY,
+                    pipeline: prepass_pipeline,
+                    batch_range: 0..1,
+                    extra_index: PhaseItemExtraIndex::NONE,
+                });
+            }
+
+            if settings.opaque_threshold.is_some() && opaque_passes(settings) {
+                let opaque_pipeline = pipelines.specialize(
+                    &pipeline_cache,
+                    &custom_pipeline,
+                    GaussianCloudPipelineKey {
+                        opaque_pass: true,
+                        ..key
+                    },
+                );
+
+                // ahead of every back-to-front sorted item, so blended splats test against the opaque depth
+                transparent_phase.add(Transparent3d {
+                    entity,
+                    draw_function: draw_opaque,
+                    distance: f32::NEG_INFINITY,
+                    pipeline: opaque_pipeline,
+                    batch_range: 0..1,
+                    extra_index: PhaseItemExtraIndex::NONE,
+                });
+            }
+
+            let stochastic = settings.effective_transparency_mode() == GaussianTransparencyMode::Stochastic
+                && settings.render_mode == GaussianCloudRenderMode::Splats;
+
+            let mut distance = cloud_sort_distance(
+                &view.rangefinder3d(),
+                settings,
+                cloud.bounds.as_ref(),
+            );
+            let mut draw_function = draw_custom;
+
+            // stochastic splats write depth in cloud order, so like the opaque pass they go ahead of every blended
+            // item. on top clouds never draw stochastic
+            if stochastic {
+                draw_function = draw_opaque;
+                distance = f32::NEG_INFINITY;
+            }
+
+            if outlines.get(entity).is_ok_and(|outline| outline.thickness > 0.0)
+                && settings.render_mode == GaussianCloudRenderMode::Splats
+            {
+                let outline_pipeline = pipelines.specialize(
+                    &pipeline_cache,
+                    &custom_pipeline,
+                    GaussianCloudPipelineKey {
+                        outline: true,
+                        ..key
+                    },
+                );
+
+                // after the depth writing passes so it tests against them, before every blended splat. on top
+                // clouds draw theirs right before the cloud
+                transparent_phase.add(Transparent3d {
+                    entity,
+                    draw_function: draw_opaque,
+                    distance: if settings.always_on_top { f32::from_bits(distance.to_bits() - 1) } else { f32::MIN },
+                    pipeline: outline_pipeline,
+                    batch_range: 0..1,
+                    extra_index: PhaseItemExtraIndex::NONE,
+                });
+            }
 
             transparent_phase.add(Transparent3d {
                 entity,
-                draw_function: draw_custom,
-                distance: 0.0,
-                // distance: rangefinder
-                //     .distance_translation(&mesh_instance.transforms.transform.translation),
+                draw_function,
+                distance,
                 pipeline,
                 batch_range: 0..1,
                 extra_index: PhaseItemExtraIndex::NONE,
@@ -338,14 +1199,22 @@ pub struct GaussianCloudPipeline {
     pub gaussian_cloud_layout: BindGroupLayout,
     pub gaussian_uniform_layout: BindGroupLayout,
     pub view_layout: BindGroupLayout,
+    // view_layout plus the prepass depth texture, for views with a DepthPrepass
+    pub scene_depth_view_layout: BindGroupLayout,
+    pub scene_depth_view_layout_multisampled: BindGroupLayout,
     pub sorted_layout: BindGroupLayout,
+    pub color_lut_fallback: TextureView,
+    pub color_lut_sampler: Sampler,
+    // without indirect execution every cloud takes the direct draw path
+    pub supports_indirect: bool,
 }
 
 impl FromWorld for GaussianCloudPipeline {
     fn from_world(render_world: &mut World) -> Self {
         let render_device = render_world.resource::<RenderDevice>();
 
-        let view_layout_entries = vec![
+        #[allow(unused_mut)]
+        let mut view_layout_entries = vec![
             BindGroupLayoutEntry {
                 binding: 0,
                 visibility: ShaderStages::all(),
@@ -356,23 +1225,50 @@ impl FromWorld for GaussianCloudPipeline {
                 },
                 count: None,
             },
-            BindGroupLayoutEntry {
-                binding: 1,
-                visibility: ShaderStages::all(),
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: Some(GlobalsUniform::min_size()),
-                },
-                count: None,
-            },
         ];
 
+        #[cfg(feature = "view_globals")]
+        view_layout_entries.push(BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::all(),
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: Some(GlobalsUniform::min_size()),
+            },
+            count: None,
+        });
+
         let view_layout = render_device.create_bind_group_layout(
             Some("gaussian_view_layout"),
             &view_layout_entries,
         );
 
+        let scene_depth_view_layout_entries = |multisampled: bool| {
+            let mut entries = view_layout_entries.clone();
+            entries.push(BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Depth,
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled,
+                },
+                count: None,
+            });
+            entries
+        };
+
+        let scene_depth_view_layout = render_device.create_bind_group_layout(
+            Some("gaussian_scene_depth_view_layout"),
+            &scene_depth_view_layout_entries(false),
+        );
+
+        let scene_depth_view_layout_multisampled = render_device.create_bind_group_layout(
+            Some("gaussian_scene_depth_view_layout_multisampled"),
+            &scene_depth_view_layout_entries(true),
+        );
+
         let gaussian_uniform_layout = render_device.create_bind_group_layout(
             Some("gaussian_uniform_layout"),
             &[
@@ -415,17 +1311,89 @@ impl FromWorld for GaussianCloudPipeline {
                     },
                     count: None,
                 },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D3,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
+                    },
+                    count: None,
+                },
             ],
         );
         #[cfg(feature = "buffer_texture")]
         let sorted_layout = texture::get_sorted_bind_group_layout(render_device);
 
+        // bound while a cloud has no lut (or it is still loading), never sampled
+        let color_lut_fallback = render_device.create_texture(&TextureDescriptor {
+            label: Some("color_lut_fallback"),
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D3,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }).create_view(&TextureViewDescriptor::default());
+
+        let color_lut_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("color_lut_sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let supports_indirect = render_world.resource::<RenderAdapter>()
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION);
+
         GaussianCloudPipeline {
             gaussian_cloud_layout,
             gaussian_uniform_layout,
             view_layout,
+            scene_depth_view_layout,
+            scene_depth_view_layout_multisampled,
             shader: GAUSSIAN_SHADER_HANDLE,
             sorted_layout,
+            color_lut_fallback,
+            color_lut_sampler,
+            supports_indirect,
         }
     }
 }
@@ -433,6 +1401,7 @@ impl FromWorld for GaussianCloudPipeline {
 // TODO: allow setting shader defines via API
 // TODO: separate shader defines for each pipeline
 pub struct ShaderDefines {
+    pub sort_key_bits: u32,
     pub radix_bits_per_digit: u32,
     pub radix_digit_places: u32,
     pub radix_base: u32,
@@ -445,22 +1414,15 @@ pub struct ShaderDefines {
     pub sorting_buffer_size: u32,
 
     pub temporal_sort_window_size: u32,
-}
-
-impl ShaderDefines {
-    pub fn max_tile_count(&self, count: usize) -> u32 {
-        (count as u32 + self.workgroup_entries_c - 1) / self.workgroup_entries_c
-    }
 
-    pub fn sorting_status_counters_buffer_size(&self, count: usize) -> usize {
-        self.radix_base as usize * self.max_tile_count(count) as usize * std::mem::size_of::<u32>()
-    }
+    pub ellipsoid_segments: u32,
 }
 
-impl Default for ShaderDefines {
-    fn default() -> Self {
+impl ShaderDefines {
+    // radix sort passes and histogram sizes follow the key width, everything else is shared
+    pub fn with_sort_key_bits(sort_key_bits: u32) -> Self {
         let radix_bits_per_digit = 8;
-        let radix_digit_places = 32 / radix_bits_per_digit;
+        let radix_digit_places = sort_key_bits / radix_bits_per_digit;
         let radix_base = 1 << radix_bits_per_digit;
         let entries_per_invocation_a = 4;
         let entries_per_invocation_c = 4;
@@ -472,6 +1434,7 @@ impl Default for ShaderDefines {
             std::mem::size_of::<u32>() as u32 + 5 * std::mem::size_of::<u32>() as u32;
 
         Self {
+            sort_key_bits,
             radix_bits_per_digit,
             radix_digit_places,
             radix_base,
@@ -483,19 +1446,47 @@ impl Default for ShaderDefines {
             workgroup_entries_c,
             sorting_buffer_size,
 
-            temporal_sort_window_size: 16,
+            temporal_sort_window_size: DEFAULT_TEMPORAL_SORT_WINDOW_SIZE,
+
+            ellipsoid_segments: 16,
         }
     }
+
+    pub fn max_tile_count(&self, count: usize) -> u32 {
+        count.div_ceil(self.workgroup_entries_c as usize) as u32
+    }
+
+    pub fn sorting_status_counters_buffer_size(&self, count: usize) -> usize {
+        self.radix_base as usize * self.max_tile_count(count) as usize * std::mem::size_of::<u32>()
+    }
+
+    // three great circles drawn as line lists
+    pub fn ellipsoid_vertex_count(&self) -> u32 {
+        3 * self.ellipsoid_segments * 2
+    }
+}
+
+impl Default for ShaderDefines {
+    fn default() -> Self {
+        Self::with_sort_key_bits(32)
+    }
 }
 
 pub fn shader_defs(
     key: GaussianCloudPipelineKey,
 ) -> Vec<ShaderDefVal> {
-    let defines = ShaderDefines::default();
+    shader_defs_with(key, &ShaderDefines::default())
+}
+
+pub fn shader_defs_with(
+    key: GaussianCloudPipelineKey,
+    defines: &ShaderDefines,
+) -> Vec<ShaderDefVal> {
     let mut shader_defs = vec![
-        ShaderDefVal::UInt("SH_COEFF_COUNT".into(), SH_COEFF_COUNT as u32),
-        ShaderDefVal::UInt("HALF_SH_COEFF_COUNT".into(), HALF_SH_COEFF_COUNT as u32),
+        ShaderDefVal::UInt("SH_COEFF_COUNT".into(), sh_coeff_count(key.sh_degree) as u32),
+        ShaderDefVal::UInt("HALF_SH_COEFF_COUNT".into(), (sh_coeff_count(key.sh_degree) / 2) as u32),
         ShaderDefVal::UInt("SH_VEC4_PLANES".into(), SH_VEC4_PLANES as u32),
+        ShaderDefVal::UInt("SH_BAND_MASK".into(), key.sh_band_mask as u32),
         ShaderDefVal::UInt("RADIX_BASE".into(), defines.radix_base),
         ShaderDefVal::UInt("RADIX_BITS_PER_DIGIT".into(), defines.radix_bits_per_digit),
         ShaderDefVal::UInt("RADIX_DIGIT_PLACES".into(), defines.radix_digit_places),
@@ -506,8 +1497,21 @@ pub fn shader_defs(
         ShaderDefVal::UInt("WORKGROUP_ENTRIES_C".into(), defines.workgroup_entries_c),
 
         ShaderDefVal::UInt("TEMPORAL_SORT_WINDOW_SIZE".into(), defines.temporal_sort_window_size),
+
+        ShaderDefVal::UInt("ELLIPSOID_SEGMENTS".into(), defines.ellipsoid_segments),
     ];
 
+    // degree 0 planar clouds read the dc term alone instead of evaluating the sh basis. packed buffers and textures
+    // lay out every band, so their pipelines keep the sh lookup
+    #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+    if key.sh_degree == 0 {
+        shader_defs.push("FLAT_COLOR".into());
+    }
+
+    if defines.sort_key_bits == 16 {
+        shader_defs.push("SORT_KEY_16".into());
+    }
+
     if key.aabb {
         shader_defs.push("USE_AABB".into());
     }
@@ -523,6 +1527,9 @@ pub fn shader_defs(
     #[cfg(feature = "morph_particles")]
     shader_defs.push("READ_WRITE_POINTS".into());
 
+    #[cfg(feature = "view_globals")]
+    shader_defs.push("VIEW_GLOBALS".into());
+
     #[cfg(feature = "packed")]
     shader_defs.push("PACKED".into());
 
@@ -565,6 +1572,84 @@ pub fn shader_defs(
         GaussianCloudRasterize::Normal => shader_defs.push("RASTERIZE_NORMAL".into()),
     }
 
+    match key.kernel {
+        GaussianCloudKernel::Gaussian => {},
+        GaussianCloudKernel::Flat => shader_defs.push("KERNEL_FLAT".into()),
+        GaussianCloudKernel::Cubic => shader_defs.push("KERNEL_CUBIC".into()),
+    }
+
+    // the unsorted passes draw in cloud order, so they have no rank to show
+    let stochastic = key.transparency_mode == GaussianTransparencyMode::Stochastic
+        && key.render_mode == GaussianCloudRenderMode::Splats;
+    let sorted_pass = !key.opaque_pass && !key.depth_prepass && !key.outline && !stochastic;
+
+    match key.debug {
+        GaussianDebugMode::None => {},
+        GaussianDebugMode::SortOrder if sorted_pass => shader_defs.push("DEBUG_SORT_ORDER".into()),
+        GaussianDebugMode::SortOrder => {},
+        GaussianDebugMode::OpacityBoost if sorted_pass => shader_defs.push("DEBUG_OPACITY_BOOST".into()),
+        GaussianDebugMode::OpacityBoost => {},
+        // the unsorted passes that write color show depth too, so the opaque cores match the blended splats
+        GaussianDebugMode::Depth if !key.depth_prepass && !key.outline => shader_defs.push("DEBUG_DEPTH".into()),
+        GaussianDebugMode::Depth => {},
+    }
+
+    if key.opacity_curve {
+        shader_defs.push("OPACITY_CURVE".into());
+    }
+
+    if key.splat_size_mode == SplatSizeMode::Constant {
+        shader_defs.push("SPLAT_SIZE_CONSTANT".into());
+    }
+
+    if stochastic && !key.outline {
+        shader_defs.push("STOCHASTIC_TRANSPARENCY".into());
+
+        if key.sample_count > 1 {
+            shader_defs.push("ALPHA_TO_COVERAGE".into());
+        }
+    }
+
+    if key.opaque_pass {
+        shader_defs.push("OPAQUE_PASS".into());
+    }
+
+    if key.depth_prepass {
+        shader_defs.push("DEPTH_PREPASS".into());
+    }
+
+    if key.color_hook {
+        shader_defs.push("COLOR_HOOK".into());
+    }
+
+    if key.outline {
+        shader_defs.push("OUTLINE".into());
+    }
+
+    if key.scene_depth_test {
+        shader_defs.push("SCENE_DEPTH_TEST".into());
+
+        if key.sample_count > 1 {
+            shader_defs.push("SCENE_DEPTH_MULTISAMPLED".into());
+        }
+    }
+
+    // the lut is bound next to the sorted entries, which only storage buffers extend
+    #[cfg(feature = "buffer_storage")]
+    if let Some(domain) = key.color_lut {
+        shader_defs.push("COLOR_LUT".into());
+
+        if domain == GaussianCloudColorLutDomain::Srgb {
+            shader_defs.push("COLOR_LUT_SRGB".into());
+        }
+    }
+
+    // the channel buffer is only bound for storage buffers
+    #[cfg(feature = "buffer_storage")]
+    if key.color_channel == GaussianCloudColorChannel::Channel {
+        shader_defs.push("COLOR_CHANNEL".into());
+    }
+
     match key.draw_mode {
         GaussianCloudDrawMode::All => {},
         GaussianCloudDrawMode::Selected => shader_defs.push("DRAW_SELECTED".into()),
@@ -574,13 +1659,83 @@ pub fn shader_defs(
     shader_defs
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub struct GaussianCloudPipelineKey {
     pub aabb: bool,
     pub visualize_bounding_box: bool,
     pub draw_mode: GaussianCloudDrawMode,
     pub rasterize_mode: GaussianCloudRasterize,
+    pub render_mode: GaussianCloudRenderMode,
+    pub kernel: GaussianCloudKernel,
+    pub cull_mode: GaussianCloudCullMode,
+    pub front_face: GaussianCloudFrontFace,
+    pub depth_bias: i32,
+    // f32 bits of GaussianCloudSettings::depth_bias_slope
+    pub depth_bias_slope: u32,
+    pub entry_points: GaussianCloudEntryPoints,
+    pub debug: GaussianDebugMode,
     pub sample_count: u32,
+    pub hdr: bool,
+    pub sh_band_mask: u8,
+    pub sh_degree: usize,
+    pub color_channel: GaussianCloudColorChannel,
+    pub opaque_pass: bool,
+    pub depth_prepass: bool,
+    pub color_lut: Option<GaussianCloudColorLutDomain>,
+    pub color_hook: bool,
+    pub outline: bool,
+    // the view has a prepass depth texture in its bind group
+    pub scene_depth: bool,
+    pub scene_depth_test: bool,
+    pub always_on_top: bool,
+    pub opacity_curve: bool,
+    pub transparency_mode: GaussianTransparencyMode,
+    pub splat_size_mode: SplatSizeMode,
+}
+
+impl Default for GaussianCloudPipelineKey {
+    fn default() -> Self {
+        Self {
+            aabb: false,
+            visualize_bounding_box: false,
+            draw_mode: GaussianCloudDrawMode::default(),
+            rasterize_mode: GaussianCloudRasterize::default(),
+            render_mode: GaussianCloudRenderMode::default(),
+            kernel: GaussianCloudKernel::default(),
+            cull_mode: GaussianCloudCullMode::default(),
+            front_face: GaussianCloudFrontFace::default(),
+            depth_bias: 0,
+            depth_bias_slope: 0,
+            entry_points: GaussianCloudEntryPoints::default(),
+            debug: GaussianDebugMode::default(),
+            sample_count: 0,
+            hdr: false,
+            sh_band_mask: SH_BAND_MASK_ALL,
+            sh_degree: SH_DEGREE,
+            color_channel: GaussianCloudColorChannel::default(),
+            opaque_pass: false,
+            depth_prepass: false,
+            color_lut: None,
+            color_hook: false,
+            outline: false,
+            scene_depth: false,
+            scene_depth_test: false,
+            always_on_top: false,
+            opacity_curve: false,
+            transparency_mode: GaussianTransparencyMode::default(),
+            splat_size_mode: SplatSizeMode::default(),
+        }
+    }
+}
+
+impl GaussianCloudPipeline {
+    pub fn view_layout(&self, key: GaussianCloudPipelineKey) -> &BindGroupLayout {
+        match (key.scene_depth, key.sample_count > 1) {
+            (false, _) => &self.view_layout,
+            (true, false) => &self.scene_depth_view_layout,
+            (true, true) => &self.scene_depth_view_layout_multisampled,
+        }
+    }
 }
 
 impl SpecializedRenderPipeline for GaussianCloudPipeline {
@@ -589,10 +1744,35 @@ impl SpecializedRenderPipeline for GaussianCloudPipeline {
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
         let shader_defs = shader_defs(key);
 
+        let topology = match key.render_mode {
+            GaussianCloudRenderMode::Splats => PrimitiveTopology::TriangleStrip,
+            GaussianCloudRenderMode::Ellipsoids => PrimitiveTopology::LineList,
+        };
+
+        // depth bias is only valid for triangle topologies
+        let depth_bias = match topology {
+            PrimitiveTopology::TriangleStrip => DepthBiasState {
+                constant: key.depth_bias,
+                slope_scale: f32::from_bits(key.depth_bias_slope),
+                clamp: 0.0,
+            },
+            _ => DepthBiasState::default(),
+        };
+
+        let stochastic = key.transparency_mode == GaussianTransparencyMode::Stochastic
+            && key.render_mode == GaussianCloudRenderMode::Splats
+            && !key.outline;
+
+        let format = if key.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+
         RenderPipelineDescriptor {
             label: Some("gaussian cloud render pipeline".into()),
             layout: vec![
-                self.view_layout.clone(),
+                self.view_layout(key).clone(),
                 self.gaussian_uniform_layout.clone(),
                 self.gaussian_cloud_layout.clone(),
                 self.sorted_layout.clone(),
@@ -600,48 +1780,61 @@ impl SpecializedRenderPipeline for GaussianCloudPipeline {
             vertex: VertexState {
                 shader: self.shader.clone(),
                 shader_defs: shader_defs.clone(),
-                entry_point: "vs_points".into(),
+                entry_point: key.entry_points.vertex.into(),
                 buffers: vec![],
             },
             fragment: Some(FragmentState {
                 shader: self.shader.clone(),
                 shader_defs,
-                entry_point: "fs_main".into(),
+                entry_point: key.entry_points.fragment.into(),
                 targets: vec![Some(ColorTargetState {
-                    format: TextureFormat::Rgba8UnormSrgb,
-                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
-                    write_mask: ColorWrites::ALL,
+                    format,
+                    // fragments are premultiplied and sorted back to front, so this is the porter-duff over operator:
+                    // color = src + (1 - src_alpha) * dst and alpha = src_alpha + (1 - src_alpha) * dst_alpha,
+                    // which holds for any clear color, opaque geometry, or a transparent window
+                    // stochastic fragments are either kept whole or dropped, so they replace
+                    blend: if stochastic {
+                        None
+                    } else {
+                        Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING)
+                    },
+                    write_mask: if key.depth_prepass {
+                        ColorWrites::empty()
+                    } else {
+                        ColorWrites::ALL
+                    },
                 })],
             }),
             primitive: PrimitiveState {
-                topology: PrimitiveTopology::TriangleStrip,
+                topology,
                 strip_index_format: None,
-                front_face: FrontFace::Ccw,
+                front_face: key.front_face.front_face(),
                 unclipped_depth: false,
-                cull_mode: None,
+                cull_mode: key.cull_mode.face(),
                 conservative: false,
                 polygon_mode: PolygonMode::Fill,
             },
             depth_stencil: Some(DepthStencilState {
                 format: TextureFormat::Depth32Float,
-                depth_write_enabled: false,
-                depth_compare: CompareFunction::GreaterEqual,
+                depth_write_enabled: (key.opaque_pass || key.depth_prepass || stochastic) && !key.always_on_top,
+                depth_compare: if key.always_on_top {
+                    CompareFunction::Always
+                } else {
+                    CompareFunction::GreaterEqual
+                },
                 stencil: StencilState {
                     front: StencilFaceState::IGNORE,
                     back: StencilFaceState::IGNORE,
                     read_mask: 0,
                     write_mask: 0,
                 },
-                bias: DepthBiasState {
-                    constant: 0,
-                    slope_scale: 0.0,
-                    clamp: 0.0,
-                },
+                bias: depth_bias,
             }),
             multisample: MultisampleState {
                 count: key.sample_count,
                 mask: !0,
-                alpha_to_coverage_enabled: false,
+                // only defined for multisampled targets
+                alpha_to_coverage_enabled: stochastic && key.sample_count > 1,
             },
             push_constant_ranges: Vec::new(),
         }
@@ -652,77 +1845,320 @@ type DrawGaussians = (
     SetItemPipeline,
     SetGaussianViewBindGroup<0>,
     SetGaussianUniformBindGroup<1>,
-    DrawGaussianInstanced,
+    scissor::ScissoredGaussianDraw<DrawGaussianInstanced>,
+);
+
+type DrawGaussiansOpaque = (
+    SetItemPipeline,
+    SetGaussianViewBindGroup<0>,
+    SetGaussianUniformBindGroup<1>,
+    scissor::ScissoredGaussianDraw<DrawGaussianOpaqueInstanced>,
 );
 
 
+// slots of GaussianCloudUniformExtension::data
+pub const GAUSSIAN_CLOUD_UNIFORM_EXTENSION_SLOTS: usize = 4;
+
+// per cloud data for out-of-tree shaders, copied verbatim into the trailing `extension` block of the cloud uniform.
+// the block size is fixed so extensions never change the bind group layout, pack whatever they need into it
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianCloudUniformExtension {
+    pub data: [Vec4; GAUSSIAN_CLOUD_UNIFORM_EXTENSION_SLOTS],
+}
+
+
+// binding contract: group 1, binding 0 is a uniform buffer with a dynamic offset per cloud, laid out with std140
+// (uniform address space) rules in field order. `GaussianUniforms` in bindings.wgsl declares the same fields in the
+// same order, a shader importing `bevy_gaussian_splatting::bindings::gaussian_uniforms` reads them by name. fields are
+// only ever appended before `extension`, which stays last
 #[derive(Component, ShaderType, Clone)]
 pub struct GaussianCloudUniform {
     pub transform: Mat4,
     pub global_scale: f32,
     pub count: u32,
     pub count_root_ceil: u32,
+    pub fog_color: Vec4,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub channel_min: f32,
+    pub channel_max: f32,
+    pub opaque_threshold: f32,
+    pub max_screen_fraction: f32,
+    pub depth_prepass_alpha: f32,
+    pub outline_thickness: f32,
+    pub outline_color: Vec4,
+    pub global_opacity: f32,
+    pub focus_point: Vec3,
+    pub focus_radius: f32,
+    pub focus_falloff: f32,
+    pub scale_clamp_min: f32,
+    pub scale_clamp_max: f32,
+    // cloud space bounding sphere the 16 bit radix sort keys quantize depth over, a zero radius falls back to clip depth
+    pub sort_center: Vec3,
+    pub sort_radius: f32,
+    // world space, see GaussianCloudSettings::world_clip_planes
+    pub clip_planes: [Vec4; GAUSSIAN_CLOUD_MAX_CLIP_PLANES],
+    pub exposure: f32,
+    pub splat_vertex_count: u32,
+    pub cutoff_sigma: f32,
+    pub debug_opacity_gamma: f32,
+    pub debug_depth_near: f32,
+    pub debug_depth_far: f32,
+    pub opacity_curve: [Vec4; GAUSSIAN_CLOUD_OPACITY_CURVE_SAMPLES / 4],
+    pub reveal_fraction: f32,
+    // nonzero when the splat draw reads the radix sort's instance count, see GaussianCloudSettings::draws_indirect
+    pub draws_indirect: u32,
+    pub color_lut_input_min: Vec3,
+    pub color_lut_input_max: Vec3,
+    pub extension: [Vec4; GAUSSIAN_CLOUD_UNIFORM_EXTENSION_SLOTS],
+}
+
+// what a cloud's uniform is built from
+pub struct GaussianCloudUniformSource<'a> {
+    pub settings: &'a GaussianCloudSettings,
+    pub outline: Option<&'a outline::GaussianOutline>,
+    pub extension: Option<&'a GaussianCloudUniformExtension>,
+    pub settings_changed: bool,
+    pub count: usize,
+    pub bounds: Option<Sphere>,
+}
+
+// static clouds reuse last frame's uniform, only a settings change or asset reload rebuilds it
+#[derive(Default)]
+pub struct GaussianCloudUniformCache {
+    uniforms: HashMap<Entity, GaussianCloudUniform>,
+}
+
+impl GaussianCloudUniformCache {
+    pub fn get_or_update(
+        &mut self,
+        entity: Entity,
+        source: GaussianCloudUniformSource,
+    ) -> GaussianCloudUniform {
+        let GaussianCloudUniformSource {
+            settings,
+            outline,
+            extension,
+            settings_changed,
+            count,
+            bounds,
+        } = source;
+
+        let count = count as u32;
+        let (sort_center, sort_radius) = bounds
+            .map(|sphere| (Vec3::from(sphere.center), sphere.radius))
+            .unwrap_or_default();
+
+        let (outline_thickness, outline_color) = outline
+            .map(|outline| {
+                let color = outline.color.to_linear();
+                (outline.thickness, Vec4::new(color.red, color.green, color.blue, color.alpha))
+            })
+            .unwrap_or_default();
+        let extension = extension.copied().unwrap_or_default().data;
+
+        if let Some(uniform) = self.uniforms.get(&entity) {
+            let outline_changed = uniform.outline_thickness != outline_thickness || uniform.outline_color != outline_color;
+            let extension_changed = uniform.extension != extension;
+            let bounds_changed = uniform.sort_center != sort_center || uniform.sort_radius != sort_radius;
+
+            if !settings_changed && !outline_changed && !extension_changed && !bounds_changed && uniform.count == count {
+                return uniform.clone();
+            }
+        }
+
+        let fog_color = settings.fog_color.to_linear();
+        let uniform = GaussianCloudUniform {
+            transform: settings.cloud_transform().into(),
+            global_scale: settings.global_scale,
+            count,
+            count_root_ceil: (count as f32).sqrt().ceil() as u32,
+            fog_color: Vec4::new(fog_color.red, fog_color.green, fog_color.blue, fog_color.alpha),
+            fog_start: settings.fog_start,
+            fog_end: settings.fog_end,
+            channel_min: settings.channel_range.x,
+            channel_max: settings.channel_range.y,
+            // opacity never exceeds one, so clouds without the opaque pass blend every gaussian
+            opaque_threshold: settings.opaque_threshold
+                .filter(|_| opaque_passes(settings))
+                .unwrap_or(2.0),
+            max_screen_fraction: settings.max_screen_fraction,
+            depth_prepass_alpha: settings.depth_prepass_alpha.unwrap_or(1.0),
+            outline_thickness,
+            outline_color,
+            global_opacity: settings.global_opacity,
+            focus_point: settings.focus_point,
+            focus_radius: settings.focus_radius,
+            focus_falloff: settings.focus_falloff,
+            scale_clamp_min: settings.scale_clamp.map_or(0.0, |(min, _)| min),
+            scale_clamp_max: settings.scale_clamp.map_or(f32::MAX, |(_, max)| max),
+            sort_center,
+            sort_radius,
+            clip_planes: settings.world_clip_planes(),
+            exposure: settings.exposure,
+            splat_vertex_count: settings.splat_vertex_count(),
+            cutoff_sigma: settings.cutoff_sigma.max(f32::EPSILON),
+            debug_opacity_gamma: settings.debug.opacity_gamma(),
+            debug_depth_near: settings.debug.depth_range().x,
+            debug_depth_far: settings.debug.depth_range().y,
+            opacity_curve: settings.opacity_curve_uniform(),
+            reveal_fraction: settings.reveal_fraction.clamp(0.0, 1.0),
+            draws_indirect: settings.draws_indirect() as u32,
+            color_lut_input_min: settings.color_lut_input_min,
+            color_lut_input_max: settings.color_lut_input_max,
+            extension,
+        };
+        self.uniforms.insert(entity, uniform.clone());
+
+        uniform
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Entity) -> bool) {
+        self.uniforms.retain(|entity, _| keep(entity));
+    }
+
+    pub fn len(&self) -> usize {
+        self.uniforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uniforms.is_empty()
+    }
+}
+
+// extracted components of each cloud entity, rebuilt only when its settings, handles or render layers change. this
+// skips the uniform rebuild and component clones of static clouds, it does not diff the render world: bevy 0.14
+// despawns every render entity at the end of the frame, so every visible cloud is still inserted each frame
+#[derive(Default)]
+pub struct GaussianCloudExtractCache {
+    clouds: HashMap<Entity, ExtractedGaussianCloud>,
+}
+
+struct ExtractedGaussianCloud {
+    bundle: GpuGaussianSplattingBundle,
+    render_layers: Option<RenderLayers>,
 }
 
+impl GaussianCloudExtractCache {
+    pub fn len(&self) -> usize {
+        self.clouds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clouds.is_empty()
+    }
+}
+
+// unified sorting draws its sources through the merged cloud
+#[cfg(not(feature = "precompute_covariance_3d"))]
+type ExtractedCloudFilter = Without<crate::sort::unified::UnifiedGaussianCloudSource>;
+
+#[cfg(feature = "precompute_covariance_3d")]
+type ExtractedCloudFilter = ();
+
 #[allow(clippy::type_complexity)]
 pub fn extract_gaussians(
     mut commands: Commands,
     mut prev_commands_len: Local<usize>,
+    mut uniform_cache: Local<GaussianCloudUniformCache>,
+    mut extract_cache: Local<GaussianCloudExtractCache>,
     asset_server: Res<AssetServer>,
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     gaussians_query: Extract<
         Query<(
             Entity,
-            // &ComputedVisibility,
             &Visibility,
-            &Handle<GaussianCloud>,
-            &Handle<SortedEntries>,
-            &GaussianCloudSettings,
-        )>,
+            Option<&InheritedVisibility>,
+            Ref<Handle<GaussianCloud>>,
+            Ref<Handle<SortedEntries>>,
+            Ref<GaussianCloudSettings>,
+            Option<&outline::GaussianOutline>,
+            Option<&GaussianCloudUniformExtension>,
+            Option<Ref<RenderLayers>>,
+        ), ExtractedCloudFilter>,
     >,
 ) {
     let mut commands_list = Vec::with_capacity(*prev_commands_len);
-    // let visible_gaussians = gaussians_query.iter().filter(|(_, vis, ..)| vis.is_visible());
+    let mut render_layers_list = Vec::new();
 
     for (
         entity,
         visibility,
+        inherited_visibility,
         cloud_handle,
         sorted_entries,
         settings,
+        outline,
+        extension,
+        render_layers,
     ) in gaussians_query.iter() {
-        if visibility == Visibility::Hidden {
+        if !cloud_visible(visibility, inherited_visibility) {
             continue;
         }
 
-        if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle){
+        let up_to_date = extract_cache.clouds.get(&entity).is_some_and(|cached| {
+            // sequences swap frames bypassing change detection, so the handle itself is compared
+            !settings.is_changed()
+                && cached.bundle.cloud_handle.id() == cloud_handle.id()
+                && !sorted_entries.is_changed()
+                && !render_layers.as_ref().is_some_and(|render_layers| render_layers.is_changed())
+                && cached.render_layers.is_some() == render_layers.is_some()
+        });
+
+        if !up_to_date && Some(LoadState::Loading) == asset_server.get_load_state(&*cloud_handle) {
             continue;
         }
 
-        if gaussian_cloud_res.get(cloud_handle).is_none() {
+        let Some(cloud) = gaussian_cloud_res.get(&*cloud_handle) else {
             continue;
+        };
+
+        let settings_uniform = uniform_cache.get_or_update(entity, GaussianCloudUniformSource {
+            settings: &settings,
+            outline,
+            extension,
+            settings_changed: settings.is_changed(),
+            count: cloud.count,
+            bounds: cloud.bounds.as_ref().map(|(sphere, _)| sphere.clone()),
+        });
+
+        if !up_to_date {
+            extract_cache.clouds.insert(entity, ExtractedGaussianCloud {
+                bundle: GpuGaussianSplattingBundle {
+                    settings: (*settings).clone(),
+                    settings_uniform: settings_uniform.clone(),
+                    sorted_entries: (*sorted_entries).clone(),
+                    cloud_handle: (*cloud_handle).clone(),
+                },
+                render_layers: render_layers.map(|render_layers| (*render_layers).clone()),
+            });
         }
 
-        let cloud = gaussian_cloud_res.get(cloud_handle).unwrap();
+        let extracted = extract_cache.clouds.get_mut(&entity).unwrap();
+        extracted.bundle.settings_uniform = settings_uniform;
 
-        let settings_uniform = GaussianCloudUniform {
-            transform: settings.transform.compute_matrix(),
-            global_scale: settings.global_scale,
-            count: cloud.count as u32,
-            count_root_ceil: (cloud.count as f32).sqrt().ceil() as u32,
-        };
-        commands_list.push((
-            entity,
-            GpuGaussianSplattingBundle {
-                settings: settings.clone(),
-                settings_uniform,
-                sorted_entries: sorted_entries.clone(),
-                cloud_handle: cloud_handle.clone(),
-            },
-        ));
+        if let Some(render_layers) = &extracted.render_layers {
+            render_layers_list.push((entity, render_layers.clone()));
+        }
+
+        commands_list.push((entity, extracted.bundle.clone()));
     }
+
+    uniform_cache.retain(|entity| gaussians_query.contains(*entity));
+    extract_cache.clouds.retain(|entity, _| gaussians_query.contains(*entity));
+
     *prev_commands_len = commands_list.len();
     commands.insert_or_spawn_batch(commands_list);
+    commands.insert_or_spawn_batch(render_layers_list);
 }
 
 
@@ -748,9 +2184,7 @@ fn queue_gaussian_bind_group(
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     sorted_entries_res: Res<RenderAssets<GpuSortedEntry>>,
     gaussian_clouds: Query<GpuGaussianBundleQuery>,
-
-    #[cfg(feature = "buffer_texture")]
-    gpu_images: Res<RenderAssets<bevy::render::texture::GpuImage>>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
 ) {
     let Some(model) = gaussian_uniforms.buffer() else {
         return;
@@ -802,6 +2236,11 @@ fn queue_gaussian_bind_group(
 
         let sorted_entries = sorted_entries_res.get(sorted_entries_handle).unwrap();
 
+        #[cfg(feature = "buffer_storage")]
+        let color_lut = query.3.color_lut.as_ref()
+            .and_then(|lut| gpu_images.get(lut))
+            .map_or(&gaussian_cloud_pipeline.color_lut_fallback, |lut| &lut.texture_view);
+
         #[cfg(feature = "packed")]
         let cloud_bind_group = packed::get_bind_group(&render_device, &gaussian_cloud_pipeline, cloud);
         #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
@@ -822,6 +2261,22 @@ fn queue_gaussian_bind_group(
                         size: BufferSize::new((cloud.count * std::mem::size_of::<(u32, u32)>()) as u64),
                     }),
                 },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: cloud.channel_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(color_lut),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::Sampler(&gaussian_cloud_pipeline.color_lut_sampler),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: cloud.reveal_rank_buffer.as_entire_binding(),
+                },
             ],
         );
         #[cfg(feature = "buffer_texture")]
@@ -848,6 +2303,8 @@ fn queue_gaussian_bind_group(
 #[derive(Component)]
 pub struct GaussianViewBindGroup {
     pub value: BindGroup,
+    // value plus the prepass depth, bound by every render pipeline of a view with a DepthPrepass
+    pub scene_depth: Option<BindGroup>,
 }
 
 pub fn queue_gaussian_view_bind_groups(
@@ -858,41 +2315,68 @@ pub fn queue_gaussian_view_bind_groups(
     views: Query<(
         Entity,
         &ExtractedView,
+        Option<&ViewPrepassTextures>,
     )>,
+    #[cfg(feature = "view_globals")]
     globals_buffer: Res<GlobalsBuffer>,
+    msaa: Res<Msaa>,
 ) {
-    if let (
-        Some(view_binding),
-        Some(globals),
-    ) = (
-        view_uniforms.uniforms.binding(),
-        globals_buffer.buffer.binding(),
-    ) {
+    #[cfg(feature = "view_globals")]
+    let Some(globals) = globals_buffer.buffer.binding() else {
+        return;
+    };
+
+    if let Some(view_binding) = view_uniforms.uniforms.binding() {
         for (
             entity,
             _extracted_view,
+            prepass_textures,
         ) in &views {
             let layout = &gaussian_cloud_pipeline.view_layout;
 
-            let entries = vec![
+            let mut entries = vec![
                 BindGroupEntry {
                     binding: 0,
                     resource: view_binding.clone(),
                 },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: globals.clone(),
-                },
             ];
 
+            #[cfg(feature = "view_globals")]
+            entries.push(BindGroupEntry {
+                binding: 1,
+                resource: globals.clone(),
+            });
+
             let view_bind_group = render_device.create_bind_group(
                 "gaussian_view_bind_group",
                 layout,
                 &entries,
             );
 
+            let scene_depth_bind_group = prepass_textures
+                .and_then(|textures| textures.depth_view())
+                .map(|scene_depth| {
+                    entries.push(BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::TextureView(scene_depth),
+                    });
+
+                    let layout = gaussian_cloud_pipeline.view_layout(GaussianCloudPipelineKey {
+                        scene_depth: true,
+                        sample_count: msaa.samples(),
+                        ..default()
+                    });
+
+                    render_device.create_bind_group(
+                        "gaussian_scene_depth_view_bind_group",
+                        layout,
+                        &entries,
+                    )
+                });
+
             commands.entity(entity).insert(GaussianViewBindGroup {
                 value: view_bind_group,
+                scene_depth: scene_depth_bind_group,
             });
         }
     }
@@ -920,7 +2404,7 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetGaussianViewBindGroup
     ) -> RenderCommandResult {
         pass.set_bind_group(
             I,
-            &gaussian_view_bind_group.value,
+            gaussian_view_bind_group.scene_depth.as_ref().unwrap_or(&gaussian_view_bind_group.value),
             &[view_uniform.offset],
         );
 
@@ -956,11 +2440,80 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetGaussianUniformBindGr
 
 pub struct DrawGaussianInstanced;
 impl<P: PhaseItem> RenderCommand<P> for DrawGaussianInstanced {
+    type Param = (
+        SRes<RenderAssets<GpuGaussianCloud>>,
+        SRes<GaussianCloudPipeline>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = (
+        Read<Handle<GaussianCloud>>,
+        Read<GaussianCloudBindGroup>,
+        Read<GaussianCloudSettings>,
+    );
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        entity: Option<(
+            &'w Handle<GaussianCloud>,
+            &'w GaussianCloudBindGroup,
+            &'w GaussianCloudSettings,
+        )>,
+        (gaussian_clouds, pipeline): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let (handle, bind_groups, settings) = entity.expect("gaussian cloud entity not found");
+
+        let gpu_gaussian_cloud = match gaussian_clouds.into_inner().get(handle) {
+            Some(gpu_gaussian_cloud) => gpu_gaussian_cloud,
+            None => return RenderCommandResult::Failure,
+        };
+
+        pass.set_bind_group(2, &bind_groups.cloud_bind_group, &[]);
+        pass.set_bind_group(3, &bind_groups.sorted_bind_group, &[]);
+
+        match settings.render_mode {
+            GaussianCloudRenderMode::Splats => {
+                let vertex_count = settings.splat_vertex_count();
+                let splat_budget = settings.splat_budget
+                    .filter(|_| settings.sort_mode.supports_splat_budget());
+
+                if let Some(budget) = splat_budget {
+                    // the cpu sort moved the most important entries to the front
+                    pass.draw(0..vertex_count, 0..gpu_gaussian_cloud.count.min(budget) as u32);
+                    return RenderCommandResult::Success;
+                }
+
+                // the indirect args are shared by every entity drawing the cloud and always hold the quad's 4 vertices
+                if settings.draws_indirect() && pipeline.supports_indirect {
+                    pass.draw_indirect(&gpu_gaussian_cloud.draw_indirect_buffer, 0);
+                } else {
+                    pass.draw(0..vertex_count, 0..gpu_gaussian_cloud.count as u32);
+                }
+            },
+            GaussianCloudRenderMode::Ellipsoids => {
+                let vertex_count = ShaderDefines::default().ellipsoid_vertex_count();
+                let instance_count = gpu_gaussian_cloud.count.min(MAX_ELLIPSOID_COUNT) as u32;
+
+                pass.draw(0..vertex_count, 0..instance_count);
+            },
+        }
+
+        RenderCommandResult::Success
+    }
+}
+
+
+// opaque splats and the depth prepass skip the sort indirection, one instance per gaussian in cloud order
+pub struct DrawGaussianOpaqueInstanced;
+impl<P: PhaseItem> RenderCommand<P> for DrawGaussianOpaqueInstanced {
     type Param = SRes<RenderAssets<GpuGaussianCloud>>;
     type ViewQuery = ();
     type ItemQuery = (
         Read<Handle<GaussianCloud>>,
         Read<GaussianCloudBindGroup>,
+        Read<GaussianCloudSettings>,
     );
 
     #[inline]
@@ -970,11 +2523,12 @@ impl<P: PhaseItem> RenderCommand<P> for DrawGaussianInstanced {
         entity: Option<(
             &'w Handle<GaussianCloud>,
             &'w GaussianCloudBindGroup,
+            &'w GaussianCloudSettings,
         )>,
         gaussian_clouds: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        let (handle, bind_groups) = entity.expect("gaussian cloud entity not found");
+        let (handle, bind_groups, settings) = entity.expect("gaussian cloud entity not found");
 
         let gpu_gaussian_cloud = match gaussian_clouds.into_inner().get(handle) {
             Some(gpu_gaussian_cloud) => gpu_gaussian_cloud,
@@ -984,11 +2538,7 @@ impl<P: PhaseItem> RenderCommand<P> for DrawGaussianInstanced {
         pass.set_bind_group(2, &bind_groups.cloud_bind_group, &[]);
         pass.set_bind_group(3, &bind_groups.sorted_bind_group, &[]);
 
-        #[cfg(feature = "webgl2")]
-        pass.draw(0..4, 0..gpu_gaussian_cloud.count as u32);
-
-        #[cfg(not(feature = "webgl2"))]
-        pass.draw_indirect(&gpu_gaussian_cloud.draw_indirect_buffer, 0);
+        pass.draw(0..settings.splat_vertex_count(), 0..gpu_gaussian_cloud.count as u32);
 
         RenderCommandResult::Success
     }
@@ -0,0 +1,576 @@
+use std::sync::{
+    Arc,
+    Mutex,
+    atomic::{
+        AtomicBool,
+        Ordering,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    core_pipeline::{
+        core_3d::graph::{
+            Core3d,
+            Node3d,
+        },
+        prepass::ViewPrepassTextures,
+    },
+    ecs::query::QueryItem,
+    math::Affine3A,
+    render::{
+        extract_component::{
+            ExtractComponent,
+            ExtractComponentPlugin,
+        },
+        primitives::Aabb,
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{
+            RenderContext,
+            RenderDevice,
+        },
+        view::ExtractedView,
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+    utils::HashMap,
+};
+
+use crate::{
+    gaussian::{
+        cloud::GaussianCloud,
+        settings::GaussianCloudSettings,
+    },
+    render::GpuGaussianCloud,
+    sort::status::ResortGaussianClouds,
+};
+
+
+const OCCLUSION_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8134259713);
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianOcclusionLabel;
+
+
+// culls whole clouds hidden behind prepass geometry for this camera, which also needs a DepthPrepass. the prepass
+// depth is reduced to `resolution` tiles on the gpu and read back asynchronously, and each cloud's bounding box is
+// tested against the most recent readback on the cpu. occluded clouds are neither sorted nor drawn. results trail
+// the camera by the readback latency (usually one to three frames), so a cloud coming out from behind a wall can
+// appear a few frames late
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    ExtractComponent,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianOcclusionCulling {
+    pub resolution: UVec2,
+}
+
+impl Default for GaussianOcclusionCulling {
+    fn default() -> Self {
+        Self {
+            resolution: UVec2::new(64, 32),
+        }
+    }
+}
+
+
+// last occlusion result per (view, cloud) entity pair, shared between the main and render worlds
+#[derive(Clone, Debug, Default, Resource)]
+pub struct GaussianCloudOcclusion {
+    states: Arc<Mutex<HashMap<(Entity, Entity), bool>>>,
+    revealed: Arc<AtomicBool>,
+}
+
+impl GaussianCloudOcclusion {
+    // None until the view's first depth readback arrives
+    pub fn get(&self, view: Entity, cloud: Entity) -> Option<bool> {
+        self.states.lock().unwrap().get(&(view, cloud)).copied()
+    }
+
+    pub fn is_occluded(&self, view: Entity, cloud: Entity) -> bool {
+        self.get(view, cloud).unwrap_or(false)
+    }
+
+    fn set(&self, view: Entity, cloud: Entity, occluded: bool) {
+        let previous = self.states.lock().unwrap().insert((view, cloud), occluded);
+
+        if previous == Some(true) && !occluded {
+            self.revealed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+
+// farthest prepass depth per tile (reverse z), and the view it was captured with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GaussianCloudDepthTiles {
+    pub resolution: UVec2,
+    pub depth: Vec<f32>,
+    pub clip_from_world: Mat4,
+}
+
+impl GaussianCloudDepthTiles {
+    // conservative: true only if every tile the projected box touches (grown by one tile) holds geometry nearer
+    // than the box's nearest corner. boxes crossing the near plane are never occluded
+    pub fn occludes(&self, aabb: &Aabb, transform: &Affine3A) -> bool {
+        if self.resolution.x == 0 || self.resolution.y == 0 {
+            return false;
+        }
+
+        let center = Vec3::from(aabb.center);
+        let half_extents = Vec3::from(aabb.half_extents);
+
+        let mut min_uv = Vec2::INFINITY;
+        let mut max_uv = Vec2::NEG_INFINITY;
+        let mut nearest = 0.0_f32;
+
+        for corner in 0..8 {
+            let sign = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let world = transform.transform_point3(center + sign * half_extents);
+            let clip = self.clip_from_world * world.extend(1.0);
+
+            if clip.w <= 0.0 {
+                return false;
+            }
+
+            let ndc = clip.truncate() / clip.w;
+            if ndc.z >= 1.0 {
+                return false;
+            }
+
+            let uv = Vec2::new(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+            min_uv = min_uv.min(uv);
+            max_uv = max_uv.max(uv);
+            nearest = nearest.max(ndc.z);
+        }
+
+        if max_uv.cmplt(Vec2::ZERO).any() || min_uv.cmpgt(Vec2::ONE).any() {
+            // off screen, the frustum test decides
+            return false;
+        }
+
+        let last = self.resolution.as_ivec2() - IVec2::ONE;
+        let low = ((min_uv * self.resolution.as_vec2()).floor().as_ivec2() - IVec2::ONE).clamp(IVec2::ZERO, last);
+        let high = ((max_uv * self.resolution.as_vec2()).floor().as_ivec2() + IVec2::ONE).clamp(IVec2::ZERO, last);
+
+        (low.y..=high.y).all(|y| {
+            (low.x..=high.x).all(|x| {
+                self.depth[(y * self.resolution.x as i32 + x) as usize] > nearest
+            })
+        })
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianOcclusionPlugin;
+
+impl Plugin for GaussianOcclusionPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            OCCLUSION_SHADER_HANDLE,
+            "occlusion.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<GaussianOcclusionCulling>();
+        app.add_plugins(ExtractComponentPlugin::<GaussianOcclusionCulling>::default());
+
+        let occlusion = GaussianCloudOcclusion::default();
+        app.insert_resource(occlusion.clone());
+        app.add_systems(Update, resort_revealed_clouds);
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(occlusion)
+                .init_resource::<GaussianOcclusionReadbacks>()
+                .add_render_graph_node::<ViewNodeRunner<GaussianOcclusionNode>>(
+                    Core3d,
+                    GaussianOcclusionLabel,
+                )
+                .add_render_graph_edges(
+                    Core3d,
+                    (
+                        Node3d::EndPrepasses,
+                        GaussianOcclusionLabel,
+                        Node3d::StartMainPass,
+                    ),
+                )
+                .add_systems(
+                    Render,
+                    (
+                        update_cloud_occlusion
+                            .in_set(RenderSet::Queue)
+                            .before(super::queue_gaussians),
+                        prepare_occlusion_readbacks.in_set(RenderSet::PrepareBindGroups),
+                        map_occlusion_readbacks.in_set(RenderSet::Cleanup),
+                    ),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<GaussianOcclusionPipeline>();
+        }
+    }
+}
+
+
+// a cloud coming back into view has skipped its cpu sorts, so its entries are stale
+fn resort_revealed_clouds(
+    occlusion: Res<GaussianCloudOcclusion>,
+    mut resort: EventWriter<ResortGaussianClouds>,
+) {
+    if occlusion.revealed.swap(false, Ordering::Relaxed) {
+        resort.send(ResortGaussianClouds);
+    }
+}
+
+
+#[derive(Resource)]
+pub struct GaussianOcclusionPipeline {
+    layouts: [BindGroupLayout; 2],
+    pipelines: [CachedComputePipelineId; 2],
+}
+
+impl GaussianOcclusionPipeline {
+    fn index(multisampled: bool) -> usize {
+        multisampled as usize
+    }
+}
+
+impl FromWorld for GaussianOcclusionPipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+
+        let layouts = [false, true].map(|multisampled| render_device.create_bind_group_layout(
+            Some("gaussian_occlusion_layout"),
+            &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Depth,
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<UVec4>() as u64),
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        ));
+
+        let pipeline_cache = render_world.resource::<PipelineCache>();
+        let pipelines = [false, true].map(|multisampled| {
+            let shader_defs = if multisampled {
+                vec!["MULTISAMPLED".into()]
+            } else {
+                vec![]
+            };
+
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some("gaussian_occlusion_pipeline".into()),
+                layout: vec![layouts[Self::index(multisampled)].clone()],
+                push_constant_ranges: vec![],
+                shader: OCCLUSION_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "reduce_depth".into(),
+            })
+        });
+
+        Self {
+            layouts,
+            pipelines,
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug)]
+enum ReadbackState {
+    Idle,
+    // the tiles were copied into the staging buffer this frame, captured with this view
+    Copied(Mat4),
+    Mapping,
+    Mapped(Mat4),
+}
+
+struct OcclusionReadback {
+    resolution: UVec2,
+    multisampled: bool,
+    uniform_buffer: Buffer,
+    tile_buffer: Buffer,
+    staging_buffer: Buffer,
+    bind_group: Option<BindGroup>,
+    state: Arc<Mutex<ReadbackState>>,
+    tiles: Option<GaussianCloudDepthTiles>,
+}
+
+impl OcclusionReadback {
+    fn new(render_device: &RenderDevice, resolution: UVec2, multisampled: bool) -> Self {
+        let size = (resolution.x * resolution.y) as u64 * std::mem::size_of::<f32>() as u64;
+
+        Self {
+            resolution,
+            multisampled,
+            uniform_buffer: render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("gaussian_occlusion_uniform_buffer"),
+                contents: bytemuck::cast_slice(&[resolution.x, resolution.y, 0, 0]),
+                usage: BufferUsages::UNIFORM,
+            }),
+            tile_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("gaussian_occlusion_tile_buffer"),
+                size,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            staging_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("gaussian_occlusion_staging_buffer"),
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            bind_group: None,
+            state: Arc::new(Mutex::new(ReadbackState::Idle)),
+            tiles: None,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct GaussianOcclusionReadbacks {
+    views: HashMap<Entity, OcclusionReadback>,
+}
+
+
+fn prepare_occlusion_readbacks(
+    render_device: Res<RenderDevice>,
+    pipeline: Res<GaussianOcclusionPipeline>,
+    mut readbacks: ResMut<GaussianOcclusionReadbacks>,
+    views: Query<(
+        Entity,
+        &GaussianOcclusionCulling,
+        &ViewPrepassTextures,
+    )>,
+) {
+    readbacks.views.retain(|entity, _| views.contains(*entity));
+
+    for (
+        entity,
+        culling,
+        prepass_textures,
+    ) in &views {
+        let Some(depth) = prepass_textures.depth.as_ref() else {
+            readbacks.views.remove(&entity);
+            continue;
+        };
+
+        let resolution = culling.resolution.max(UVec2::ONE);
+        let multisampled = depth.texture.texture.sample_count() > 1;
+
+        let readback = readbacks.views.entry(entity)
+            .or_insert_with(|| OcclusionReadback::new(&render_device, resolution, multisampled));
+
+        if readback.resolution != resolution || readback.multisampled != multisampled {
+            *readback = OcclusionReadback::new(&render_device, resolution, multisampled);
+        }
+
+        let mapped = *readback.state.lock().unwrap();
+        if let ReadbackState::Mapped(clip_from_world) = mapped {
+            let slice = readback.staging_buffer.slice(..);
+            let tile_depth = bytemuck::cast_slice::<u8, f32>(&slice.get_mapped_range()).to_vec();
+            readback.staging_buffer.unmap();
+
+            readback.tiles = Some(GaussianCloudDepthTiles {
+                resolution,
+                depth: tile_depth,
+                clip_from_world,
+            });
+            *readback.state.lock().unwrap() = ReadbackState::Idle;
+        }
+
+        // the prepass texture is recreated on resize, so the bind group is rebuilt every frame
+        readback.bind_group = Some(render_device.create_bind_group(
+            "gaussian_occlusion_bind_group",
+            &pipeline.layouts[GaussianOcclusionPipeline::index(multisampled)],
+            &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&depth.texture.default_view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: readback.uniform_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: readback.tile_buffer.as_entire_binding(),
+                },
+            ],
+        ));
+    }
+}
+
+// runs after this frame's submission, so the copies recorded by the node are in flight
+fn map_occlusion_readbacks(
+    readbacks: Res<GaussianOcclusionReadbacks>,
+) {
+    for readback in readbacks.views.values() {
+        let mut state = readback.state.lock().unwrap();
+        let ReadbackState::Copied(clip_from_world) = *state else {
+            continue;
+        };
+
+        *state = ReadbackState::Mapping;
+
+        let callback_state = readback.state.clone();
+        readback.staging_buffer.slice(..).map_async(MapMode::Read, move |result| {
+            *callback_state.lock().unwrap() = match result {
+                Ok(()) => ReadbackState::Mapped(clip_from_world),
+                Err(_) => ReadbackState::Idle,
+            };
+        });
+    }
+}
+
+fn update_cloud_occlusion(
+    occlusion: Res<GaussianCloudOcclusion>,
+    readbacks: Res<GaussianOcclusionReadbacks>,
+    gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
+    clouds: Query<(
+        Entity,
+        &Handle<GaussianCloud>,
+        &GaussianCloudSettings,
+    )>,
+) {
+    for (view, readback) in readbacks.views.iter() {
+        let Some(tiles) = readback.tiles.as_ref() else {
+            continue;
+        };
+
+        for (
+            entity,
+            cloud_handle,
+            settings,
+        ) in &clouds {
+            let Some((_, aabb)) = gaussian_clouds.get(cloud_handle).and_then(|cloud| cloud.bounds.as_ref()) else {
+                continue;
+            };
+
+            occlusion.set(*view, entity, tiles.occludes(aabb, &settings.cloud_transform()));
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianOcclusionNode;
+
+impl ViewNode for GaussianOcclusionNode {
+    type ViewQuery = (
+        Entity,
+        &'static ExtractedView,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (entity, view): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let readbacks = world.resource::<GaussianOcclusionReadbacks>();
+        let Some(readback) = readbacks.views.get(&entity) else {
+            return Ok(());
+        };
+
+        let Some(bind_group) = readback.bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        let pipeline = world.resource::<GaussianOcclusionPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(reduce_depth) = pipeline_cache.get_compute_pipeline(
+            pipeline.pipelines[GaussianOcclusionPipeline::index(readback.multisampled)],
+        ) else {
+            return Ok(());
+        };
+
+        // the previous readback is still in flight
+        let mut state = readback.state.lock().unwrap();
+        if !matches!(*state, ReadbackState::Idle) {
+            return Ok(());
+        }
+
+        let command_encoder = render_context.command_encoder();
+
+        {
+            let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("gaussian_occlusion_pass"),
+                timestamp_writes: None,
+            });
+
+            pass.set_pipeline(reduce_depth);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(
+                readback.resolution.x.div_ceil(8),
+                readback.resolution.y.div_ceil(8),
+                1,
+            );
+        }
+
+        command_encoder.copy_buffer_to_buffer(
+            &readback.tile_buffer,
+            0,
+            &readback.staging_buffer,
+            0,
+            readback.staging_buffer.size(),
+        );
+
+        let clip_from_world = view.clip_from_world
+            .unwrap_or_else(|| view.clip_from_view * view.world_from_view.compute_matrix().inverse());
+        *state = ReadbackState::Copied(clip_from_world);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var scene_depth: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(0) var scene_depth: texture_depth_2d;
+#endif
+@group(0) @binding(1) var<uniform> tile_count: vec4<u32>;
+@group(0) @binding(2) var<storage, read_write> tiles: array<f32>;
+
+
+// each tile keeps the farthest depth it covers, reverse z so that is the smallest value. a cloud whose nearest
+// point is farther than every tile under it is hidden behind prepass geometry
+@compute @workgroup_size(8, 8, 1)
+fn reduce_depth(@builtin(global_invocation_id) id: vec3<u32>) {
+    let count = tile_count.xy;
+    if (any(id.xy >= count)) {
+        return;
+    }
+
+    let size = textureDimensions(scene_depth);
+    // tiles smaller than a texel still cover one, otherwise an empty tile would read as nearest
+    let start = min(id.xy * size / count, size - 1u);
+    let end = max(min((id.xy + 1u) * size / count, size), start + 1u);
+
+    var farthest = 1.0;
+    for (var y = start.y; y < end.y; y += 1u) {
+        for (var x = start.x; x < end.x; x += 1u) {
+            let texel = vec2<i32>(vec2<u32>(x, y));
+#ifdef MULTISAMPLED
+            for (var sample = 0u; sample < textureNumSamples(scene_depth); sample += 1u) {
+                farthest = min(farthest, textureLoad(scene_depth, texel, i32(sample)));
+            }
+#else
+            farthest = min(farthest, textureLoad(scene_depth, texel, 0));
+#endif
+        }
+    }
+
+    tiles[id.y * count.x + id.x] = farthest;
+}
@@ -0,0 +1,32 @@
+use bevy::{
+    prelude::*,
+    render::extract_component::ExtractComponent,
+};
+
+
+// flat colored silhouette around a whole cloud, e.g. to highlight the active cloud in an editor. splats are dilated
+// by `thickness` pixels and drawn in `color` behind the blended pass, so the color only shows where the cloud is not
+// already covering the pixel. the outline is depth tested but never writes depth: geometry in front of the cloud
+// hides it, and with an opaque pass or depth prepass the cloud's own core occludes it as well
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    ExtractComponent,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianOutline {
+    pub color: Color,
+    // dilation in physical pixels
+    pub thickness: f32,
+}
+
+impl Default for GaussianOutline {
+    fn default() -> Self {
+        Self {
+            color: Color::srgb(1.0, 0.6, 0.1),
+            thickness: 3.0,
+        }
+    }
+}
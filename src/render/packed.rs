@@ -9,12 +9,14 @@ use bevy::render::{
         Buffer,
         BufferBinding,
         BufferBindingType,
-        BufferInitDescriptor,
         BufferSize,
         BufferUsages,
         ShaderStages,
     },
-    renderer::RenderDevice,
+    renderer::{
+        RenderDevice,
+        RenderQueue,
+    },
 };
 
 use crate::{
@@ -23,8 +25,11 @@ use crate::{
         packed::Gaussian,
     },
     render::{
+        BufferReadback,
+        GaussianCloudAttribute,
         GaussianCloudPipeline,
         GpuGaussianCloud,
+        create_cloud_buffer,
     },
 };
 
@@ -34,16 +39,27 @@ pub struct PackedBuffers {
     gaussians: Buffer,
 }
 
+impl PackedBuffers {
+    pub(crate) fn attribute(&self, attribute: GaussianCloudAttribute) -> Option<&Buffer> {
+        match attribute {
+            GaussianCloudAttribute::Packed => Some(&self.gaussians),
+            _ => None,
+        }
+    }
+}
+
 
 pub fn prepare_cloud(
     render_device: &RenderDevice,
     cloud: &GaussianCloud,
+    usage: BufferUsages,
 ) -> PackedBuffers {
-    let gaussians = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("packed_gaussian_cloud_buffer"),
-        contents: bytemuck::cast_slice(cloud.gaussian_iter().collect::<Vec<Gaussian>>().as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
+    let gaussians = create_cloud_buffer(
+        render_device,
+        "packed_gaussian_cloud_buffer",
+        bytemuck::cast_slice(cloud.gaussian_iter().collect::<Vec<Gaussian>>().as_slice()),
+        usage,
+    );
 
     PackedBuffers {
         gaussians,
@@ -51,6 +67,34 @@ pub fn prepare_cloud(
 }
 
 
+pub fn write_range(
+    render_queue: &RenderQueue,
+    buffers: &PackedBuffers,
+    cloud: &GaussianCloud,
+    range: std::ops::Range<usize>,
+) {
+    let gaussians = cloud.gaussian_iter()
+        .skip(range.start)
+        .take(range.len())
+        .collect::<Vec<Gaussian>>();
+
+    render_queue.write_buffer(
+        &buffers.gaussians,
+        (range.start * std::mem::size_of::<Gaussian>()) as u64,
+        bytemuck::cast_slice(gaussians.as_slice()),
+    );
+}
+
+
+pub fn readback(
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    buffers: &PackedBuffers,
+) -> BufferReadback {
+    BufferReadback::new(render_device, render_queue, &buffers.gaussians)
+}
+
+
 pub fn get_bind_group_layout(
     render_device: &RenderDevice,
     read_only: bool
@@ -1,7 +1,10 @@
 #[allow(unused_imports)]
 use bevy::render::{
     render_resource::*,
-    renderer::RenderDevice,
+    renderer::{
+        RenderDevice,
+        RenderQueue,
+    },
 };
 
 #[allow(unused_imports)]
@@ -15,21 +18,82 @@ use crate::{
         },
     },
     render::{
+        BufferReadback,
+        GaussianCloudAttribute,
         GaussianCloudPipeline,
         GpuGaussianCloud,
+        create_cloud_buffer,
+    },
+    material::spherical_harmonics::{
+        SH_DEGREE,
+        SphericalHarmonicCoefficients,
+        sh_word_count,
     },
-    material::spherical_harmonics::SphericalHarmonicCoefficients,
 };
 
 #[cfg(feature = "f16")]
 use crate::gaussian::f16::RotationScaleOpacityPacked128;
 
 
+// truncates each gaussian's coefficients to the words of `sh_degree`, bands are stored lowest first
+fn compact_spherical_harmonics(
+    cloud: &GaussianCloud,
+    range: std::ops::Range<usize>,
+    sh_degree: usize,
+) -> Vec<u32> {
+    let words = sh_word_count(sh_degree);
+
+    cloud.spherical_harmonic[range].iter()
+        .flat_map(|sh| bytemuck::cast_slice::<_, u32>(std::slice::from_ref(sh))[..words].to_vec())
+        .collect()
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn expand_spherical_harmonics(
+    words: &[u32],
+    sh_degree: usize,
+    count: usize,
+) -> Vec<SphericalHarmonicCoefficients> {
+    words.chunks_exact(sh_word_count(sh_degree))
+        .take(count)
+        .map(|chunk| {
+            let mut sh = SphericalHarmonicCoefficients::default();
+            bytemuck::cast_slice_mut::<_, u32>(std::slice::from_mut(&mut sh))[..chunk.len()].copy_from_slice(chunk);
+            sh
+        })
+        .collect()
+}
+
+fn create_spherical_harmonics_buffer(
+    render_device: &RenderDevice,
+    cloud: &GaussianCloud,
+    usage: BufferUsages,
+) -> (Buffer, usize) {
+    let sh_degree = cloud.sh_degree();
+    let mut words = compact_spherical_harmonics(cloud, 0..cloud.len(), sh_degree);
+
+    // compute pipelines sharing the layout (sort, morph) are compiled for the full degree, which needs at least
+    // one full stride bound
+    words.resize(words.len().max(sh_word_count(SH_DEGREE)), 0);
+
+    let buffer = create_cloud_buffer(
+        render_device,
+        "planar_spherical_harmonics_buffer",
+        bytemuck::cast_slice(words.as_slice()),
+        usage,
+    );
+
+    (buffer, sh_degree)
+}
+
+
 #[cfg(feature = "f16")]
 #[derive(Debug, Clone)]
 pub struct PlanarBuffers {
     position_visibility: Buffer,
     spherical_harmonics: Buffer,
+    // bands above this degree are not uploaded, see GaussianCloud::sh_degree
+    pub sh_degree: usize,
 
     #[cfg(feature = "precompute_covariance_3d")]
     covariance_3d_opacity: Buffer,
@@ -44,6 +108,8 @@ pub struct PlanarBuffers {
 pub struct PlanarBuffers {
     position_visibility: Buffer,
     spherical_harmonics: Buffer,
+    // bands above this degree are not uploaded, see GaussianCloud::sh_degree
+    pub sh_degree: usize,
 
     #[cfg(feature = "precompute_covariance_3d")]
     covariance_3d_opacity: Buffer,
@@ -55,40 +121,69 @@ pub struct PlanarBuffers {
 }
 
 
+impl PlanarBuffers {
+    pub(crate) fn attribute(&self, attribute: GaussianCloudAttribute) -> Option<&Buffer> {
+        match attribute {
+            GaussianCloudAttribute::PositionVisibility => Some(&self.position_visibility),
+            GaussianCloudAttribute::SphericalHarmonics => Some(&self.spherical_harmonics),
+            #[cfg(feature = "precompute_covariance_3d")]
+            GaussianCloudAttribute::Covariance3dOpacity => Some(&self.covariance_3d_opacity),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f16",
+            ))]
+            GaussianCloudAttribute::RotationScaleOpacity => Some(&self.rotation_scale_opacity),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f32",
+            ))]
+            GaussianCloudAttribute::Rotation => Some(&self.rotation),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f32",
+            ))]
+            GaussianCloudAttribute::ScaleOpacity => Some(&self.scale_opacity),
+            _ => None,
+        }
+    }
+}
+
+
 #[cfg(feature = "f16")]
 pub fn prepare_cloud(
     render_device: &RenderDevice,
     cloud: &GaussianCloud,
+    usage: BufferUsages,
 ) -> PlanarBuffers {
-    let position_visibility = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_position_visibility_buffer"),
-        contents: bytemuck::cast_slice(cloud.position_visibility.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
+    let position_visibility = create_cloud_buffer(
+        render_device,
+        "planar_position_visibility_buffer",
+        bytemuck::cast_slice(cloud.position_visibility.as_slice()),
+        usage,
+    );
 
     #[cfg(feature = "precompute_covariance_3d")]
-    let covariance_3d_opacity = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_covariance_3d_opacity"),
-        contents: bytemuck::cast_slice(cloud.covariance_3d_opacity_packed128.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
+    let covariance_3d_opacity = create_cloud_buffer(
+        render_device,
+        "planar_covariance_3d_opacity",
+        bytemuck::cast_slice(cloud.covariance_3d_opacity_packed128.as_slice()),
+        usage,
+    );
 
     #[cfg(not(feature = "precompute_covariance_3d"))]
-    let rotation_scale_opacity = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_rotation_scale_opacity_buffer"),
-        contents: bytemuck::cast_slice(cloud.rotation_scale_opacity_packed128.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
-
-    let spherical_harmonics = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_spherical_harmonics_buffer"),
-        contents: bytemuck::cast_slice(cloud.spherical_harmonic.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
+    let rotation_scale_opacity = create_cloud_buffer(
+        render_device,
+        "planar_rotation_scale_opacity_buffer",
+        bytemuck::cast_slice(cloud.rotation_scale_opacity_packed128.as_slice()),
+        usage,
+    );
+
+    let (spherical_harmonics, sh_degree) = create_spherical_harmonics_buffer(render_device, cloud, usage);
 
     PlanarBuffers {
         position_visibility,
         spherical_harmonics,
+        sh_degree,
 
         #[cfg(feature = "precompute_covariance_3d")]
         covariance_3d_opacity,
@@ -102,34 +197,35 @@ pub fn prepare_cloud(
 pub fn prepare_cloud(
     render_device: &RenderDevice,
     cloud: &GaussianCloud,
+    usage: BufferUsages,
 ) -> PlanarBuffers {
-    let position_visibility = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_f32_position_visibility_buffer"),
-        contents: bytemuck::cast_slice(cloud.position_visibility.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
-
-    let rotation = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_f32_rotation_buffer"),
-        contents: bytemuck::cast_slice(cloud.rotation.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
-
-    let scale_opacity = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_f32_scale_opacity_buffer"),
-        contents: bytemuck::cast_slice(cloud.scale_opacity.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
-
-    let spherical_harmonics = render_device.create_buffer_with_data(&BufferInitDescriptor {
-        label: Some("planar_f32_spherical_harmonics_buffer"),
-        contents: bytemuck::cast_slice(cloud.spherical_harmonic.as_slice()),
-        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST | BufferUsages::STORAGE,
-    });
+    let position_visibility = create_cloud_buffer(
+        render_device,
+        "planar_f32_position_visibility_buffer",
+        bytemuck::cast_slice(cloud.position_visibility.as_slice()),
+        usage,
+    );
+
+    let rotation = create_cloud_buffer(
+        render_device,
+        "planar_f32_rotation_buffer",
+        bytemuck::cast_slice(cloud.rotation.as_slice()),
+        usage,
+    );
+
+    let scale_opacity = create_cloud_buffer(
+        render_device,
+        "planar_f32_scale_opacity_buffer",
+        bytemuck::cast_slice(cloud.scale_opacity.as_slice()),
+        usage,
+    );
+
+    let (spherical_harmonics, sh_degree) = create_spherical_harmonics_buffer(render_device, cloud, usage);
 
     PlanarBuffers {
         position_visibility,
         spherical_harmonics,
+        sh_degree,
 
         #[cfg(feature = "precompute_covariance_3d")]
         covariance_3d_opacity,
@@ -141,6 +237,157 @@ pub fn prepare_cloud(
 }
 
 
+#[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+pub fn write_range(
+    render_queue: &RenderQueue,
+    buffers: &PlanarBuffers,
+    cloud: &GaussianCloud,
+    range: std::ops::Range<usize>,
+) {
+    render_queue.write_buffer(
+        &buffers.position_visibility,
+        (range.start * std::mem::size_of::<PositionVisibility>()) as u64,
+        bytemuck::cast_slice(&cloud.position_visibility[range.clone()]),
+    );
+
+    render_queue.write_buffer(
+        &buffers.spherical_harmonics,
+        (range.start * sh_word_count(buffers.sh_degree) * std::mem::size_of::<u32>()) as u64,
+        bytemuck::cast_slice(&compact_spherical_harmonics(cloud, range.clone(), buffers.sh_degree)),
+    );
+
+    render_queue.write_buffer(
+        &buffers.rotation_scale_opacity,
+        (range.start * std::mem::size_of::<RotationScaleOpacityPacked128>()) as u64,
+        bytemuck::cast_slice(&cloud.rotation_scale_opacity_packed128[range]),
+    );
+}
+
+
+#[cfg(all(
+    feature = "f32",
+    not(feature = "precompute_covariance_3d"),
+))]
+pub fn write_range(
+    render_queue: &RenderQueue,
+    buffers: &PlanarBuffers,
+    cloud: &GaussianCloud,
+    range: std::ops::Range<usize>,
+) {
+    render_queue.write_buffer(
+        &buffers.position_visibility,
+        (range.start * std::mem::size_of::<PositionVisibility>()) as u64,
+        bytemuck::cast_slice(&cloud.position_visibility[range.clone()]),
+    );
+
+    render_queue.write_buffer(
+        &buffers.spherical_harmonics,
+        (range.start * sh_word_count(buffers.sh_degree) * std::mem::size_of::<u32>()) as u64,
+        bytemuck::cast_slice(&compact_spherical_harmonics(cloud, range.clone(), buffers.sh_degree)),
+    );
+
+    render_queue.write_buffer(
+        &buffers.rotation,
+        (range.start * std::mem::size_of::<Rotation>()) as u64,
+        bytemuck::cast_slice(&cloud.rotation[range.clone()]),
+    );
+
+    render_queue.write_buffer(
+        &buffers.scale_opacity,
+        (range.start * std::mem::size_of::<ScaleOpacity>()) as u64,
+        bytemuck::cast_slice(&cloud.scale_opacity[range]),
+    );
+}
+
+
+// the planar buffers of a cloud in flight to the cpu, see GaussianCloudReadback
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub struct PlanarReadback {
+    sh_degree: usize,
+    position_visibility: BufferReadback,
+    spherical_harmonics: BufferReadback,
+
+    #[cfg(feature = "f16")]
+    rotation_scale_opacity: BufferReadback,
+
+    #[cfg(feature = "f32")]
+    rotation: BufferReadback,
+    #[cfg(feature = "f32")]
+    scale_opacity: BufferReadback,
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+impl PlanarReadback {
+    pub fn new(
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+        buffers: &PlanarBuffers,
+    ) -> Self {
+        Self {
+            sh_degree: buffers.sh_degree,
+            position_visibility: BufferReadback::new(render_device, render_queue, &buffers.position_visibility),
+            spherical_harmonics: BufferReadback::new(render_device, render_queue, &buffers.spherical_harmonics),
+
+            #[cfg(feature = "f16")]
+            rotation_scale_opacity: BufferReadback::new(render_device, render_queue, &buffers.rotation_scale_opacity),
+
+            #[cfg(feature = "f32")]
+            rotation: BufferReadback::new(render_device, render_queue, &buffers.rotation),
+            #[cfg(feature = "f32")]
+            scale_opacity: BufferReadback::new(render_device, render_queue, &buffers.scale_opacity),
+        }
+    }
+
+    // checks every buffer, so each map is noticed as soon as it completes
+    pub fn is_mapped(&mut self) -> Result<bool, wgpu::BufferAsyncError> {
+        let mut mapped = self.position_visibility.is_mapped()?;
+        mapped &= self.spherical_harmonics.is_mapped()?;
+
+        #[cfg(feature = "f16")]
+        {
+            mapped &= self.rotation_scale_opacity.is_mapped()?;
+        }
+
+        #[cfg(feature = "f32")]
+        {
+            mapped &= self.rotation.is_mapped()?;
+            mapped &= self.scale_opacity.is_mapped()?;
+        }
+
+        Ok(mapped)
+    }
+
+    // none unless is_mapped returned true
+    pub fn read(&self) -> Option<GaussianCloud> {
+        let position_visibility: Vec<PositionVisibility> = self.position_visibility.read()?;
+        let count = position_visibility.len();
+
+        Some(GaussianCloud {
+            position_visibility,
+            spherical_harmonic: expand_spherical_harmonics(
+                &self.spherical_harmonics.read::<u32>()?,
+                self.sh_degree,
+                count,
+            ),
+
+            #[cfg(feature = "f16")]
+            rotation_scale_opacity_packed128: self.rotation_scale_opacity.read()?,
+
+            #[cfg(feature = "f32")]
+            rotation: self.rotation.read()?,
+            #[cfg(feature = "f32")]
+            scale_opacity: self.scale_opacity.read()?,
+
+            channel: Vec::new(),
+            importance_luminance_weight: 0.0,
+            reveal_rank: Vec::new(),
+            spatial_index: Default::default(),
+            importance: Default::default(),
+        })
+    }
+}
+
+
 #[cfg(feature = "f16")]
 pub fn get_bind_group_layout(
     render_device: &RenderDevice,
@@ -165,7 +412,7 @@ pub fn get_bind_group_layout(
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Storage { read_only: true },
                     has_dynamic_offset: false,
-                    min_binding_size: BufferSize::new(std::mem::size_of::<SphericalHarmonicCoefficients>() as u64),
+                    min_binding_size: BufferSize::new((sh_word_count(0) * std::mem::size_of::<u32>()) as u64),
                 },
                 count: None,
             },
@@ -208,7 +455,7 @@ pub fn get_bind_group_layout(
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Storage { read_only },
                     has_dynamic_offset: false,
-                    min_binding_size: BufferSize::new(std::mem::size_of::<SphericalHarmonicCoefficients>() as u64),
+                    min_binding_size: BufferSize::new((sh_word_count(0) * std::mem::size_of::<u32>()) as u64),
                 },
                 count: None,
             },
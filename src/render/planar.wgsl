@@ -17,6 +17,7 @@
 #endif
 
 #import bevy_gaussian_splatting::spherical_harmonics::{
+    flat_color,
     spherical_harmonics_lookup,
     srgb_to_linear,
 }
@@ -24,6 +25,17 @@
 
 #ifdef PLANAR_F16
 
+#ifdef FLAT_COLOR
+// only the dc words are read, the full coefficient array is never unpacked
+fn get_color(
+    index: u32,
+    ray_direction: vec3<f32>,
+) -> vec3<f32> {
+    let rg = unpack2x16float(spherical_harmonics[index][0]);
+    let b = unpack2x16float(spherical_harmonics[index][1]);
+    return srgb_to_linear(flat_color(vec3<f32>(rg, b.x)));
+}
+#else
 fn get_color(
     index: u32,
     ray_direction: vec3<f32>,
@@ -32,6 +44,7 @@ fn get_color(
     let color = spherical_harmonics_lookup(ray_direction, sh);
     return srgb_to_linear(color);
 }
+#endif
 
 fn get_position(index: u32) -> vec3<f32> {
     return position_visibility[index].xyz;
@@ -104,6 +117,18 @@ fn get_visibility(index: u32) -> f32 {
 
 
 #ifdef PLANAR_F32
+#ifdef FLAT_COLOR
+fn get_color(
+    index: u32,
+    ray_direction: vec3<f32>,
+) -> vec3<f32> {
+    return srgb_to_linear(flat_color(vec3<f32>(
+        spherical_harmonics[index][0],
+        spherical_harmonics[index][1],
+        spherical_harmonics[index][2],
+    )));
+}
+#else
 fn get_color(
     index: u32,
     ray_direction: vec3<f32>,
@@ -112,6 +137,7 @@ fn get_color(
     let color = spherical_harmonics_lookup(ray_direction, sh);
     return srgb_to_linear(color);
 }
+#endif
 
 fn get_position(index: u32) -> vec3<f32> {
     return position_visibility[index].xyz;
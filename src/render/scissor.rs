@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    prelude::*,
+    ecs::{
+        query::ROQueryItem,
+        system::{
+            lifetimeless::*,
+            SystemParamItem,
+        },
+    },
+    render::{
+        camera::ExtractedCamera,
+        extract_component::ExtractComponent,
+        render_phase::{
+            PhaseItem,
+            RenderCommand,
+            RenderCommandResult,
+            TrackedRenderPass,
+        },
+    },
+};
+
+
+// restricts splat rendering of this camera to `rect`, in physical pixels of the render target, e.g. for picture in
+// picture panels or partial updates. the rect is clipped to the camera's viewport, and without this component splats
+// are still scissored to the viewport. other draws in the pass are not affected
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    ExtractComponent,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianScissor {
+    pub rect: URect,
+}
+
+
+fn viewport_rect(camera: &ExtractedCamera) -> Option<URect> {
+    match &camera.viewport {
+        Some(viewport) => Some(URect::from_corners(
+            viewport.physical_position,
+            viewport.physical_position + viewport.physical_size,
+        )),
+        None => camera.physical_target_size.map(|size| URect::from_corners(UVec2::ZERO, size)),
+    }
+}
+
+
+// wraps the draw command so the scissor is restored to the whole target for the draws that follow, whatever the draw
+// returns. a separate reset command after a failed draw would never run and leave the rect set for the rest of the pass
+pub struct ScissoredGaussianDraw<C>(PhantomData<C>);
+impl<P: PhaseItem, C: RenderCommand<P>> RenderCommand<P> for ScissoredGaussianDraw<C> {
+    type Param = C::Param;
+    type ViewQuery = (
+        Option<Read<ExtractedCamera>>,
+        Option<Read<GaussianScissor>>,
+        C::ViewQuery,
+    );
+    type ItemQuery = C::ItemQuery;
+
+    #[inline]
+    fn render<'w>(
+        item: &P,
+        (camera, scissor, view): ROQueryItem<'w, Self::ViewQuery>,
+        entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
+        param: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(viewport) = camera.and_then(viewport_rect) else {
+            return C::render(item, view, entity, param, pass);
+        };
+
+        let rect = match scissor {
+            Some(scissor) => scissor.rect.intersect(viewport),
+            None => viewport,
+        };
+
+        // nothing of the cloud is inside the scissor, which is expected rather than a failed draw. the draw is skipped
+        // before any state changes
+        if rect.is_empty() {
+            return RenderCommandResult::Success;
+        }
+
+        pass.set_scissor_rect(rect.min.x, rect.min.y, rect.width(), rect.height());
+
+        let result = C::render(item, view, entity, param, pass);
+
+        if let Some(size) = camera.and_then(|camera| camera.physical_target_size) {
+            pass.set_scissor_rect(0, 0, size.x, size.y);
+        }
+
+        result
+    }
+}
@@ -1,14 +1,36 @@
 use bevy::{
-    asset::LoadState, ecs::query::QueryItem, prelude::*, render::{
-        extract_component::{
-            ExtractComponent,
-            ExtractComponentPlugin,
-        }, render_asset::{
-            RenderAssetUsages, RenderAssets
-        }, render_resource::{
-            BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource, BindingType, Extent3d, ShaderStages, TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureViewDimension
-        }, renderer::RenderDevice, Render, RenderApp, RenderSet
-    }
+    prelude::*,
+    render::{
+        render_asset::{
+            prepare_assets,
+            RenderAssets,
+        },
+        render_resource::{
+            BindGroup,
+            BindGroupEntry,
+            BindGroupLayout,
+            BindGroupLayoutEntry,
+            BindingResource,
+            BindingType,
+            Extent3d,
+            ShaderStages,
+            Texture,
+            TextureDescriptor,
+            TextureDimension,
+            TextureFormat,
+            TextureSampleType,
+            TextureUsages,
+            TextureViewDescriptor,
+            TextureViewDimension,
+        },
+        renderer::{
+            RenderDevice,
+            RenderQueue,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
 };
 use static_assertions::assert_cfg;
 
@@ -28,6 +50,7 @@ use crate::{
         SphericalHarmonicCoefficients,
     },
     render::{
+        GaussianCloudAttribute,
         GaussianCloudPipeline,
         GpuGaussianCloud,
     },
@@ -48,40 +71,57 @@ assert_cfg!(
 );
 
 
+// square textures of len_sqrt_ceil texels a side, created with the cloud's render asset so they are replaced with it
 #[cfg(feature = "f16")]
-#[derive(Component, Clone, Debug, Reflect)]
+#[derive(Clone, Debug)]
 pub struct TextureBuffers {
-    position_visibility: Handle<Image>,
-    spherical_harmonics: Handle<Image>,
+    position_visibility: Texture,
+    spherical_harmonics: Texture,
 
     #[cfg(feature = "precompute_covariance_3d")]
-    covariance_3d_opacity: Handle<Image>,
+    covariance_3d_opacity: Texture,
     #[cfg(not(feature = "precompute_covariance_3d"))]
-    rotation_scale_opacity: Handle<Image>,
+    rotation_scale_opacity: Texture,
 }
 
 #[cfg(feature = "f32")]
-#[derive(Component, Clone, Debug, Reflect)]
+#[derive(Clone, Debug)]
 pub struct TextureBuffers {
-    position_visibility: Handle<Image>,
-    spherical_harmonics: Handle<Image>,
+    position_visibility: Texture,
+    spherical_harmonics: Texture,
 
     #[cfg(feature = "precompute_covariance_3d")]
-    covariance_3d_opacity: Handle<Image>,
+    covariance_3d_opacity: Texture,
     #[cfg(not(feature = "precompute_covariance_3d"))]
-    rotation: Handle<Image>,
+    rotation: Texture,
     #[cfg(not(feature = "precompute_covariance_3d"))]
-    scale_opacity: Handle<Image>,
+    scale_opacity: Texture,
 }
 
-impl ExtractComponent for TextureBuffers {
-    type QueryData = &'static Self;
-
-    type QueryFilter = ();
-    type Out = Self;
-
-    fn extract_component(texture_buffers: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
-        texture_buffers.clone().into()
+impl TextureBuffers {
+    pub(crate) fn attribute(&self, attribute: GaussianCloudAttribute) -> Option<&Texture> {
+        match attribute {
+            GaussianCloudAttribute::PositionVisibility => Some(&self.position_visibility),
+            GaussianCloudAttribute::SphericalHarmonics => Some(&self.spherical_harmonics),
+            #[cfg(feature = "precompute_covariance_3d")]
+            GaussianCloudAttribute::Covariance3dOpacity => Some(&self.covariance_3d_opacity),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f16",
+            ))]
+            GaussianCloudAttribute::RotationScaleOpacity => Some(&self.rotation_scale_opacity),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f32",
+            ))]
+            GaussianCloudAttribute::Rotation => Some(&self.rotation),
+            #[cfg(all(
+                not(feature = "precompute_covariance_3d"),
+                feature = "f32",
+            ))]
+            GaussianCloudAttribute::ScaleOpacity => Some(&self.scale_opacity),
+            _ => None,
+        }
     }
 }
 
@@ -91,15 +131,12 @@ pub struct BufferTexturePlugin;
 
 impl Plugin for BufferTexturePlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<TextureBuffers>();
-        app.add_plugins(ExtractComponentPlugin::<TextureBuffers>::default());
-
-        app.add_systems(Update, queue_textures);
-
         let render_app = app.sub_app_mut(RenderApp);
         render_app.add_systems(
             Render,
-            queue_gpu_texture_buffers.in_set(RenderSet::PrepareAssets),
+            queue_gpu_texture_buffers
+                .in_set(RenderSet::PrepareAssets)
+                .after(prepare_assets::<GpuGaussianCloud>),
         );
     }
 }
@@ -112,215 +149,206 @@ pub struct GpuTextureBuffers {
 
 pub fn queue_gpu_texture_buffers(
     mut commands: Commands,
-    // gaussian_cloud_pipeline: Res<GaussianCloudPipeline>,
-    pipeline: ResMut<GaussianCloudPipeline>,
-    render_device: ResMut<RenderDevice>,
-    gpu_images: Res<RenderAssets<GpuImage>>,
+    pipeline: Res<GaussianCloudPipeline>,
+    render_device: Res<RenderDevice>,
+    gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     clouds: Query<(
         Entity,
-        &TextureBuffers,
+        &Handle<GaussianCloud>,
     )>,
 ) {
-    // TODO: verify gpu_images are loaded
+    for (entity, cloud_handle) in clouds.iter() {
+        let Some(cloud) = gaussian_cloud_res.get(cloud_handle) else {
+            continue;
+        };
+        let textures = &cloud.textures;
+
+        // the default views are 2d arrays for textures with several layers, matching the layout
+        let position_visibility = textures.position_visibility.create_view(&TextureViewDescriptor::default());
+        let spherical_harmonics = textures.spherical_harmonics.create_view(&TextureViewDescriptor::default());
 
-    for (entity, texture_buffers,) in clouds.iter() {
         #[cfg(feature = "f16")]
-        let bind_group = render_device.create_bind_group(
-            Some("texture_gaussian_cloud_bind_group"),
-            &pipeline.gaussian_cloud_layout,
-            &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.position_visibility).unwrap().texture_view
-                    ),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.spherical_harmonics).unwrap().texture_view
-                    ),
-                },
-                #[cfg(feature = "precompute_covariance_3d")]
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.covariance_3d_opacity).unwrap().texture_view
-                    ),
-                },
-                #[cfg(not(feature = "precompute_covariance_3d"))]
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.rotation_scale_opacity).unwrap().texture_view
-                    ),
-                },
-            ],
-        );
+        let bind_group = {
+            #[cfg(feature = "precompute_covariance_3d")]
+            let transform = textures.covariance_3d_opacity.create_view(&TextureViewDescriptor::default());
+            #[cfg(not(feature = "precompute_covariance_3d"))]
+            let transform = textures.rotation_scale_opacity.create_view(&TextureViewDescriptor::default());
+
+            render_device.create_bind_group(
+                Some("texture_gaussian_cloud_bind_group"),
+                &pipeline.gaussian_cloud_layout,
+                &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&position_visibility),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&spherical_harmonics),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::TextureView(&transform),
+                    },
+                ],
+            )
+        };
 
         #[cfg(feature = "f32")]
-        let bind_group = render_device.create_bind_group(
-            Some("texture_gaussian_cloud_bind_group"),
-            &pipeline.gaussian_cloud_layout,
-            &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.position_visibility).unwrap().texture_view
-                    ),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.spherical_harmonics).unwrap().texture_view
-                    ),
-                },
-                BindGroupEntry {
-                    binding: 2,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.rotation).unwrap().texture_view
-                    ),
-                },
-                BindGroupEntry {
-                    binding: 3,
-                    resource: BindingResource::TextureView(
-                        &gpu_images.get(&texture_buffers.scale_opacity).unwrap().texture_view
-                    ),
-                },
-            ],
-        );
+        let bind_group = {
+            let rotation = textures.rotation.create_view(&TextureViewDescriptor::default());
+            let scale_opacity = textures.scale_opacity.create_view(&TextureViewDescriptor::default());
+
+            render_device.create_bind_group(
+                Some("texture_gaussian_cloud_bind_group"),
+                &pipeline.gaussian_cloud_layout,
+                &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&position_visibility),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&spherical_harmonics),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::TextureView(&rotation),
+                    },
+                    BindGroupEntry {
+                        binding: 3,
+                        resource: BindingResource::TextureView(&scale_opacity),
+                    },
+                ],
+            )
+        };
 
         commands.entity(entity).insert(GpuTextureBuffers { bind_group });
     }
 }
 
 
-// TODO: support asset change detection and reupload
-fn queue_textures(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    gaussian_cloud_res: Res<Assets<GaussianCloud>>,
-    mut images: ResMut<Assets<Image>>,
-    clouds: Query<
-        (
-            Entity,
-            &Handle<GaussianCloud>,
-        ),
-        Without<TextureBuffers>,
-    >,
-) {
-    for (entity, cloud_handle) in clouds.iter() {
-        if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle){
-            continue;
-        }
-
-        if gaussian_cloud_res.get(cloud_handle).is_none() {
-            continue;
-        }
-
-        let cloud = gaussian_cloud_res.get(cloud_handle).unwrap();
+// one rgba32 texel per gaussian and layer, the texels past the cloud's count are zero
+fn create_texture(
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    label: &str,
+    size: Extent3d,
+    format: TextureFormat,
+    data: &[u8],
+) -> Texture {
+    let layer_len = (size.width * size.height) as usize * 16;
+    let layers = size.depth_or_array_layers as usize;
+
+    let mut contents = vec![0; layer_len * layers];
+    for (layer, chunk) in contents.chunks_exact_mut(layer_len).zip(data.chunks((data.len() / layers).max(1))) {
+        layer[..chunk.len()].copy_from_slice(chunk);
+    }
 
-        let square = cloud.len_sqrt_ceil() as u32;
-        let extent_1d = Extent3d {
-            width: square,
-            height: square, // TODO: shrink height to save memory (consider fixed width)
-            depth_or_array_layers: 1,
-        };
+    render_device.create_texture_with_data(
+        render_queue,
+        &TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &contents,
+    )
+}
 
-        let mut position_visibility = Image::new(
-            extent_1d,
-            TextureDimension::D2,
-            bytemuck::cast_slice(cloud.position_visibility.as_slice()).to_vec(),
-            TextureFormat::Rgba32Float,
-            RenderAssetUsages::default(),  // TODO: if there are no CPU image derived features, set to render only
-        );
-        position_visibility.texture_descriptor.usage = TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING;
-        let position_visibility = images.add(position_visibility);
 
-        let texture_buffers: TextureBuffers;
+#[cfg(feature = "f16")]
+pub fn prepare_cloud(
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    cloud: &GaussianCloud,
+) -> TextureBuffers {
+    let square = (cloud.len_sqrt_ceil() as u32).max(1);
+    let extent_1d = Extent3d {
+        width: square,
+        height: square, // TODO: shrink height to save memory (consider fixed width)
+        depth_or_array_layers: 1,
+    };
 
-        #[cfg(feature = "f16")]
-        {
-            let planar_spherical_harmonics: Vec<u32> = (0..SH_VEC4_PLANES)
-                .flat_map(|plane_index| {
-                    cloud.spherical_harmonic.iter()
-                        .flat_map(move |sh| {
-                            let start_index = plane_index * 4;
-                            let end_index = std::cmp::min(start_index + 4, sh.coefficients.len());
-
-                            let mut depthwise = sh.coefficients[start_index..end_index].to_vec();
-                            depthwise.resize(4, 0);
-
-                            depthwise
-                        })
+    let position_visibility = create_texture(
+        render_device,
+        render_queue,
+        "position_visibility_texture",
+        extent_1d,
+        TextureFormat::Rgba32Float,
+        bytemuck::cast_slice(cloud.position_visibility.as_slice()),
+    );
+
+    let planar_spherical_harmonics: Vec<u32> = (0..SH_VEC4_PLANES)
+        .flat_map(|plane_index| {
+            cloud.spherical_harmonic.iter()
+                .flat_map(move |sh| {
+                    let start_index = plane_index * 4;
+                    let end_index = std::cmp::min(start_index + 4, sh.coefficients.len());
+
+                    let mut depthwise = sh.coefficients[start_index..end_index].to_vec();
+                    depthwise.resize(4, 0);
+
+                    depthwise
                 })
-                .collect();
-
-            let mut spherical_harmonics = Image::new(
-                Extent3d {
-                    width: square,
-                    height: square,
-                    depth_or_array_layers: SH_VEC4_PLANES as u32,
-                },
-                TextureDimension::D2,
-                bytemuck::cast_slice(planar_spherical_harmonics.as_slice()).to_vec(),
-                TextureFormat::Rgba32Uint,
-                RenderAssetUsages::default(),
-            );
-            spherical_harmonics.texture_descriptor.usage = TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING;
-            let spherical_harmonics = images.add(spherical_harmonics);
-
-            #[cfg(feature = "precompute_covariance_3d")]
-            {
-                let mut covariance_3d_opacity = Image::new(
-                    extent_1d,
-                    TextureDimension::D2,
-                    bytemuck::cast_slice(cloud.covariance_3d_opacity_packed128.as_slice()).to_vec(),
-                    TextureFormat::Rgba32Uint,
-                    RenderAssetUsages::default(),
-                );
-                covariance_3d_opacity.texture_descriptor.usage = TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING;
-                let covariance_3d_opacity = images.add(covariance_3d_opacity);
-
-                texture_buffers = TextureBuffers {
-                    position_visibility,
-                    spherical_harmonics,
-                    covariance_3d_opacity,
-                };
-            }
+        })
+        .collect();
+
+    let spherical_harmonics = create_texture(
+        render_device,
+        render_queue,
+        "spherical_harmonics_texture",
+        Extent3d {
+            width: square,
+            height: square,
+            depth_or_array_layers: SH_VEC4_PLANES as u32,
+        },
+        TextureFormat::Rgba32Uint,
+        bytemuck::cast_slice(planar_spherical_harmonics.as_slice()),
+    );
 
-            #[cfg(not(feature = "precompute_covariance_3d"))]
-            {
-                let mut rotation_scale_opacity = Image::new(
-                    extent_1d,
-                    TextureDimension::D2,
-                    bytemuck::cast_slice(cloud.rotation_scale_opacity_packed128.as_slice()).to_vec(),
-                    TextureFormat::Rgba32Uint,
-                    RenderAssetUsages::default(),
-                );
-                rotation_scale_opacity.texture_descriptor.usage = TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING;
-                let rotation_scale_opacity = images.add(rotation_scale_opacity);
-
-                texture_buffers = TextureBuffers {
-                    position_visibility,
-                    spherical_harmonics,
-                    rotation_scale_opacity,
-                };
-            }
-        }
+    #[cfg(feature = "precompute_covariance_3d")]
+    return TextureBuffers {
+        position_visibility,
+        spherical_harmonics,
+        covariance_3d_opacity: create_texture(
+            render_device,
+            render_queue,
+            "covariance_3d_opacity_texture",
+            extent_1d,
+            TextureFormat::Rgba32Uint,
+            bytemuck::cast_slice(cloud.covariance_3d_opacity_packed128.as_slice()),
+        ),
+    };
 
-        #[cfg(feature = "f32")]
-        {
-            texture_buffers = TextureBuffers {
-                position_visibility,
-                spherical_harmonics: todo!(),
-                rotation: todo!(),
-                scale_opacity: todo!(),
-            };
-        }
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    return TextureBuffers {
+        position_visibility,
+        spherical_harmonics,
+        rotation_scale_opacity: create_texture(
+            render_device,
+            render_queue,
+            "rotation_scale_opacity_texture",
+            extent_1d,
+            TextureFormat::Rgba32Uint,
+            bytemuck::cast_slice(cloud.rotation_scale_opacity_packed128.as_slice()),
+        ),
+    };
+}
 
-        commands.entity(entity).insert(texture_buffers);
-    }
+#[cfg(feature = "f32")]
+pub fn prepare_cloud(
+    _render_device: &RenderDevice,
+    _render_queue: &RenderQueue,
+    _cloud: &GaussianCloud,
+) -> TextureBuffers {
+    todo!()
 }
 
 
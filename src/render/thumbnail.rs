@@ -0,0 +1,506 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+    },
+};
+
+use bevy::{
+    prelude::*,
+    asset::LoadState,
+    core::FrameCount,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        Extract,
+        Render,
+        RenderApp,
+        RenderSet,
+        camera::{
+            ClearColorConfig,
+            RenderTarget,
+        },
+        graph::CameraDriverLabel,
+        render_asset::{
+            RenderAssets,
+            RenderAssetUsages,
+        },
+        render_graph::{
+            Node,
+            NodeRunError,
+            RenderGraph,
+            RenderGraphContext,
+            RenderLabel,
+        },
+        render_resource::{
+            Buffer,
+            BufferDescriptor,
+            BufferUsages,
+            Extent3d,
+            ImageCopyBuffer,
+            ImageDataLayout,
+            MapMode,
+            TextureDimension,
+            TextureFormat,
+            TextureUsages,
+        },
+        renderer::{
+            RenderContext,
+            RenderDevice,
+        },
+        texture::GpuImage,
+        view::RenderLayers,
+    },
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianCloudSortStatus,
+    GaussianSplattingBundle,
+};
+
+
+// each in flight thumbnail renders on its own layer from here up, so its camera only sees its cloud
+pub const GAUSSIAN_CLOUD_THUMBNAIL_FIRST_LAYER: usize = 24;
+
+// captures without a completed sort after this many frames (e.g. SortMode::None) read back anyway
+const SORT_TIMEOUT_FRAMES: u32 = 60;
+
+
+#[derive(Clone, Reflect)]
+pub struct GaussianCloudThumbnailSettings {
+    pub width: u32,
+    pub height: u32,
+    // world space direction from the framed cloud toward the camera
+    pub view_direction: Vec3,
+    // vertical field of view in radians
+    pub fov: f32,
+    // about the fraction of the smaller frame extent the cloud's bounding sphere spans
+    pub fill: f32,
+    pub clear_color: Color,
+    // render settings of every thumbnail, `transform` and `coordinate_convention` place the cloud before framing
+    pub cloud_settings: GaussianCloudSettings,
+    // frames rendered before the readback, at least one. the readback also waits for a completed sort
+    pub pre_roll_frames: u32,
+}
+
+impl Default for GaussianCloudThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            width: 256,
+            height: 256,
+            view_direction: Vec3::new(0.0, 0.4, 1.0),
+            fov: std::f32::consts::FRAC_PI_4,
+            fill: 0.9,
+            clear_color: Color::NONE,
+            cloud_settings: GaussianCloudSettings::default(),
+            pre_roll_frames: 2,
+        }
+    }
+}
+
+impl GaussianCloudThumbnailSettings {
+    // camera looking at a world space sphere along view_direction, far enough that the sphere fits the frame
+    pub fn frame_sphere(&self, center: Vec3, radius: f32) -> Transform {
+        let aspect = self.width.max(1) as f32 / self.height.max(1) as f32;
+        let half_vertical = 0.5 * self.fov;
+        let half_horizontal = (half_vertical.tan() * aspect).atan();
+        let half_fov = half_vertical.min(half_horizontal);
+
+        let distance = radius.max(f32::EPSILON) / (half_fov.sin() * self.fill.clamp(0.05, 1.0));
+        let direction = self.view_direction.try_normalize().unwrap_or(Vec3::Z);
+        let up = if direction.cross(Vec3::Y).length_squared() > 1e-6 { Vec3::Y } else { Vec3::Z };
+
+        Transform::from_translation(center + direction * distance).looking_at(center, up)
+    }
+
+    // frames the aabb of the gaussian centers, placed by cloud_settings
+    pub fn frame_cloud(&self, cloud: &GaussianCloud) -> (Transform, Vec3, f32) {
+        let bounds = self.cloud_settings.world_aabb(&cloud.aabb());
+        let center = Vec3::from(bounds.center);
+        let radius = Vec3::from(bounds.half_extents).length();
+
+        (self.frame_sphere(center, radius), center, radius)
+    }
+}
+
+
+// sent once per requested cloud, in completion order. clouds that fail to load are dropped without an event
+#[derive(Event, Clone, Debug)]
+pub struct GaussianCloudThumbnailReady {
+    pub cloud: Handle<GaussianCloud>,
+    pub image: Image,
+}
+
+
+// batches offscreen thumbnail renders through the regular pipelines. at most max_in_flight render targets and
+// readback buffers exist at once, each freed as soon as its image is read back
+#[derive(Resource)]
+pub struct GaussianCloudThumbnails {
+    pub settings: GaussianCloudThumbnailSettings,
+    pub max_in_flight: usize,
+    pending: VecDeque<Handle<GaussianCloud>>,
+    jobs: Vec<ThumbnailJob>,
+}
+
+impl Default for GaussianCloudThumbnails {
+    fn default() -> Self {
+        Self {
+            settings: GaussianCloudThumbnailSettings::default(),
+            max_in_flight: 4,
+            pending: VecDeque::new(),
+            jobs: Vec::new(),
+        }
+    }
+}
+
+impl GaussianCloudThumbnails {
+    pub fn request(&mut self, clouds: impl IntoIterator<Item = Handle<GaussianCloud>>) {
+        self.pending.extend(clouds);
+    }
+
+    // requested thumbnails not yet sent
+    pub fn remaining(&self) -> usize {
+        self.pending.len() + self.jobs.len()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ThumbnailState {
+    PreRoll(u32),
+    Capture,
+    Mapping,
+}
+
+struct ThumbnailJob {
+    cloud: Handle<GaussianCloud>,
+    layer: usize,
+    camera: Entity,
+    splats: Entity,
+    start_frame: u32,
+    state: ThumbnailState,
+    readback: ThumbnailReadback,
+}
+
+#[derive(Component, Clone)]
+struct ThumbnailReadback {
+    target: Handle<Image>,
+    buffer: Buffer,
+    size: UVec2,
+    padded_bytes_per_row: u32,
+    mapped: Arc<AtomicBool>,
+}
+
+
+#[derive(Default)]
+pub struct GaussianCloudThumbnailPlugin;
+
+impl Plugin for GaussianCloudThumbnailPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianCloudThumbnailSettings>();
+        app.init_resource::<GaussianCloudThumbnails>();
+        app.add_event::<GaussianCloudThumbnailReady>();
+
+        app.add_systems(Update, update_thumbnails);
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_systems(ExtractSchedule, extract_thumbnail_readbacks)
+                .add_systems(Render, map_thumbnail_readbacks.in_set(RenderSet::Cleanup));
+
+            let node = ThumbnailCopyNode::from_world(render_app.world_mut());
+            let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+
+            graph.add_node(ThumbnailCopyLabel, node);
+            graph.add_node_edge(CameraDriverLabel, ThumbnailCopyLabel);
+        }
+    }
+}
+
+
+#[allow(clippy::too_many_arguments)]
+fn update_thumbnails(
+    mut commands: Commands,
+    mut thumbnails: ResMut<GaussianCloudThumbnails>,
+    mut images: ResMut<Assets<Image>>,
+    mut ev_ready: EventWriter<GaussianCloudThumbnailReady>,
+    asset_server: Res<AssetServer>,
+    gaussian_clouds: Res<Assets<GaussianCloud>>,
+    render_device: Res<RenderDevice>,
+    sort_status: Res<GaussianCloudSortStatus>,
+    frame_count: Res<FrameCount>,
+) {
+    let thumbnails = thumbnails.as_mut();
+
+    if thumbnails.jobs.iter().any(|job| job.state == ThumbnailState::Mapping) {
+        render_device.poll(wgpu::Maintain::Poll);
+    }
+
+    thumbnails.jobs.retain_mut(|job| match job.state {
+        ThumbnailState::PreRoll(frames) if frames > 0 => {
+            job.state = ThumbnailState::PreRoll(frames - 1);
+            true
+        },
+        ThumbnailState::PreRoll(_) => {
+            let timed_out = frame_count.0.wrapping_sub(job.start_frame) > SORT_TIMEOUT_FRAMES;
+            if timed_out || sort_status.is_ready(job.camera, job.splats, job.start_frame) {
+                job.state = ThumbnailState::Capture;
+            }
+            true
+        },
+        // extracted at the end of the previous frame, its copy and map are in flight
+        ThumbnailState::Capture => {
+            job.state = ThumbnailState::Mapping;
+            true
+        },
+        ThumbnailState::Mapping => {
+            if !job.readback.mapped.load(Ordering::Acquire) {
+                return true;
+            }
+
+            ev_ready.send(GaussianCloudThumbnailReady {
+                cloud: job.cloud.clone(),
+                image: read_thumbnail(&job.readback),
+            });
+
+            commands.entity(job.camera).despawn_recursive();
+            commands.entity(job.splats).despawn_recursive();
+            images.remove(&job.readback.target);
+            job.readback.buffer.destroy();
+
+            false
+        },
+    });
+
+    thumbnails.pending.retain(|cloud| !matches!(asset_server.get_load_state(cloud), Some(LoadState::Failed(_))));
+
+    while thumbnails.jobs.len() < thumbnails.max_in_flight.max(1) {
+        let Some(index) = thumbnails.pending.iter().position(|cloud| gaussian_clouds.contains(cloud)) else {
+            break;
+        };
+
+        let handle = thumbnails.pending.remove(index).unwrap();
+        let cloud = gaussian_clouds.get(&handle).unwrap();
+
+        let layer = (GAUSSIAN_CLOUD_THUMBNAIL_FIRST_LAYER..)
+            .find(|layer| thumbnails.jobs.iter().all(|job| job.layer != *layer))
+            .unwrap();
+
+        let job = start_thumbnail(
+            &mut commands,
+            &mut images,
+            &render_device,
+            &thumbnails.settings,
+            handle,
+            cloud,
+            layer,
+            frame_count.0,
+        );
+        thumbnails.jobs.push(job);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_thumbnail(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    render_device: &RenderDevice,
+    settings: &GaussianCloudThumbnailSettings,
+    handle: Handle<GaussianCloud>,
+    cloud: &GaussianCloud,
+    layer: usize,
+    frame: u32,
+) -> ThumbnailJob {
+    let size = UVec2::new(settings.width.max(1), settings.height.max(1));
+    let extent = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+
+    // render world only, the pixels come back through the readback buffer
+    let mut target = Image::new_fill(
+        extent,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    target.texture_descriptor.usage = TextureUsages::COPY_SRC
+        | TextureUsages::TEXTURE_BINDING
+        | TextureUsages::RENDER_ATTACHMENT;
+    let target = images.add(target);
+
+    let padded_bytes_per_row = RenderDevice::align_copy_bytes_per_row(size.x as usize * 4) as u32;
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("thumbnail readback buffer"),
+        size: padded_bytes_per_row as u64 * size.y as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let (transform, center, radius) = settings.frame_cloud(cloud);
+    let distance = transform.translation.distance(center);
+    let layers = RenderLayers::layer(layer);
+
+    let camera = commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(target.clone()),
+                clear_color: ClearColorConfig::Custom(settings.clear_color),
+                ..default()
+            },
+            projection: Projection::Perspective(PerspectiveProjection {
+                fov: settings.fov,
+                near: (distance - radius).max(distance * 1e-3),
+                far: distance + 2.0 * radius,
+                ..default()
+            }),
+            tonemapping: Tonemapping::None,
+            transform,
+            ..default()
+        },
+        layers.clone(),
+    )).id();
+
+    let splats = commands.spawn((
+        GaussianSplattingBundle {
+            cloud: handle.clone(),
+            settings: settings.cloud_settings.clone(),
+            ..default()
+        },
+        layers,
+    )).id();
+
+    ThumbnailJob {
+        cloud: handle,
+        layer,
+        camera,
+        splats,
+        start_frame: frame,
+        state: ThumbnailState::PreRoll(settings.pre_roll_frames.max(1)),
+        readback: ThumbnailReadback {
+            target,
+            buffer,
+            size,
+            padded_bytes_per_row,
+            mapped: Arc::new(AtomicBool::new(false)),
+        },
+    }
+}
+
+fn read_thumbnail(readback: &ThumbnailReadback) -> Image {
+    let row_bytes = readback.size.x as usize * 4;
+
+    let pixels = {
+        let mapped = readback.buffer.slice(..).get_mapped_range();
+
+        mapped.chunks(readback.padded_bytes_per_row as usize)
+            .take(readback.size.y as usize)
+            .flat_map(|row| row[..row_bytes].iter().copied())
+            .collect::<Vec<u8>>()
+    };
+    readback.buffer.unmap();
+
+    Image::new(
+        Extent3d {
+            width: readback.size.x,
+            height: readback.size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+
+fn extract_thumbnail_readbacks(
+    mut commands: Commands,
+    thumbnails: Extract<Res<GaussianCloudThumbnails>>,
+) {
+    for job in thumbnails.jobs.iter() {
+        if job.state == ThumbnailState::Capture {
+            commands.spawn(job.readback.clone());
+        }
+    }
+}
+
+// after the frame holding the copies is submitted
+fn map_thumbnail_readbacks(
+    readbacks: Query<&ThumbnailReadback>,
+) {
+    for readback in readbacks.iter() {
+        let mapped = readback.mapped.clone();
+
+        readback.buffer.slice(..).map_async(MapMode::Read, move |result| {
+            mapped.store(result.is_ok(), Ordering::Release);
+        });
+    }
+}
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ThumbnailCopyLabel;
+
+struct ThumbnailCopyNode {
+    readbacks: QueryState<&'static ThumbnailReadback>,
+}
+
+impl FromWorld for ThumbnailCopyNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            readbacks: world.query(),
+        }
+    }
+}
+
+impl Node for ThumbnailCopyNode {
+    fn update(&mut self, world: &mut World) {
+        self.readbacks.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+
+        for readback in self.readbacks.iter_manual(world) {
+            let Some(target) = gpu_images.get(&readback.target) else {
+                continue;
+            };
+
+            render_context.command_encoder().copy_texture_to_buffer(
+                target.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &readback.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(readback.padded_bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: readback.size.x,
+                    height: readback.size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
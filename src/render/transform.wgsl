@@ -1,6 +1,9 @@
 #define_import_path bevy_gaussian_splatting::transform
 
-#import bevy_gaussian_splatting::bindings::view
+#import bevy_gaussian_splatting::bindings::{
+    view,
+    gaussian_uniforms,
+}
 
 
 fn world_to_clip(world_pos: vec3<f32>) -> vec4<f32> {
@@ -14,3 +17,50 @@ fn in_frustum(clip_space_pos: vec3<f32>) -> bool {
         && abs(clip_space_pos.y) < 1.1
         && abs(clip_space_pos.z - 0.5) < 0.5;
 }
+
+
+// upper 3x3 of the cloud transform, carrying rotation, non-uniform scale and shear
+fn cloud_linear_transform() -> mat3x3<f32> {
+    return mat3x3<f32>(
+        gaussian_uniforms.transform[0].xyz,
+        gaussian_uniforms.transform[1].xyz,
+        gaussian_uniforms.transform[2].xyz,
+    );
+}
+
+
+// a cloud space covariance maps to world space as M * Sigma * transpose(M), so non-uniform scale and shear stretch
+// each ellipsoid along world axes instead of only rotating it
+fn transform_cov3d(cov3d: array<f32, 6>) -> array<f32, 6> {
+    let M = cloud_linear_transform();
+    let Sigma = mat3x3<f32>(
+        cov3d[0], cov3d[1], cov3d[2],
+        cov3d[1], cov3d[3], cov3d[4],
+        cov3d[2], cov3d[4], cov3d[5],
+    );
+    let world = M * Sigma * transpose(M);
+
+    return array<f32, 6>(
+        world[0][0],
+        world[0][1],
+        world[0][2],
+        world[1][1],
+        world[1][2],
+        world[2][2],
+    );
+}
+
+
+// spherical harmonics are stored in the cloud's local frame, so view directions are mapped back before lookup.
+// the inverse (the adjugate, signed by the determinant) rather than the transpose keeps them right under non-uniform scale
+fn world_to_local_direction(world_direction: vec3<f32>) -> vec3<f32> {
+    let linear = cloud_linear_transform();
+    let adjugate = transpose(mat3x3<f32>(
+        cross(linear[1], linear[2]),
+        cross(linear[2], linear[0]),
+        cross(linear[0], linear[1]),
+    ));
+    let determinant = dot(linear[0], cross(linear[1], linear[2]));
+
+    return normalize(adjugate * world_direction * sign(determinant));
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    GaussianCloud,
+    sort::SortEntry,
+};
+
+
+// least recently used sort orders of the cpu sorts, keyed by the cloud entity and the camera position in cloud space
+// snapped to a grid of `granularity`. entities sharing an asset never share orders, the sorts order by world space
+// distance under each entity's own transform. returning to a cached viewpoint (preset views, turntables) copies the
+// stored order instead of sorting. coarse cells hit more often but reuse an order sorted from up to half a cell away,
+// which shows as popping on splats whose depths are closer than that. each entry holds a full order (8 bytes per
+// gaussian), so keep `capacity` small for large clouds. 0 disables the cache
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct GaussianCloudSortCache {
+    pub capacity: usize,
+    pub granularity: f32,
+    #[reflect(ignore)]
+    entries: VecDeque<(SortCacheKey, Vec<SortEntry>)>,
+}
+
+impl Default for GaussianCloudSortCache {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            granularity: 0.05,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortCacheKey {
+    entity: Entity,
+    cloud: AssetId<GaussianCloud>,
+    cell: IVec3,
+    splat_budget: Option<usize>,
+}
+
+impl GaussianCloudSortCache {
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn key(
+        &self,
+        entity: Entity,
+        cloud: AssetId<GaussianCloud>,
+        local_camera_position: Vec3,
+        splat_budget: Option<usize>,
+    ) -> SortCacheKey {
+        SortCacheKey {
+            entity,
+            cloud,
+            cell: (local_camera_position / self.granularity.max(f32::EPSILON)).round().as_ivec3(),
+            splat_budget,
+        }
+    }
+
+    // marks the entry as most recently used, entries of a different length are stale
+    pub fn get(&mut self, key: &SortCacheKey, len: usize) -> Option<&[SortEntry]> {
+        let index = self.entries.iter().position(|(cached, _)| cached == key)?;
+        let entry = self.entries.remove(index)?;
+
+        if entry.1.len() != len {
+            return None;
+        }
+
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, sorted)| sorted.as_slice())
+    }
+
+    pub fn insert(&mut self, key: SortCacheKey, sorted: &[SortEntry]) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.entries.retain(|(cached, _)| *cached != key);
+        self.entries.push_back((key, sorted.to_vec()));
+
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn invalidate(&mut self, cloud: AssetId<GaussianCloud>) {
+        self.entries.retain(|(cached, _)| cached.cloud != cloud);
+    }
+
+    pub fn invalidate_entity(&mut self, entity: Entity) {
+        self.entries.retain(|(cached, _)| cached.entity != entity);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+
+#[derive(Default)]
+pub struct SortCachePlugin;
+
+impl Plugin for SortCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianCloudSortCache>();
+        app.init_resource::<GaussianCloudSortCache>();
+
+        app.add_systems(PreUpdate, invalidate_sort_cache);
+    }
+}
+
+
+fn invalidate_sort_cache(
+    mut sort_cache: ResMut<GaussianCloudSortCache>,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    mut removed_clouds: RemovedComponents<Handle<GaussianCloud>>,
+) {
+    for ev in ev_asset.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = ev {
+            sort_cache.invalidate(*id);
+        }
+    }
+
+    // entity ids are reused, a despawned cloud's orders must not reach the next entity with its id
+    for entity in removed_clouds.read() {
+        sort_cache.invalidate_entity(entity);
+    }
+
+    // a lowered capacity evicts the least recently used entries
+    while sort_cache.entries.len() > sort_cache.capacity {
+        sort_cache.entries.pop_front();
+    }
+}
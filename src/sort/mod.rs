@@ -25,9 +25,12 @@ use static_assertions::assert_cfg;
 use crate::{
     GaussianCloud,
     GaussianCloudSettings,
+    render::assert_storage_binding_fits,
 };
 
 
+pub mod cache;
+
 #[cfg(feature = "sort_radix")]
 pub mod radix;
 
@@ -37,17 +40,30 @@ pub mod rayon;
 #[cfg(feature = "sort_std")]
 pub mod std; // rename to std_sort.rs to avoid name conflict with std crate
 
+pub mod status;
+
+#[cfg(feature = "sort_temporal")]
+pub mod temporal;
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub mod unified;
+
 
 assert_cfg!(
     any(
         feature = "sort_radix",
         feature = "sort_rayon",
         feature = "sort_std",
+        feature = "sort_temporal",
     ),
     "no sort mode enabled",
 );
 
 
+// frames over which the temporal sort amortizes a full pass
+pub const DEFAULT_TEMPORAL_SORT_WINDOW_SIZE: u32 = 16;
+
+
 #[derive(
     Component,
     Debug,
@@ -66,6 +82,9 @@ pub enum SortMode {
 
     #[cfg(feature = "sort_std")]
     Std,
+
+    #[cfg(feature = "sort_temporal")]
+    Temporal,
 }
 
 impl Default for SortMode {
@@ -80,10 +99,117 @@ impl Default for SortMode {
         #[cfg(feature = "sort_std")]
         return Self::Std;
 
+        #[cfg(feature = "sort_temporal")]
+        return Self::Temporal;
+
         Self::None
     }
 }
 
+impl SortMode {
+    // budgets need a cpu sort, the gpu and temporal sorts own the draw order of every entry
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn supports_splat_budget(&self) -> bool {
+        match self {
+            #[cfg(feature = "sort_rayon")]
+            Self::Rayon => true,
+
+            #[cfg(feature = "sort_std")]
+            Self::Std => true,
+
+            _ => false,
+        }
+    }
+}
+
+
+// width of the radix sort keys. Bits16 maps the cloud's bounding sphere depth range linearly into 16 bits and sorts
+// in two digit passes instead of four, gaussians closer in depth than ~1/65000th of that range may swap order
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Reflect,
+)]
+pub enum SortKeyPrecision {
+    Bits16,
+    #[default]
+    Bits32,
+}
+
+impl SortKeyPrecision {
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::Bits16 => 16,
+            Self::Bits32 => 32,
+        }
+    }
+}
+
+
+// back to front order of the cpu sorts, keys hold squared view distance. equal keys fall back to the gaussian index
+// so coplanar and duplicate gaussians keep the same relative order every frame instead of shimmering
+pub fn compare_back_to_front(a: &SortEntry, b: &SortEntry) -> ::std::cmp::Ordering {
+    bytemuck::cast::<u32, f32>(b.key).total_cmp(&bytemuck::cast::<u32, f32>(a.key))
+        .then(a.index.cmp(&b.index))
+}
+
+
+// moves the `budget` most important entries to the front of a back-to-front order, keeping their relative order.
+// importance approximates screen coverage: GaussianCloud::importance / view_distance^2, so the draw can stop after
+// `budget` instances and still blend correctly. expects keys to hold squared view distance as written by the std and
+// rayon sorts.
+pub fn apply_splat_budget(
+    cloud: &GaussianCloud,
+    sorted: &mut Vec<SortEntry>,
+    budget: usize,
+) {
+    if budget == 0 || budget >= sorted.len() {
+        return;
+    }
+
+    let cloud_importance = cloud.importance();
+    let importance = |entry: &SortEntry| {
+        let distance_squared = bytemuck::cast::<u32, f32>(entry.key).max(f32::EPSILON);
+
+        cloud_importance[entry.index as usize] / distance_squared
+    };
+
+    let importances = sorted.iter()
+        .map(importance)
+        .collect::<Vec<f32>>();
+
+    let mut ranked = importances.clone();
+    let (_, threshold, _) = ranked.select_nth_unstable_by(budget - 1, |a, b| b.total_cmp(a));
+    let threshold = *threshold;
+
+    let greater = importances.iter()
+        .filter(|importance| **importance > threshold)
+        .count();
+    let mut ties = budget - greater;
+
+    let mut kept = Vec::with_capacity(sorted.len());
+    let mut dropped = Vec::with_capacity(sorted.len() - budget);
+
+    for (entry, importance) in sorted.iter().zip(importances) {
+        if importance > threshold {
+            kept.push(*entry);
+        } else if importance == threshold && ties > 0 {
+            ties -= 1;
+            kept.push(*entry);
+        } else {
+            dropped.push(*entry);
+        }
+    }
+
+    kept.extend(dropped);
+    *sorted = kept;
+}
+
 
 #[derive(Default)]
 pub struct SortPlugin;
@@ -99,6 +225,15 @@ impl Plugin for SortPlugin {
         #[cfg(feature = "sort_std")]
         app.add_plugins(std::StdSortPlugin);
 
+        #[cfg(feature = "sort_temporal")]
+        app.add_plugins(temporal::TemporalSortPlugin);
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        app.add_plugins(unified::UnifiedSortPlugin);
+
+        app.add_plugins(status::SortStatusPlugin);
+        app.add_plugins(cache::SortCachePlugin);
+
 
         app.register_type::<SortedEntries>();
         app.init_asset::<SortedEntries>();
@@ -106,7 +241,10 @@ impl Plugin for SortPlugin {
 
         app.add_plugins(RenderAssetPlugin::<GpuSortedEntry>::default());
 
-        app.add_systems(Update, auto_insert_sorted_entries);
+        app.add_systems(Update, (
+            reset_resized_sorted_entries,
+            auto_insert_sorted_entries,
+        ).chain());
 
         #[cfg(feature = "buffer_texture")]
         app.add_systems(PostUpdate, update_textures_on_change);
@@ -137,6 +275,60 @@ fn update_textures_on_change(
 }
 
 
+// the sorted entries are sized to the cloud when inserted, so a cloud whose gaussian count changed (e.g. after
+// GaussianCloud::compact or a hot reload) drops them and has a fresh, matching set inserted and sorted. the swap
+// lands a frame or more after the resize, until then the cpu sorts skip clouds whose entries do not match their length.
+// any modified cloud is re-sorted, the cpu sorts otherwise wait for the camera to move
+fn reset_resized_sorted_entries(
+    mut commands: Commands,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    mut resort: EventWriter<status::ResortGaussianClouds>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    sorted_entries_res: Res<Assets<SortedEntries>>,
+    gaussian_clouds: Query<(
+        Entity,
+        &Handle<GaussianCloud>,
+        &Handle<SortedEntries>,
+    )>,
+) {
+    let modified = ev_asset.read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if modified.is_empty() {
+        return;
+    }
+
+    let mut any_modified = false;
+
+    for (entity, cloud_handle, sorted_entries_handle) in gaussian_clouds.iter() {
+        if !modified.contains(&cloud_handle.id()) {
+            continue;
+        }
+
+        let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+            continue;
+        };
+
+        any_modified = true;
+
+        let resized = sorted_entries_res.get(sorted_entries_handle)
+            .is_some_and(|sorted_entries| sorted_entries.sorted.len() != cloud.len());
+
+        if resized {
+            commands.entity(entity).remove::<Handle<SortedEntries>>();
+        }
+    }
+
+    if any_modified {
+        resort.send(status::ResortGaussianClouds);
+    }
+}
+
+
 #[allow(clippy::type_complexity)]
 fn auto_insert_sorted_entries(
     mut commands: Commands,
@@ -252,6 +444,13 @@ impl RenderAsset for GpuSortedEntry {
         source: Self::SourceAsset,
         render_device: &mut SystemParamItem<Self::Param>,
     ) -> Result<Self, PrepareAssetError<Self::SourceAsset>> {
+        // the radix sort's ping-pong buffer is the same size
+        assert_storage_binding_fits(
+            render_device,
+            "sorted_entry_buffer",
+            source.sorted.len() * ::std::mem::size_of::<SortEntry>(),
+        );
+
         let sorted_entry_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
             label: Some("sorted_entry_buffer"),
             contents: bytemuck::cast_slice(source.sorted.as_slice()),
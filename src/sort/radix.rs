@@ -1,4 +1,10 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
 
 use bevy::{
     prelude::*,
@@ -6,11 +12,18 @@ use bevy::{
         load_internal_asset,
         LoadState,
     },
+    core::FrameCount,
     core_pipeline::core_3d::graph::{
         Core3d,
         Node3d,
     },
     render::{
+        Extract,
+        extract_component::DynamicUniformIndex,
+        extract_resource::{
+            ExtractResource,
+            ExtractResourcePlugin,
+        },
         render_asset::RenderAssets,
         render_resource::{
             BindGroup,
@@ -31,6 +44,7 @@ use bevy::{
             ComputePassDescriptor,
             ComputePipelineDescriptor,
             PipelineCache,
+            ShaderDefVal,
             ShaderStages,
         },
         renderer::{
@@ -56,19 +70,24 @@ use crate::{
     gaussian::cloud::GaussianCloud,
     GaussianCloudSettings,
     render::{
+        occlusion::GaussianCloudOcclusion,
         GaussianCloudBindGroup,
         GaussianCloudPipeline,
         GaussianCloudPipelineKey,
+        GaussianCloudUniform,
         GaussianUniformBindGroups,
         GaussianViewBindGroup,
+        GpuGaussianCloud,
         ShaderDefines,
-        shader_defs,
+        shader_defs_with,
     },
     sort::{
         GpuSortedEntry,
         SortEntry,
         SortedEntries,
+        SortKeyPrecision,
         SortMode,
+        status::GaussianCloudSortStatus,
     },
 };
 
@@ -108,7 +127,16 @@ impl Plugin for RadixSortPlugin {
             Shader::from_wgsl
         );
 
+        app.register_type::<RadixSortBatching>();
+        app.init_resource::<RadixSortBatching>();
+        app.add_plugins(ExtractResourcePlugin::<RadixSortBatching>::default());
+
+        let stats = RadixSortStats::default();
+        app.insert_resource(stats.clone());
+
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.insert_resource(stats);
+
             render_app
                 .add_render_graph_node::<RadixSortNode>(
                     Core3d,
@@ -141,6 +169,65 @@ impl Plugin for RadixSortPlugin {
     }
 }
 
+// clouds of at most `max_cloud_size` gaussians are sorted together, in one compute pass per view over scratch slabs
+// shared by every small cloud, instead of each with buffers of its own and a pass per digit. larger clouds keep their
+// dedicated buffers and passes. a batched entity takes about 12 bytes of slab space per gaussian, at least 8 KiB
+#[derive(
+    Resource,
+    Clone,
+    Debug,
+    ExtractResource,
+    Reflect,
+)]
+#[reflect(Resource)]
+pub struct RadixSortBatching {
+    pub enabled: bool,
+    pub max_cloud_size: usize,
+}
+
+impl Default for RadixSortBatching {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_cloud_size: 4096,
+        }
+    }
+}
+
+impl RadixSortBatching {
+    pub fn batches(&self, count: usize) -> bool {
+        self.enabled && count <= self.max_cloud_size
+    }
+}
+
+
+// radix sort work encoded by the last run of the sort node, shared by the main and render worlds
+#[derive(Resource, Clone, Default)]
+pub struct RadixSortStats(Arc<Mutex<RadixSortCounts>>);
+
+impl RadixSortStats {
+    pub fn last_frame(&self) -> RadixSortCounts {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+)]
+pub struct RadixSortCounts {
+    pub sorts: u32,
+    pub batched_sorts: u32,
+    pub compute_passes: u32,
+    pub dispatches: u32,
+    pub buffer_clears: u32,
+}
+
+
 #[derive(Resource, Default)]
 pub struct RadixSortBuffers {
     // TODO: use a more ECS-friendly approach
@@ -148,10 +235,46 @@ pub struct RadixSortBuffers {
         AssetId<GaussianCloud>,
         GpuRadixBuffers,
     >,
+    // scratch of the RadixSortBatching clouds, grown to fit and never shrunk
+    pub batch: Option<GpuRadixBatchBuffers>,
+}
+
+
+// the scratch bindings of one sort, whole buffers for dedicated sorts and slices of the slabs for batched ones
+pub struct RadixScratch<'a> {
+    pub sorting_pass_buffers: &'a [Buffer; 4],
+    pub sorting_global: BufferBinding<'a>,
+    // per bind group (digit pass) index
+    pub sorting_status_counters: [BufferBinding<'a>; 4],
+    pub entry_buffer_b: BufferBinding<'a>,
+}
+
+fn create_sorting_pass_buffers(render_device: &RenderDevice) -> [Buffer; 4] {
+    (0..4)
+        .map(|idx| {
+            render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: format!("sorting pass buffer {}", idx).as_str().into(),
+                contents: &[idx as u8, 0, 0, 0],
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            })
+        })
+        .collect::<Vec<Buffer>>()
+        .try_into()
+        .unwrap()
+}
+
+fn whole_buffer(buffer: &Buffer) -> BufferBinding<'_> {
+    BufferBinding {
+        buffer,
+        offset: 0,
+        size: BufferSize::new(buffer.size()),
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct GpuRadixBuffers {
+    pub count: usize,
+    pub precision: SortKeyPrecision,
     pub sorting_global_buffer: Buffer,
     pub sorting_status_counter_buffer: Buffer,
     pub sorting_pass_buffers: [Buffer; 4],
@@ -160,33 +283,26 @@ pub struct GpuRadixBuffers {
 impl GpuRadixBuffers {
     pub fn new(
         count: usize,
+        precision: SortKeyPrecision,
         render_device: &RenderDevice,
     ) -> Self {
+        let defines = ShaderDefines::with_sort_key_bits(precision.bits());
+
         let sorting_global_buffer = render_device.create_buffer(&BufferDescriptor {
             label: Some("sorting global buffer"),
-            size: ShaderDefines::default().sorting_buffer_size as u64,
+            size: defines.sorting_buffer_size as u64,
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
             mapped_at_creation: false,
         });
 
         let sorting_status_counter_buffer = render_device.create_buffer(&BufferDescriptor {
             label: Some("status counters buffer"),
-            size: ShaderDefines::default().sorting_status_counters_buffer_size(count) as u64,
+            size: defines.sorting_status_counters_buffer_size(count) as u64,
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
             mapped_at_creation: false,
         });
 
-        let sorting_pass_buffers = (0..4)
-            .map(|idx| {
-                render_device.create_buffer_with_data(&BufferInitDescriptor {
-                    label: format!("sorting pass buffer {}", idx).as_str().into(),
-                    contents: &[idx as u8, 0, 0, 0],
-                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-                })
-            })
-            .collect::<Vec<Buffer>>()
-            .try_into()
-            .unwrap();
+        let sorting_pass_buffers = create_sorting_pass_buffers(render_device);
 
         let entry_buffer_b = render_device.create_buffer(&BufferDescriptor {
             label: Some("entry buffer b"),
@@ -196,12 +312,150 @@ impl GpuRadixBuffers {
         });
 
         GpuRadixBuffers {
+            count,
+            precision,
             sorting_global_buffer,
             sorting_status_counter_buffer,
             sorting_pass_buffers,
             entry_buffer_b,
         }
     }
+
+    // the status counters are cleared between digit passes, so every pass binds all of them
+    pub fn scratch(&self) -> RadixScratch<'_> {
+        RadixScratch {
+            sorting_pass_buffers: &self.sorting_pass_buffers,
+            sorting_global: whole_buffer(&self.sorting_global_buffer),
+            sorting_status_counters: std::array::from_fn(|_| whole_buffer(&self.sorting_status_counter_buffer)),
+            entry_buffer_b: whole_buffer(&self.entry_buffer_b),
+        }
+    }
+}
+
+
+// where one batched sort lives in the batch slabs, offsets in bytes and aligned for storage bindings
+#[derive(Debug, Clone, Copy)]
+pub struct RadixBatchSlot {
+    pub precision: SortKeyPrecision,
+    pub sorting_global_offset: u64,
+    pub sorting_status_counters_offset: u64,
+    // of the counters of one digit pass, each pass has its own so none are cleared between passes
+    pub sorting_status_counters_size: u64,
+    pub entry_buffer_b_offset: u64,
+    pub entry_buffer_b_size: u64,
+}
+
+// slab sizes needed by the batched sorts of a frame
+#[derive(Debug, Clone, Default)]
+pub struct RadixBatchLayout {
+    pub sorting_global_size: u64,
+    pub sorting_status_counters_size: u64,
+    pub entry_buffer_b_size: u64,
+}
+
+impl RadixBatchLayout {
+    pub fn push(
+        &mut self,
+        count: usize,
+        precision: SortKeyPrecision,
+        alignment: u64,
+    ) -> RadixBatchSlot {
+        let defines = ShaderDefines::with_sort_key_bits(precision.bits());
+        let align = |size: u64| size.div_ceil(alignment) * alignment;
+
+        let sorting_status_counters_size = align(defines.sorting_status_counters_buffer_size(count.max(1)) as u64);
+        let entry_buffer_b_size = align((count.max(1) * std::mem::size_of::<SortEntry>()) as u64);
+
+        let slot = RadixBatchSlot {
+            precision,
+            sorting_global_offset: self.sorting_global_size,
+            sorting_status_counters_offset: self.sorting_status_counters_size,
+            sorting_status_counters_size,
+            entry_buffer_b_offset: self.entry_buffer_b_size,
+            entry_buffer_b_size,
+        };
+
+        self.sorting_global_size += align(defines.sorting_buffer_size as u64);
+        self.sorting_status_counters_size += sorting_status_counters_size * defines.radix_digit_places as u64;
+        self.entry_buffer_b_size += entry_buffer_b_size;
+
+        slot
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GpuRadixBatchBuffers {
+    pub sorting_global_buffer: Buffer,
+    pub sorting_status_counter_buffer: Buffer,
+    pub sorting_pass_buffers: [Buffer; 4],
+    pub entry_buffer_b: Buffer,
+}
+
+impl GpuRadixBatchBuffers {
+    // rounded up to powers of two so a few clouds coming and going do not reallocate every frame
+    pub fn new(
+        layout: &RadixBatchLayout,
+        render_device: &RenderDevice,
+    ) -> Self {
+        let sorting_global_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("batched sorting global buffer"),
+            size: layout.sorting_global_size.next_power_of_two(),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let sorting_status_counter_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("batched status counters buffer"),
+            size: layout.sorting_status_counters_size.next_power_of_two(),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let entry_buffer_b = render_device.create_buffer(&BufferDescriptor {
+            label: Some("batched entry buffer b"),
+            size: layout.entry_buffer_b_size.next_power_of_two(),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        GpuRadixBatchBuffers {
+            sorting_global_buffer,
+            sorting_status_counter_buffer,
+            sorting_pass_buffers: create_sorting_pass_buffers(render_device),
+            entry_buffer_b,
+        }
+    }
+
+    pub fn fits(&self, layout: &RadixBatchLayout) -> bool {
+        layout.sorting_global_size <= self.sorting_global_buffer.size()
+            && layout.sorting_status_counters_size <= self.sorting_status_counter_buffer.size()
+            && layout.entry_buffer_b_size <= self.entry_buffer_b.size()
+    }
+
+    pub fn scratch(&self, slot: &RadixBatchSlot) -> RadixScratch<'_> {
+        let defines = ShaderDefines::with_sort_key_bits(slot.precision.bits());
+
+        RadixScratch {
+            sorting_pass_buffers: &self.sorting_pass_buffers,
+            sorting_global: BufferBinding {
+                buffer: &self.sorting_global_buffer,
+                offset: slot.sorting_global_offset,
+                size: BufferSize::new(defines.sorting_buffer_size as u64),
+            },
+            // bind groups past the digit places only run the histogram passes, which leave the counters alone
+            sorting_status_counters: std::array::from_fn(|idx| BufferBinding {
+                buffer: &self.sorting_status_counter_buffer,
+                offset: slot.sorting_status_counters_offset
+                    + (idx as u64 % defines.radix_digit_places as u64) * slot.sorting_status_counters_size,
+                size: BufferSize::new(slot.sorting_status_counters_size),
+            }),
+            entry_buffer_b: BufferBinding {
+                buffer: &self.entry_buffer_b,
+                offset: slot.entry_buffer_b_offset,
+                size: BufferSize::new(slot.entry_buffer_b_size),
+            },
+        }
+    }
 }
 
 
@@ -209,14 +463,42 @@ fn update_sort_buffers(
     gpu_gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
     mut sort_buffers: ResMut<RadixSortBuffers>,
     render_device: Res<RenderDevice>,
+    batching: Extract<Res<RadixSortBatching>>,
+    gaussian_clouds: Extract<
+        Query<(
+            &Handle<GaussianCloud>,
+            &GaussianCloudSettings,
+        )>,
+    >,
 ) {
-    for (asset_id, cloud,) in gpu_gaussian_clouds.iter() {
-        // TODO: handle cloud resize operations and resolve leaked stale buffers
-        if sort_buffers.asset_map.contains_key(&asset_id) {
+    // clouds with other sort modes (e.g. pre-sorted data with SortMode::None) never allocate radix buffers
+    for (cloud_handle, settings) in gaussian_clouds.iter() {
+        if settings.effective_sort_mode() != SortMode::Radix {
+            continue;
+        }
+
+        let asset_id = cloud_handle.id();
+
+        let Some(cloud) = gpu_gaussian_clouds.get(asset_id) else {
+            continue;
+        };
+
+        // small clouds sort in the batch slabs, see queue_radix_bind_group
+        if batching.batches(cloud.count) {
+            sort_buffers.asset_map.remove(&asset_id);
+            continue;
+        }
+
+        // TODO: resolve leaked stale buffers
+        // a resized cloud (e.g. after GaussianCloud::compact) replaces the scratch buffers, which are sized per gaussian
+        let precision = settings.sort_key_precision;
+        if sort_buffers.asset_map.get(&asset_id).is_some_and(|buffers| {
+            buffers.precision == precision && buffers.count == cloud.count
+        }) {
             continue;
         }
 
-        let gpu_radix_buffers = GpuRadixBuffers::new(cloud.count, &render_device);
+        let gpu_radix_buffers = GpuRadixBuffers::new(cloud.count, precision, &render_device);
         sort_buffers.asset_map.insert(asset_id, gpu_radix_buffers);
     }
 }
@@ -226,139 +508,640 @@ fn update_sort_buffers(
 pub struct RadixSortPipeline {
     pub radix_sort_layout: BindGroupLayout,
     pub radix_sort_pipelines: [CachedComputePipelineId; 3],
+    pub radix_sort_layout_16: BindGroupLayout,
+    pub radix_sort_pipelines_16: [CachedComputePipelineId; 3],
+    // the same passes over caller provided entries, see GpuRadixSorter
+    pub standalone_layout: BindGroupLayout,
+    pub standalone_pipelines: [CachedComputePipelineId; 3],
+    pub standalone_layout_16: BindGroupLayout,
+    pub standalone_pipelines_16: [CachedComputePipelineId; 3],
+}
+
+impl RadixSortPipeline {
+    pub fn layout(&self, precision: SortKeyPrecision) -> &BindGroupLayout {
+        match precision {
+            SortKeyPrecision::Bits16 => &self.radix_sort_layout_16,
+            SortKeyPrecision::Bits32 => &self.radix_sort_layout,
+        }
+    }
+
+    pub fn pipelines(&self, precision: SortKeyPrecision) -> &[CachedComputePipelineId; 3] {
+        match precision {
+            SortKeyPrecision::Bits16 => &self.radix_sort_pipelines_16,
+            SortKeyPrecision::Bits32 => &self.radix_sort_pipelines,
+        }
+    }
+
+    pub fn standalone_layout(&self, precision: SortKeyPrecision) -> &BindGroupLayout {
+        match precision {
+            SortKeyPrecision::Bits16 => &self.standalone_layout_16,
+            SortKeyPrecision::Bits32 => &self.standalone_layout,
+        }
+    }
+
+    pub fn standalone_pipelines(&self, precision: SortKeyPrecision) -> &[CachedComputePipelineId; 3] {
+        match precision {
+            SortKeyPrecision::Bits16 => &self.standalone_pipelines_16,
+            SortKeyPrecision::Bits32 => &self.standalone_pipelines,
+        }
+    }
 }
 
 impl FromWorld for RadixSortPipeline {
     fn from_world(render_world: &mut World) -> Self {
-        let render_device = render_world.resource::<RenderDevice>();
-        let gaussian_cloud_pipeline = render_world.resource::<GaussianCloudPipeline>();
+        let (radix_sort_layout, radix_sort_pipelines) = queue_radix_sort_pipelines(render_world, SortKeyPrecision::Bits32, false);
+        let (radix_sort_layout_16, radix_sort_pipelines_16) = queue_radix_sort_pipelines(render_world, SortKeyPrecision::Bits16, false);
+        let (standalone_layout, standalone_pipelines) = queue_radix_sort_pipelines(render_world, SortKeyPrecision::Bits32, true);
+        let (standalone_layout_16, standalone_pipelines_16) = queue_radix_sort_pipelines(render_world, SortKeyPrecision::Bits16, true);
 
-        let sorting_buffer_entry = BindGroupLayoutEntry {
-            binding: 1,
+        RadixSortPipeline {
+            radix_sort_layout,
+            radix_sort_pipelines,
+            radix_sort_layout_16,
+            radix_sort_pipelines_16,
+            standalone_layout,
+            standalone_pipelines,
+            standalone_layout_16,
+            standalone_pipelines_16,
+        }
+    }
+}
+
+// the histogram layout and digit passes differ per key width, so each precision gets its own layout and pipelines.
+// standalone pipelines bind only the sort group (at group 0, with the entry count at binding 6) and histogram the
+// caller's keys instead of computing gaussian depth keys
+fn queue_radix_sort_pipelines(
+    render_world: &World,
+    precision: SortKeyPrecision,
+    standalone: bool,
+) -> (BindGroupLayout, [CachedComputePipelineId; 3]) {
+    let render_device = render_world.resource::<RenderDevice>();
+    let gaussian_cloud_pipeline = render_world.resource::<GaussianCloudPipeline>();
+    let defines = ShaderDefines::with_sort_key_bits(precision.bits());
+
+    let sorting_buffer_entry = BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: BufferSize::new(defines.sorting_buffer_size as u64),
+        },
+        count: None,
+    };
+
+    let sorting_status_counters_buffer_entry = BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: BufferSize::new(defines.sorting_status_counters_buffer_size(1) as u64),
+        },
+        count: None,
+    };
+
+    let draw_indirect_buffer_entry = BindGroupLayoutEntry {
+        binding: 3,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: BufferSize::new(std::mem::size_of::<wgpu::util::DrawIndirectArgs>() as u64),
+        },
+        count: None,
+    };
+
+    let mut entries = vec![
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
+            },
+            count: None,
+        },
+        sorting_buffer_entry,
+        sorting_status_counters_buffer_entry,
+        draw_indirect_buffer_entry,
+        BindGroupLayoutEntry {
+            binding: 4,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
                 ty: BufferBindingType::Storage { read_only: false },
                 has_dynamic_offset: false,
-                min_binding_size: BufferSize::new(ShaderDefines::default().sorting_buffer_size as u64),
+                min_binding_size: BufferSize::new(std::mem::size_of::<SortEntry>() as u64),
             },
             count: None,
-        };
-
-        let sorting_status_counters_buffer_entry = BindGroupLayoutEntry {
-            binding: 2,
+        },
+        BindGroupLayoutEntry {
+            binding: 5,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
                 ty: BufferBindingType::Storage { read_only: false },
                 has_dynamic_offset: false,
-                min_binding_size: BufferSize::new(ShaderDefines::default().sorting_status_counters_buffer_size(1) as u64),
+                min_binding_size: BufferSize::new(std::mem::size_of::<SortEntry>() as u64),
             },
             count: None,
-        };
+        },
+    ];
 
-        let draw_indirect_buffer_entry = BindGroupLayoutEntry {
-            binding: 3,
+    if standalone {
+        entries.push(BindGroupLayoutEntry {
+            binding: 6,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
-                ty: BufferBindingType::Storage { read_only: false },
+                ty: BufferBindingType::Uniform,
                 has_dynamic_offset: false,
-                min_binding_size: BufferSize::new(std::mem::size_of::<wgpu::util::DrawIndirectArgs>() as u64),
+                min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
             },
             count: None,
-        };
+        });
+    }
+
+    let label = if standalone { "radix_sort_standalone_layout" } else { "radix_sort_layout" };
+    let radix_sort_layout = render_device.create_bind_group_layout(
+        Some(format!("{}_{}", label, precision.bits()).as_str()),
+        &entries,
+    );
 
-        let radix_sort_layout = render_device.create_bind_group_layout(
-            Some("radix_sort_layout"),
-            &[
-                BindGroupLayoutEntry {
+    let mut shader_defs = shader_defs_with(GaussianCloudPipelineKey::default(), &defines);
+    let sorting_layout = if standalone {
+        shader_defs.push("RADIX_STANDALONE".into());
+        shader_defs.push(ShaderDefVal::UInt("RADIX_SORT_GROUP".into(), 0));
+
+        vec![radix_sort_layout.clone()]
+    } else {
+        shader_defs.push(ShaderDefVal::UInt("RADIX_SORT_GROUP".into(), 3));
+
+        vec![
+            gaussian_cloud_pipeline.view_layout.clone(),
+            gaussian_cloud_pipeline.gaussian_uniform_layout.clone(),
+            gaussian_cloud_pipeline.gaussian_cloud_layout.clone(),
+            radix_sort_layout.clone(),
+        ]
+    };
+
+    let pipeline_cache = render_world.resource::<PipelineCache>();
+    let radix_sort_a = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        label: Some("radix_sort_a".into()),
+        layout: sorting_layout.clone(),
+        push_constant_ranges: vec![],
+        shader: RADIX_SHADER_HANDLE,
+        shader_defs: shader_defs.clone(),
+        entry_point: "radix_sort_a".into(),
+    });
+
+    let radix_sort_b = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        label: Some("radix_sort_b".into()),
+        layout: sorting_layout.clone(),
+        push_constant_ranges: vec![],
+        shader: RADIX_SHADER_HANDLE,
+        shader_defs: shader_defs.clone(),
+        entry_point: "radix_sort_b".into(),
+    });
+
+    let radix_sort_c = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+        label: Some("radix_sort_c".into()),
+        layout: sorting_layout.clone(),
+        push_constant_ranges: vec![],
+        shader: RADIX_SHADER_HANDLE,
+        shader_defs: shader_defs.clone(),
+        entry_point: "radix_sort_c".into(),
+    });
+
+    (
+        radix_sort_layout,
+        [
+            radix_sort_a,
+            radix_sort_b,
+            radix_sort_c,
+        ],
+    )
+}
+
+
+
+#[derive(Component)]
+pub struct RadixBindGroup {
+    pub radix_sort_bind_groups: [BindGroup; 4],
+    pub precision: SortKeyPrecision,
+    // sorted in the shared batch pass, see RadixSortBatching
+    pub batched: bool,
+}
+
+
+// one bind group per digit pass, ping-ponging between `entries` and entry_buffer_b so even pass counts end in
+// `entries`. `entry_count` is bound at binding 6 for standalone sorts
+fn create_sort_bind_groups(
+    render_device: &RenderDevice,
+    layout: &BindGroupLayout,
+    scratch: RadixScratch,
+    draw_indirect_buffer: &Buffer,
+    entries: &Buffer,
+    count: usize,
+    entry_count: Option<&Buffer>,
+) -> [BindGroup; 4] {
+    let entries_size = BufferSize::new((count.max(1) * std::mem::size_of::<SortEntry>()) as u64);
+
+    let entries_binding = BufferBinding {
+        buffer: entries,
+        offset: 0,
+        size: entries_size,
+    };
+
+    let entry_buffer_b_binding = BufferBinding {
+        buffer: scratch.entry_buffer_b.buffer,
+        offset: scratch.entry_buffer_b.offset,
+        size: entries_size,
+    };
+
+    let sorting_global_entry = BindGroupEntry {
+        binding: 1,
+        resource: BindingResource::Buffer(scratch.sorting_global.clone()),
+    };
+
+    let draw_indirect_entry = BindGroupEntry {
+        binding: 3,
+        resource: BindingResource::Buffer(BufferBinding {
+            buffer: draw_indirect_buffer,
+            offset: 0,
+            size: BufferSize::new(draw_indirect_buffer.size()),
+        }),
+    };
+
+    (0..4)
+        .map(|idx| {
+            let mut bind_group_entries = vec![
+                BindGroupEntry {
                     binding: 0,
-                    visibility: ShaderStages::COMPUTE,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
-                    },
-                    count: None,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &scratch.sorting_pass_buffers[idx],
+                        offset: 0,
+                        size: BufferSize::new(std::mem::size_of::<u32>() as u64),
+                    }),
+                },
+                sorting_global_entry.clone(),
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(scratch.sorting_status_counters[idx].clone()),
                 },
-                sorting_buffer_entry,
-                sorting_status_counters_buffer_entry,
-                draw_indirect_buffer_entry,
-                BindGroupLayoutEntry {
+                draw_indirect_entry.clone(),
+                BindGroupEntry {
                     binding: 4,
-                    visibility: ShaderStages::COMPUTE,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: BufferSize::new(std::mem::size_of::<SortEntry>() as u64),
-                    },
-                    count: None,
+                    resource: BindingResource::Buffer(if idx % 2 == 0 {
+                        entries_binding.clone()
+                    } else {
+                        entry_buffer_b_binding.clone()
+                    }),
                 },
-                BindGroupLayoutEntry {
+                BindGroupEntry {
                     binding: 5,
-                    visibility: ShaderStages::COMPUTE,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: BufferSize::new(std::mem::size_of::<SortEntry>() as u64),
-                    },
-                    count: None,
+                    resource: BindingResource::Buffer(if idx % 2 == 0 {
+                        entry_buffer_b_binding.clone()
+                    } else {
+                        entries_binding.clone()
+                    }),
                 },
-            ],
+            ];
+
+            if let Some(entry_count) = entry_count {
+                bind_group_entries.push(BindGroupEntry {
+                    binding: 6,
+                    resource: entry_count.as_entire_binding(),
+                });
+            }
+
+            render_device.create_bind_group(
+                format!("radix_sort_bind_group {}", idx).as_str(),
+                layout,
+                &bind_group_entries,
+            )
+        })
+        .collect::<Vec<BindGroup>>()
+        .try_into()
+        .unwrap()
+}
+
+
+// webgpu guarantees 65535 workgroups per dimension, larger dispatches are folded into rows. the shaders linearize the
+// workgroup id, and pass c skips the spare workgroups of the last row
+const MAX_WORKGROUPS_PER_DIMENSION: usize = 65535;
+
+fn fold_workgroups(workgroups: usize) -> (u32, u32) {
+    let x = workgroups.clamp(1, MAX_WORKGROUPS_PER_DIMENSION);
+    let y = workgroups.div_ceil(x).max(1);
+
+    assert!(
+        y <= MAX_WORKGROUPS_PER_DIMENSION,
+        "radix sort dispatch of {} workgroups exceeds {}^2",
+        workgroups,
+        MAX_WORKGROUPS_PER_DIMENSION,
+    );
+
+    (x as u32, y as u32)
+}
+
+
+// encodes one complete sort. `leading_bind_groups` (with their dynamic offsets) are set ahead of the sort group,
+// the view, uniforms and cloud for gaussian sorts and nothing for standalone ones. the first pass histograms through
+// `first_bind_group`: index 1 writes the generated gaussian keys into the entries buffer, index 0 reads the caller's
+// keys from it. a None `draw_indirect_buffer` belongs to a cloud drawn directly, whose args are left untouched.
+// returns false while the pipelines are still compiling
+#[allow(clippy::too_many_arguments)]
+fn encode_radix_sort(
+    command_encoder: &mut wgpu::CommandEncoder,
+    pipeline_cache: &PipelineCache,
+    sort_pipelines: &[CachedComputePipelineId; 3],
+    precision: SortKeyPrecision,
+    sorting_assets: &GpuRadixBuffers,
+    draw_indirect_buffer: Option<&Buffer>,
+    sort_bind_groups: &[BindGroup; 4],
+    leading_bind_groups: &[(&BindGroup, &[u32])],
+    first_bind_group: usize,
+    count: usize,
+) -> bool {
+    let [Some(radix_sort_a), Some(radix_sort_b), Some(radix_sort_c)] = sort_pipelines
+        .map(|pipeline| pipeline_cache.get_compute_pipeline(pipeline))
+    else {
+        return false;
+    };
+
+    let defines = ShaderDefines::with_sort_key_bits(precision.bits());
+    let radix_digit_places = defines.radix_digit_places;
+    let sort_group = leading_bind_groups.len() as u32;
+
+    // the scratch buffers may be shared with other sorts, so they are cleared before each dispatch. passes encoded on
+    // one encoder execute in order, which keeps one dispatch's counters from leaking into the next
+    {
+        command_encoder.clear_buffer(
+            &sorting_assets.sorting_global_buffer,
+            0,
+            None,
         );
 
-        let sorting_layout = vec![
-            gaussian_cloud_pipeline.view_layout.clone(),
-            gaussian_cloud_pipeline.gaussian_uniform_layout.clone(),
-            gaussian_cloud_pipeline.gaussian_cloud_layout.clone(),
-            radix_sort_layout.clone(),
-        ];
-        let shader_defs = shader_defs(GaussianCloudPipelineKey::default());
-
-        let pipeline_cache = render_world.resource::<PipelineCache>();
-        let radix_sort_a = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
-            label: Some("radix_sort_a".into()),
-            layout: sorting_layout.clone(),
-            push_constant_ranges: vec![],
-            shader: RADIX_SHADER_HANDLE,
-            shader_defs: shader_defs.clone(),
-            entry_point: "radix_sort_a".into(),
-        });
+        command_encoder.clear_buffer(
+            &sorting_assets.sorting_status_counter_buffer,
+            0,
+            None,
+        );
 
-        let radix_sort_b = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
-            label: Some("radix_sort_b".into()),
-            layout: sorting_layout.clone(),
-            push_constant_ranges: vec![],
-            shader: RADIX_SHADER_HANDLE,
-            shader_defs: shader_defs.clone(),
-            entry_point: "radix_sort_b".into(),
-        });
+        if let Some(draw_indirect_buffer) = draw_indirect_buffer {
+            command_encoder.clear_buffer(
+                draw_indirect_buffer,
+                0,
+                None,
+            );
+        }
+    }
 
-        let radix_sort_c = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
-            label: Some("radix_sort_c".into()),
-            layout: sorting_layout.clone(),
-            push_constant_ranges: vec![],
-            shader: RADIX_SHADER_HANDLE,
-            shader_defs: shader_defs.clone(),
-            entry_point: "radix_sort_c".into(),
-        });
+    // TODO: add options to only complete a fraction of the sorting process
+    {
+        let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor::default());
 
-        RadixSortPipeline {
-            radix_sort_layout,
-            radix_sort_pipelines: [
-                radix_sort_a,
-                radix_sort_b,
-                radix_sort_c,
-            ],
+        for (index, (bind_group, offsets)) in leading_bind_groups.iter().enumerate() {
+            pass.set_bind_group(index as u32, bind_group, offsets);
+        }
+        pass.set_bind_group(
+            sort_group,
+            &sort_bind_groups[first_bind_group],
+            &[],
+        );
+
+        pass.set_pipeline(radix_sort_a);
+
+        let (x, y) = fold_workgroups(count.div_ceil(defines.workgroup_entries_a as usize));
+        pass.dispatch_workgroups(x, y, 1);
+
+        pass.set_pipeline(radix_sort_b);
+
+        pass.dispatch_workgroups(1, radix_digit_places, 1);
+    }
+
+    for pass_idx in 0..radix_digit_places {
+        if pass_idx > 0 {
+            command_encoder.clear_buffer(
+                &sorting_assets.sorting_status_counter_buffer,
+                0,
+                None,
+            );
+        }
+
+        let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor::default());
+
+        pass.set_pipeline(radix_sort_c);
+
+        for (index, (bind_group, offsets)) in leading_bind_groups.iter().enumerate() {
+            pass.set_bind_group(index as u32, bind_group, offsets);
         }
+        pass.set_bind_group(
+            sort_group,
+            &sort_bind_groups[pass_idx as usize],
+            &[],
+        );
+
+        let (x, y) = fold_workgroups(defines.max_tile_count(count) as usize);
+        pass.dispatch_workgroups(x, y, 1);
     }
+
+    true
 }
 
 
+// one small cloud sort of a batch, see encode_batched_radix_sorts
+pub struct BatchedRadixSort<'a> {
+    pub precision: SortKeyPrecision,
+    pub count: usize,
+    // None for clouds drawn directly, see encode_radix_sort
+    pub draw_indirect_buffer: Option<&'a Buffer>,
+    pub sort_bind_groups: &'a [BindGroup; 4],
+    pub uniform_index: u32,
+    pub cloud_bind_group: &'a BindGroup,
+}
 
-#[derive(Component)]
-pub struct RadixBindGroup {
-    pub radix_sort_bind_groups: [BindGroup; 4],
+// encodes the sorts of every batched cloud of one view in a single compute pass. each sort reads and writes only its
+// own slots of the batch slabs, with separate status counters per digit pass, so the slabs are cleared once up front
+// instead of around every pass. dispatches of a pass execute in order like separate passes would. returns false while
+// the pipelines are still compiling
+#[allow(clippy::too_many_arguments)]
+fn encode_batched_radix_sorts(
+    command_encoder: &mut wgpu::CommandEncoder,
+    pipeline_cache: &PipelineCache,
+    pipeline: &RadixSortPipeline,
+    batch: &GpuRadixBatchBuffers,
+    view_bind_group: &BindGroup,
+    view_uniform_offset: u32,
+    uniform_bind_group: &BindGroup,
+    sorts: &[BatchedRadixSort],
+    counts: &mut RadixSortCounts,
+) -> bool {
+    let resolve = |precision: SortKeyPrecision| {
+        let [Some(a), Some(b), Some(c)] = pipeline.pipelines(precision)
+            .map(|pipeline| pipeline_cache.get_compute_pipeline(pipeline))
+        else {
+            return None;
+        };
+
+        Some([a, b, c])
+    };
+
+    let (Some(pipelines), Some(pipelines_16)) = (resolve(SortKeyPrecision::Bits32), resolve(SortKeyPrecision::Bits16))
+    else {
+        return false;
+    };
+
+    command_encoder.clear_buffer(&batch.sorting_global_buffer, 0, None);
+    command_encoder.clear_buffer(&batch.sorting_status_counter_buffer, 0, None);
+    counts.buffer_clears += 2;
+
+    for draw_indirect_buffer in sorts.iter().filter_map(|sort| sort.draw_indirect_buffer) {
+        command_encoder.clear_buffer(draw_indirect_buffer, 0, None);
+        counts.buffer_clears += 1;
+    }
+
+    let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor::default());
+    counts.compute_passes += 1;
+
+    pass.set_bind_group(0, view_bind_group, &[view_uniform_offset]);
+
+    for sort in sorts {
+        let [radix_sort_a, radix_sort_b, radix_sort_c] = match sort.precision {
+            SortKeyPrecision::Bits16 => pipelines_16,
+            SortKeyPrecision::Bits32 => pipelines,
+        };
+
+        let defines = ShaderDefines::with_sort_key_bits(sort.precision.bits());
+        let radix_digit_places = defines.radix_digit_places;
+
+        pass.set_bind_group(1, uniform_bind_group, &[sort.uniform_index]);
+        pass.set_bind_group(2, sort.cloud_bind_group, &[]);
+        pass.set_bind_group(3, &sort.sort_bind_groups[1], &[]);
+
+        pass.set_pipeline(radix_sort_a);
+
+        let (x, y) = fold_workgroups(sort.count.div_ceil(defines.workgroup_entries_a as usize));
+        pass.dispatch_workgroups(x, y, 1);
+
+        pass.set_pipeline(radix_sort_b);
+
+        pass.dispatch_workgroups(1, radix_digit_places, 1);
+
+        pass.set_pipeline(radix_sort_c);
+
+        for pass_idx in 0..radix_digit_places {
+            pass.set_bind_group(3, &sort.sort_bind_groups[pass_idx as usize], &[]);
+
+            let (x, y) = fold_workgroups(defines.max_tile_count(sort.count) as usize);
+            pass.dispatch_workgroups(x, y, 1);
+        }
+
+        counts.sorts += 1;
+        counts.batched_sorts += 1;
+        counts.dispatches += 2 + radix_digit_places;
+    }
+
+    true
+}
+
+
+// the radix sort over caller owned data, decoupled from gaussian clouds. `entries` holds `count` SortEntry
+// { key, index } pairs (STORAGE usage) and is sorted in place, ascending by key (the low 16 bits only with Bits16).
+// after the sort `draw_indirect_buffer` holds the number of entries outside the top key bucket as its instance count,
+// so keys of u32::MAX (0xFFFF with Bits16) act as culled entries. create once per entries buffer, then encode from a
+// render graph node:
+//
+//   let sorter = GpuRadixSorter::new(&render_device, &radix_pipeline, &entries, count, SortKeyPrecision::Bits32);
+//   sorter.encode(render_context.command_encoder(), &pipeline_cache, &radix_pipeline);
+//
+// every encode clears and reuses the same scratch buffers, so one sorter sorts one buffer at a time
+pub struct GpuRadixSorter {
+    pub count: usize,
+    pub precision: SortKeyPrecision,
+    pub buffers: GpuRadixBuffers,
+    pub draw_indirect_buffer: Buffer,
+    pub entry_count_buffer: Buffer,
+    bind_groups: [BindGroup; 4],
 }
 
+impl GpuRadixSorter {
+    pub fn new(
+        render_device: &RenderDevice,
+        pipeline: &RadixSortPipeline,
+        entries: &Buffer,
+        count: usize,
+        precision: SortKeyPrecision,
+    ) -> Self {
+        let buffers = GpuRadixBuffers::new(count.max(1), precision, render_device);
+
+        let draw_indirect_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("radix sort count buffer"),
+            size: std::mem::size_of::<wgpu::util::DrawIndirectArgs>() as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let entry_count_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("radix sort entry count"),
+            contents: &(count as u32).to_le_bytes(),
+            usage: BufferUsages::UNIFORM,
+        });
+
+        let bind_groups = create_sort_bind_groups(
+            render_device,
+            pipeline.standalone_layout(precision),
+            buffers.scratch(),
+            &draw_indirect_buffer,
+            entries,
+            count,
+            Some(&entry_count_buffer),
+        );
+
+        Self {
+            count,
+            precision,
+            buffers,
+            draw_indirect_buffer,
+            entry_count_buffer,
+            bind_groups,
+        }
+    }
+
+    // false while the pipelines are still compiling, nothing is encoded then
+    pub fn encode(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        pipeline_cache: &PipelineCache,
+        pipeline: &RadixSortPipeline,
+    ) -> bool {
+        if self.count == 0 {
+            return true;
+        }
+
+        encode_radix_sort(
+            command_encoder,
+            pipeline_cache,
+            pipeline.standalone_pipelines(self.precision),
+            self.precision,
+            &self.buffers,
+            Some(&self.draw_indirect_buffer),
+            &self.bind_groups,
+            &[],
+            0,
+            self.count,
+        )
+    }
+}
+
+type RadixBindGroupQuery = (
+    Entity,
+    &'static Handle<GaussianCloud>,
+    &'static Handle<SortedEntries>,
+    &'static GaussianCloudSettings,
+    Option<&'static RadixBindGroup>,
+);
+
 #[allow(clippy::too_many_arguments)]
 pub fn queue_radix_bind_group(
     mut commands: Commands,
@@ -367,139 +1150,124 @@ pub fn queue_radix_bind_group(
     asset_server: Res<AssetServer>,
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     sorted_entries_res: Res<RenderAssets<GpuSortedEntry>>,
-    gaussian_clouds: Query<(
-        Entity,
-        &Handle<GaussianCloud>,
-        &Handle<SortedEntries>,
-        &GaussianCloudSettings,
-    )>,
-    sort_buffers: Res<RadixSortBuffers>,
+    gaussian_clouds: Query<RadixBindGroupQuery>,
+    batching: Res<RadixSortBatching>,
+    mut sort_buffers: ResMut<RadixSortBuffers>,
 ) {
+    let alignment = render_device.limits().min_storage_buffer_offset_alignment as u64;
+    let mut batch_layout = RadixBatchLayout::default();
+    let mut batched = Vec::new();
+
     for (
         entity,
         cloud_handle,
         sorted_entries_handle,
         settings,
+        radix_bind_group,
     ) in gaussian_clouds.iter() {
-        if settings.sort_mode != SortMode::Radix {
-            continue;
-        }
+        let ready = || {
+            if settings.effective_sort_mode() != SortMode::Radix {
+                return None;
+            }
 
-        // TODO: deduplicate asset load checks
-        if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle) {
-            continue;
-        }
+            // TODO: deduplicate asset load checks
+            if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle) {
+                return None;
+            }
 
-        if gaussian_cloud_res.get(cloud_handle).is_none() {
-            continue;
-        }
+            let cloud = gaussian_cloud_res.get(cloud_handle)?;
 
-        if Some(LoadState::Loading) == asset_server.get_load_state(sorted_entries_handle) {
-            continue;
-        }
+            if Some(LoadState::Loading) == asset_server.get_load_state(sorted_entries_handle) {
+                return None;
+            }
+
+            let sorted_entries = sorted_entries_res.get(sorted_entries_handle)?;
+
+            if !batching.batches(cloud.count) && !sort_buffers.asset_map.contains_key(&cloud_handle.id()) {
+                return None;
+            }
+
+            Some((cloud, sorted_entries))
+        };
+
+        let Some((cloud, sorted_entries)) = ready() else {
+            // batch slots are handed out every frame, a stale batched bind group could alias another cloud's slots
+            if radix_bind_group.is_some_and(|radix_bind_group| radix_bind_group.batched) {
+                commands.entity(entity).remove::<RadixBindGroup>();
+            }
 
-        if sorted_entries_res.get(sorted_entries_handle).is_none() {
             continue;
-        }
+        };
 
-        if !sort_buffers.asset_map.contains_key(&cloud_handle.id()) {
+        // every batched entity gets its own slots, the slabs are cleared once per view instead of per sort
+        if batching.batches(cloud.count) {
+            let slot = batch_layout.push(cloud.count, settings.sort_key_precision, alignment);
+            batched.push((entity, cloud, sorted_entries, slot));
             continue;
         }
 
-        let cloud = gaussian_cloud_res.get(cloud_handle).unwrap();
-        let sorted_entries = sorted_entries_res.get(sorted_entries_handle).unwrap();
         let sorting_assets = &sort_buffers.asset_map[&cloud_handle.id()];
 
-        let sorting_global_entry = BindGroupEntry {
-            binding: 1,
-            resource: BindingResource::Buffer(BufferBinding {
-                buffer: &sorting_assets.sorting_global_buffer,
-                offset: 0,
-                size: BufferSize::new(sorting_assets.sorting_global_buffer.size()),
-            }),
-        };
+        let radix_sort_bind_groups = create_sort_bind_groups(
+            &render_device,
+            radix_pipeline.layout(sorting_assets.precision),
+            sorting_assets.scratch(),
+            &cloud.draw_indirect_buffer,
+            &sorted_entries.sorted_entry_buffer,
+            cloud.count,
+            None,
+        );
 
-        let sorting_status_counters_entry = BindGroupEntry {
-            binding: 2,
-            resource: BindingResource::Buffer(BufferBinding {
-                buffer: &sorting_assets.sorting_status_counter_buffer,
-                offset: 0,
-                size: BufferSize::new(sorting_assets.sorting_status_counter_buffer.size()),
-            }),
-        };
+        commands.entity(entity).insert(RadixBindGroup {
+            radix_sort_bind_groups,
+            precision: sorting_assets.precision,
+            batched: false,
+        });
+    }
 
-        let draw_indirect_entry = BindGroupEntry {
-            binding: 3,
-            resource: BindingResource::Buffer(BufferBinding {
-                buffer: &cloud.draw_indirect_buffer,
-                offset: 0,
-                size: BufferSize::new(cloud.draw_indirect_buffer.size()),
-            }),
-        };
+    if batched.is_empty() {
+        return;
+    }
 
-        let radix_sort_bind_groups: [BindGroup; 4] = (0..4)
-            .map(|idx| {
-                render_device.create_bind_group(
-                    format!("radix_sort_bind_group {}", idx).as_str(),
-                    &radix_pipeline.radix_sort_layout,
-                    &[
-                        BindGroupEntry {
-                            binding: 0,
-                            resource: BindingResource::Buffer(BufferBinding {
-                                buffer: &sorting_assets.sorting_pass_buffers[idx],
-                                offset: 0,
-                                size: BufferSize::new(std::mem::size_of::<u32>() as u64),
-                            }),
-                        },
-                        sorting_global_entry.clone(),
-                        sorting_status_counters_entry.clone(),
-                        draw_indirect_entry.clone(),
-                        BindGroupEntry {
-                            binding: 4,
-                            resource: BindingResource::Buffer(BufferBinding {
-                                buffer: if idx % 2 == 0 {
-                                    &sorted_entries.sorted_entry_buffer
-                                } else {
-                                    &sorting_assets.entry_buffer_b
-                                },
-                                offset: 0,
-                                size: BufferSize::new((cloud.count * std::mem::size_of::<SortEntry>()) as u64),
-                            }),
-                        },
-                        BindGroupEntry {
-                            binding: 5,
-                            resource: BindingResource::Buffer(BufferBinding {
-                                buffer: if idx % 2 == 0 {
-                                    &sorting_assets.entry_buffer_b
-                                } else {
-                                    &sorted_entries.sorted_entry_buffer
-                                },
-                                offset: 0,
-                                size: BufferSize::new((cloud.count * std::mem::size_of::<SortEntry>()) as u64),
-                            }),
-                        },
-                    ],
-                )
-            })
-            .collect::<Vec<BindGroup>>()
-            .try_into()
-            .unwrap();
+    if !sort_buffers.batch.as_ref().is_some_and(|batch| batch.fits(&batch_layout)) {
+        sort_buffers.batch = Some(GpuRadixBatchBuffers::new(&batch_layout, &render_device));
+    }
+    let batch = sort_buffers.batch.as_ref().unwrap();
+
+    for (entity, cloud, sorted_entries, slot) in batched {
+        let radix_sort_bind_groups = create_sort_bind_groups(
+            &render_device,
+            radix_pipeline.layout(slot.precision),
+            batch.scratch(&slot),
+            &cloud.draw_indirect_buffer,
+            &sorted_entries.sorted_entry_buffer,
+            cloud.count,
+            None,
+        );
 
         commands.entity(entity).insert(RadixBindGroup {
             radix_sort_bind_groups,
+            precision: slot.precision,
+            batched: true,
         });
     }
 }
 
 
+type RadixSortNodeQuery = (
+    Entity,
+    &'static Handle<GaussianCloud>,
+    &'static GaussianCloudBindGroup,
+    &'static RadixBindGroup,
+    &'static DynamicUniformIndex<GaussianCloudUniform>,
+    &'static GaussianCloudSettings,
+);
+
 pub struct RadixSortNode {
-    gaussian_clouds: QueryState<(
-        &'static Handle<GaussianCloud>,
-        &'static GaussianCloudBindGroup,
-        &'static RadixBindGroup,
-    )>,
+    gaussian_clouds: QueryState<RadixSortNodeQuery>,
     initialized: bool,
     view_bind_group: QueryState<(
+        Entity,
         &'static GaussianViewBindGroup,
         &'static ViewUniformOffset,
     )>,
@@ -522,7 +1290,7 @@ impl Node for RadixSortNode {
 
         if !self.initialized {
             let mut pipelines_loaded = true;
-            for sort_pipeline in pipeline.radix_sort_pipelines.iter() {
+            for sort_pipeline in pipeline.radix_sort_pipelines.iter().chain(pipeline.radix_sort_pipelines_16.iter()) {
                 if let CachedPipelineState::Ok(_) =
                         pipeline_cache.get_compute_pipeline_state(*sort_pipeline)
                 {
@@ -556,126 +1324,198 @@ impl Node for RadixSortNode {
         let pipeline_cache = world.resource::<PipelineCache>();
         let pipeline = world.resource::<RadixSortPipeline>();
         let gaussian_uniforms = world.resource::<GaussianUniformBindGroups>();
+        let gpu_gaussian_clouds = world.resource::<RenderAssets<GpuGaussianCloud>>();
         let sort_buffers = world.resource::<RadixSortBuffers>();
+        let sort_status = world.resource::<GaussianCloudSortStatus>();
+        let occlusion = world.resource::<GaussianCloudOcclusion>();
+
+        // the extracted FrameCount was already advanced past the main world frame being rendered
+        let frame = world.resource::<FrameCount>().0.wrapping_sub(1);
+
+        let mut counts = RadixSortCounts::default();
 
         for (
+            view_entity,
             view_bind_group,
             view_uniform_offset,
         ) in self.view_bind_group.iter_manual(world) {
+            let mut batched_entities = Vec::new();
+            let mut batched_sorts = Vec::new();
+
             for (
+                cloud_entity,
                 cloud_handle,
                 cloud_bind_group,
                 radix_bind_group,
+                uniform_index,
+                settings,
             ) in self.gaussian_clouds.iter_manual(world) {
-                let cloud = world.get_resource::<RenderAssets<GpuGaussianCloud>>().unwrap().get(cloud_handle).unwrap();
+                if occlusion.is_occluded(view_entity, cloud_entity) {
+                    continue;
+                }
+
+                let cloud = gpu_gaussian_clouds.get(cloud_handle).unwrap();
+
+                // the shader skips the count for direct draws too, see GaussianCloudUniform::draws_indirect
+                let draw_indirect_buffer = Some(&cloud.draw_indirect_buffer)
+                    .filter(|_| settings.draws_indirect());
+
+                if radix_bind_group.batched {
+                    batched_entities.push(cloud_entity);
+                    batched_sorts.push(BatchedRadixSort {
+                        precision: radix_bind_group.precision,
+                        count: cloud.count,
+                        draw_indirect_buffer,
+                        sort_bind_groups: &radix_bind_group.radix_sort_bind_groups,
+                        uniform_index: uniform_index.index(),
+                        cloud_bind_group: &cloud_bind_group.cloud_bind_group,
+                    });
+                    continue;
+                }
 
                 assert!(sort_buffers.asset_map.contains_key(&cloud_handle.id()));
                 let sorting_assets = &sort_buffers.asset_map[&cloud_handle.id()];
 
-                {
-                    let command_encoder = render_context.command_encoder();
-                    let radix_digit_places = ShaderDefines::default().radix_digit_places;
-
-                    {
-                        command_encoder.clear_buffer(
-                            &sorting_assets.sorting_global_buffer,
-                            0,
-                            None,
-                        );
-
-                        command_encoder.clear_buffer(
-                            &sorting_assets.sorting_status_counter_buffer,
-                            0,
-                            None,
-                        );
-
-                        command_encoder.clear_buffer(
-                            &cloud.draw_indirect_buffer,
-                            0,
-                            None,
-                        );
-                    }
-
-                    // TODO: add options to only complete a fraction of the sorting process
-                    {
-                        let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor::default());
-
-                        pass.set_bind_group(
-                            0,
-                            &view_bind_group.value,
-                            &[view_uniform_offset.offset],
-                        );
-                        pass.set_bind_group(
-                            1,
-                            gaussian_uniforms.base_bind_group.as_ref().unwrap(),
-                            &[0], // TODO: fix transforms - dynamic offset using DynamicUniformIndex
-                        );
-                        pass.set_bind_group(
-                            2,
-                            &cloud_bind_group.cloud_bind_group,
-                            &[]
-                        );
-                        pass.set_bind_group(
-                            3,
-                            &radix_bind_group.radix_sort_bind_groups[1],
-                            &[],
-                        );
-
-                        let radix_sort_a = pipeline_cache.get_compute_pipeline(pipeline.radix_sort_pipelines[0]).unwrap();
-                        pass.set_pipeline(radix_sort_a);
-
-                        let workgroup_entries_a = ShaderDefines::default().workgroup_entries_a;
-                        pass.dispatch_workgroups((cloud.count as u32 + workgroup_entries_a - 1) / workgroup_entries_a, 1, 1);
-
-
-                        let radix_sort_b = pipeline_cache.get_compute_pipeline(pipeline.radix_sort_pipelines[1]).unwrap();
-                        pass.set_pipeline(radix_sort_b);
-
-                        pass.dispatch_workgroups(1, radix_digit_places, 1);
-                    }
-
-                    for pass_idx in 0..radix_digit_places {
-                        if pass_idx > 0 {
-                            command_encoder.clear_buffer(
-                                &sorting_assets.sorting_status_counter_buffer,
-                                0,
-                                None,
-                            );
-                        }
-
-                        let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor::default());
-
-                        let radix_sort_c = pipeline_cache.get_compute_pipeline(pipeline.radix_sort_pipelines[2]).unwrap();
-                        pass.set_pipeline(radix_sort_c);
-
-                        pass.set_bind_group(
-                            0,
-                            &view_bind_group.value,
-                            &[view_uniform_offset.offset],
-                        );
-                        pass.set_bind_group(
-                            1,
-                            gaussian_uniforms.base_bind_group.as_ref().unwrap(),
-                            &[0], // TODO: fix transforms - dynamic offset using DynamicUniformIndex
-                        );
-                        pass.set_bind_group(
-                            2,
-                            &cloud_bind_group.cloud_bind_group,
-                            &[]
-                        );
-                        pass.set_bind_group(
-                            3,
-                            &radix_bind_group.radix_sort_bind_groups[pass_idx as usize],
-                            &[],
-                        );
-
-                        let workgroup_entries_c = ShaderDefines::default().workgroup_entries_c;
-                        pass.dispatch_workgroups(1, (cloud.count as u32 + workgroup_entries_c - 1) / workgroup_entries_c, 1);
-                    }
+                // the scratch buffers are shared by every entity drawing this asset and reused by every view
+                let encoded = encode_radix_sort(
+                    render_context.command_encoder(),
+                    pipeline_cache,
+                    pipeline.pipelines(sorting_assets.precision),
+                    sorting_assets.precision,
+                    sorting_assets,
+                    draw_indirect_buffer,
+                    &radix_bind_group.radix_sort_bind_groups,
+                    &[
+                        (&view_bind_group.value, &[view_uniform_offset.offset]),
+                        (gaussian_uniforms.base_bind_group.as_ref().unwrap(), &[uniform_index.index()]),
+                        (&cloud_bind_group.cloud_bind_group, &[]),
+                    ],
+                    1,
+                    cloud.count,
+                );
+
+                if !encoded {
+                    continue;
                 }
+
+                let radix_digit_places = ShaderDefines::with_sort_key_bits(sorting_assets.precision.bits())
+                    .radix_digit_places;
+                counts.sorts += 1;
+                counts.compute_passes += 1 + radix_digit_places;
+                counts.dispatches += 2 + radix_digit_places;
+                counts.buffer_clears += 1 + radix_digit_places + draw_indirect_buffer.is_some() as u32;
+
+                // encoded only, completion is tracked once the frame is submitted
+                sort_status.mark_sorted(view_entity, cloud_entity, frame);
+            }
+
+            if batched_sorts.is_empty() {
+                continue;
+            }
+
+            let Some(batch) = sort_buffers.batch.as_ref() else {
+                continue;
+            };
+
+            let encoded = encode_batched_radix_sorts(
+                render_context.command_encoder(),
+                pipeline_cache,
+                pipeline,
+                batch,
+                &view_bind_group.value,
+                view_uniform_offset.offset,
+                gaussian_uniforms.base_bind_group.as_ref().unwrap(),
+                &batched_sorts,
+                &mut counts,
+            );
+
+            if !encoded {
+                continue;
+            }
+
+            for cloud_entity in batched_entities {
+                sort_status.mark_sorted(view_entity, cloud_entity, frame);
             }
         }
 
+        *world.resource::<RadixSortStats>().0.lock().unwrap() = counts;
+
         Ok(())
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_layout_slots() {
+        let alignment = 256;
+        let sizes = [
+            (1, SortKeyPrecision::Bits32),
+            (4096, SortKeyPrecision::Bits16),
+            (0, SortKeyPrecision::Bits32),
+            (1000, SortKeyPrecision::Bits16),
+            (3000, SortKeyPrecision::Bits32),
+        ];
+
+        let mut layout = RadixBatchLayout::default();
+        let slots = sizes.iter()
+            .map(|(count, precision)| {
+                let slot = layout.push(*count, *precision, alignment);
+                let defines = ShaderDefines::with_sort_key_bits(precision.bits());
+
+                let global_size = (defines.sorting_buffer_size as u64).div_ceil(alignment) * alignment;
+                let counters_size = slot.sorting_status_counters_size * defines.radix_digit_places as u64;
+
+                (
+                    slot.sorting_global_offset..slot.sorting_global_offset + global_size,
+                    slot.sorting_status_counters_offset..slot.sorting_status_counters_offset + counters_size,
+                    slot.entry_buffer_b_offset..slot.entry_buffer_b_offset + slot.entry_buffer_b_size,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let slabs = [
+            layout.sorting_global_size,
+            layout.sorting_status_counters_size,
+            layout.entry_buffer_b_size,
+        ];
+
+        for (a, (global, counters, entries)) in slots.iter().enumerate() {
+            for (slab, range) in [global, counters, entries].into_iter().enumerate() {
+                assert_eq!(range.start % alignment, 0, "slot {} slab {} is unaligned", a, slab);
+                assert!(range.start < range.end, "slot {} slab {} is empty", a, slab);
+                assert!(range.end <= slabs[slab], "slot {} slab {} overruns the slab", a, slab);
+            }
+
+            for (b, (other_global, other_counters, other_entries)) in slots.iter().enumerate().skip(a + 1) {
+                let pairs = [(global, other_global), (counters, other_counters), (entries, other_entries)];
+                for (slab, (range, other)) in pairs.into_iter().enumerate() {
+                    assert!(
+                        range.end <= other.start || other.end <= range.start,
+                        "slots {} and {} overlap in slab {}",
+                        a,
+                        b,
+                        slab,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_batching_threshold() {
+        let batching = RadixSortBatching::default();
+        assert!(batching.batches(0));
+        assert!(batching.batches(batching.max_cloud_size));
+        assert!(!batching.batches(batching.max_cloud_size + 1));
+
+        let disabled = RadixSortBatching {
+            enabled: false,
+            ..default()
+        };
+        assert!(!disabled.batches(1));
+    }
+}
@@ -1,6 +1,12 @@
+#ifdef RADIX_STANDALONE
+// sorts caller provided entries, see GpuRadixSorter. nothing gaussian specific is bound
+#import bevy_gaussian_splatting::bindings::{
+    DrawIndirect,
+    Entry,
+}
+#else
 #import bevy_gaussian_splatting::bindings::{
     view,
-    globals,
     gaussian_uniforms,
     sorting_pass_index,
     sorting,
@@ -30,6 +36,7 @@
 #ifdef BUFFER_TEXTURE
 #import bevy_gaussian_splatting::texture::get_position
 #endif
+#endif
 
 
 struct SortingGlobal {
@@ -37,12 +44,63 @@ struct SortingGlobal {
     assignment_counter: atomic<u32>,
 }
 
-@group(3) @binding(0) var<uniform> sorting_pass_index: u32;
-@group(3) @binding(1) var<storage, read_write> sorting: SortingGlobal;
-@group(3) @binding(2) var<storage, read_write> status_counters: array<array<atomic<u32>, #{RADIX_BASE}>>;
-@group(3) @binding(3) var<storage, read_write> draw_indirect: DrawIndirect;
-@group(3) @binding(4) var<storage, read_write> input_entries: array<Entry>;
-@group(3) @binding(5) var<storage, read_write> output_entries: array<Entry>;
+@group(#{RADIX_SORT_GROUP}) @binding(0) var<uniform> sorting_pass_index: u32;
+@group(#{RADIX_SORT_GROUP}) @binding(1) var<storage, read_write> sorting: SortingGlobal;
+@group(#{RADIX_SORT_GROUP}) @binding(2) var<storage, read_write> status_counters: array<array<atomic<u32>, #{RADIX_BASE}>>;
+@group(#{RADIX_SORT_GROUP}) @binding(3) var<storage, read_write> draw_indirect: DrawIndirect;
+@group(#{RADIX_SORT_GROUP}) @binding(4) var<storage, read_write> input_entries: array<Entry>;
+@group(#{RADIX_SORT_GROUP}) @binding(5) var<storage, read_write> output_entries: array<Entry>;
+
+#ifdef RADIX_STANDALONE
+@group(#{RADIX_SORT_GROUP}) @binding(6) var<uniform> standalone_entry_count: u32;
+
+fn entry_count() -> u32 {
+    return standalone_entry_count;
+}
+
+fn writes_draw_indirect() -> bool {
+    return true;
+}
+#else
+fn entry_count() -> u32 {
+    return gaussian_uniforms.count;
+}
+
+// direct draws never read the count, so their sorts leave the args as they are
+fn writes_draw_indirect() -> bool {
+    return gaussian_uniforms.draws_indirect != 0u;
+}
+#endif
+
+
+#ifndef RADIX_STANDALONE
+#ifdef SORT_KEY_16
+// linear view depth across the cloud's bounding sphere, nearest gaussians get the largest key so the ascending
+// sort stays back to front. the top digit bucket is left to culled entries, so draw_indirect counts every visible one
+fn sort_key_16(world_position: vec3<f32>, clip_depth: f32) -> u32 {
+    if (gaussian_uniforms.sort_radius <= 0.0) {
+        return u32(clip_depth * 0xFEFF.0);
+    }
+
+    let linear = mat3x3<f32>(
+        gaussian_uniforms.transform[0].xyz,
+        gaussian_uniforms.transform[1].xyz,
+        gaussian_uniforms.transform[2].xyz,
+    );
+    let radius = gaussian_uniforms.sort_radius * max(
+        length(linear[0]),
+        max(length(linear[1]), length(linear[2])),
+    );
+    let center = (gaussian_uniforms.transform * vec4<f32>(gaussian_uniforms.sort_center, 1.0)).xyz;
+
+    let depth = -(view.view_from_world * vec4<f32>(world_position, 1.0)).z;
+    let center_depth = -(view.view_from_world * vec4<f32>(center, 1.0)).z;
+    let t = clamp((depth - center_depth + radius) / (2.0 * radius), 0.0, 1.0);
+
+    return u32((1.0 - t) * 0xFEFF.0);
+}
+#endif
+#endif
 
 
 struct SortingSharedA {
@@ -53,30 +111,45 @@ var<workgroup> sorting_shared_a: SortingSharedA;
 @compute @workgroup_size(#{RADIX_BASE}, #{RADIX_DIGIT_PLACES})
 fn radix_sort_a(
     @builtin(local_invocation_id) gl_LocalInvocationID: vec3<u32>,
-    @builtin(global_invocation_id) gl_GlobalInvocationID: vec3<u32>,
+    @builtin(workgroup_id) gl_WorkGroupID: vec3<u32>,
+    @builtin(num_workgroups) gl_NumWorkGroups: vec3<u32>,
 ) {
     sorting_shared_a.digit_histogram[gl_LocalInvocationID.y][gl_LocalInvocationID.x] = 0u;
     workgroupBarrier();
 
-    let thread_index = gl_GlobalInvocationID.x * #{RADIX_DIGIT_PLACES}u + gl_GlobalInvocationID.y;
+    // large clouds fold the dispatch into rows of at most 65535 workgroups
+    let workgroup_index = gl_WorkGroupID.y * gl_NumWorkGroups.x + gl_WorkGroupID.x;
+    let invocation_x = workgroup_index * #{RADIX_BASE}u + gl_LocalInvocationID.x;
+    let thread_index = invocation_x * #{RADIX_DIGIT_PLACES}u + gl_LocalInvocationID.y;
     let start_entry_index = thread_index * #{ENTRIES_PER_INVOCATION_A}u;
     let end_entry_index = start_entry_index + #{ENTRIES_PER_INVOCATION_A}u;
     for(var entry_index = start_entry_index; entry_index < end_entry_index; entry_index += 1u) {
-        if(entry_index >= gaussian_uniforms.count) {
+        if(entry_index >= entry_count()) {
             continue;
         }
+#ifdef RADIX_STANDALONE
+        // the caller's keys are histogrammed in place, pass c reads them from the same buffer
+        let key = input_entries[entry_index].key;
+#else
         var key: u32 = 0xFFFFFFFFu; // Stream compaction for frustum culling
         let position = vec4<f32>(get_position(entry_index), 1.0);
         let transformed_position = (gaussian_uniforms.transform * position).xyz;
         let clip_space_pos = world_to_clip(transformed_position);
         if(in_frustum(clip_space_pos.xyz)) {
-            // key = bitcast<u32>(1.0 - clip_space_pos.z);
-            key = u32(clip_space_pos.z * 0xFFFF.0) << 16u;
-            key |= u32((clip_space_pos.x * 0.5 + 0.5) * 0xFF.0) << 8u;
-            key |= u32((clip_space_pos.y * 0.5 + 0.5) * 0xFF.0);
+#ifdef SORT_KEY_16
+            key = sort_key_16(transformed_position, clip_space_pos.z);
+#else
+            // 16 bits of clip depth above the low 16 bits of the gaussian index. ranks within a digit are handed out
+            // by atomics, so entries with equal keys can swap from frame to frame, the index makes equal depths
+            // distinct below 65536 gaussians, past that indices 65536 apart share a key. depth stops short of 0xFF00 so
+            // visible entries stay out of the culled top bucket
+            key = u32(clip_space_pos.z * 0xFEFF.0) << 16u;
+            key |= entry_index & 0xFFFFu;
+#endif
         }
         output_entries[entry_index].key = key;
         output_entries[entry_index].value = entry_index;
+#endif
         for(var shift = 0u; shift < #{RADIX_DIGIT_PLACES}u; shift += 1u) {
             let digit = (key >> (shift * #{RADIX_BITS_PER_DIGIT}u)) & (#{RADIX_BASE}u - 1u);
             atomicAdd(&sorting_shared_a.digit_histogram[shift][digit], 1u);
@@ -158,8 +231,16 @@ fn exclusive_scan(local_invocation_index: u32, value: u32) -> u32 {
 @compute @workgroup_size(#{WORKGROUP_INVOCATIONS_C})
 fn radix_sort_c(
     @builtin(local_invocation_id) gl_LocalInvocationID: vec3<u32>,
-    @builtin(global_invocation_id) gl_GlobalInvocationID: vec3<u32>,
+    @builtin(workgroup_id) gl_WorkGroupID: vec3<u32>,
+    @builtin(num_workgroups) gl_NumWorkGroups: vec3<u32>,
 ) {
+    // tiles are handed out by the assignment counter, a folded dispatch can overshoot the tile count and the spare
+    // workgroups must not draw an assignment
+    let workgroup_index = gl_WorkGroupID.y * gl_NumWorkGroups.x + gl_WorkGroupID.x;
+    if(workgroup_index * #{WORKGROUP_ENTRIES_C}u >= entry_count()) {
+        return;
+    }
+
     // Draw an assignment number
     if(gl_LocalInvocationID.x == 0u) {
         sorting_shared_c.entries[0] = atomicAdd(&sorting.assignment_counter, 1u);
@@ -172,7 +253,7 @@ fn radix_sort_c(
     let assignment = sorting_shared_c.entries[0];
     let global_entry_offset = assignment * #{WORKGROUP_ENTRIES_C}u;
     // TODO: Specialize end shader
-    if(gl_LocalInvocationID.x == 0u && assignment * #{WORKGROUP_ENTRIES_C}u + #{WORKGROUP_ENTRIES_C}u >= gaussian_uniforms.count) {
+    if(gl_LocalInvocationID.x == 0u && assignment * #{WORKGROUP_ENTRIES_C}u + #{WORKGROUP_ENTRIES_C}u >= entry_count()) {
         // Last workgroup resets the assignment number for the next pass
         sorting.assignment_counter = 0u;
     }
@@ -213,7 +294,12 @@ fn radix_sort_c(
         }
     }
     atomicStore(&status_counters[assignment][gl_LocalInvocationID.x], 0x80000000u | (global_digit_count + local_digit_count));
-    if(sorting_pass_index == #{RADIX_DIGIT_PLACES}u - 1u && gl_LocalInvocationID.x == #{WORKGROUP_INVOCATIONS_C}u - 2u && global_entry_offset + #{WORKGROUP_ENTRIES_C}u >= gaussian_uniforms.count) {
+    let final_tile = sorting_pass_index == #{RADIX_DIGIT_PLACES}u - 1u
+        && gl_LocalInvocationID.x == #{WORKGROUP_INVOCATIONS_C}u - 2u
+        && global_entry_offset + #{WORKGROUP_ENTRIES_C}u >= entry_count();
+    if(writes_draw_indirect() && final_tile) {
+        // culled entries carry the 0xFFFFFFFF key and land in the last digit bucket, so the prefix up to
+        // that bucket on the final pass is the survivor count and draw_indirect only draws visible gaussians
         draw_indirect.vertex_count = 4u;
         draw_indirect.instance_count = global_digit_count + local_digit_count;
     }
@@ -0,0 +1,197 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    core::FrameCount,
+    render::{
+        renderer::RenderQueue,
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+    utils::HashMap,
+};
+
+use crate::GaussianCloud;
+
+
+// forces every cloud to be fully re-sorted on the next frame, bypassing the cpu sort debounce
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ResortGaussianClouds;
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GaussianCloudSortState {
+    // main world FrameCount of the most recent sort
+    pub frame: u32,
+    // FrameCount of the most recent sort the gpu finished (uploads and radix passes included). later sorts do not
+    // reset it, so a cloud sorted every frame keeps reporting the sorts that already landed
+    pub completed_frame: Option<u32>,
+}
+
+impl GaussianCloudSortState {
+    // the gpu finished the most recent sort
+    pub fn complete(&self) -> bool {
+        self.completed_frame == Some(self.frame)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct SortProgress {
+    state: GaussianCloudSortState,
+    // frames sorted since the last completed one, completion pops them in order
+    pending: VecDeque<u32>,
+}
+
+// sort progress per (view, cloud) entity pair, shared between the main and render worlds
+//
+// cpu sorts record during Update, the radix sort when its node is encoded. neither means the gpu has the order yet:
+// cpu results are uploaded during the following render, and the radix passes only run once the render graph is
+// submitted. after each submission the render world asks the queue for a callback when that work is done, which
+// is when entries become complete. with pipelined rendering this trails the main world by a frame or two, so
+// capture code should trigger ResortGaussianClouds, note the current FrameCount, and poll `is_ready` with it.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct GaussianCloudSortStatus {
+    states: Arc<Mutex<HashMap<(Entity, Entity), SortProgress>>>,
+}
+
+impl GaussianCloudSortStatus {
+    pub fn get(&self, view: Entity, cloud: Entity) -> Option<GaussianCloudSortState> {
+        self.states.lock().unwrap().get(&(view, cloud)).map(|progress| progress.state)
+    }
+
+    // true once a sort from `frame` or later has finished on the gpu
+    pub fn is_ready(&self, view: Entity, cloud: Entity, frame: u32) -> bool {
+        self.get(view, cloud)
+            .and_then(|state| state.completed_frame)
+            .is_some_and(|completed_frame| completed_frame >= frame)
+    }
+
+    pub(crate) fn mark_sorted(&self, view: Entity, cloud: Entity, frame: u32) {
+        let mut states = self.states.lock().unwrap();
+        let progress = states.entry((view, cloud)).or_default();
+
+        progress.state.frame = frame;
+        if progress.pending.back() != Some(&frame) {
+            progress.pending.push_back(frame);
+        }
+    }
+
+    // drops the entries of a despawned view or cloud, entity ids are reused
+    fn remove_entity(&self, entity: Entity) {
+        self.states.lock().unwrap().retain(|(view, cloud), _| *view != entity && *cloud != entity);
+    }
+
+    fn mark_complete(&self, rendered_frame: u32) {
+        for progress in self.states.lock().unwrap().values_mut() {
+            while progress.pending.front().is_some_and(|frame| *frame <= rendered_frame) {
+                progress.state.completed_frame = progress.pending.pop_front();
+            }
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct SortStatusPlugin;
+
+impl Plugin for SortStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ResortGaussianClouds>();
+
+        let status = GaussianCloudSortStatus::default();
+        app.insert_resource(status.clone());
+
+        app.add_systems(PreUpdate, prune_sort_status);
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.insert_resource(status);
+            render_app.add_systems(
+                Render,
+                track_sort_completion.in_set(RenderSet::Cleanup),
+            );
+        }
+    }
+}
+
+
+// entity ids are reused, a despawned view or cloud's progress must not be reported for the next entity with its id
+fn prune_sort_status(
+    status: Res<GaussianCloudSortStatus>,
+    mut removed_clouds: RemovedComponents<Handle<GaussianCloud>>,
+    mut removed_views: RemovedComponents<Camera>,
+) {
+    for entity in removed_clouds.read().chain(removed_views.read()) {
+        status.remove_entity(entity);
+    }
+}
+
+
+// runs after this frame's submission, so the callback fires once its sorts and uploads are on the gpu
+fn track_sort_completion(
+    status: Res<GaussianCloudSortStatus>,
+    render_queue: Res<RenderQueue>,
+    frame_count: Res<FrameCount>,
+) {
+    // the extracted FrameCount was already advanced past the main world frame being rendered
+    let rendered_frame = frame_count.0.wrapping_sub(1);
+
+    let status = status.clone();
+    render_queue.on_submitted_work_done(move || status.mark_complete(rendered_frame));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continuous_sorts_complete() {
+        let status = GaussianCloudSortStatus::default();
+        let (view, cloud) = (Entity::from_raw(0), Entity::from_raw(1));
+
+        // sorted every frame, completion trails the sorts by two frames
+        for frame in 1..=6 {
+            status.mark_sorted(view, cloud, frame);
+            if frame > 2 {
+                status.mark_complete(frame - 2);
+            }
+        }
+
+        let state = status.get(view, cloud).unwrap();
+        assert_eq!(state.frame, 6);
+        assert_eq!(state.completed_frame, Some(4));
+        assert!(!state.complete());
+
+        assert!(status.is_ready(view, cloud, 3));
+        assert!(status.is_ready(view, cloud, 4));
+        assert!(!status.is_ready(view, cloud, 5));
+
+        status.mark_complete(6);
+        assert!(status.get(view, cloud).unwrap().complete());
+        assert!(status.is_ready(view, cloud, 6));
+        assert!(!status.is_ready(Entity::from_raw(2), cloud, 0));
+    }
+
+    #[test]
+    fn test_removed_entities_are_pruned() {
+        let status = GaussianCloudSortStatus::default();
+        let (view, other_view, cloud) = (Entity::from_raw(0), Entity::from_raw(1), Entity::from_raw(2));
+
+        status.mark_sorted(view, cloud, 1);
+        status.mark_sorted(other_view, cloud, 1);
+
+        status.remove_entity(view);
+        assert!(status.get(view, cloud).is_none());
+        assert!(status.get(other_view, cloud).is_some());
+
+        status.remove_entity(cloud);
+        assert!(status.get(other_view, cloud).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::{
     prelude::*,
     asset::LoadState,
+    core::FrameCount,
     math::Vec3A,
     utils::Instant,
 };
@@ -8,9 +9,17 @@ use bevy::{
 use crate::{
     GaussianCloud,
     GaussianCloudSettings,
+    render::occlusion::GaussianCloudOcclusion,
     sort::{
         SortedEntries,
         SortMode,
+        apply_splat_budget,
+        compare_back_to_front,
+        cache::GaussianCloudSortCache,
+        status::{
+            GaussianCloudSortStatus,
+            ResortGaussianClouds,
+        },
     },
 };
 
@@ -31,14 +40,21 @@ pub fn std_sort(
     gaussian_clouds_res: Res<Assets<GaussianCloud>>,
     mut sorted_entries_res: ResMut<Assets<SortedEntries>>,
     gaussian_clouds: Query<(
+        Entity,
         &Handle<GaussianCloud>,
         &Handle<SortedEntries>,
         &GaussianCloudSettings,
     )>,
     cameras: Query<(
+        Entity,
         &Transform,
         &Camera3d,
     )>,
+    mut resort: EventReader<ResortGaussianClouds>,
+    sort_status: Res<GaussianCloudSortStatus>,
+    occlusion: Res<GaussianCloudOcclusion>,
+    mut sort_cache: ResMut<GaussianCloudSortCache>,
+    frame_count: Res<FrameCount>,
     mut last_camera_position: Local<Vec3A>,
     mut last_sort_time: Local<Option<Instant>>,
     mut period: Local<std::time::Duration>,
@@ -49,8 +65,10 @@ pub fn std_sort(
         *period = std::time::Duration::from_millis(100);
     }
 
+    let force = resort.read().count() > 0;
+
     if let Some(last_sort_time) = last_sort_time.as_ref() {
-        if last_sort_time.elapsed() < *period {
+        if last_sort_time.elapsed() < *period && !force {
             return;
         }
     }
@@ -59,6 +77,7 @@ pub fn std_sort(
     let mut performed_sort = false;
 
     for (
+        camera_entity,
         camera_transform,
         _camera,
     ) in cameras.iter() {
@@ -68,22 +87,30 @@ pub fn std_sort(
         if camera_movement {
             *sort_done = false;
             *camera_debounce = true;
-        } else if *sort_done {
+        } else if *sort_done && !force {
             return;
         }
 
-        if *camera_debounce {
+        if force {
+            *last_camera_position = camera_position;
+            *camera_debounce = false;
+        } else if *camera_debounce {
             *last_camera_position = camera_position;
             *camera_debounce = false;
             return;
         }
 
         for (
+            cloud_entity,
             gaussian_cloud_handle,
             sorted_entries_handle,
             settings,
         ) in gaussian_clouds.iter() {
-            if settings.sort_mode != SortMode::Std {
+            if settings.effective_sort_mode() != SortMode::Std {
+                continue;
+            }
+
+            if occlusion.is_occluded(camera_entity, cloud_entity) {
                 continue;
             }
 
@@ -97,29 +124,49 @@ pub fn std_sort(
 
             if let Some(gaussian_cloud) = gaussian_clouds_res.get(gaussian_cloud_handle) {
                 if let Some(sorted_entries) = sorted_entries_res.get_mut(sorted_entries_handle) {
-                    assert_eq!(gaussian_cloud.len(), sorted_entries.sorted.len());
+                    if gaussian_cloud.len() != sorted_entries.sorted.len() {
+                        continue;
+                    }
 
                     *sort_done = true;
                     *last_sort_time = Some(Instant::now());
 
                     performed_sort = true;
 
-                    gaussian_cloud.position_iter()
-                        .zip(sorted_entries.sorted.iter_mut())
-                        .enumerate()
-                        .for_each(|(idx, (position, sort_entry))| {
-                            let position = Vec3A::from_slice(position.as_ref());
-                            let position = settings.transform.compute_affine().transform_point3a(position);
+                    let local_camera_position = settings.cloud_transform().inverse().transform_point3a(camera_position);
+                    let cache_key = sort_cache.key(
+                        cloud_entity,
+                        gaussian_cloud_handle.id(),
+                        local_camera_position.into(),
+                        settings.splat_budget,
+                    );
+
+                    if let Some(cached) = sort_cache.get(&cache_key, sorted_entries.sorted.len()) {
+                        sorted_entries.sorted.copy_from_slice(cached);
+                    } else {
+                        gaussian_cloud.position_iter()
+                            .zip(sorted_entries.sorted.iter_mut())
+                            .enumerate()
+                            .for_each(|(idx, (position, sort_entry))| {
+                                let position = Vec3A::from_slice(position.as_ref());
+                                let position = settings.cloud_transform().transform_point3a(position);
+
+                                let delta = camera_position - position;
+
+                                sort_entry.key = bytemuck::cast(delta.length_squared());
+                                sort_entry.index = idx as u32;
+                            });
+
+                        sorted_entries.sorted.sort_unstable_by(compare_back_to_front);
 
-                            let delta = camera_position - position;
+                        if let Some(budget) = settings.splat_budget {
+                            apply_splat_budget(gaussian_cloud, &mut sorted_entries.sorted, budget);
+                        }
 
-                            sort_entry.key = bytemuck::cast(delta.length_squared());
-                            sort_entry.index = idx as u32;
-                        });
+                        sort_cache.insert(cache_key, &sorted_entries.sorted);
+                    }
 
-                    sorted_entries.sorted.sort_unstable_by(|a, b| {
-                        bytemuck::cast::<u32, f32>(b.key).partial_cmp(&bytemuck::cast::<u32, f32>(a.key)).unwrap()
-                    });
+                    sort_status.mark_sorted(camera_entity, cloud_entity, frame_count.0);
 
                     // TODO: update DrawIndirect buffer during sort phase (GPU sort will override default DrawIndirect)
                 }
@@ -0,0 +1,283 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    asset::LoadState,
+    core::FrameCount,
+    math::Vec3A,
+    render::{
+        render_asset::{
+            prepare_assets,
+            RenderAssets,
+        },
+        renderer::RenderQueue,
+        MainWorld,
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+    utils::{
+        HashMap,
+        HashSet,
+    },
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    render::occlusion::GaussianCloudOcclusion,
+    sort::{
+        GpuSortedEntry,
+        SortEntry,
+        SortedEntries,
+        SortMode,
+        compare_back_to_front,
+        status::{
+            GaussianCloudSortStatus,
+            ResortGaussianClouds,
+        },
+    },
+};
+
+
+#[derive(Default)]
+pub struct TemporalSortPlugin;
+
+impl Plugin for TemporalSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, temporal_sort);
+
+        // without a render app nothing takes the writes, the sort then modifies the assets instead
+        if app.get_sub_app(RenderApp).is_some() {
+            app.init_resource::<TemporalSortWrites>();
+        }
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<TemporalSortWrites>()
+                .add_systems(ExtractSchedule, extract_temporal_sort_writes)
+                .add_systems(
+                    Render,
+                    write_temporal_sort_ranges
+                        .in_set(RenderSet::PrepareAssets)
+                        .after(prepare_assets::<GpuSortedEntry>),
+                );
+        }
+    }
+}
+
+
+// the segments reordered by the temporal sort. they are written into the existing sorted entry buffer instead of
+// modifying the SortedEntries asset, which would upload every entry again
+#[derive(Debug, Default, Resource)]
+pub struct TemporalSortWrites {
+    writes: Vec<(AssetId<SortedEntries>, usize, Vec<SortEntry>)>,
+}
+
+
+// each frame re-sorts one overlapping segment of the previous order, sweeping the segments front to back every
+// window frames. an entry rides forward through every segment of a sweep but moves back by at most one segment
+// start per sweep, so a scrambled order converges within window sweeps (window² frames), a coherent one much sooner
+pub fn temporal_sort_range(
+    len: usize,
+    window_size: u32,
+    frame: u32,
+) -> Range<usize> {
+    let window_size = window_size.max(1) as usize;
+    let chunk_size = len.div_ceil(window_size).max(1);
+
+    let start = (frame as usize % window_size) * chunk_size;
+    let end = (start + 2 * chunk_size).min(len);
+
+    start.min(end)..end
+}
+
+// one incremental pass over `entries`, returns the range it reordered
+pub fn refine_temporal_order(
+    entries: &mut [SortEntry],
+    window_size: u32,
+    frame: u32,
+    depth: impl Fn(u32) -> u32,
+) -> Option<Range<usize>> {
+    let range = temporal_sort_range(entries.len(), window_size, frame);
+    let mut segment = entries[range.clone()].to_vec();
+
+    segment.iter_mut()
+        .for_each(|entry| entry.key = depth(entry.index));
+    segment.sort_unstable_by(compare_back_to_front);
+
+    let moved = |(sorted, previous): (&SortEntry, &SortEntry)| sorted.index != previous.index;
+    let first = segment.iter().zip(entries[range.clone()].iter()).position(moved);
+    let last = segment.iter().zip(entries[range.clone()].iter()).rposition(moved);
+
+    // keys are refreshed either way, the gpu only needs the indices that moved
+    entries[range.clone()].copy_from_slice(&segment);
+
+    Some(range.start + first?..range.start + last? + 1)
+}
+
+
+#[allow(clippy::too_many_arguments)]
+pub fn temporal_sort(
+    asset_server: Res<AssetServer>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    mut sorted_entries_res: ResMut<Assets<SortedEntries>>,
+    gaussian_clouds: Query<(
+        Entity,
+        &Handle<GaussianCloud>,
+        &Handle<SortedEntries>,
+        &GaussianCloudSettings,
+    )>,
+    cameras: Query<(
+        Entity,
+        &Camera,
+        &Transform,
+    ), With<Camera3d>>,
+    mut resort: EventReader<ResortGaussianClouds>,
+    mut sorted_entries_events: EventReader<AssetEvent<SortedEntries>>,
+    sort_status: Res<GaussianCloudSortStatus>,
+    occlusion: Res<GaussianCloudOcclusion>,
+    mut writes: Option<ResMut<TemporalSortWrites>>,
+    frame_count: Res<FrameCount>,
+    mut frame: Local<u32>,
+    mut orders: Local<HashMap<AssetId<SortedEntries>, Vec<SortEntry>>>,
+) {
+    // a forced resort replaces the incremental segment with a full sort
+    if resort.read().count() > 0 {
+        orders.clear();
+    }
+
+    // entries changed by anything but this sort no longer match the order it refines
+    for event in sorted_entries_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            let current = sorted_entries_res.get(*id).map(|sorted_entries| &sorted_entries.sorted);
+
+            if orders.get(id).is_some_and(|order| Some(order) != current) {
+                orders.remove(id);
+            }
+        }
+    }
+
+    let live = gaussian_clouds.iter()
+        .map(|(_, _, sorted_entries_handle, _)| sorted_entries_handle.id())
+        .collect::<HashSet<_>>();
+    orders.retain(|id, _| live.contains(id));
+
+    *frame = frame.wrapping_add(1);
+
+    for (
+        cloud_entity,
+        gaussian_cloud_handle,
+        sorted_entries_handle,
+        settings,
+    ) in gaussian_clouds.iter() {
+        if settings.effective_sort_mode() != SortMode::Temporal {
+            continue;
+        }
+
+        // every view draws the same entries (see GpuSortedEntry), refining them against several cameras would never
+        // converge, so each cloud follows the last drawn active camera that sees it
+        let Some((camera_entity, _, camera_transform)) = cameras.iter()
+            .filter(|(camera_entity, camera, _)| camera.is_active && !occlusion.is_occluded(*camera_entity, cloud_entity))
+            .max_by_key(|(_, camera, _)| camera.order) else {
+            continue;
+        };
+        let camera_position = camera_transform.compute_affine().translation;
+
+        if Some(LoadState::Loading) == asset_server.get_load_state(gaussian_cloud_handle) {
+            continue;
+        }
+
+        if Some(LoadState::Loading) == asset_server.get_load_state(sorted_entries_handle) {
+            continue;
+        }
+
+        let Some(gaussian_cloud) = gaussian_clouds_res.get(gaussian_cloud_handle) else {
+            continue;
+        };
+
+        let Some(sorted_entries) = sorted_entries_res.get(sorted_entries_handle) else {
+            continue;
+        };
+
+        if gaussian_cloud.len() != sorted_entries.sorted.len() {
+            continue;
+        }
+
+        let transform = settings.cloud_transform();
+        let depth = |index: u32| {
+            let position = Vec3A::from_slice(gaussian_cloud.position(index as usize).as_ref());
+            let delta = camera_position - transform.transform_point3a(position);
+
+            bytemuck::cast::<f32, u32>(delta.length_squared())
+        };
+
+        // the first pass has no coherent order to refine, so it sorts everything and uploads it whole
+        let Some(order) = orders.get_mut(&sorted_entries_handle.id()) else {
+            let mut order = (0..sorted_entries.sorted.len() as u32)
+                .map(|index| SortEntry {
+                    key: depth(index),
+                    index,
+                })
+                .collect::<Vec<_>>();
+            order.sort_unstable_by(compare_back_to_front);
+
+            sorted_entries_res.get_mut(sorted_entries_handle).unwrap().sorted.copy_from_slice(&order);
+            orders.insert(sorted_entries_handle.id(), order);
+
+            sort_status.mark_sorted(camera_entity, cloud_entity, frame_count.0);
+            continue;
+        };
+
+        let reordered = refine_temporal_order(
+            order,
+            settings.temporal_sort_window_size,
+            *frame,
+            depth,
+        );
+
+        if let Some(range) = reordered {
+            match writes.as_mut() {
+                #[cfg(feature = "buffer_storage")]
+                Some(writes) => writes.writes.push((sorted_entries_handle.id(), range.start, order[range].to_vec())),
+                // textures are copied from the asset
+                _ => sorted_entries_res.get_mut(sorted_entries_handle).unwrap().sorted.copy_from_slice(order),
+            }
+        }
+
+        sort_status.mark_sorted(camera_entity, cloud_entity, frame_count.0);
+    }
+}
+
+
+fn extract_temporal_sort_writes(
+    mut commands: Commands,
+    mut main_world: ResMut<MainWorld>,
+) {
+    let writes = std::mem::take(&mut *main_world.resource_mut::<TemporalSortWrites>());
+    commands.insert_resource(writes);
+}
+
+// entries prepared this frame were uploaded from the asset the temporal sort started from, the segments refined since
+// are written on top
+fn write_temporal_sort_ranges(
+    writes: Res<TemporalSortWrites>,
+    render_queue: Res<RenderQueue>,
+    sorted_entries: Res<RenderAssets<GpuSortedEntry>>,
+) {
+    for (id, start, entries) in writes.writes.iter() {
+        let Some(gpu_sorted_entries) = sorted_entries.get(*id) else {
+            continue;
+        };
+
+        if start + entries.len() > gpu_sorted_entries.count {
+            continue;
+        }
+
+        render_queue.write_buffer(
+            &gpu_sorted_entries.sorted_entry_buffer,
+            (start * std::mem::size_of::<SortEntry>()) as u64,
+            bytemuck::cast_slice(entries),
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+    render::view::RenderLayers,
+    utils::HashSet,
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    gaussian::packed::Gaussian,
+    render::cloud_visible,
+};
+
+
+// merges the visible clouds sharing a render layer set and settings into one world-space cloud so a single sort
+// interleaves their gaussians. clouds whose settings differ (transform aside) are not merged with each other, a
+// group rebuilds when a source's handle, transform or the group's members change
+#[derive(
+    Resource,
+    Clone,
+    Debug,
+    Default,
+    Reflect,
+)]
+#[reflect(Resource)]
+pub struct GaussianCloudUnifiedSort {
+    pub enabled: bool,
+}
+
+
+#[derive(Component, Default)]
+pub struct UnifiedGaussianCloud;
+
+// a cloud drawn through a unified cloud, extraction skips it and leaves its Visibility alone
+#[derive(Component, Default)]
+pub struct UnifiedGaussianCloudSource;
+
+
+#[derive(Default)]
+pub struct UnifiedSortPlugin;
+
+impl Plugin for UnifiedSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianCloudUnifiedSort>();
+        app.init_resource::<GaussianCloudUnifiedSort>();
+
+        app.add_systems(Update, merge_unified_clouds);
+    }
+}
+
+
+struct UnifiedGroup {
+    render_layers: RenderLayers,
+    // the sources' shared settings with an identity transform
+    settings: GaussianCloudSettings,
+    sources: Vec<(Entity, AssetId<GaussianCloud>, Affine3A)>,
+    entity: Option<Entity>,
+}
+
+
+#[allow(clippy::type_complexity)]
+fn merge_unified_clouds(
+    mut commands: Commands,
+    unified_sort: Res<GaussianCloudUnifiedSort>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    mut ev_asset: EventReader<AssetEvent<GaussianCloud>>,
+    gaussian_clouds: Query<
+        (
+            Entity,
+            &Handle<GaussianCloud>,
+            &GaussianCloudSettings,
+            &Visibility,
+            Option<&InheritedVisibility>,
+            Option<&RenderLayers>,
+            Has<UnifiedGaussianCloudSource>,
+        ),
+        Without<UnifiedGaussianCloud>,
+    >,
+    mut merged_groups: Local<Vec<UnifiedGroup>>,
+) {
+    let modified = ev_asset.read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Added { id } => Some(*id),
+            AssetEvent::Modified { id } => Some(*id),
+            AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    if !unified_sort.enabled {
+        for (entity, .., is_source) in gaussian_clouds.iter() {
+            if is_source {
+                commands.entity(entity).remove::<UnifiedGaussianCloudSource>();
+            }
+        }
+
+        for entity in merged_groups.drain(..).filter_map(|group| group.entity) {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    let mut groups: Vec<UnifiedGroup> = Vec::new();
+    for (
+        entity,
+        handle,
+        settings,
+        visibility,
+        inherited_visibility,
+        render_layers,
+        _,
+    ) in gaussian_clouds.iter() {
+        if !cloud_visible(visibility, inherited_visibility) || !gaussian_clouds_res.contains(handle.id()) {
+            continue;
+        }
+
+        let render_layers = render_layers.cloned().unwrap_or_default();
+        let shared_settings = GaussianCloudSettings {
+            transform: Transform::IDENTITY,
+            coordinate_convention: default(),
+            ..settings.clone()
+        };
+        let source = (entity, handle.id(), settings.cloud_transform());
+
+        let group = groups.iter_mut()
+            .find(|group| group.render_layers == render_layers && group.settings == shared_settings);
+        match group {
+            Some(group) => group.sources.push(source),
+            None => groups.push(UnifiedGroup {
+                render_layers,
+                settings: shared_settings,
+                sources: vec![source],
+                entity: None,
+            }),
+        }
+    }
+
+    // a lone cloud has nothing to interleave with and keeps drawing itself
+    groups.retain(|group| group.sources.len() > 1);
+
+    for group in groups.iter_mut() {
+        let unchanged = merged_groups.iter().position(|merged| {
+            merged.render_layers == group.render_layers
+                && merged.sources == group.sources
+                && !merged.sources.iter().any(|(_, id, _)| modified.contains(id))
+        });
+
+        if let Some(index) = unchanged {
+            let merged = merged_groups.swap_remove(index);
+            group.entity = merged.entity;
+
+            if merged.settings != group.settings {
+                if let Some(entity) = group.entity {
+                    commands.entity(entity).insert(group.settings.clone());
+                }
+            }
+
+            continue;
+        }
+
+        let mut gaussians: Vec<Gaussian> = Vec::new();
+        for (_, id, transform) in &group.sources {
+            let Some(cloud) = gaussian_clouds_res.get(*id) else {
+                continue;
+            };
+
+            let mut cloud = cloud.clone();
+            cloud.transform(*transform);
+            gaussians.extend(cloud.gaussian_iter());
+        }
+
+        // a fresh entity also gets fresh sorted entries sized to the merged cloud
+        group.entity = Some(commands.spawn((
+            GaussianSplattingBundle {
+                cloud: gaussian_clouds_res.add(GaussianCloud::from_gaussians(gaussians)),
+                settings: group.settings.clone(),
+                ..default()
+            },
+            group.render_layers.clone(),
+            UnifiedGaussianCloud,
+            Name::new("unified_gaussian_cloud"),
+        )).id());
+    }
+
+    for entity in merged_groups.drain(..).filter_map(|group| group.entity) {
+        commands.entity(entity).despawn();
+    }
+
+    let sources = groups.iter()
+        .flat_map(|group| group.sources.iter().map(|(entity, ..)| *entity))
+        .collect::<HashSet<_>>();
+    for (entity, .., is_source) in gaussian_clouds.iter() {
+        match (sources.contains(&entity), is_source) {
+            (true, false) => { commands.entity(entity).insert(UnifiedGaussianCloudSource); },
+            (false, true) => { commands.entity(entity).remove::<UnifiedGaussianCloudSource>(); },
+            _ => {},
+        }
+    }
+
+    *merged_groups = groups;
+}
@@ -1,17 +1,1211 @@
+use bevy::{
+    math::{
+        Affine3A,
+        Mat3,
+        Quat,
+        Ray3d,
+        Vec2,
+        Vec3,
+        Vec4,
+    },
+    transform::components::Transform,
+};
+
 use bevy_gaussian_splatting::{
+    Gaussian,
     GaussianCloud,
+    GaussianCloudDiffError,
+    GaussianCloudDiffMetric,
+    GaussianCloudPatch,
+    GaussianCloudPatchError,
+    GaussianCloudSettings,
+    CloudSnapshot,
+    GaussianCloudMeshMode,
+    GaussianCloudMeshSettings,
+    gaussian::{
+        covariance::{
+            compute_covariance_3d,
+            transform_covariance_3d,
+        },
+        merge::GaussianCloudMergeSettings,
+        resample::GaussianCloudResampleSettings,
+        settings::GaussianCloudCoordinateConvention,
+    },
     io::codec::GaussianCloudCodec,
     random_gaussians,
 };
 
 
+fn gaussian_at(position: [f32; 3], opacity: f32) -> Gaussian {
+    Gaussian {
+        rotation: [1.0, 0.0, 0.0, 0.0].into(),
+        position_visibility: [position[0], position[1], position[2], 1.0].into(),
+        scale_opacity: [0.5, 0.5, 0.5, opacity].into(),
+        ..Default::default()
+    }
+}
+
+
 #[test]
 fn test_codec() {
     let count = 100;
 
     let gaussians = random_gaussians(count);
     let encoded = gaussians.encode();
-    let decoded = GaussianCloud::decode(encoded.as_slice());
+    let decoded = GaussianCloud::decode(encoded.as_slice()).unwrap();
 
     assert_eq!(gaussians, decoded);
 }
+
+#[test]
+fn test_weighted_centroid() {
+    let cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 1.0),
+        gaussian_at([8.0, 0.0, 0.0], 0.0),
+    ]);
+
+    assert_eq!(cloud.centroid(), Vec3::new(4.0, 0.0, 0.0));
+    assert_eq!(cloud.weighted_centroid(), Vec3::new(1.0, 0.0, 0.0));
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_rotate_spherical_harmonics() {
+    let mut gaussian = gaussian_at([1.0, 0.0, 0.0], 1.0);
+
+    // red channel of the first band's y lobe
+    gaussian.spherical_harmonic.set(3, 1.0);
+
+    let mut cloud = GaussianCloud::from_gaussians(vec![gaussian]);
+    cloud.rotate(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+
+    let rotated = cloud.gaussian(0);
+    let position = Vec3::from_array(rotated.position_visibility.position);
+    assert!(position.abs_diff_eq(Vec3::Y, 1e-5));
+
+    // a quarter turn about z maps the y lobe onto the negated x lobe
+    assert!(rotated.spherical_harmonic.get(3).abs() < 1e-2);
+    assert!((rotated.spherical_harmonic.get(9) + 1.0).abs() < 1e-2);
+    assert!(rotated.spherical_harmonic.get(6).abs() < 1e-2);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_apply_patch() {
+    let mut cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 1.0),
+        gaussian_at([3.0, 0.0, 0.0], 1.0),
+    ]);
+
+    let patch = GaussianCloudPatch {
+        updates: vec![
+            (2, gaussian_at([5.0, 0.0, 0.0], 1.0)),
+            (3, gaussian_at([6.0, 0.0, 0.0], 1.0)),
+        ],
+        appends: vec![gaussian_at([7.0, 0.0, 0.0], 1.0)],
+        removals: vec![1],
+    };
+    assert_eq!(patch.dirty_ranges(), vec![2..4]);
+
+    // out of range indices are rejected without touching the cloud
+    let out_of_range = GaussianCloudPatch {
+        updates: vec![(0, gaussian_at([9.0, 0.0, 0.0], 1.0)), (4, gaussian_at([9.0, 0.0, 0.0], 1.0))],
+        ..Default::default()
+    };
+    assert_eq!(
+        cloud.apply_patch(&out_of_range),
+        Err(GaussianCloudPatchError::IndexOutOfRange { index: 4, count: 4 }),
+    );
+    assert_eq!(cloud.position(0)[0], 0.0);
+
+    cloud.apply_patch(&patch).unwrap();
+
+    let xs = cloud.position_iter()
+        .map(|position| position[0])
+        .collect::<Vec<f32>>();
+    assert_eq!(xs, vec![0.0, 5.0, 6.0, 7.0]);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_snapshot_restore() {
+    let original = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 1.0),
+    ]);
+    let xs = |cloud: &GaussianCloud| cloud.position_iter()
+        .map(|position| position[0])
+        .collect::<Vec<f32>>();
+
+    let mut cloud = original.clone();
+    let move_patch = GaussianCloudPatch {
+        updates: vec![
+            (1, gaussian_at([4.0, 0.0, 0.0], 1.0)),
+            (1, gaussian_at([5.0, 0.0, 0.0], 1.0)),
+        ],
+        ..Default::default()
+    };
+    let undo = cloud.snapshot_patch(&move_patch);
+    assert!(matches!(&undo, CloudSnapshot::Diff(diff) if diff.updates.len() == 1));
+
+    cloud.apply_patch(&move_patch).unwrap();
+    assert_eq!(xs(&cloud), vec![0.0, 5.0, 2.0]);
+
+    cloud.restore(&undo).unwrap();
+    assert_eq!(xs(&cloud), xs(&original));
+
+    let remove_patch = GaussianCloudPatch {
+        removals: vec![0],
+        ..Default::default()
+    };
+    let undo = cloud.snapshot_patch(&remove_patch);
+    assert!(matches!(undo, CloudSnapshot::Full(_)));
+
+    cloud.apply_patch(&remove_patch).unwrap();
+    assert_eq!(cloud.len(), 2);
+
+    cloud.restore(&undo).unwrap();
+    assert_eq!(xs(&cloud), xs(&original));
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_paint_opacity() {
+    let original = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 0.5),
+        gaussian_at([1.0, 0.0, 0.0], 0.25),
+        gaussian_at([2.0, 0.0, 0.0], 0.75),
+    ]);
+    let opacities = |cloud: &GaussianCloud| (0..cloud.len())
+        .map(|index| cloud.opacity(index))
+        .collect::<Vec<f32>>();
+
+    let mut cloud = original.clone();
+    let stroke = cloud.paint_opacity(&[1, 2, 1], -0.5);
+    assert_eq!(stroke.patch.dirty_ranges(), vec![1..3]);
+
+    // clamped at zero, the unbrushed gaussian is untouched
+    assert_eq!(opacities(&cloud), vec![0.5, 0.0, 0.25]);
+
+    cloud.paint_opacity(&[0], 1.0);
+    assert_eq!(cloud.opacity(0), 1.0);
+
+    cloud.restore(&stroke.undo).unwrap();
+    assert_eq!(opacities(&cloud)[1..], opacities(&original)[1..]);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_resample_fills_gap() {
+    let cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+        gaussian_at([3.0, 0.0, 0.0], 1.0),
+        gaussian_at([4.0, 0.0, 0.0], 1.0),
+    ]);
+
+    let settings = GaussianCloudResampleSettings {
+        resolution: 8,
+        target_density: 0.8,
+        opacity_threshold: 0.1,
+    };
+    let resampled = cloud.resample(&settings);
+    assert!(resampled.len() > cloud.len());
+
+    // originals come first and are unchanged
+    for index in 0..cloud.len() {
+        assert_eq!(resampled.gaussian(index), cloud.gaussian(index));
+    }
+
+    // a fill lands in the gap around x = 2, none stray beyond the splatted footprints
+    let fills = (cloud.len()..resampled.len())
+        .map(|index| resampled.gaussian(index))
+        .collect::<Vec<_>>();
+    assert!(fills.iter().any(|fill| (fill.position_visibility.position[0] - 2.0).abs() < 0.5));
+    for fill in fills.iter() {
+        assert!(fill.scale_opacity.opacity <= settings.target_density);
+        assert!(fill.position_visibility.position[0] > -1.5 && fill.position_visibility.position[0] < 5.5);
+    }
+
+    let saturated = cloud.resample(&GaussianCloudResampleSettings {
+        target_density: 0.0,
+        ..settings
+    });
+    assert_eq!(saturated.len(), cloud.len());
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_merge_dedup() {
+    let count = 1000;
+    let cloud = random_gaussians(count);
+
+    let merged = GaussianCloud::merge([&cloud, &cloud]);
+    assert_eq!(merged.len(), 2 * count);
+
+    // two overlapping copies collapse back to roughly the original, random neighbours closer than the epsilon with
+    // a similar rotation may merge too
+    let deduped = GaussianCloud::merge_dedup([&cloud, &cloud], &GaussianCloudMergeSettings::default());
+    assert!(deduped.len() <= count);
+    assert!(deduped.len() >= count * 95 / 100);
+
+    // averaged, not accumulated
+    let total_opacity = |cloud: &GaussianCloud| (0..cloud.len()).map(|index| cloud.opacity(index)).sum::<f32>();
+    assert!(total_opacity(&deduped) <= total_opacity(&cloud) * 1.01);
+
+    // distinct orientations at the same center stay apart
+    let mut rotated = gaussian_at([0.0, 0.0, 0.0], 0.5);
+    rotated.rotation = [0.0, 1.0, 0.0, 0.0].into();
+    let pair = GaussianCloud::from_gaussians(vec![gaussian_at([0.0, 0.0, 0.0], 0.5), rotated]);
+    let shifted = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0005, 0.0, 0.0], 1.0),
+        gaussian_at([5.0, 0.0, 0.0], 1.0),
+    ]);
+
+    let deduped = GaussianCloud::merge_dedup([&pair, &shifted], &GaussianCloudMergeSettings::default());
+    assert_eq!(deduped.len(), 3);
+
+    let collapsed = deduped.gaussian(0);
+    assert!((collapsed.position_visibility.position[0] - 0.00025).abs() < 1e-6);
+    assert!((collapsed.scale_opacity.opacity - 0.75).abs() < 1e-3);
+
+    let concatenated = GaussianCloud::merge_dedup([&pair, &shifted], &GaussianCloudMergeSettings {
+        position_epsilon: 0.0,
+        ..Default::default()
+    });
+    assert_eq!(concatenated.len(), 4);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_to_mesh() {
+    let cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 0.9),
+        gaussian_at([4.0, 0.0, 0.0], 0.1),
+    ]);
+
+    let settings = GaussianCloudMeshSettings {
+        rings: 3,
+        ..Default::default()
+    };
+    let ellipsoids = cloud.to_mesh(&settings);
+    assert_eq!(ellipsoids.count_vertices(), 2 * 4 * 7);
+    assert_eq!(ellipsoids.indices().unwrap().len(), 2 * 4 * 9 * 3);
+
+    let billboards = cloud.to_mesh(&GaussianCloudMeshSettings {
+        mode: GaussianCloudMeshMode::Billboards,
+        max_gaussians: Some(1),
+        ..settings
+    });
+    assert_eq!(billboards.count_vertices(), 4);
+    assert_eq!(billboards.indices().unwrap().len(), 6);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_bounding_sphere() {
+    let cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 1.0),
+    ]);
+
+    let centers = cloud.bounding_sphere(0.0);
+    assert_eq!(Vec3::from(centers.center), Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(centers.radius, 1.0);
+
+    assert_eq!(cloud.bounding_sphere(3.0).radius, 2.5);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_transform() {
+    let mut cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+    ]);
+
+    cloud.transform(Affine3A::from_scale_rotation_translation(
+        Vec3::splat(2.0),
+        Quat::IDENTITY,
+        Vec3::new(1.0, 0.0, 0.0),
+    ));
+
+    let transformed = cloud.gaussian(0);
+    assert_eq!(transformed.position_visibility.position, [3.0, 0.0, 0.0]);
+    assert_eq!(transformed.scale_opacity.scale, [1.0, 1.0, 1.0]);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_transform_non_uniform_scale() {
+    // an elongated gaussian turned 45 degrees about z, stretched 2x along world x
+    let rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_4);
+    let mut gaussian = gaussian_at([1.0, 0.0, 0.0], 1.0);
+    gaussian.rotation.rotation = [rotation.w, rotation.x, rotation.y, rotation.z];
+    gaussian.scale_opacity.scale = [1.0, 0.5, 0.5];
+
+    let mut cloud = GaussianCloud::from_gaussians(vec![gaussian]);
+    let scale = Vec3::new(2.0, 1.0, 1.0);
+    cloud.transform(Affine3A::from_scale(scale));
+
+    let transformed = cloud.gaussian(0);
+    let [w, x, y, z] = transformed.rotation.rotation;
+    let cov3d = compute_covariance_3d(
+        Vec4::new(w, x, y, z),
+        Vec3::from_array(transformed.scale_opacity.scale),
+    );
+
+    // cloud space variances are (1 + 0.25) / 2 on x and y with covariance (1 - 0.25) / 2, the 2x stretch
+    // quadruples the x variance and doubles the covariance, an orthographic view down z sees the upper 2x2
+    let expected = [2.5, 0.75, 0.0, 0.625, 0.0, 0.25];
+    for (actual, expected) in cov3d.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-3, "{:?} != {:?}", cov3d, expected);
+    }
+
+    let source = compute_covariance_3d(Vec4::new(rotation.w, rotation.x, rotation.y, rotation.z), Vec3::new(1.0, 0.5, 0.5));
+    let shader = transform_covariance_3d(source, Mat3::from_diagonal(scale));
+    for (actual, expected) in shader.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-5);
+    }
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_mirror() {
+    let rotation = Quat::from_euler(bevy::math::EulerRot::XYZ, 0.3, -0.7, 1.1);
+    let mut gaussian = gaussian_at([1.0, 2.0, 3.0], 1.0);
+    gaussian.rotation.rotation = [rotation.w, rotation.x, rotation.y, rotation.z];
+    gaussian.scale_opacity.scale = [1.0, 0.5, 0.25];
+    for index in 3..12 {
+        gaussian.spherical_harmonic.set(index, index as f32 * 0.1);
+    }
+
+    let original = GaussianCloud::from_gaussians(vec![gaussian]);
+    let mut cloud = original.clone();
+
+    cloud.mirror(0);
+    let mirrored = cloud.gaussian(0);
+    assert_eq!(mirrored.position_visibility.position, [-1.0, 2.0, 3.0]);
+
+    // the mirrored covariance is D * Sigma * D
+    let [w, x, y, z] = mirrored.rotation.rotation;
+    let cov3d = compute_covariance_3d(Vec4::new(w, x, y, z), Vec3::from_array(mirrored.scale_opacity.scale));
+    let source = compute_covariance_3d(Vec4::new(rotation.w, rotation.x, rotation.y, rotation.z), Vec3::new(1.0, 0.5, 0.25));
+    let expected = transform_covariance_3d(source, Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0)));
+    for (actual, expected) in cov3d.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-3, "{:?} != {:?}", cov3d, expected);
+    }
+
+    // the first band's x lobe flips, y and z are kept
+    assert!((mirrored.spherical_harmonic.get(9) + 0.9).abs() < 1e-3);
+    assert!((mirrored.spherical_harmonic.get(3) - 0.3).abs() < 1e-3);
+
+    cloud.mirror(0);
+    let restored = cloud.gaussian(0);
+    let source = original.gaussian(0);
+    assert_eq!(restored.position_visibility.position, source.position_visibility.position);
+    for (actual, expected) in restored.rotation.rotation.iter().zip(source.rotation.rotation.iter()) {
+        assert!((actual - expected).abs() < 1e-6);
+    }
+    for index in 0..12 {
+        assert!((restored.spherical_harmonic.get(index) - source.spherical_harmonic.get(index)).abs() < 1e-3);
+    }
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_recenter_and_normalize() {
+    let mut cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([10.0, 0.0, 0.0], 1.0),
+        gaussian_at([14.0, 2.0, 0.0], 1.0),
+    ]);
+
+    let transform = cloud.recenter_and_normalize();
+
+    let positions = cloud.position_iter()
+        .map(|position| Vec3::from_array(*position))
+        .collect::<Vec<Vec3>>();
+    assert!(positions[0].abs_diff_eq(Vec3::new(-0.5, -0.25, 0.0), 1e-6));
+    assert!(positions[1].abs_diff_eq(Vec3::new(0.5, 0.25, 0.0), 1e-6));
+
+    let source = transform.inverse().transform_point3(positions[1]);
+    assert!(source.abs_diff_eq(Vec3::new(14.0, 2.0, 0.0), 1e-5));
+}
+
+#[test]
+fn test_diff_channel() {
+    let reference = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 0.0, 0.0], 0.5),
+        gaussian_at([2.0, 0.0, 0.0], 0.5),
+        gaussian_at([3.0, 0.0, 0.0], 0.5),
+    ]);
+    let mut checkpoint = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 2.0, 0.0], 0.5),
+        gaussian_at([2.0, 0.0, 0.5], 0.25),
+        gaussian_at([3.0, 0.0, 0.0], 0.5),
+    ]);
+
+    assert_eq!(
+        checkpoint.diff(&reference, GaussianCloudDiffMetric::Opacity).unwrap(),
+        vec![0.0, 0.0, 0.25, 0.0],
+    );
+
+    let range = checkpoint.set_diff_channel(&reference, GaussianCloudDiffMetric::Position).unwrap();
+    assert_eq!(range, Vec2::new(0.0, 2.0));
+    assert_eq!(checkpoint.channel, vec![0.0, 2.0, 0.5, 0.0]);
+
+    let pruned = GaussianCloud::from_gaussians(vec![gaussian_at([0.0, 0.0, 0.0], 1.0)]);
+    assert_eq!(
+        pruned.diff(&reference, GaussianCloudDiffMetric::Position),
+        Err(GaussianCloudDiffError::CountMismatch { count: 1, reference_count: 4 }),
+    );
+}
+
+#[test]
+fn test_channel_subset() {
+    let mut cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 1.0),
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 1.0),
+    ]);
+    cloud.set_channel(vec![0.25, 0.5, 0.75]);
+
+    let subset = cloud.subset(&[2, 0]);
+    assert_eq!(subset.channel, vec![0.75, 0.25]);
+}
+
+#[test]
+fn test_auto_exposure() {
+    let grey = |value: f32, opacity: f32| {
+        let mut gaussian = gaussian_at([0.0, 0.0, 0.0], opacity);
+        for channel in 0..3 {
+            gaussian.spherical_harmonic.set(channel, (value - 0.5) / 0.282_094_8);
+        }
+        gaussian
+    };
+
+    // the dimmer gaussian carries three quarters of the opacity
+    let cloud = GaussianCloud::from_gaussians(vec![
+        grey(0.1, 0.75),
+        grey(0.9, 0.25),
+        grey(1.0, 0.0),
+        grey(0.5, 0.0),
+    ]);
+
+    assert!((cloud.mean_luminance() - 0.3).abs() < 1e-3);
+    assert!((cloud.auto_exposure(0.6) - 2.0).abs() < 1e-2);
+
+    assert_eq!(GaussianCloud::from_gaussians(vec![grey(0.0, 1.0)]).auto_exposure(0.5), 1.0);
+}
+
+#[test]
+fn test_world_clip_planes() {
+    let mut settings = GaussianCloudSettings {
+        transform: Transform::from_xyz(2.0, 0.0, 0.0).with_scale(Vec3::splat(2.0)),
+        ..Default::default()
+    };
+    settings.clip_planes[0] = Vec4::new(1.0, 0.0, 0.0, -1.0);
+
+    let planes = settings.world_clip_planes();
+    let distance = |world: Vec3| planes[0].truncate().dot(world) + planes[0].w;
+
+    // local x = 1 lands on world x = 4
+    assert!(distance(Vec3::new(4.0, 5.0, -3.0)).abs() < 1e-5);
+    assert!(distance(Vec3::new(4.5, 0.0, 0.0)) > 0.0);
+    assert!(distance(Vec3::new(3.5, 0.0, 0.0)) < 0.0);
+
+    assert_eq!(planes[1], Vec4::ZERO);
+
+    // colmap flips cloud space y before the transform, local y = 1 lands on world y = 0
+    let mut settings = GaussianCloudSettings {
+        transform: Transform::from_xyz(0.0, 1.0, 0.0),
+        coordinate_convention: GaussianCloudCoordinateConvention::Colmap,
+        ..Default::default()
+    };
+    settings.clip_planes[0] = Vec4::new(0.0, 1.0, 0.0, -1.0);
+
+    let planes = settings.world_clip_planes();
+    let distance = |world: Vec3| planes[0].truncate().dot(world) + planes[0].w;
+
+    assert!(distance(Vec3::new(3.0, 0.0, 2.0)).abs() < 1e-5);
+    assert!(distance(Vec3::new(0.0, -0.5, 0.0)) > 0.0);
+    assert!(distance(Vec3::new(0.0, 0.5, 0.0)) < 0.0);
+}
+
+#[test]
+fn test_opacity_curve_uniform() {
+    let identity = GaussianCloudSettings::default().opacity_curve_uniform();
+    assert_eq!(identity[0].x, 0.0);
+    assert!((identity[1].y - 5.0 / 15.0).abs() < 1e-6);
+    assert_eq!(identity[3].w, 1.0);
+
+    let mut curve = [0.0; 16];
+    curve[5] = 0.5;
+    curve[15] = 1.0;
+    let settings = GaussianCloudSettings {
+        opacity_curve: Some(curve),
+        ..Default::default()
+    };
+
+    let packed = settings.opacity_curve_uniform();
+    assert_eq!(packed[1], Vec4::new(0.0, 0.5, 0.0, 0.0));
+    assert_eq!(packed[3].w, 1.0);
+}
+
+#[test]
+fn test_world_aabb() {
+    use bevy::render::primitives::Aabb;
+
+    let settings = GaussianCloudSettings {
+        transform: Transform::from_xyz(0.0, 1.0, 0.0)
+            .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_4)),
+        ..Default::default()
+    };
+
+    let local = Aabb::from_min_max(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+    let world = settings.world_aabb(&local);
+
+    // the rotated cube's corners reach sqrt(2) along x and z
+    assert!((Vec3::from(world.center) - Vec3::Y).length() < 1e-5);
+    assert!((Vec3::from(world.half_extents) - Vec3::new(2.0_f32.sqrt(), 1.0, 2.0_f32.sqrt())).length() < 1e-5);
+}
+
+#[test]
+fn test_morton_reorder() {
+    // two gaussians per corner of a cube, in reverse z-order
+    let gaussians = (0..16)
+        .rev()
+        .map(|i| {
+            let corner = i / 2;
+            gaussian_at([(corner & 1) as f32, ((corner >> 1) & 1) as f32, (corner >> 2) as f32], 0.5 + 0.01 * i as f32)
+        })
+        .collect::<Vec<Gaussian>>();
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+
+    let order = cloud.morton_order();
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..cloud.len()).collect::<Vec<_>>());
+
+    let mut reordered = cloud.clone();
+    reordered.morton_reorder();
+    assert_eq!(reordered.len(), cloud.len());
+
+    for (index, &source) in order.iter().enumerate() {
+        assert_eq!(reordered.gaussian(index), cloud.gaussian(source));
+    }
+
+    assert_eq!(reordered.position(0), &[0.0, 0.0, 0.0]);
+    assert_eq!(reordered.position(15), &[1.0, 1.0, 1.0]);
+
+    // gaussians sharing a corner keep their input order
+    assert_eq!(&order[..2], &[14, 15]);
+}
+
+#[test]
+fn test_crop() {
+    use bevy::render::primitives::Aabb;
+
+    let gaussians = (0..27)
+        .map(|i| gaussian_at([(i % 3) as f32 - 1.0, ((i / 3) % 3) as f32 - 1.0, (i / 9) as f32 - 1.0], 1.0))
+        .collect::<Vec<Gaussian>>();
+    let mut cloud = GaussianCloud::from_gaussians(gaussians);
+    cloud.channel = (0..27).map(|i| i as f32).collect();
+
+    // the boundary is inside, so the box keeps the x >= 0 half of the grid
+    let cropped = cloud.crop(&Aabb::from_min_max(Vec3::new(0.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)));
+    assert_eq!(cropped.len(), 18);
+    assert!(cropped.position_iter().all(|position| position[0] >= 0.0));
+    assert_eq!(cropped.channel.len(), 18);
+
+    // a floor at y = -0.5
+    let above = cloud.crop_plane(Vec4::new(0.0, 1.0, 0.0, 0.5));
+    assert_eq!(above.len(), 18);
+    assert!(above.position_iter().all(|position| position[1] >= 0.0));
+
+    assert_eq!(cloud.crop_plane(Vec4::ZERO).len(), cloud.len());
+    assert!(cloud.crop(&Aabb::from_min_max(Vec3::splat(5.0), Vec3::splat(6.0))).is_empty());
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_normalize_density() {
+    // a dense clump of 27 at the origin and 28 spread out gaussians far away
+    let mut gaussians = (0..27)
+        .map(|i| gaussian_at([0.01 * (i % 3) as f32, 0.01 * ((i / 3) % 3) as f32, 0.01 * (i / 9) as f32], 0.8))
+        .collect::<Vec<Gaussian>>();
+    gaussians.extend((0..28).map(|i| gaussian_at([100.0 + 10.0 * i as f32, 0.0, 0.0], 0.8)));
+
+    let mut cloud = GaussianCloud::from_gaussians(gaussians);
+
+    let mut unchanged = cloud.clone();
+    unchanged.normalize_density(0.0);
+    assert!((0..unchanged.len()).all(|index| (unchanged.opacity(index) - cloud.opacity(index)).abs() < 1e-6));
+
+    cloud.normalize_density(1.0);
+
+    // the median neighborhood holds one gaussian, the clump is pulled down to it
+    for index in 0..27 {
+        assert!((cloud.opacity(index) - 0.8 / 27.0).abs() < 1e-3, "{}", cloud.opacity(index));
+    }
+    for index in 27..55 {
+        assert!((cloud.opacity(index) - 0.8).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_compact() {
+    // the back half is removed in place by collapsing its opacity
+    let gaussians = (0..10)
+        .map(|i| gaussian_at([i as f32, 0.0, 0.0], if i < 5 { 1.0 } else { 0.0 }))
+        .collect::<Vec<Gaussian>>();
+    let mut cloud = GaussianCloud::from_gaussians(gaussians);
+
+    assert_eq!(cloud.compact(), 5);
+    assert_eq!(cloud.len(), 5);
+
+    let xs = cloud.position_iter().map(|position| position[0]).collect::<Vec<f32>>();
+    assert_eq!(xs, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+    let aabb = cloud.aabb();
+    assert_eq!(Vec3::from(aabb.min()), Vec3::ZERO);
+    assert_eq!(Vec3::from(aabb.max()), Vec3::new(4.0, 0.0, 0.0));
+
+    assert_eq!(cloud.compact(), 0);
+    assert_eq!(cloud.len(), 5);
+}
+
+#[test]
+fn test_importance_reveal_order() {
+    let opacities = [0.1, 0.4, 0.2, 0.3];
+    let mut cloud = GaussianCloud::from_gaussians(
+        opacities.iter()
+            .enumerate()
+            .map(|(i, opacity)| gaussian_at([i as f32, 0.0, 0.0], *opacity))
+            .collect(),
+    );
+
+    cloud.set_importance_reveal_order();
+
+    // equal scales, so the most opaque gaussian reveals first, and every gaussian stays where it was
+    assert_eq!(cloud.reveal_rank, vec![3, 0, 2, 1]);
+    let positions = (0..cloud.len()).map(|index| cloud.position(index)[0]).collect::<Vec<f32>>();
+    assert_eq!(positions, vec![0.0, 1.0, 2.0, 3.0]);
+
+    // a subset keeps the relative order, ranked from zero again
+    let subset = cloud.subset(&[0, 2, 3]);
+    assert_eq!(subset.reveal_rank, vec![2, 1, 0]);
+
+    cloud.clear_reveal_order();
+    assert!(cloud.reveal_rank.is_empty());
+}
+
+#[test]
+fn test_cluster() {
+    let mut gaussians = Vec::new();
+    for i in 0..5 {
+        gaussians.push(gaussian_at([i as f32 * 0.1, 0.0, 0.0], 1.0));
+        gaussians.push(gaussian_at([10.0 + i as f32 * 0.1, 0.0, 0.0], 1.0));
+    }
+    gaussians.push(gaussian_at([5.0, 5.0, 5.0], 1.0));
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+
+    let labels = cloud.cluster_labels(0.15, 2);
+    assert_eq!(labels[0], Some(0));
+    assert_eq!(labels[1], Some(1));
+    assert_eq!(labels[10], None);
+
+    // the lone gaussian is noise, each clump becomes its own cloud in cloud order
+    let clusters = cloud.cluster(0.15, 2);
+    assert_eq!(clusters.iter().map(GaussianCloud::len).collect::<Vec<_>>(), vec![5, 5]);
+    assert!(clusters[0].position_iter().all(|position| position[0] < 1.0));
+    assert!(clusters[1].position_iter().all(|position| position[0] > 9.0));
+
+    // too coarse a neighborhood merges the clumps
+    assert_eq!(cloud.cluster(20.0, 2).len(), 1);
+}
+
+#[test]
+fn test_importance_luminance_weight() {
+    let grey = |value: f32| {
+        let mut gaussian = gaussian_at([0.0, 0.0, 0.0], 0.5);
+        for channel in 0..3 {
+            gaussian.spherical_harmonic.set(channel, (value - 0.5) / 0.282_094_8);
+        }
+        gaussian
+    };
+    let mut cloud = GaussianCloud::from_gaussians(vec![grey(0.5), grey(4.0)]);
+
+    // no weight keeps the opacity and area heuristic, both gaussians tie
+    assert_eq!(cloud.importance()[0], cloud.importance()[1]);
+    assert_eq!(cloud.importance_order(), &[0, 1]);
+
+    cloud.set_importance_luminance_weight(1.0);
+    let importance = cloud.importance();
+    assert!((importance[1] / importance[0] - 5.0 / 1.5).abs() < 1e-2);
+    assert_eq!(cloud.importance_order(), &[1, 0]);
+
+    // clouds cut from it rank the same way
+    let subset = cloud.subset(&[0, 1]);
+    assert_eq!(subset.importance_luminance_weight(), 1.0);
+    assert_eq!(subset.importance_order(), &[1, 0]);
+
+    // operations that rebuild the storage keep it
+    #[cfg(not(feature = "precompute_covariance_3d"))]
+    {
+        let mut rebuilt = subset.clone();
+        rebuilt.rotate(Quat::from_rotation_y(1.0));
+        rebuilt.mirror(0);
+        rebuilt.transform(Affine3A::from_scale(Vec3::new(1.0, 2.0, 3.0)));
+        rebuilt.apply_patch(&GaussianCloudPatch {
+            appends: vec![grey(0.5)],
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(rebuilt.importance_luminance_weight(), 1.0);
+    }
+
+    // the weight is cloud data, not part of the cache
+    let mut unweighted = subset.clone();
+    unweighted.set_importance_luminance_weight(0.0);
+    assert_ne!(subset, unweighted);
+
+    // editing the color through spherical_harmonic_mut re-ranks
+    let dim = *cloud.spherical_harmonic(0);
+    *cloud.spherical_harmonic_mut(1) = dim;
+    assert_eq!(cloud.importance()[0], cloud.importance()[1]);
+}
+
+#[test]
+fn test_robust_bounds() {
+    let mut gaussians = (0..98)
+        .map(|i| gaussian_at([(i % 7) as f32 / 6.0, (i % 5) as f32 / 4.0, (i % 3) as f32 / 2.0], 1.0))
+        .collect::<Vec<Gaussian>>();
+    gaussians.push(gaussian_at([1000.0, 0.5, 0.5], 1.0));
+    gaussians.push(gaussian_at([0.5, -1000.0, 0.5], 1.0));
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+
+    // one floater at each end of an axis is past its 1st and 99th percentiles
+    let robust = cloud.robust_bounds(1.0);
+    assert_eq!(Vec3::from(robust.min()), Vec3::ZERO);
+    assert_eq!(Vec3::from(robust.max()), Vec3::ONE);
+
+    let aabb = cloud.aabb();
+    assert_eq!(cloud.robust_bounds(0.0).min(), aabb.min());
+    assert_eq!(cloud.robust_bounds(0.0).max(), aabb.max());
+}
+
+#[test]
+fn test_morton_chunks() {
+    // four gaussians per corner of a cube
+    let gaussians = (0..32)
+        .map(|i| {
+            let corner = i % 8;
+            gaussian_at([(corner & 1) as f32, ((corner >> 1) & 1) as f32, (corner >> 2) as f32], 1.0)
+        })
+        .collect::<Vec<Gaussian>>();
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+
+    let chunks = cloud.morton_chunks(8);
+    assert_eq!(chunks.iter().map(GaussianCloud::len).collect::<Vec<_>>(), vec![8, 8, 8, 8]);
+
+    // each chunk covers two corners, and together they hold every gaussian once
+    for chunk in chunks.iter() {
+        let mut corners = chunk.position_iter()
+            .map(|position| position.map(|axis| axis as u32))
+            .collect::<Vec<_>>();
+        corners.dedup();
+        assert_eq!(corners.len(), 2);
+    }
+    assert_eq!(GaussianCloud::merge(chunks.iter()).len(), cloud.len());
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_importance() {
+    let mut cloud = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 0.25),
+        gaussian_at([1.0, 0.0, 0.0], 1.0),
+        gaussian_at([2.0, 0.0, 0.0], 0.5),
+        gaussian_at([3.0, 0.0, 0.0], 0.5),
+    ]);
+
+    // scale 0.5 on every axis, so importance is opacity / 4
+    assert_eq!(cloud.importance(), &[0.0625, 0.25, 0.125, 0.125]);
+    assert_eq!(cloud.importance_order(), &[1, 2, 3, 0]);
+
+    cloud.set_gaussian(0, gaussian_at([0.0, 0.0, 0.0], 0.75));
+    assert_eq!(cloud.importance(), cloud.compute_importance().as_slice());
+    assert_eq!(cloud.importance_order(), &[1, 0, 2, 3]);
+}
+
+#[test]
+fn test_spatial_index() {
+    let gaussians = (0..64)
+        .map(|i| gaussian_at([(i % 4) as f32, ((i / 4) % 4) as f32, (i / 16) as f32], 1.0))
+        .collect::<Vec<Gaussian>>();
+    let mut cloud = GaussianCloud::from_gaussians(gaussians);
+
+    assert_eq!(cloud.nearest(Vec3::new(1.1, 2.2, 2.9)), Some(1 + 2 * 4 + 3 * 16));
+
+    let ray = Ray3d::new(Vec3::new(2.0, 1.0, -5.0), Vec3::Z);
+    let (index, distance) = cloud.raycast(ray, 0.1).unwrap();
+    assert_eq!(index, 2 + 4);
+    assert!((distance - 5.0).abs() < 1e-5);
+
+    // diagonal rays cross cells on every axis, the grid walk must agree with testing every center
+    let ray = Ray3d::new(Vec3::new(-1.0, -0.5, -1.5), Vec3::new(1.0, 0.9, 1.1));
+    let brute_force = (0..cloud.len())
+        .filter_map(|index| {
+            let offset = Vec3::from_array(*cloud.position(index)) - ray.origin;
+            let along = offset.dot(*ray.direction);
+            (along >= 0.0 && offset.length_squared() - along * along <= 0.35 * 0.35).then_some((index, along))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    assert!(brute_force.is_some());
+    assert_eq!(cloud.raycast(ray, 0.35), brute_force);
+
+    *cloud.position_mut(0) = [10.0, 10.0, 10.0];
+    assert_eq!(cloud.nearest(Vec3::splat(9.0)), Some(0));
+
+    *cloud.position_mut(1) = [20.0, 20.0, 20.0];
+    let (index, _) = cloud.raycast(Ray3d::new(Vec3::new(20.0, 20.0, 30.0), Vec3::NEG_Z), 0.1).unwrap();
+    assert_eq!(index, 1);
+}
+
+#[test]
+fn test_stats() {
+    let stats = GaussianCloud::from_gaussians(vec![
+        gaussian_at([0.0, 0.0, 0.0], 0.25),
+        gaussian_at([2.0, 4.0, 0.0], 0.75),
+        gaussian_at([f32::NAN, 0.0, 0.0], 1.0),
+    ]).stats();
+
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.bounds_min, [0.0, 0.0, 0.0]);
+    assert_eq!(stats.bounds_max, [2.0, 4.0, 0.0]);
+    assert_eq!(stats.centroid, [1.0, 2.0, 0.0]);
+
+    let opacity = stats.opacity.unwrap();
+    assert_eq!((opacity.min, opacity.mean, opacity.max), (0.25, 0.5, 0.75));
+
+    assert!((stats.non_finite_fraction - 1.0 / 3.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_view_renders_cloud() {
+    use bevy::render::view::RenderLayers;
+    use bevy_gaussian_splatting::render::view_renders_cloud;
+
+    let main_camera = None;
+    let minimap_camera = Some(RenderLayers::layer(1));
+
+    let scene_cloud = None;
+    let minimap_cloud = Some(RenderLayers::layer(1));
+    let shared_cloud = Some(RenderLayers::from_layers(&[0, 1]));
+
+    let visible = |camera: &Option<RenderLayers>, cloud: &Option<RenderLayers>| {
+        view_renders_cloud(camera.as_ref(), cloud.as_ref())
+    };
+
+    assert!(visible(&main_camera, &scene_cloud));
+    assert!(!visible(&main_camera, &minimap_cloud));
+    assert!(visible(&main_camera, &shared_cloud));
+
+    assert!(!visible(&minimap_camera, &scene_cloud));
+    assert!(visible(&minimap_camera, &minimap_cloud));
+    assert!(visible(&minimap_camera, &shared_cloud));
+}
+
+#[test]
+fn test_hidden_parent_hides_cloud() {
+    use bevy::{
+        prelude::*,
+        render::view::VisibilityPlugin,
+    };
+    use bevy_gaussian_splatting::{
+        GaussianSplattingBundle,
+        render::cloud_visible,
+    };
+
+    let mut app = App::new();
+    app.add_plugins(VisibilityPlugin);
+    app.init_resource::<Assets<Mesh>>();
+
+    let mut child = Entity::PLACEHOLDER;
+    let parent = app.world_mut()
+        .spawn(SpatialBundle {
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .with_children(|parent| {
+            child = parent.spawn(GaussianSplattingBundle::default()).id();
+        })
+        .id();
+
+    let child_visible = |app: &App| {
+        let child = app.world().entity(child);
+        cloud_visible(child.get::<Visibility>().unwrap(), child.get::<InheritedVisibility>())
+    };
+
+    app.update();
+    assert!(!child_visible(&app));
+
+    *app.world_mut().get_mut::<Visibility>(parent).unwrap() = Visibility::Inherited;
+    app.update();
+    assert!(child_visible(&app));
+}
+
+#[test]
+fn test_depth_tiles_occlude() {
+    use bevy::{
+        math::Mat4,
+        render::primitives::Aabb,
+    };
+    use bevy_gaussian_splatting::render::occlusion::GaussianCloudDepthTiles;
+
+    // camera at the origin looking down -z, a wall everywhere 0.2 units away (reverse z depth near / distance)
+    let tiles = GaussianCloudDepthTiles {
+        resolution: [4, 4].into(),
+        depth: vec![0.5; 16],
+        clip_from_world: Mat4::perspective_infinite_reverse_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1),
+    };
+
+    let behind_wall = Aabb::from_min_max(Vec3::new(-1.0, -1.0, -6.0), Vec3::new(1.0, 1.0, -4.0));
+    let before_wall = Aabb::from_min_max(Vec3::new(-0.01, -0.01, -0.16), Vec3::new(0.01, 0.01, -0.14));
+    let behind_camera = Aabb::from_min_max(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+
+    assert!(tiles.occludes(&behind_wall, &Affine3A::IDENTITY));
+    assert!(!tiles.occludes(&before_wall, &Affine3A::IDENTITY));
+    assert!(!tiles.occludes(&behind_camera, &Affine3A::IDENTITY));
+
+    // one tile of open sky under the box is enough to keep it
+    let mut gap = tiles.clone();
+    gap.depth[5] = 0.0;
+    assert!(!gap.occludes(&behind_wall, &Affine3A::IDENTITY));
+}
+
+#[cfg(not(feature = "web"))]
+#[test]
+fn test_sh_degree() {
+    use bevy_gaussian_splatting::material::spherical_harmonics::{
+        SphericalHarmonicCoefficients,
+        sh_word_count,
+    };
+
+    let mut gaussians = vec![gaussian_at([0.0, 0.0, 0.0], 1.0); 4];
+    gaussians[0].spherical_harmonic.set(0, 0.5);
+    assert_eq!(GaussianCloud::from_gaussians(gaussians.clone()).sh_degree(), 0);
+
+    gaussians[1].spherical_harmonic.set(3, 0.1);
+    assert_eq!(GaussianCloud::from_gaussians(gaussians.clone()).sh_degree(), 1);
+
+    gaussians[2].spherical_harmonic.set(44, -0.1);
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+    assert_eq!(cloud.sh_degree(), 3);
+    assert_eq!(cloud.sh_degree_in(0..2), 1);
+
+    // a color only cloud stores 4 of the 48 padded coefficients
+    let full = std::mem::size_of::<SphericalHarmonicCoefficients>();
+    let color_only = sh_word_count(0) * std::mem::size_of::<u32>();
+    assert_eq!(full / color_only, 12);
+}
+
+#[test]
+fn test_sequence_frame_index() {
+    use bevy_gaussian_splatting::GaussianSequencePlayback;
+
+    let mut playback = GaussianSequencePlayback {
+        fps: 10.0,
+        elapsed: 0.25,
+        ..Default::default()
+    };
+    assert_eq!(playback.frame_index(4), 2);
+
+    playback.elapsed = 0.45;
+    assert_eq!(playback.frame_index(4), 0);
+    assert!(!playback.finished(4));
+    assert_eq!(playback.next_frame_index(4), Some(1));
+
+    playback.elapsed = 0.35;
+    assert_eq!(playback.next_frame_index(4), Some(0));
+
+    playback.looping = false;
+    assert_eq!(playback.next_frame_index(4), None);
+
+    playback.elapsed = 0.45;
+    assert_eq!(playback.frame_index(4), 3);
+    assert!(playback.finished(4));
+
+    assert_eq!(playback.frame_index(0), 0);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_estimate_normals() {
+    let flat = |position: [f32; 3]| {
+        let mut gaussian = gaussian_at(position, 0.8);
+        gaussian.scale_opacity.scale = [0.5, 0.4, 0.05];
+        gaussian
+    };
+
+    // rotated 90 degrees about x, the thin local z axis lies along world -y
+    let mut tilted = flat([0.0, -2.0, 0.0]);
+    let rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    tilted.rotation = [rotation.w, rotation.x, rotation.y, rotation.z].into();
+
+    let cloud = GaussianCloud::from_gaussians(vec![
+        flat([0.0, 0.0, 2.0]),
+        flat([0.0, 0.0, -2.0]),
+        tilted,
+        gaussian_at([0.0, 2.0, 0.0], 0.8),
+    ]);
+    let normals = cloud.estimate_normals();
+
+    // oriented away from the centroid
+    assert!((normals[0] - Vec3::Z).length() < 1e-5);
+    assert!((normals[1] + Vec3::Z).length() < 1e-5);
+    assert!((normals[2] + Vec3::Y).length() < 1e-5);
+
+    // spherical gaussians have no normal
+    assert_eq!(normals[3], Vec3::ZERO);
+}
+
+#[test]
+fn test_thumbnail_framing() {
+    use bevy_gaussian_splatting::GaussianCloudThumbnailSettings;
+
+    let settings = GaussianCloudThumbnailSettings {
+        width: 200,
+        height: 100,
+        view_direction: Vec3::Z,
+        fov: std::f32::consts::FRAC_PI_2,
+        fill: 1.0,
+        ..Default::default()
+    };
+
+    let center = Vec3::new(1.0, 2.0, 3.0);
+    let transform = settings.frame_sphere(center, 1.0);
+
+    // the vertical field of view is the tighter one, its half angle of 45 degrees sees the sphere at sqrt(2)
+    assert!((transform.translation - (center + Vec3::Z * 2.0_f32.sqrt())).length() < 1e-4);
+    assert!((transform.forward().as_vec3() + Vec3::Z).length() < 1e-5);
+}
+
+#[cfg(not(feature = "precompute_covariance_3d"))]
+#[test]
+fn test_unified_sort_groups() {
+    use bevy::{
+        prelude::*,
+        render::view::{
+            RenderLayers,
+            VisibilityPlugin,
+        },
+    };
+    use bevy_gaussian_splatting::{
+        GaussianSplattingBundle,
+        sort::unified::{
+            GaussianCloudUnifiedSort,
+            UnifiedGaussianCloud,
+            UnifiedGaussianCloudSource,
+            UnifiedSortPlugin,
+        },
+    };
+
+    let mut app = App::new();
+    app.add_plugins((VisibilityPlugin, UnifiedSortPlugin));
+    app.init_resource::<Assets<Mesh>>();
+    app.init_resource::<Assets<GaussianCloud>>();
+    app.add_event::<AssetEvent<GaussianCloud>>();
+    app.insert_resource(GaussianCloudUnifiedSort { enabled: true });
+
+    let handle = app.world_mut().resource_mut::<Assets<GaussianCloud>>().add(random_gaussians(8));
+    let spawn = |app: &mut App, x: f32, exposure: f32, render_layers: RenderLayers| {
+        app.world_mut()
+            .spawn((
+                GaussianSplattingBundle {
+                    cloud: handle.clone(),
+                    settings: GaussianCloudSettings {
+                        transform: Transform::from_xyz(x, 0.0, 0.0),
+                        exposure,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                render_layers,
+            ))
+            .id()
+    };
+
+    let scene = [
+        spawn(&mut app, 0.0, 1.0, RenderLayers::layer(0)),
+        spawn(&mut app, 4.0, 1.0, RenderLayers::layer(0)),
+    ];
+    let minimap = [
+        spawn(&mut app, 0.0, 1.0, RenderLayers::layer(1)),
+        spawn(&mut app, 4.0, 1.0, RenderLayers::layer(1)),
+    ];
+    let brighter = spawn(&mut app, 8.0, 2.0, RenderLayers::layer(0));
+
+    let unified = |app: &mut App| {
+        let mut query = app.world_mut().query_filtered::<
+            (Entity, &Handle<GaussianCloud>, &GaussianCloudSettings, &RenderLayers),
+            With<UnifiedGaussianCloud>,
+        >();
+        let mut clouds = query.iter(app.world())
+            .map(|(entity, handle, settings, layers)| (entity, handle.clone(), settings.clone(), layers.clone()))
+            .collect::<Vec<_>>();
+        clouds.sort_by_key(|(.., layers)| !layers.intersects(&RenderLayers::layer(0)));
+        clouds
+    };
+    let is_source = |app: &App, entity: Entity| app.world().entity(entity).contains::<UnifiedGaussianCloudSource>();
+
+    // the first frame propagates visibility
+    app.update();
+    app.update();
+
+    // one merged cloud per layer set, the differing settings draw on their own
+    let merged = unified(&mut app);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].3, RenderLayers::layer(0));
+    assert_eq!(merged[1].3, RenderLayers::layer(1));
+    {
+        let clouds = app.world().resource::<Assets<GaussianCloud>>();
+        assert_eq!(clouds.get(&merged[0].1).unwrap().len(), 16);
+        assert_eq!(clouds.get(&merged[1].1).unwrap().len(), 16);
+    }
+
+    assert!(scene.iter().chain(minimap.iter()).all(|entity| is_source(&app, *entity)));
+    assert!(!is_source(&app, brighter));
+    assert_eq!(app.world().get::<Visibility>(scene[0]), Some(&Visibility::Inherited));
+
+    // shared settings changes carry over without merging again
+    for entity in scene {
+        app.world_mut().get_mut::<GaussianCloudSettings>(entity).unwrap().exposure = 3.0;
+    }
+    app.update();
+
+    let adjusted = unified(&mut app);
+    assert_eq!(adjusted[0].0, merged[0].0);
+    assert_eq!(adjusted[0].1, merged[0].1);
+    assert_eq!(adjusted[0].2.exposure, 3.0);
+
+    // moving a source merges its group again
+    app.world_mut().get_mut::<GaussianCloudSettings>(scene[0]).unwrap().transform.translation.y = 1.0;
+    app.update();
+
+    let moved = unified(&mut app);
+    assert_ne!(moved[0].1, merged[0].1);
+    assert_eq!(moved[1].1, merged[1].1);
+
+    app.world_mut().resource_mut::<GaussianCloudUnifiedSort>().enabled = false;
+    app.update();
+
+    assert!(unified(&mut app).is_empty());
+    assert!(!scene.iter().chain(minimap.iter()).any(|entity| is_source(&app, *entity)));
+}
@@ -0,0 +1,182 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    app::AppExit,
+    core::FrameCount,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        render_resource::TextureFormat,
+        view::screenshot::ScreenshotManager,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianCloudCullMode,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+    TestStateArc,
+};
+
+mod _harness;
+
+
+const BACKGROUND: [f32; 3] = [0.1, 0.3, 0.8];
+const OPACITY: f32 = 0.5;
+
+
+#[derive(Resource)]
+struct CullMode(GaussianCloudCullMode);
+
+
+// run with `cargo run --bin test_compositing`, and `-- cull_back` to check splats survive back face culling
+fn main() {
+    let cull_mode = if std::env::args().any(|arg| arg == "cull_back") {
+        GaussianCloudCullMode::Back
+    } else {
+        GaussianCloudCullMode::None
+    };
+
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    app.insert_resource(ClearColor(Color::linear_rgb(BACKGROUND[0], BACKGROUND[1], BACKGROUND[2])));
+    app.insert_resource(CullMode(cull_mode));
+
+    app.add_systems(Startup, setup);
+    app.add_systems(Update, capture_ready);
+
+    app.run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+    cull_mode: Res<CullMode>,
+) {
+    // zero spherical harmonics evaluate to srgb 0.5 gray
+    let gaussian = Gaussian {
+        rotation: [1.0, 0.0, 0.0, 0.0].into(),
+        position_visibility: [0.0, 0.0, 0.0, 1.0].into(),
+        scale_opacity: [1.0, 1.0, 1.0, OPACITY].into(),
+        ..default()
+    };
+    let cloud = gaussian_assets.add(GaussianCloud::from_gaussians(vec![gaussian]));
+
+    commands.spawn((
+        GaussianSplattingBundle {
+            cloud,
+            settings: GaussianCloudSettings {
+                cull_mode: cull_mode.0,
+                ..default()
+            },
+            ..default()
+        },
+        Name::new("gaussian_cloud"),
+    ));
+
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+    ));
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> [f32; 3] {
+    let offset = ((y * image.width() + x) * 4) as usize;
+    let texel = &image.data[offset..offset + 4];
+
+    let rgb = match image.texture_descriptor.format {
+        TextureFormat::Bgra8UnormSrgb | TextureFormat::Bgra8Unorm => [texel[2], texel[1], texel[0]],
+        _ => [texel[0], texel[1], texel[2]],
+    };
+
+    rgb.map(|channel| channel as f32 / 255.0)
+}
+
+fn test_over_compositing(image: &Image) {
+    let tolerance = 3.0 / 255.0;
+
+    let corner = pixel(image, 0, 0);
+    for (channel, background) in corner.iter().zip(BACKGROUND.iter()) {
+        let expected = linear_to_srgb(*background);
+        assert!((channel - expected).abs() < tolerance, "background tinted: {:?}", corner);
+    }
+
+    // the splat center has alpha == opacity, blended over the clear color in linear space
+    let splat = srgb_to_linear(0.5);
+    let center = pixel(image, image.width() / 2, image.height() / 2);
+    for (channel, background) in center.iter().zip(BACKGROUND.iter()) {
+        let expected = linear_to_srgb(splat * OPACITY + background * (1.0 - OPACITY));
+        assert!((channel - expected).abs() < tolerance, "center {:?} is not splat over background", center);
+    }
+}
+
+fn capture_ready(
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut exit: EventWriter<AppExit>,
+    frame_count: Res<FrameCount>,
+    state: Local<TestStateArc>,
+    buffer: Local<Arc<Mutex<Vec<Image>>>>,
+) {
+    let buffer = buffer.to_owned();
+
+    let wait_frames = 10;  // wait for gaussian cloud to load
+    if frame_count.0 < wait_frames {
+        return;
+    }
+
+    let state_clone = Arc::clone(&state);
+    let buffer_clone = Arc::clone(&buffer);
+
+    let mut state = state.lock().unwrap();
+    state.test_loaded = true;
+
+    if state.test_completed {
+        let captures = buffer.lock().unwrap();
+        test_over_compositing(captures.last().unwrap());
+
+        exit.send(AppExit::Success);
+        return;
+    }
+
+    if let Ok(window_entity) = main_window.get_single() {
+        screenshot_manager.take_screenshot(window_entity, move |image: Image| {
+            buffer_clone.lock().unwrap().push(image);
+
+            let mut state = state_clone.lock().unwrap();
+            state.test_completed = true;
+        }).unwrap();
+    }
+}
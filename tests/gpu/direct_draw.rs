@@ -0,0 +1,210 @@
+use std::{
+    process::exit,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    core::FrameCount,
+    core_pipeline::{
+        core_3d::graph::{
+            Core3d,
+            Node3d,
+        },
+        tonemapping::Tonemapping,
+    },
+    render::{
+        RenderApp,
+        renderer::{
+            RenderContext,
+            RenderQueue,
+        },
+        render_asset::RenderAssets,
+        render_graph::{
+            Node,
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+        },
+    },
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    random_gaussians,
+    render::GpuGaussianCloud,
+    sort::{
+        SortMode,
+        radix::RadixSortLabel,
+    },
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+    TestState,
+    TestStateArc,
+};
+
+mod _harness;
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DirectDrawTestLabel;
+
+
+const GAUSSIAN_COUNT: usize = 1000;
+
+
+// two radix sorted clouds with half their gaussians behind the camera. the indirect cloud's sort writes the culled
+// count, the direct cloud's sort leaves its args at the initial count:
+// `cargo run --bin test_direct_draw --features="sort_radix testing"`
+fn main() {
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    app.add_systems(Startup, setup);
+
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .add_render_graph_node::<DirectDrawTestNode>(
+                Core3d,
+                DirectDrawTestLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    RadixSortLabel,
+                    DirectDrawTestLabel,
+                    Node3d::EndMainPass,
+                ),
+            );
+    }
+
+    app.run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+) {
+    for use_indirect in [true, false] {
+        let mut cloud = random_gaussians(GAUSSIAN_COUNT);
+        for index in 0..cloud.len() {
+            let z = if index % 2 == 0 { 0.0 } else { 10.0 };
+            *cloud.position_mut(index) = [0.0, 0.0, z];
+        }
+
+        commands.spawn(GaussianSplattingBundle {
+            cloud: gaussian_assets.add(cloud),
+            settings: GaussianCloudSettings {
+                sort_mode: SortMode::Radix,
+                use_indirect,
+                ..default()
+            },
+            ..default()
+        });
+    }
+
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+    ));
+}
+
+
+pub struct DirectDrawTestNode {
+    gaussian_clouds: QueryState<(
+        &'static Handle<GaussianCloud>,
+        &'static GaussianCloudSettings,
+    )>,
+    state: TestStateArc,
+    start_frame: u32,
+}
+
+impl FromWorld for DirectDrawTestNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            gaussian_clouds: world.query(),
+            state: Arc::new(Mutex::new(TestState::default())),
+            start_frame: 0,
+        }
+    }
+}
+
+
+impl Node for DirectDrawTestNode {
+    fn update(
+        &mut self,
+        world: &mut World,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if state.test_completed {
+            exit(0);
+        }
+
+        if state.test_loaded && self.start_frame == 0 {
+            self.start_frame = world.get_resource::<FrameCount>().unwrap().0;
+        }
+
+        let frame_count = world.get_resource::<FrameCount>().unwrap().0;
+        const FRAME_LIMIT: u32 = 10;
+        if state.test_loaded && frame_count >= self.start_frame + FRAME_LIMIT {
+            state.test_completed = true;
+        }
+
+        self.gaussian_clouds.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let gaussian_cloud_res = world.get_resource::<RenderAssets<GpuGaussianCloud>>().unwrap();
+
+        for (cloud_handle, settings) in self.gaussian_clouds.iter_manual(world) {
+            let Some(cloud) = gaussian_cloud_res.get(cloud_handle) else {
+                continue;
+            };
+
+            self.state.lock().unwrap().test_loaded = true;
+
+            let use_indirect = settings.use_indirect;
+
+            wgpu::util::DownloadBuffer::read_buffer(
+                render_context.render_device().wgpu_device(),
+                world.get_resource::<RenderQueue>().unwrap().0.as_ref(),
+                &cloud.draw_indirect_buffer.slice(..),
+                move |buffer: Result<wgpu::util::DownloadBuffer, wgpu::BufferAsyncError>| {
+                    let binding = buffer.unwrap();
+                    let args = bytemuck::cast_slice::<u8, u32>(&binding);
+
+                    assert_eq!(args[0], 4, "direct draw, vertex count");
+
+                    // the sort clears the args before writing the survivors, a direct cloud's are never touched
+                    let expected = if use_indirect { GAUSSIAN_COUNT / 2 } else { GAUSSIAN_COUNT };
+                    assert_eq!(
+                        args[1] as usize,
+                        expected,
+                        "direct draw, instance count with use_indirect = {}",
+                        use_indirect,
+                    );
+                }
+            );
+        }
+
+        Ok(())
+    }
+}
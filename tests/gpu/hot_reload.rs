@@ -0,0 +1,209 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    app::AppExit,
+    core::FrameCount,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        Render,
+        RenderApp,
+        RenderSet,
+        render_asset::RenderAssets,
+        view::screenshot::ScreenshotManager,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianSplattingBundle,
+    io::ply::write_ply,
+    render::GpuGaussianCloud,
+    sort::SortedEntries,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+};
+
+mod _harness;
+
+
+const CLOUD_PATH: &str = "hot_reload_test.ply";
+
+// frames to wait for each step before failing, the file watcher debounces changes
+const TIMEOUT_FRAMES: u32 = 600;
+
+// gaussian x positions written for each step. the camera looks down -z from x = 0, so larger x is farther away and
+// sorts first. the last step keeps the count and only mirrors the cloud, which must still re-sort it
+const STEPS: [&[f32]; 3] = [
+    &[0.0],
+    &[0.0, 0.5, 1.0],
+    &[1.0, 0.5, 0.0],
+];
+
+
+// gaussian counts of the prepared clouds in the render world
+#[derive(Resource, Clone, Default)]
+struct GpuCounts(Arc<Mutex<Vec<usize>>>);
+
+
+// run with `cargo run --bin test_hot_reload --features="hot_reload io_ply testing"`
+fn main() {
+    write_cloud(STEPS[0]);
+
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    let gpu_counts = GpuCounts::default();
+    app.insert_resource(gpu_counts.clone());
+    app.sub_app_mut(RenderApp)
+        .insert_resource(gpu_counts)
+        .add_systems(Render, record_gpu_counts.in_set(RenderSet::Cleanup));
+
+    app.add_systems(Startup, setup);
+    app.add_systems(Update, check_reload);
+
+    app.run();
+
+    let _ = std::fs::remove_file(asset_path());
+}
+
+fn asset_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets").join(CLOUD_PATH)
+}
+
+fn write_cloud(positions: &[f32]) {
+    let gaussians = positions.iter()
+        .map(|&x| Gaussian {
+            rotation: [1.0, 0.0, 0.0, 0.0].into(),
+            position_visibility: [x, 0.0, 0.0, 1.0].into(),
+            scale_opacity: [0.1, 0.1, 0.1, 0.9].into(),
+            ..default()
+        })
+        .collect::<Vec<_>>();
+
+    std::fs::write(asset_path(), write_ply(&gaussians, None)).expect("failed to write the watched cloud");
+}
+
+fn record_gpu_counts(
+    gpu_gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
+    gpu_counts: Res<GpuCounts>,
+) {
+    *gpu_counts.0.lock().unwrap() = gpu_gaussian_clouds.iter()
+        .map(|(_, cloud)| cloud.count)
+        .collect();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        GaussianSplattingBundle {
+            cloud: asset_server.load(CLOUD_PATH),
+            ..default()
+        },
+        Name::new("gaussian_cloud"),
+    ));
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+// back to front order of a cloud written from `positions`
+fn expected_order(positions: &[f32]) -> Vec<u32> {
+    let mut order = (0..positions.len() as u32).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| positions[b as usize].total_cmp(&positions[a as usize]));
+    order
+}
+
+// the cloud, its sorted entries and its gpu buffers follow the file from 1 to 3 gaussians, the entries are sorted for
+// each version of the file, and the last version is drawn
+#[allow(clippy::too_many_arguments)]
+fn check_reload(
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    sorted_entries_res: Res<Assets<SortedEntries>>,
+    gaussian_clouds: Query<(&Handle<GaussianCloud>, Option<&Handle<SortedEntries>>)>,
+    gpu_counts: Res<GpuCounts>,
+    frame_count: Res<FrameCount>,
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut step: Local<usize>,
+    mut step_start: Local<u32>,
+    captured: Local<Arc<Mutex<Option<Image>>>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if *step == STEPS.len() {
+        let Some(image) = captured.lock().unwrap().take() else {
+            return;
+        };
+
+        // the nearest gaussian of the last version sits in the middle of the view
+        let center = ((image.height() / 2 * image.width() + image.width() / 2) * 4) as usize;
+        let texel = &image.data[center..center + 4];
+        assert!(texel[..3].iter().any(|&channel| channel > 16), "reloaded cloud not drawn: {:?}", texel);
+
+        exit.send(AppExit::Success);
+        return;
+    }
+
+    let positions = STEPS[*step];
+
+    assert!(
+        frame_count.0 - *step_start < TIMEOUT_FRAMES,
+        "cloud did not reach step {} ({:?}) after {} frames",
+        *step,
+        positions,
+        TIMEOUT_FRAMES,
+    );
+
+    let Ok((cloud_handle, sorted_entries_handle)) = gaussian_clouds.get_single() else {
+        return;
+    };
+
+    let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+        return;
+    };
+
+    let cloud_positions = (0..cloud.len()).map(|index| cloud.position(index)[0]).collect::<Vec<_>>();
+    let sorted_order = sorted_entries_handle
+        .and_then(|handle| sorted_entries_res.get(handle))
+        .map(|sorted_entries| sorted_entries.sorted.iter().map(|entry| entry.index).collect::<Vec<_>>());
+    let gpu_count = gpu_counts.0.lock().unwrap().first().copied();
+
+    if cloud_positions != positions
+        || sorted_order != Some(expected_order(positions))
+        || gpu_count != Some(positions.len())
+    {
+        return;
+    }
+
+    *step += 1;
+    *step_start = frame_count.0;
+
+    if let Some(next) = STEPS.get(*step) {
+        write_cloud(next);
+        return;
+    }
+
+    if let Ok(window_entity) = main_window.get_single() {
+        let captured = Arc::clone(&captured);
+        screenshot_manager.take_screenshot(window_entity, move |image: Image| {
+            *captured.lock().unwrap() = Some(image);
+        }).unwrap();
+    }
+}
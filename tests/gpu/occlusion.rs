@@ -0,0 +1,164 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    app::AppExit,
+    core::FrameCount,
+    core_pipeline::{
+        prepass::DepthPrepass,
+        tonemapping::Tonemapping,
+    },
+    render::{
+        render_resource::TextureFormat,
+        view::screenshot::ScreenshotManager,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+    TestStateArc,
+};
+
+mod _harness;
+
+
+const WALL: [f32; 3] = [1.0, 0.0, 0.0];
+
+
+// run with `cargo run --bin test_occlusion --features testing`
+fn main() {
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    app.add_systems(Startup, setup);
+    app.add_systems(Update, capture_ready);
+
+    app.run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // a fully opaque splat at the origin, far larger than the wall on screen
+    let gaussian = Gaussian {
+        rotation: [1.0, 0.0, 0.0, 0.0].into(),
+        position_visibility: [0.0, 0.0, 0.0, 1.0].into(),
+        scale_opacity: [1.0, 1.0, 1.0, 1.0].into(),
+        ..default()
+    };
+    let cloud = gaussian_assets.add(GaussianCloud::from_gaussians(vec![gaussian]));
+
+    commands.spawn((
+        GaussianSplattingBundle {
+            cloud,
+            settings: GaussianCloudSettings {
+                scene_depth_test: true,
+                ..default()
+            },
+            ..default()
+        },
+        Name::new("gaussian_cloud"),
+    ));
+
+    // between the camera and the splat
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Cuboid::new(1.0, 1.0, 0.1)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(WALL[0], WALL[1], WALL[2]),
+            unlit: true,
+            ..default()
+        }),
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+        ..default()
+    });
+
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+        DepthPrepass,
+    ));
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> [f32; 3] {
+    let offset = ((y * image.width() + x) * 4) as usize;
+    let texel = &image.data[offset..offset + 4];
+
+    let rgb = match image.texture_descriptor.format {
+        TextureFormat::Bgra8UnormSrgb | TextureFormat::Bgra8Unorm => [texel[2], texel[1], texel[0]],
+        _ => [texel[0], texel[1], texel[2]],
+    };
+
+    rgb.map(|channel| channel as f32 / 255.0)
+}
+
+fn test_wall_occludes_cloud(image: &Image) {
+    let tolerance = 3.0 / 255.0;
+
+    // the splat is behind the wall, nothing of it may blend over the wall color
+    let center = pixel(image, image.width() / 2, image.height() / 2);
+    for (channel, wall) in center.iter().zip(WALL.iter()) {
+        assert!((channel - wall).abs() < tolerance, "center {:?} is not the wall color", center);
+    }
+
+    // beside the wall the splat is still drawn
+    let beside = pixel(image, image.width() / 2 + image.width() / 3, image.height() / 2);
+    assert!(beside.iter().any(|channel| *channel > tolerance), "splat beside the wall is missing: {:?}", beside);
+}
+
+fn capture_ready(
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut exit: EventWriter<AppExit>,
+    frame_count: Res<FrameCount>,
+    state: Local<TestStateArc>,
+    buffer: Local<Arc<Mutex<Vec<Image>>>>,
+) {
+    let buffer = buffer.to_owned();
+
+    let wait_frames = 10;  // wait for gaussian cloud to load
+    if frame_count.0 < wait_frames {
+        return;
+    }
+
+    let state_clone = Arc::clone(&state);
+    let buffer_clone = Arc::clone(&buffer);
+
+    let mut state = state.lock().unwrap();
+    state.test_loaded = true;
+
+    if state.test_completed {
+        let captures = buffer.lock().unwrap();
+        test_wall_occludes_cloud(captures.last().unwrap());
+
+        exit.send(AppExit::Success);
+        return;
+    }
+
+    if let Ok(window_entity) = main_window.get_single() {
+        screenshot_manager.take_screenshot(window_entity, move |image: Image| {
+            buffer_clone.lock().unwrap().push(image);
+
+            let mut state = state_clone.lock().unwrap();
+            state.test_completed = true;
+        }).unwrap();
+    }
+}
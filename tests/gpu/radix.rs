@@ -14,7 +14,6 @@ use bevy::{
             Core3d,
             Node3d,
         },
-        Transparent3d,
         tonemapping::Tonemapping,
     },
     render::{
@@ -29,17 +28,24 @@ use bevy::{
             NodeRunError,
             RenderGraphApp,
             RenderGraphContext,
+            RenderLabel,
         },
-        render_phase::SortedRenderPhase,
         view::ExtractedView,
     },
 };
 
 use bevy_gaussian_splatting::{
     GaussianCloud,
+    GaussianCloudSettings,
     GaussianSplattingBundle,
     random_gaussians,
-    sort::SortedEntries,
+    render::GpuGaussianCloud,
+    sort::{
+        GpuSortedEntry,
+        SortedEntries,
+        SortMode,
+        radix::RadixSortLabel,
+    },
 };
 
 use _harness::{
@@ -52,29 +58,43 @@ use _harness::{
 mod _harness;
 
 
-pub mod node {
-    pub const RADIX_SORT_TEST: &str = "radix_sort_test";
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct RadixSortTestLabel;
+
+
+#[derive(Resource)]
+struct SpawnOrder {
+    reverse: bool,
 }
 
 
-// run with `cargo run --bin test_gaussian --features="debug_gpu"`
+// two clouds with different counts and transforms share the sort pass, each must come out sorted by its own
+// world space depth. run both spawn orders:
+// `cargo run --bin test_radix --features="debug_gpu sort_radix testing"`
+// `cargo run --bin test_radix --features="debug_gpu sort_radix testing" -- reverse`
 fn main() {
+    let reverse = std::env::args().any(|arg| arg == "reverse");
+
     let mut app = test_harness_app(TestHarness {
         resolution: (512.0, 512.0),
     });
 
+    app.insert_resource(SpawnOrder { reverse });
     app.add_systems(Startup, setup);
 
     if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
         render_app
             .add_render_graph_node::<RadixTestNode>(
-                CORE_3D,
-                node::RADIX_SORT_TEST,
+                Core3d,
+                RadixSortTestLabel,
             )
-            .add_render_graph_edge(
-                CORE_3D,
-                node::RADIX_SORT_TEST,
-                 bevy::core_pipeline::core_3d::graph::node::END_MAIN_PASS,
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    RadixSortLabel,
+                    RadixSortTestLabel,
+                    Node3d::EndMainPass,
+                ),
             );
     }
 
@@ -84,20 +104,35 @@ fn main() {
 fn setup(
     mut commands: Commands,
     mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+    order: Res<SpawnOrder>,
 ) {
-    let cloud = gaussian_assets.add(random_gaussians(10000));
+    let mut clouds = vec![
+        (random_gaussians(10000), Transform::IDENTITY, "large_cloud"),
+        (
+            random_gaussians(2500),
+            Transform::from_xyz(3.0, 0.0, -2.0).with_scale(Vec3::splat(2.0)),
+            "small_cloud",
+        ),
+    ];
+
+    if order.reverse {
+        clouds.reverse();
+    }
 
-    commands.spawn((
-        GaussianSplattingBundle {
-            cloud,
-            settings: GaussianCloudSettings {
-                sort_mode: SortMode::Radix,
+    for (cloud, transform, name) in clouds {
+        commands.spawn((
+            GaussianSplattingBundle {
+                cloud: gaussian_assets.add(cloud),
+                settings: GaussianCloudSettings {
+                    sort_mode: SortMode::Radix,
+                    transform,
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        },
-        Name::new("gaussian_cloud"),
-    ));
+            Name::new(name),
+        ));
+    }
 
     commands.spawn((
         Camera3dBundle {
@@ -113,12 +148,10 @@ pub struct RadixTestNode {
     gaussian_clouds: QueryState<(
         &'static Handle<GaussianCloud>,
         &'static Handle<SortedEntries>,
+        &'static GaussianCloudSettings,
     )>,
     state: TestStateArc,
-    views: QueryState<(
-        &'static ExtractedView,
-        &'static SortedRenderPhase<Transparent3d>,
-    )>,
+    views: QueryState<&'static ExtractedView>,
     start_frame: u32,
 }
 
@@ -164,26 +197,35 @@ impl Node for RadixTestNode {
         render_context: &mut RenderContext,
         world: &World,
     ) -> Result<(), NodeRunError> {
-        for (view, _phase,) in self.views.iter_manual(world) {
-            let camera_position = view.transform.translation();
+        for view in self.views.iter_manual(world) {
+            let camera_position = view.world_from_view.translation();
+
+            let gaussian_cloud_res = world.get_resource::<RenderAssets<GpuGaussianCloud>>().unwrap();
+            let sorted_entries_res = world.get_resource::<RenderAssets<GpuSortedEntry>>().unwrap();
+
+            let loaded = self.gaussian_clouds.iter_manual(world)
+                .filter(|(cloud_handle, sorted_entries_handle, _)| {
+                    gaussian_cloud_res.get(*cloud_handle).is_some()
+                        && sorted_entries_res.get(*sorted_entries_handle).is_some()
+                })
+                .collect::<Vec<_>>();
+
+            // both clouds have to be sorting in the same frame for one to disturb the other
+            if loaded.len() < 2 {
+                continue;
+            }
+
+            self.state.lock().unwrap().test_loaded = true;
 
             for (
                 cloud_handle,
                 sorted_entries_handle,
-            ) in self.gaussian_clouds.iter_manual(world) {
-                let gaussian_cloud_res = world.get_resource::<RenderAssets<GpuGaussianCloud>>().unwrap();
-                let sorted_entries_res = world.get_resource::<RenderAssets<GpuSortedEntry>>().unwrap();
-
-                let mut state = self.state.lock().unwrap();
-                if gaussian_cloud_res.get(cloud_handle).is_none() || sorted_entries_res.get(sorted_entries_handle).is_none() {
-                    continue;
-                } else if !state.test_loaded {
-                    state.test_loaded = true;
-                }
-
+                settings,
+            ) in loaded {
                 let cloud = gaussian_cloud_res.get(cloud_handle).unwrap();
                 let sorted_entries = sorted_entries_res.get(sorted_entries_handle).unwrap();
-                let gaussians = cloud.debug_gpu.gaussians.clone();
+                let gaussians = cloud.debug_gpu.clone();
+                let cloud_transform = settings.cloud_transform();
 
                 wgpu::util::DownloadBuffer::read_buffer(
                     render_context.render_device().wgpu_device(),
@@ -193,13 +235,21 @@ impl Node for RadixTestNode {
                     ),
                     move |buffer: Result<wgpu::util::DownloadBuffer, wgpu::BufferAsyncError>| {
                         let binding = buffer.unwrap();
-                        let u32_muck = bytemuck::cast_slice::<u8, u32>(&*binding);
+                        let u32_muck = bytemuck::cast_slice::<u8, u32>(&binding);
 
                         let mut radix_sorted_indices = Vec::new();
                         for i in (1..u32_muck.len()).step_by(2) {
                             radix_sorted_indices.push((i, u32_muck[i] as usize));
                         }
 
+                        // every index exactly once, a sort reading another cloud's count drops or repeats entries
+                        let mut seen = vec![false; gaussians.len()];
+                        for &(_, idx) in radix_sorted_indices.iter() {
+                            assert!(idx < gaussians.len(), "radix sort, index {} out of range", idx);
+                            assert!(!seen[idx], "radix sort, index {} repeated", idx);
+                            seen[idx] = true;
+                        }
+
                         // TODO: depth order validation over ndc cells
 
                         radix_sorted_indices.iter()
@@ -208,9 +258,8 @@ impl Node for RadixTestNode {
                                     return depth_acc;
                                 }
 
-                                let position = gaussians[idx].position_visibility;
-                                let position_vec3 = Vec3::new(position[0], position[1], position[2]);
-                                let depth = (position_vec3 - camera_position).length();
+                                let position = Vec3::from_array(*gaussians.position(idx));
+                                let depth = (cloud_transform.transform_point3(position) - camera_position).length();
 
                                 let depth_is_non_decreasing = depth_acc <= depth;
                                 if !depth_is_non_decreasing {
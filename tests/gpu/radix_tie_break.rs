@@ -0,0 +1,238 @@
+use std::{
+    process::exit,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    core::FrameCount,
+    core_pipeline::{
+        core_3d::graph::{
+            Core3d,
+            Node3d,
+        },
+        tonemapping::Tonemapping,
+    },
+    render::{
+        RenderApp,
+        renderer::{
+            RenderContext,
+            RenderQueue,
+        },
+        render_asset::RenderAssets,
+        render_graph::{
+            Node,
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+        },
+    },
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    GaussianCloudSettings,
+    GaussianSplattingBundle,
+    random_gaussians,
+    render::GpuGaussianCloud,
+    sort::{
+        GpuSortedEntry,
+        SortedEntries,
+        SortMode,
+        radix::RadixSortLabel,
+    },
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+    TestState,
+    TestStateArc,
+};
+
+mod _harness;
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct RadixTieBreakTestLabel;
+
+
+// more gaussians than the 16 index bits of a 32 bit key can tell apart, all at the same depth
+const GAUSSIAN_COUNT: usize = 70000;
+
+
+// every gaussian sits at the origin, so the keys differ in their index bits only. the tie break holds for indices
+// below 65536, past that indices a multiple of 65536 apart share a key and are the only entries free to swap:
+// `cargo run --bin test_radix_tie_break --features="debug_gpu sort_radix testing"`
+fn main() {
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    app.add_systems(Startup, setup);
+
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .add_render_graph_node::<RadixTieBreakTestNode>(
+                Core3d,
+                RadixTieBreakTestLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    RadixSortLabel,
+                    RadixTieBreakTestLabel,
+                    Node3d::EndMainPass,
+                ),
+            );
+    }
+
+    app.run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+) {
+    let mut cloud = random_gaussians(GAUSSIAN_COUNT);
+    for index in 0..cloud.len() {
+        *cloud.position_mut(index) = [0.0, 0.0, 0.0];
+    }
+
+    commands.spawn(GaussianSplattingBundle {
+        cloud: gaussian_assets.add(cloud),
+        settings: GaussianCloudSettings {
+            sort_mode: SortMode::Radix,
+            ..default()
+        },
+        ..default()
+    });
+
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+    ));
+}
+
+
+pub struct RadixTieBreakTestNode {
+    gaussian_clouds: QueryState<(
+        &'static Handle<GaussianCloud>,
+        &'static Handle<SortedEntries>,
+    )>,
+    state: TestStateArc,
+    start_frame: u32,
+}
+
+impl FromWorld for RadixTieBreakTestNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            gaussian_clouds: world.query(),
+            state: Arc::new(Mutex::new(TestState::default())),
+            start_frame: 0,
+        }
+    }
+}
+
+
+impl Node for RadixTieBreakTestNode {
+    fn update(
+        &mut self,
+        world: &mut World,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if state.test_completed {
+            exit(0);
+        }
+
+        if state.test_loaded && self.start_frame == 0 {
+            self.start_frame = world.get_resource::<FrameCount>().unwrap().0;
+        }
+
+        let frame_count = world.get_resource::<FrameCount>().unwrap().0;
+        const FRAME_LIMIT: u32 = 10;
+        if state.test_loaded && frame_count >= self.start_frame + FRAME_LIMIT {
+            state.test_completed = true;
+        }
+
+        self.gaussian_clouds.update_archetypes(world);
+    }
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let gaussian_cloud_res = world.get_resource::<RenderAssets<GpuGaussianCloud>>().unwrap();
+        let sorted_entries_res = world.get_resource::<RenderAssets<GpuSortedEntry>>().unwrap();
+
+        for (cloud_handle, sorted_entries_handle) in self.gaussian_clouds.iter_manual(world) {
+            if gaussian_cloud_res.get(cloud_handle).is_none() {
+                continue;
+            }
+
+            let Some(sorted_entries) = sorted_entries_res.get(sorted_entries_handle) else {
+                continue;
+            };
+
+            self.state.lock().unwrap().test_loaded = true;
+
+            wgpu::util::DownloadBuffer::read_buffer(
+                render_context.render_device().wgpu_device(),
+                world.get_resource::<RenderQueue>().unwrap().0.as_ref(),
+                &sorted_entries.sorted_entry_buffer.slice(
+                    0..sorted_entries.sorted_entry_buffer.size()
+                ),
+                move |buffer: Result<wgpu::util::DownloadBuffer, wgpu::BufferAsyncError>| {
+                    let binding = buffer.unwrap();
+                    let u32_muck = bytemuck::cast_slice::<u8, u32>(&binding);
+
+                    let entries = u32_muck.chunks_exact(2)
+                        .take(GAUSSIAN_COUNT)
+                        .map(|entry| (entry[0], entry[1] as usize))
+                        .collect::<Vec<_>>();
+
+                    // the index collisions must not drop or repeat entries
+                    let mut seen = vec![false; GAUSSIAN_COUNT];
+                    for &(_, idx) in entries.iter() {
+                        assert!(idx < GAUSSIAN_COUNT, "radix tie break, index {} out of range", idx);
+                        assert!(!seen[idx], "radix tie break, index {} repeated", idx);
+                        seen[idx] = true;
+                    }
+
+                    let depth_bits = entries[0].0 >> 16;
+                    for &(key, idx) in entries.iter() {
+                        assert_eq!(key >> 16, depth_bits, "radix tie break, gaussian {} at another depth", idx);
+                        assert_eq!(key & 0xFFFF, idx as u32 & 0xFFFF, "radix tie break, gaussian {} key", idx);
+                    }
+
+                    // ascending keys put low indices first, equal keys only between indices 65536 apart
+                    for pair in entries.windows(2) {
+                        let ((key_a, idx_a), (key_b, idx_b)) = (pair[0], pair[1]);
+
+                        assert!(key_a <= key_b, "radix tie break, keys {:#010x} > {:#010x}", key_a, key_b);
+                        if key_a == key_b {
+                            assert_eq!(
+                                idx_a % 65536,
+                                idx_b % 65536,
+                                "radix tie break, gaussians {} and {} share a key",
+                                idx_a,
+                                idx_b,
+                            );
+                        }
+                    }
+                }
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::process::exit;
+
+use bevy::{
+    prelude::*,
+    core_pipeline::{
+        core_3d::Transparent3d,
+        tonemapping::Tonemapping,
+    },
+    render::{
+        Render,
+        RenderApp,
+        RenderSet,
+        camera::Viewport,
+        render_phase::ViewSortedRenderPhases,
+        view::{
+            ExtractedView,
+            RenderLayers,
+        },
+    },
+    utils::HashSet,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianSplattingBundle,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+};
+
+mod _harness;
+
+
+const MINIMAP_LAYER: usize = 1;
+
+
+// the main world entities of the clouds, one per layer assignment
+#[derive(Resource, Clone)]
+struct LayeredClouds {
+    scene: Entity,
+    minimap: Entity,
+    shared: Entity,
+}
+
+
+// a main camera on the default layer and a minimap camera on layer 1, each view's transparent phase must hold only
+// the clouds on its layers: `cargo run --bin test_render_layers --features="testing"`
+fn main() {
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    let clouds = spawn_scene(app.world_mut());
+
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .insert_resource(clouds)
+            .add_systems(Render, check_view_phases.in_set(RenderSet::Prepare));
+    }
+
+    app.run();
+}
+
+fn spawn_scene(world: &mut World) -> LayeredClouds {
+    let gaussian = Gaussian {
+        rotation: [1.0, 0.0, 0.0, 0.0].into(),
+        position_visibility: [0.0, 0.0, 0.0, 1.0].into(),
+        scale_opacity: [0.5, 0.5, 0.5, 1.0].into(),
+        ..default()
+    };
+
+    let mut spawn_cloud = |render_layers: Option<RenderLayers>| {
+        let cloud = world.resource_mut::<Assets<GaussianCloud>>()
+            .add(GaussianCloud::from_gaussians(vec![gaussian]));
+
+        let mut entity = world.spawn(GaussianSplattingBundle {
+            cloud,
+            ..default()
+        });
+
+        if let Some(render_layers) = render_layers {
+            entity.insert(render_layers);
+        }
+
+        entity.id()
+    };
+
+    let clouds = LayeredClouds {
+        scene: spawn_cloud(None),
+        minimap: spawn_cloud(Some(RenderLayers::layer(MINIMAP_LAYER))),
+        shared: spawn_cloud(Some(RenderLayers::from_layers(&[0, MINIMAP_LAYER]))),
+    };
+
+    world.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+
+    world.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                order: 1,
+                viewport: Some(Viewport {
+                    physical_position: UVec2::ZERO,
+                    physical_size: UVec2::new(128, 128),
+                    ..default()
+                }),
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+        RenderLayers::layer(MINIMAP_LAYER),
+    ));
+
+    clouds
+}
+
+fn check_view_phases(
+    clouds: Res<LayeredClouds>,
+    transparent_phases: Res<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, Option<&RenderLayers>), With<ExtractedView>>,
+    mut checked_frames: Local<u32>,
+) {
+    let mut checked_views = 0;
+
+    for (view_entity, render_layers) in views.iter() {
+        let Some(phase) = transparent_phases.get(&view_entity) else {
+            continue;
+        };
+
+        let drawn = phase.items.iter()
+            .map(|item| item.entity)
+            .collect::<HashSet<Entity>>();
+
+        // the clouds are still loading
+        if drawn.is_empty() {
+            return;
+        }
+
+        let minimap_view = render_layers.is_some_and(|layers| *layers == RenderLayers::layer(MINIMAP_LAYER));
+        let (name, expected) = if minimap_view {
+            ("minimap", HashSet::from_iter([clouds.minimap, clouds.shared]))
+        } else {
+            ("main", HashSet::from_iter([clouds.scene, clouds.shared]))
+        };
+
+        assert_eq!(drawn, expected, "render layers, clouds queued for the {} view", name);
+        checked_views += 1;
+    }
+
+    if checked_views < 2 {
+        return;
+    }
+
+    *checked_frames += 1;
+
+    const FRAME_LIMIT: u32 = 10;
+    if *checked_frames >= FRAME_LIMIT {
+        exit(0);
+    }
+}
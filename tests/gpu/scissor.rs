@@ -0,0 +1,260 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    app::AppExit,
+    core::FrameCount,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        camera::Viewport,
+        render_resource::TextureFormat,
+        view::screenshot::ScreenshotManager,
+    },
+    window::PrimaryWindow,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianScissor,
+    GaussianSplattingBundle,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+    TestStateArc,
+};
+
+mod _harness;
+
+
+const BACKGROUND: [f32; 3] = [0.1, 0.3, 0.8];
+
+
+#[derive(Resource)]
+struct SubViewport {
+    enabled: bool,
+    // the scissor lies in the right half, outside the left half viewport
+    disjoint_scissor: bool,
+}
+
+
+// run with `cargo run --bin test_scissor`, with the camera drawing into the left half of the target only:
+// `cargo run --bin test_scissor -- viewport`, and with a scissor outside that viewport, which must draw nothing
+// without failing: `cargo run --bin test_scissor -- disjoint`
+fn main() {
+    let disjoint_scissor = std::env::args().any(|arg| arg == "disjoint");
+    let viewport = disjoint_scissor || std::env::args().any(|arg| arg == "viewport");
+
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    app.insert_resource(SubViewport {
+        enabled: viewport,
+        disjoint_scissor,
+    });
+    app.insert_resource(ClearColor(Color::linear_rgb(BACKGROUND[0], BACKGROUND[1], BACKGROUND[2])));
+
+    app.add_systems(Startup, setup);
+    app.add_systems(Update, capture_ready);
+
+    app.run();
+}
+
+// the middle quarter of the target, in physical pixels
+fn scissor_rect(size: UVec2) -> URect {
+    URect::from_corners(size / 4, size * 3 / 4)
+}
+
+// a quarter of the target in its right half
+fn disjoint_scissor_rect(size: UVec2) -> URect {
+    URect::from_corners(UVec2::new(size.x * 5 / 8, size.y / 4), UVec2::new(size.x * 7 / 8, size.y * 3 / 4))
+}
+
+// the left half of the target
+fn viewport_rect(size: UVec2) -> URect {
+    URect::from_corners(UVec2::ZERO, UVec2::new(size.x / 2, size.y))
+}
+
+// the scissor is clipped to the viewport, so only its left half is drawn with a sub viewport
+fn drawn_rect(size: UVec2, viewport: bool) -> URect {
+    if viewport {
+        scissor_rect(size).intersect(viewport_rect(size))
+    } else {
+        scissor_rect(size)
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut gaussian_assets: ResMut<Assets<GaussianCloud>>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    sub_viewport: Res<SubViewport>,
+) {
+    // an opaque splat far larger than the view, so every pixel outside the scissor would be covered
+    let gaussian = Gaussian {
+        rotation: [1.0, 0.0, 0.0, 0.0].into(),
+        position_visibility: [0.0, 0.0, 0.0, 1.0].into(),
+        scale_opacity: [20.0, 20.0, 20.0, 1.0].into(),
+        ..default()
+    };
+    let cloud = gaussian_assets.add(GaussianCloud::from_gaussians(vec![gaussian]));
+
+    commands.spawn((
+        GaussianSplattingBundle {
+            cloud,
+            ..default()
+        },
+        Name::new("gaussian_cloud"),
+    ));
+
+    let window = primary_window.single();
+    let size = UVec2::new(window.physical_width(), window.physical_height());
+
+    let viewport = sub_viewport.enabled.then(|| {
+        let rect = viewport_rect(size);
+        Viewport {
+            physical_position: rect.min,
+            physical_size: rect.size(),
+            ..default()
+        }
+    });
+
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                viewport,
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            tonemapping: Tonemapping::None,
+            ..default()
+        },
+        GaussianScissor {
+            rect: if sub_viewport.disjoint_scissor {
+                disjoint_scissor_rect(size)
+            } else {
+                scissor_rect(size)
+            },
+        },
+    ));
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> [f32; 3] {
+    let offset = ((y * image.width() + x) * 4) as usize;
+    let texel = &image.data[offset..offset + 4];
+
+    let rgb = match image.texture_descriptor.format {
+        TextureFormat::Bgra8UnormSrgb | TextureFormat::Bgra8Unorm => [texel[2], texel[1], texel[0]],
+        _ => [texel[0], texel[1], texel[2]],
+    };
+
+    rgb.map(|channel| channel as f32 / 255.0)
+}
+
+fn is_background(rgb: [f32; 3]) -> bool {
+    let tolerance = 3.0 / 255.0;
+
+    rgb.iter()
+        .zip(BACKGROUND.iter())
+        .all(|(channel, background)| (channel - linear_to_srgb(*background)).abs() < tolerance)
+}
+
+fn test_scissor(image: &Image, viewport: bool) {
+    let rect = drawn_rect(image.size(), viewport);
+
+    let inside = [
+        rect.center(),
+        rect.min + 2,
+        rect.max - 3,
+    ];
+    for position in inside {
+        let rgb = pixel(image, position.x, position.y);
+        assert!(!is_background(rgb), "no splat inside the scissor at {:?}: {:?}", position, rgb);
+    }
+
+    let outside = [
+        UVec2::new(2, 2),
+        UVec2::new(rect.center().x, rect.min.y - 3),
+        UVec2::new(rect.max.x + 2, rect.center().y),
+        image.size() - 3,
+    ];
+    for position in outside {
+        let rgb = pixel(image, position.x, position.y);
+        assert!(is_background(rgb), "splat drawn outside the scissor at {:?}: {:?}", position, rgb);
+    }
+}
+
+// the empty intersection skips the draw, so the whole target keeps the clear color
+fn test_disjoint_scissor(image: &Image) {
+    let size = image.size();
+
+    let positions = [
+        UVec2::new(2, 2),
+        viewport_rect(size).center(),
+        disjoint_scissor_rect(size).center(),
+        scissor_rect(size).center(),
+        size - 3,
+    ];
+    for position in positions {
+        let rgb = pixel(image, position.x, position.y);
+        assert!(is_background(rgb), "splat drawn with a scissor outside the viewport at {:?}: {:?}", position, rgb);
+    }
+}
+
+fn capture_ready(
+    main_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    mut exit: EventWriter<AppExit>,
+    frame_count: Res<FrameCount>,
+    sub_viewport: Res<SubViewport>,
+    state: Local<TestStateArc>,
+    buffer: Local<Arc<Mutex<Vec<Image>>>>,
+) {
+    let buffer = buffer.to_owned();
+
+    let wait_frames = 10;  // wait for gaussian cloud to load
+    if frame_count.0 < wait_frames {
+        return;
+    }
+
+    let state_clone = Arc::clone(&state);
+    let buffer_clone = Arc::clone(&buffer);
+
+    let mut state = state.lock().unwrap();
+    state.test_loaded = true;
+
+    if state.test_completed {
+        let captures = buffer.lock().unwrap();
+        if sub_viewport.disjoint_scissor {
+            test_disjoint_scissor(captures.last().unwrap());
+        } else {
+            test_scissor(captures.last().unwrap(), sub_viewport.enabled);
+        }
+
+        exit.send(AppExit::Success);
+        return;
+    }
+
+    if let Ok(window_entity) = main_window.get_single() {
+        screenshot_manager.take_screenshot(window_entity, move |image: Image| {
+            buffer_clone.lock().unwrap().push(image);
+
+            let mut state = state_clone.lock().unwrap();
+            state.test_completed = true;
+        }).unwrap();
+    }
+}
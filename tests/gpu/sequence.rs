@@ -0,0 +1,183 @@
+use std::{
+    path::PathBuf,
+    process::exit,
+};
+
+use bevy::{
+    prelude::*,
+    core::FrameCount,
+    core_pipeline::{
+        core_3d::Transparent3d,
+        tonemapping::Tonemapping,
+    },
+    render::{
+        Render,
+        RenderApp,
+        RenderSet,
+        render_asset::RenderAssets,
+        render_phase::ViewSortedRenderPhases,
+        view::ExtractedView,
+    },
+    utils::HashSet,
+};
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    GaussianSequence,
+    GaussianSequencePlayback,
+    GaussianSplattingBundle,
+    io::splat::write_splat,
+    render::GpuGaussianCloud,
+};
+
+use _harness::{
+    TestHarness,
+    test_harness_app,
+};
+
+mod _harness;
+
+
+// one gaussian in the first frame, two in the second, three in the third, so the drawn frame shows in its count
+const FRAME_COUNT: usize = 3;
+
+// frames to wait for every sequence frame to be extracted before failing
+const TIMEOUT_FRAMES: u32 = 600;
+
+
+// the main world entity of a cloud drawn next to the sequence
+#[derive(Resource, Clone, Copy)]
+struct StaticCloud(Entity);
+
+
+// the extracted cloud handle of the sequence entity must follow the playback through every frame, and a static cloud
+// next to it must stay queued while the sequence swaps to frames that are not prepared yet:
+// `cargo run --bin test_sequence --features="testing"`
+fn main() {
+    for frame in 0..FRAME_COUNT {
+        write_frame(frame);
+    }
+
+    let mut app = test_harness_app(TestHarness {
+        resolution: (512.0, 512.0),
+    });
+
+    let static_cloud = spawn_static_cloud(app.world_mut());
+
+    app.add_systems(Startup, setup);
+    app.sub_app_mut(RenderApp)
+        .insert_resource(static_cloud)
+        .add_systems(Render, check_extracted_frames.in_set(RenderSet::Prepare));
+
+    app.run();
+}
+
+fn frame_path(frame: usize) -> String {
+    format!("sequence_test_{}.splat", frame)
+}
+
+fn asset_path(frame: usize) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets").join(frame_path(frame))
+}
+
+fn write_frame(frame: usize) {
+    let gaussians = (0..=frame)
+        .map(|index| Gaussian {
+            rotation: [1.0, 0.0, 0.0, 0.0].into(),
+            position_visibility: [index as f32 * 0.5, 0.0, 0.0, 1.0].into(),
+            scale_opacity: [0.1, 0.1, 0.1, 0.9].into(),
+            ..default()
+        })
+        .collect::<Vec<_>>();
+
+    std::fs::write(asset_path(frame), write_splat(&gaussians)).expect("failed to write a sequence frame");
+}
+
+fn remove_frames() {
+    for frame in 0..FRAME_COUNT {
+        let _ = std::fs::remove_file(asset_path(frame));
+    }
+}
+
+fn spawn_static_cloud(world: &mut World) -> StaticCloud {
+    let cloud = world.resource_mut::<Assets<GaussianCloud>>()
+        .add(GaussianCloud::from_gaussians(vec![Gaussian {
+            rotation: [1.0, 0.0, 0.0, 0.0].into(),
+            position_visibility: [0.0, -1.0, 0.0, 1.0].into(),
+            scale_opacity: [0.1, 0.1, 0.1, 0.9].into(),
+            ..default()
+        }]));
+
+    StaticCloud(world.spawn(GaussianSplattingBundle {
+        cloud,
+        ..default()
+    }).id())
+}
+
+fn setup(
+    mut commands: Commands,
+    mut sequences: ResMut<Assets<GaussianSequence>>,
+) {
+    commands.spawn((
+        GaussianSplattingBundle::default(),
+        GaussianSequencePlayback {
+            sequence: sequences.add(GaussianSequence::new((0..FRAME_COUNT).map(frame_path))),
+            fps: 10.0,
+            ..default()
+        },
+    ));
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+        tonemapping: Tonemapping::None,
+        ..default()
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_extracted_frames(
+    static_cloud: Res<StaticCloud>,
+    gpu_gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
+    gaussian_clouds: Query<(Entity, &Handle<GaussianCloud>)>,
+    transparent_phases: Res<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<Entity, With<ExtractedView>>,
+    frame_count: Res<FrameCount>,
+    mut extracted_counts: Local<HashSet<usize>>,
+    mut static_cloud_drawn: Local<bool>,
+) {
+    for (entity, cloud_handle) in gaussian_clouds.iter() {
+        if entity == static_cloud.0 {
+            continue;
+        }
+
+        if let Some(cloud) = gpu_gaussian_clouds.get(cloud_handle) {
+            extracted_counts.insert(cloud.count);
+        }
+    }
+
+    // once queued, the static cloud must not be dropped by a sequence frame that is still streaming in
+    for view in views.iter() {
+        let Some(phase) = transparent_phases.get(&view) else {
+            continue;
+        };
+
+        let drawn = phase.items.iter().any(|item| item.entity == static_cloud.0);
+        if *static_cloud_drawn && !drawn {
+            remove_frames();
+            panic!("the static cloud was not queued after the sequence swapped frames");
+        }
+
+        *static_cloud_drawn |= drawn;
+    }
+
+    if extracted_counts.len() == FRAME_COUNT && *static_cloud_drawn {
+        remove_frames();
+        exit(0);
+    }
+
+    if frame_count.0 >= TIMEOUT_FRAMES {
+        remove_frames();
+        panic!("sequence extracted only the frames with {:?} gaussians", extracted_counts);
+    }
+}
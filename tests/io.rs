@@ -1,6 +1,16 @@
+use bevy::{
+    math::Vec3,
+    render::texture::Image,
+};
+use half::f16;
+
 use bevy_gaussian_splatting::{
     GaussianCloud,
-    io::codec::GaussianCloudCodec,
+    io::{
+        codec::GaussianCloudCodec,
+        colmap::CameraTrack,
+        cube::parse_cube,
+    },
     random_gaussians,
 };
 
@@ -11,7 +21,1071 @@ fn test_codec() {
 
     let gaussians = random_gaussians(count);
     let encoded = gaussians.encode();
-    let decoded = GaussianCloud::decode(encoded.as_slice());
+    let decoded = GaussianCloud::decode(encoded.as_slice()).unwrap();
 
     assert_eq!(gaussians, decoded);
 }
+
+// a cloud encoded with the unversioned layout written before GaussianCloud grew its optional fields
+#[cfg(all(
+    feature = "io_bincode2",
+    feature = "f16",
+    not(feature = "precompute_covariance_3d"),
+))]
+#[test]
+fn test_bincode_baseline() {
+    use std::io::Write;
+
+    use bevy_gaussian_splatting::{
+        gaussian::{
+            f16::RotationScaleOpacityPacked128,
+            f32::PositionVisibility,
+        },
+        io::error::GaussianLoadError,
+        material::spherical_harmonics::SphericalHarmonicCoefficients,
+    };
+
+    #[derive(serde::Serialize)]
+    struct BaselineCloud {
+        position_visibility: Vec<PositionVisibility>,
+        spherical_harmonic: Vec<SphericalHarmonicCoefficients>,
+        rotation_scale_opacity_packed128: Vec<RotationScaleOpacityPacked128>,
+    }
+
+    let mut cloud = random_gaussians(64);
+    let baseline = BaselineCloud {
+        position_visibility: cloud.position_visibility.clone(),
+        spherical_harmonic: cloud.spherical_harmonic.clone(),
+        rotation_scale_opacity_packed128: cloud.rotation_scale_opacity_packed128.clone(),
+    };
+
+    let mut fixture = Vec::new();
+    {
+        let mut gz_encoder = flate2::write::GzEncoder::new(&mut fixture, flate2::Compression::default());
+        bincode2::serialize_into(&mut gz_encoder, &baseline).unwrap();
+        gz_encoder.flush().unwrap();
+    }
+
+    assert_eq!(GaussianCloud::decode(&fixture).unwrap(), cloud);
+
+    // the versioned layout carries the fields the baseline lacks
+    cloud.set_channel((0..cloud.len()).map(|index| index as f32).collect());
+    let encoded = cloud.encode();
+    assert_eq!(GaussianCloud::decode(&encoded).unwrap(), cloud);
+
+    // other versions and truncated payloads are load errors instead of panics
+    let rewrite = |edit: &dyn Fn(&mut Vec<u8>)| {
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(encoded.as_slice()), &mut decompressed).unwrap();
+        edit(&mut decompressed);
+
+        let mut bytes = Vec::new();
+        let mut gz_encoder = flate2::write::GzEncoder::new(&mut bytes, flate2::Compression::default());
+        gz_encoder.write_all(&decompressed).unwrap();
+        gz_encoder.finish().unwrap();
+
+        bytes
+    };
+
+    assert!(matches!(
+        GaussianCloud::decode(&rewrite(&|bytes| bytes[4] = 2)),
+        Err(GaussianLoadError::UnsupportedVersion { version: 2, .. }),
+    ));
+    assert!(matches!(
+        GaussianCloud::decode(&rewrite(&|bytes| bytes.truncate(bytes.len() / 2))),
+        Err(GaussianLoadError::Io(_)),
+    ));
+    assert!(matches!(
+        GaussianCloud::decode(&rewrite(&|bytes| bytes.truncate(6))),
+        Err(GaussianLoadError::Io(_)),
+    ));
+}
+
+#[test]
+fn test_colmap_camera_track() {
+    let cameras = "\
+# Camera list with one line of data per camera:
+1 PINHOLE 200 100 100.0 100.0 100.0 50.0
+";
+    let images = "\
+# Image list with two lines of data per image:
+1 1.0 0.0 0.0 0.0 0.0 0.0 2.0 1 frame_000.png
+
+";
+
+    let track = CameraTrack::from_colmap(cameras, images).unwrap();
+    assert_eq!(track.poses.len(), 1);
+
+    let pose = &track.poses[0];
+    assert_eq!(pose.name, "frame_000.png");
+    assert!(pose.transform.translation.abs_diff_eq(Vec3::new(0.0, 0.0, -2.0), 1e-5));
+
+    // colmap cameras look down +z with +y pointing down the image
+    assert!(pose.transform.forward().abs_diff_eq(Vec3::Z, 1e-5));
+    assert!(pose.transform.up().abs_diff_eq(-Vec3::Y, 1e-5));
+
+    assert!((pose.projection.fov - 2.0 * 0.5_f32.atan()).abs() < 1e-5);
+    assert_eq!(pose.projection.aspect_ratio, 2.0);
+}
+
+#[test]
+fn test_colmap_image_without_observations() {
+    let cameras = "1 PINHOLE 200 100 100.0 100.0 100.0 50.0\n";
+
+    // the first image has an empty 2d points line, the pairs must stay aligned past it
+    let images = "\
+# Image list with two lines of data per image:
+1 1.0 0.0 0.0 0.0 0.0 0.0 2.0 1 frame_000.png
+
+2 1.0 0.0 0.0 0.0 1.0 0.0 2.0 1 frame_001.png
+10.0 20.0 -1 30.0 40.0 7
+3 1.0 0.0 0.0 0.0 2.0 0.0 2.0 1 frame_002.png
+
+
+";
+
+    let track = CameraTrack::from_colmap(cameras, images).unwrap();
+
+    let names = track.poses.iter()
+        .map(|pose| pose.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, ["frame_000.png", "frame_001.png", "frame_002.png"]);
+
+    assert!(track.poses[1].transform.translation.abs_diff_eq(Vec3::new(-1.0, 0.0, -2.0), 1e-5));
+    assert!(track.poses[2].transform.translation.abs_diff_eq(Vec3::new(-2.0, 0.0, -2.0), 1e-5));
+}
+
+#[cfg(feature = "io_ply")]
+fn binary_ply(vertices: &[[f32; 11]]) -> Vec<u8> {
+    let mut ply = ply_header("binary_little_endian", vertices.len()).into_bytes();
+    for vertex in vertices {
+        for value in vertex.iter().chain(&[0.0; 3]) {
+            ply.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    ply
+}
+
+#[cfg(feature = "io_ply")]
+fn ply_header(format: &str, count: usize) -> String {
+    format!("\
+ply
+format {} 1.0
+element vertex {}
+property float x
+property float y
+property float z
+property float scale_0
+property float scale_1
+property float scale_2
+property float opacity
+property float rot_0
+property float rot_1
+property float rot_2
+property float rot_3
+property float f_dc_0
+property float f_dc_1
+property float f_dc_2
+end_header
+", format, count)
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_sanitize_ply() {
+    use bevy_gaussian_splatting::io::ply::{
+        parse_ply,
+        sanitize_gaussians,
+    };
+
+    let ply = binary_ply(&[0.0, f32::NAN, 2.0].map(|x| [x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]));
+
+    let mut reader = std::io::BufReader::new(ply.as_slice());
+    let mut gaussians = parse_ply(&mut reader).unwrap();
+    assert_eq!(gaussians.len(), 3);
+
+    assert_eq!(sanitize_gaussians(&mut gaussians), 1);
+
+    let xs = gaussians.iter()
+        .map(|gaussian| gaussian.position_visibility.position[0])
+        .collect::<Vec<f32>>();
+    assert_eq!(xs, vec![0.0, 2.0]);
+
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+    assert_eq!(cloud.len(), 2);
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_load_errors() {
+    use bevy_gaussian_splatting::{
+        GaussianLoadError,
+        io::ply::parse_ply,
+    };
+
+    let parse = |ply: &[u8]| parse_ply(&mut std::io::BufReader::new(ply));
+
+    let ply = binary_ply(&[[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]; 2]);
+    let header_len = ply.len() - 2 * 14 * 4;
+
+    match parse(&ply[..ply.len() - 4]) {
+        Err(GaussianLoadError::Truncated { element, expected, byte_offset }) => {
+            assert_eq!(element, "vertex");
+            assert_eq!(expected, 2);
+            assert_eq!(byte_offset, (ply.len() - 4) as u64);
+            assert!(byte_offset > header_len as u64);
+        },
+        other => panic!("expected truncation, got {:?}", other),
+    }
+
+    let unknown_format = String::from_utf8_lossy(&ply[..header_len]).replace("binary_little_endian", "binary_middle_endian");
+    assert!(matches!(
+        parse(unknown_format.as_bytes()),
+        Err(GaussianLoadError::UnsupportedFormat { format, byte_offset: 4 }) if format == "binary_middle_endian",
+    ));
+
+    let no_x = String::from_utf8_lossy(&ply[..header_len]).replace("property float x\n", "");
+    assert!(matches!(
+        parse(no_x.as_bytes()),
+        Err(GaussianLoadError::MissingProperty { property: "x", .. }),
+    ));
+
+    let points = String::from_utf8_lossy(&ply[..header_len]).replace("element vertex", "element point");
+    assert!(matches!(
+        parse(points.as_bytes()),
+        Err(GaussianLoadError::MissingElement { element: "vertex", found }) if found == vec!["point".to_string()],
+    ));
+
+    assert!(matches!(parse(b"solid stl\n"), Err(GaussianLoadError::NotPly)));
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_formats() {
+    use bevy_gaussian_splatting::{
+        GaussianLoadError,
+        io::ply::parse_ply,
+    };
+
+    let parse = |ply: &[u8]| parse_ply(&mut std::io::BufReader::new(ply));
+
+    let vertices: [[f32; 11]; 2] = [
+        [0.5, -1.0, 2.0, -2.0, -1.5, -3.0, 1.25, 1.0, 0.0, 0.0, 0.0],
+        [-0.125, 3.0, 1e-5, 0.0, 0.5, -0.25, -2.0, 0.5, 0.5, 0.5, 0.5],
+    ];
+
+    let mut big_endian = ply_header("binary_big_endian", vertices.len()).into_bytes();
+    for vertex in &vertices {
+        for value in vertex.iter().chain(&[0.0; 3]) {
+            big_endian.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    // exporters vary in float formatting and whitespace, blank lines are skipped
+    let ascii = ply_header("ascii", vertices.len()) + "\
+.5 -1 2.0 -2 -1.5 -3 1.25 1 0 0 0 0 0 0
+
+-0.125  3 1e-5 0 0.5 -0.25 -2 0.5 0.5 0.5 0.5 0 0 0
+";
+
+    let little_endian = parse(&binary_ply(&vertices)).unwrap();
+    assert_eq!(little_endian.len(), 2);
+    assert_eq!(parse(&big_endian).unwrap(), little_endian);
+    assert_eq!(parse(ascii.as_bytes()).unwrap(), little_endian);
+
+    let header_len = ply_header("ascii", vertices.len()).len();
+    assert!(matches!(
+        parse(ascii.replace("1.25", "1,25").as_bytes()),
+        Err(GaussianLoadError::InvalidPayload { byte_offset, .. }) if byte_offset == header_len as u64,
+    ));
+
+    let truncated = &ascii[..ascii.trim_end().rfind('\n').unwrap() + 1];
+    assert!(matches!(
+        parse(truncated.as_bytes()),
+        Err(GaussianLoadError::Truncated { expected: 2, .. }),
+    ));
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_extra_properties() {
+    use bevy_gaussian_splatting::io::{
+        loader::GaussianCloudLoaderSettings,
+        ply::{
+            parse_ply,
+            parse_ply_with_extras,
+        },
+    };
+
+    let vertices = [
+        [0.5, -1.0, 2.0, -2.0, -1.5, -3.0, 1.25, 1.0, 0.0, 0.0, 0.0],
+        [-0.125, 3.0, 1e-5, 0.0, 0.5, -0.25, -2.0, 0.5, 0.5, 0.5, 0.5],
+    ];
+    let plain = parse_ply(&mut std::io::BufReader::new(binary_ply(&vertices).as_slice())).unwrap();
+
+    // an exporter adding a double confidence after x, a list, and a u16 timestamp and int8 flag at the end
+    let header = ply_header("binary_little_endian", vertices.len())
+        .replace("property float x\n", "property float x\nproperty double confidence\n")
+        .replace("property float y\n", "property float y\nproperty list uchar int neighbors\n")
+        .replace("end_header\n", "property ushort timestamp\nproperty char flag\nend_header\n");
+    let mut ply = header.into_bytes();
+    for (index, vertex) in vertices.iter().enumerate() {
+        for (property, value) in vertex.iter().enumerate() {
+            ply.extend_from_slice(&value.to_le_bytes());
+
+            if property == 0 {
+                ply.extend_from_slice(&(0.25 + index as f64).to_le_bytes());
+            }
+
+            if property == 1 {
+                ply.push(2);
+                ply.extend_from_slice(&7_i32.to_le_bytes());
+                ply.extend_from_slice(&(index as i32).to_le_bytes());
+            }
+        }
+
+        for dc in [0.0_f32; 3] {
+            ply.extend_from_slice(&dc.to_le_bytes());
+        }
+        ply.extend_from_slice(&(1000 + index as u16).to_le_bytes());
+        ply.push((-1_i8) as u8);
+    }
+
+    let (gaussians, extras) = parse_ply_with_extras(
+        &mut std::io::BufReader::new(ply.as_slice()),
+        &GaussianCloudLoaderSettings::default(),
+    ).unwrap();
+
+    // the core attributes load as without the extras, which are read past at their own widths
+    assert_eq!(gaussians, plain);
+
+    assert_eq!(extras.len(), 3);
+    assert_eq!(extras["confidence"], vec![0.25, 1.25]);
+    assert_eq!(extras["timestamp"], vec![1000.0, 1001.0]);
+    assert_eq!(extras["flag"], vec![-1.0, -1.0]);
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_vertex_colors() {
+    use bevy_gaussian_splatting::{
+        GaussianLoadError,
+        io::{
+            loader::GaussianCloudLoaderSettings,
+            ply::parse_ply_with_extras,
+        },
+        material::spherical_harmonics::SH_C0,
+    };
+
+    let dc_properties = "property float f_dc_0\nproperty float f_dc_1\nproperty float f_dc_2\n";
+    let header = ply_header("binary_little_endian", 1)
+        .replace(dc_properties, "property uchar red\nproperty uchar green\nproperty uchar blue\n");
+
+    let mut ply = header.clone().into_bytes();
+    for value in [0.0_f32, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0] {
+        ply.extend_from_slice(&value.to_le_bytes());
+    }
+    ply.extend_from_slice(&[255, 0, 51]);
+
+    let parse = |ply: &[u8]| parse_ply_with_extras(
+        &mut std::io::BufReader::new(ply),
+        &GaussianCloudLoaderSettings::default(),
+    );
+
+    // a point cloud export without dc terms takes its vertex colors, which are not kept as extras
+    let (gaussians, extras) = parse(&ply).unwrap();
+    for (channel, color) in [1.0, 0.0, 0.2].into_iter().enumerate() {
+        let loaded = 0.5 + SH_C0 * gaussians[0].spherical_harmonic.get(channel);
+        assert!((loaded - color).abs() < 1e-5, "channel {} loaded as {}", channel, loaded);
+    }
+    assert!(extras.is_empty());
+
+    // without either the cloud would load black
+    let no_color = ply_header("binary_little_endian", 1).replace(dc_properties, "");
+    assert!(matches!(
+        parse(no_color.as_bytes()),
+        Err(GaussianLoadError::MissingProperty { property: "f_dc_0", .. }),
+    ));
+
+    let partial_dc = ply_header("binary_little_endian", 1).replace("property float f_dc_2\n", "");
+    assert!(matches!(
+        parse(partial_dc.as_bytes()),
+        Err(GaussianLoadError::MissingProperty { property: "f_dc_2", .. }),
+    ));
+
+    let no_blue = header.replace("property uchar blue\n", "");
+    assert!(matches!(
+        parse(no_blue.as_bytes()),
+        Err(GaussianLoadError::MissingProperty { property: "f_dc_0", .. }),
+    ));
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_write_ply() {
+    use bevy_gaussian_splatting::io::{
+        loader::{
+            ActivationMode,
+            GaussianCloudLoaderSettings,
+        },
+        ply::{
+            parse_ply_with_settings,
+            write_ply,
+        },
+    };
+
+    let mut cloud = random_gaussians(64);
+    cloud.morton_reorder();
+    let mut gaussians = cloud.gaussian_iter().collect::<Vec<_>>();
+
+    // keeps the log scales within the loader's MAX_SIZE_VARIANCE of their mean, which would clamp them
+    for gaussian in &mut gaussians {
+        for scale in &mut gaussian.scale_opacity.scale {
+            *scale = scale.max(0.05);
+        }
+    }
+
+    let settings = GaussianCloudLoaderSettings {
+        activation: ActivationMode::Activate,
+        ..Default::default()
+    };
+    let mut reader = std::io::BufReader::new(std::io::Cursor::new(write_ply(&gaussians, None)));
+    let loaded = parse_ply_with_settings(&mut reader, &settings).unwrap();
+
+    assert_eq!(loaded.len(), gaussians.len());
+    for (loaded, source) in loaded.iter().zip(gaussians.iter()) {
+        assert_eq!(loaded.position_visibility.position, source.position_visibility.position);
+        assert!((loaded.scale_opacity.opacity - source.scale_opacity.opacity).abs() < 1e-4);
+        assert_eq!(loaded.spherical_harmonic, source.spherical_harmonic);
+
+        for (loaded, source) in loaded.scale_opacity.scale.iter().zip(source.scale_opacity.scale) {
+            assert!((loaded - source).abs() < 1e-5, "scale {} loaded as {}", source, loaded);
+        }
+
+        // the loader normalizes rotations, the random ones are not
+        let norm = source.rotation.rotation.iter().map(|x| x * x).sum::<f32>().sqrt();
+        for (loaded, source) in loaded.rotation.rotation.iter().zip(source.rotation.rotation) {
+            assert!((loaded - source / norm).abs() < 1e-5, "rotation {} loaded as {}", source / norm, loaded);
+        }
+    }
+
+    // normals go between the position and the dc terms, which the loader skips over
+    let normals = vec![Vec3::Y; gaussians.len()];
+    let with_normals = write_ply(&gaussians, Some(&normals));
+    assert!(String::from_utf8_lossy(&with_normals).contains("property float z\nproperty float nx\n"));
+
+    let mut reader = std::io::BufReader::new(std::io::Cursor::new(with_normals));
+    assert_eq!(parse_ply_with_settings(&mut reader, &settings).unwrap(), loaded);
+}
+
+#[test]
+fn test_parse_cube() {
+    let cube = "\
+TITLE \"identity\"
+LUT_3D_SIZE 2
+0 0 0
+1 0 0
+0 1 0
+1 1 0
+0 0 1
+1 0 1
+0 1 1
+1 1 1
+";
+    let lut = parse_cube(cube).unwrap();
+    assert_eq!(lut.image.texture_descriptor.size.depth_or_array_layers, 2);
+    assert_eq!((lut.domain_min, lut.domain_max), (Vec3::ZERO, Vec3::ONE));
+
+    let texel = |lut: &Image, index: usize| {
+        lut.data[index * 8..index * 8 + 8]
+            .chunks_exact(2)
+            .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]).to_f32())
+            .collect::<Vec<f32>>()
+    };
+    assert_eq!(texel(&lut.image, 1), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(texel(&lut.image, 7), [1.0, 1.0, 1.0, 1.0]);
+
+    // a shade between two 8 bit steps survives, so gradients through the lut don't band
+    let subtle = parse_cube(&cube.replacen("0 0 0", "0.1234 0.5 0.0039", 1)).unwrap();
+    let graded = texel(&subtle.image, 0);
+    assert!((graded[0] - 0.1234).abs() < 1e-4, "{:?}", graded);
+    assert!((graded[2] - 0.0039).abs() < 1e-5, "{:?}", graded);
+
+    assert!(parse_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+
+    // the domain bounds the inputs, the table values are kept as written
+    let wide = parse_cube(&cube.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MIN -1 0 0\nDOMAIN_MAX 4 2 2")
+        .replacen("1 1 1", "3 2.5 0.5", 1)).unwrap();
+    assert_eq!(wide.domain_min, Vec3::new(-1.0, 0.0, 0.0));
+    assert_eq!(wide.domain_max, Vec3::new(4.0, 2.0, 2.0));
+    assert_eq!(texel(&wide.image, 1), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(texel(&wide.image, 7), [3.0, 2.5, 0.5, 1.0]);
+
+    let mut images = bevy::asset::Assets::<Image>::default();
+    let mut settings = bevy_gaussian_splatting::GaussianCloudSettings::default();
+    wide.apply(&mut images, &mut settings);
+    assert!(settings.color_lut.is_some());
+    assert_eq!(settings.color_lut_input_max, Vec3::new(4.0, 2.0, 2.0));
+
+    assert!(parse_cube(&cube.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MAX 1 0 1")).is_err());
+}
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_activation() {
+    use bevy_gaussian_splatting::io::{
+        loader::ActivationMode,
+        ply::parse_ply_with_activation,
+    };
+
+    let logit_space = binary_ply(&[
+        [0.0, 0.0, 0.0, -1.0, -1.0, -1.0, 2.0, 1.0, 0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0, 0.5, 0.5, 0.5, -2.0, 1.0, 0.0, 0.0, 0.0],
+    ]);
+    let activated_space = binary_ply(&[
+        [0.0, 0.0, 0.0, 0.25, 0.25, 0.25, 0.9, 1.0, 0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0, 2.0, 2.0, 2.0, 0.1, 1.0, 0.0, 0.0, 0.0],
+    ]);
+
+    let parse = |ply: &[u8], activation| {
+        let mut reader = std::io::BufReader::new(ply);
+        parse_ply_with_activation(&mut reader, activation).unwrap()
+    };
+
+    let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+
+    for activation in [ActivationMode::Activate, ActivationMode::Auto] {
+        let gaussians = parse(&logit_space, activation);
+        assert!((gaussians[0].scale_opacity.opacity - sigmoid(2.0)).abs() < 1e-6);
+        assert!((gaussians[0].scale_opacity.scale[0] - (-1.0_f32).exp()).abs() < 1e-6);
+        assert!((gaussians[1].scale_opacity.opacity - sigmoid(-2.0)).abs() < 1e-6);
+    }
+
+    for activation in [ActivationMode::Raw, ActivationMode::Auto] {
+        let gaussians = parse(&activated_space, activation);
+        assert_eq!(gaussians[0].scale_opacity.opacity, 0.9);
+        assert_eq!(gaussians[0].scale_opacity.scale, [0.25, 0.25, 0.25]);
+        assert_eq!(gaussians[1].scale_opacity.scale, [2.0, 2.0, 2.0]);
+    }
+}
+
+#[cfg(all(feature = "io_ply", not(feature = "web")))]
+#[test]
+fn test_ply_sh_layout() {
+    use bevy_gaussian_splatting::io::{
+        loader::{
+            GaussianCloudLoaderSettings,
+            ShCoefficientLayout,
+        },
+        ply::parse_ply_with_settings,
+    };
+
+    // one degree 1 gaussian where channel c, coefficient k holds 10 * c + k
+    let sh_value = |channel: usize, coefficient: usize| (10 * channel + coefficient) as f32;
+
+    let parse = |properties: Vec<(String, f32)>, sh_layout| {
+        let mut ply = b"ply\nformat binary_little_endian 1.0\nelement vertex 1\n".to_vec();
+        for (name, _) in &properties {
+            ply.extend_from_slice(format!("property float {}\n", name).as_bytes());
+        }
+        ply.extend_from_slice(b"end_header\n");
+
+        for (_, value) in &properties {
+            ply.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let settings = GaussianCloudLoaderSettings {
+            sh_layout,
+            ..Default::default()
+        };
+
+        let mut reader = std::io::BufReader::new(ply.as_slice());
+        parse_ply_with_settings(&mut reader, &settings).unwrap().remove(0)
+    };
+
+    let base = vec![
+        ("x".to_string(), 0.0),
+        ("y".to_string(), 0.0),
+        ("z".to_string(), 0.0),
+        ("rot_0".to_string(), 1.0),
+        ("f_dc_0".to_string(), sh_value(0, 0)),
+        ("f_dc_1".to_string(), sh_value(1, 0)),
+        ("f_dc_2".to_string(), sh_value(2, 0)),
+    ];
+
+    // reference exporter: channels in sequence, properties listed in index order
+    let mut channel_major = base.clone();
+    for channel in 0..3 {
+        for coefficient in 1..=3 {
+            channel_major.push((format!("f_rest_{}", channel * 3 + coefficient - 1), sh_value(channel, coefficient)));
+        }
+    }
+
+    // interleaving exporter: rgb per coefficient, properties listed in reverse
+    let mut coefficient_major = base;
+    for coefficient in (1..=3).rev() {
+        for channel in (0..3).rev() {
+            coefficient_major.push((format!("f_rest_{}", (coefficient - 1) * 3 + channel), sh_value(channel, coefficient)));
+        }
+    }
+
+    let a = parse(channel_major, ShCoefficientLayout::ChannelMajor);
+    let b = parse(coefficient_major, ShCoefficientLayout::CoefficientMajor);
+
+    assert_eq!(a.spherical_harmonic, b.spherical_harmonic);
+
+    for channel in 0..3 {
+        for coefficient in 1..=3 {
+            assert_eq!(a.spherical_harmonic.get(coefficient * 3 + channel), sh_value(channel, coefficient));
+        }
+    }
+}
+
+#[cfg(feature = "io_spz")]
+#[test]
+fn test_spz() {
+    use std::io::Write;
+
+    use bevy_gaussian_splatting::{
+        Gaussian,
+        io::spz::{
+            SPZ_FRACTIONAL_BITS,
+            parse_spz,
+            write_spz,
+        },
+    };
+
+    let close = |a: f32, b: f32, tolerance: f32| (a - b).abs() <= tolerance;
+
+    // hand encoded version 2 reference, one degree 0 point with 4 fractional position bits
+    let mut raw = b"NGSP".to_vec();
+    raw.extend_from_slice(&2u32.to_le_bytes());
+    raw.extend_from_slice(&1u32.to_le_bytes());
+    raw.extend_from_slice(&[0, 4, 0, 0]);
+    raw.extend_from_slice(&[0x18, 0x00, 0x00, 0xf0, 0xff, 0xff, 0x00, 0x00, 0x00]);
+    raw.push(255);
+    raw.extend_from_slice(&[128, 128, 128]);
+    raw.extend_from_slice(&[160, 176, 144]);
+    raw.extend_from_slice(&[128, 128, 128]);
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&raw).unwrap();
+    let reference = encoder.finish().unwrap();
+
+    let gaussians = parse_spz(&reference).unwrap();
+    assert_eq!(gaussians.len(), 1);
+
+    let gaussian = gaussians[0];
+    assert_eq!(gaussian.position_visibility.position, [1.5, -1.0, 0.0]);
+    assert_eq!(gaussian.scale_opacity.opacity, 1.0);
+    assert!(close(gaussian.scale_opacity.scale[0], 1.0, 1e-6));
+    assert!(close(gaussian.scale_opacity.scale[1], std::f32::consts::E, 1e-5));
+    assert!(close(gaussian.scale_opacity.scale[2], 1.0 / std::f32::consts::E, 1e-6));
+    assert!(close(gaussian.rotation.rotation[0], 1.0, 1e-3));
+
+    let mut source = Gaussian::default();
+    source.position_visibility.position = [0.25, -3.0, 12.5];
+    source.scale_opacity.scale = [0.01, 0.2, 1.5];
+    source.scale_opacity.opacity = 0.6;
+    source.rotation.rotation = [0.5, 0.5, -0.5, 0.5];
+    source.spherical_harmonic.set(0, 0.8);
+
+    let decoded = parse_spz(&write_spz(&[source], SPZ_FRACTIONAL_BITS).unwrap()).unwrap();
+    assert_eq!(decoded.len(), 1);
+
+    let decoded = decoded[0];
+    for (a, b) in decoded.position_visibility.position.iter().zip(source.position_visibility.position) {
+        assert!(close(*a, b, 1e-3));
+    }
+
+    // 8 bit log scales step by 1/16
+    for (a, b) in decoded.scale_opacity.scale.iter().zip(source.scale_opacity.scale) {
+        assert!(close((a / b).ln(), 0.0, 1.0 / 32.0));
+    }
+
+    for (a, b) in decoded.rotation.rotation.iter().zip(source.rotation.rotation) {
+        assert!(close(*a, b, 1e-2));
+    }
+    assert!(close(decoded.scale_opacity.opacity, 0.6, 1.0 / 255.0));
+    assert!(close(decoded.spherical_harmonic.get(0), 0.8, 0.05));
+}
+
+
+// version 3 packs rotations as the smallest three components, checked here with degree 1 sh, whose rest coefficients
+// follow the rotations
+#[cfg(all(feature = "io_spz", not(feature = "web")))]
+#[test]
+fn test_spz_version_3() {
+    use std::io::Write;
+
+    use bevy_gaussian_splatting::io::spz::parse_spz;
+
+    let close = |a: f32, b: f32, tolerance: f32| (a - b).abs() <= tolerance;
+
+    // one degree 1 point at the origin with 0 fractional position bits
+    let mut raw = b"NGSP".to_vec();
+    raw.extend_from_slice(&3u32.to_le_bytes());
+    raw.extend_from_slice(&1u32.to_le_bytes());
+    raw.extend_from_slice(&[1, 0, 0, 0]);
+    raw.extend_from_slice(&[0; 9]);
+    raw.push(255);
+    raw.extend_from_slice(&[128, 128, 128]);
+    raw.extend_from_slice(&[160, 160, 160]);
+
+    // xyzw (0.1, 0.86, -0.3, 0.4): y is the largest (index 1 in the top two bits), then x, z (sign bit set) and w
+    // from the high bits down, 9 bit magnitudes of 72, 217 and 289 out of 511 * sqrt(1/2)
+    let packed: u32 = (1 << 30) | (72 << 20) | (((1 << 9) | 217) << 10) | 289;
+    assert_eq!(packed.to_le_bytes(), [0x21, 0x65, 0x8b, 0x44]);
+    raw.extend_from_slice(&packed.to_le_bytes());
+
+    // rest coefficients, rgb interleaved per coefficient
+    raw.extend_from_slice(&[192, 64, 128, 255, 0, 128, 160, 96, 128]);
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&raw).unwrap();
+    let gaussians = parse_spz(&encoder.finish().unwrap()).unwrap();
+    assert_eq!(gaussians.len(), 1);
+
+    // gaussians store wxyz
+    let gaussian = gaussians[0];
+    let expected = [0.4, 0.1, 0.8602, -0.3];
+    for (a, b) in gaussian.rotation.rotation.iter().zip(expected) {
+        assert!(close(*a, b, 2e-3), "rotation {:?}, expected {:?}", gaussian.rotation.rotation, expected);
+    }
+
+    let rest = [
+        [0.5, -0.5, 0.0],
+        [127.0 / 128.0, -1.0, 0.0],
+        [0.25, -0.25, 0.0],
+    ];
+    for (coefficient, values) in rest.iter().enumerate() {
+        for (channel, value) in values.iter().enumerate() {
+            assert_eq!(gaussian.spherical_harmonic.get((coefficient + 1) * 3 + channel), *value);
+        }
+    }
+
+    // bands past the file's degree stay zero
+    for index in 12..(1 + 3 + 5 + 7) * 3 {
+        assert_eq!(gaussian.spherical_harmonic.get(index), 0.0);
+    }
+}
+
+#[test]
+fn test_splat() {
+    use bevy_gaussian_splatting::{
+        Gaussian,
+        io::splat::{
+            SPLAT_POINT_SIZE,
+            parse_splat,
+            write_splat,
+        },
+    };
+
+    let close = |a: f32, b: f32, tolerance: f32| (a - b).abs() <= tolerance;
+
+    let mut source = Gaussian::default();
+    source.position_visibility.position = [0.25, -3.0, 12.5];
+    source.scale_opacity.scale = [0.01, 0.2, 1.5];
+    source.scale_opacity.opacity = 0.6;
+    source.rotation.rotation = [0.5, 0.5, -0.5, 0.5];
+    source.spherical_harmonic.set(0, 0.8);
+    source.spherical_harmonic.set(1, -0.4);
+
+    let bytes = write_splat(&[source, source]);
+    assert_eq!(bytes.len(), 2 * SPLAT_POINT_SIZE);
+    assert!(parse_splat(&bytes[..SPLAT_POINT_SIZE + 1]).is_err());
+
+    let decoded = parse_splat(&bytes).unwrap();
+    assert_eq!(decoded.len(), 2);
+
+    let decoded = decoded[0];
+    assert_eq!(decoded.position_visibility.position, source.position_visibility.position);
+    assert_eq!(decoded.position_visibility.visibility, 1.0);
+    assert_eq!(decoded.scale_opacity.scale, source.scale_opacity.scale);
+    assert!(close(decoded.scale_opacity.opacity, 0.6, 1.0 / 255.0));
+
+    for (a, b) in decoded.rotation.rotation.iter().zip(source.rotation.rotation) {
+        assert!(close(*a, b, 1e-2));
+    }
+
+    // one 8 bit color step is 1 / (255 * SH_C0) in dc units
+    assert!(close(decoded.spherical_harmonic.get(0), 0.8, 0.02));
+    assert!(close(decoded.spherical_harmonic.get(1), -0.4, 0.02));
+    assert!(close(decoded.spherical_harmonic.get(2), 0.0, 0.02));
+}
+
+
+#[cfg(all(feature = "io_gltf", not(feature = "precompute_covariance_3d")))]
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize((json.len() + 3) & !3, b' ');
+
+    let mut bin = bin.to_vec();
+    bin.resize((bin.len() + 3) & !3, 0);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"glTF");
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"JSON");
+    bytes.extend_from_slice(&json);
+
+    bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"BIN\0");
+    bytes.extend_from_slice(&bin);
+
+    bytes
+}
+
+#[cfg(all(feature = "io_gltf", not(feature = "precompute_covariance_3d")))]
+fn gltf_fixture() -> (String, Vec<u8>) {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    let mut bin = Vec::new();
+    for value in [
+        0.0, 0.0, 0.0, 0.0, 2.0, 0.0,  // positions
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2,  // xyzw rotations
+        0.1, 0.2, 0.3, 0.5, 0.5, 0.5,  // scales
+    ] {
+        bin.extend_from_slice(&f32::to_le_bytes(value));
+    }
+    bin.extend_from_slice(&[255, 128, 0, 255, 128, 128, 128, 64]);  // normalized rgba
+
+    let json = r#"{
+        "asset": { "version": "2.0" },
+        "extensionsUsed": ["KHR_gaussian_splatting"],
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 1] }],
+        "nodes": [
+            { "name": "splats", "mesh": 0, "translation": [1.0, 0.0, 0.0] },
+            { "name": "plain", "mesh": 1 }
+        ],
+        "meshes": [
+            { "primitives": [
+                {
+                    "mode": 0,
+                    "attributes": {
+                        "POSITION": 0,
+                        "KHR_gaussian_splatting:ROTATION": 1,
+                        "KHR_gaussian_splatting:SCALE": 2,
+                        "COLOR_0": 3
+                    },
+                    "extensions": { "KHR_gaussian_splatting": {} }
+                },
+                { "mode": 0, "attributes": { "POSITION": 0 } }
+            ] },
+            { "primitives": [{ "mode": 0, "attributes": { "POSITION": 0 } }] }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [0.0, 2.0, 0.0] },
+            { "bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC4" },
+            { "bufferView": 2, "componentType": 5126, "count": 2, "type": "VEC3" },
+            { "bufferView": 3, "componentType": 5121, "normalized": true, "count": 2, "type": "VEC4" }
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 24, "byteLength": 32 },
+            { "buffer": 0, "byteOffset": 56, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 80, "byteLength": 8 }
+        ],
+        "buffers": [{ "byteLength": 88 }]
+    }"#;
+
+    (json.to_string(), bin)
+}
+
+#[cfg(all(feature = "io_gltf", not(feature = "precompute_covariance_3d")))]
+#[test]
+fn test_gltf() {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use base64::Engine;
+    use bevy_gaussian_splatting::io::gltf::parse_gltf;
+
+    let close = |a: f32, b: f32| (a - b).abs() <= 1e-2;
+
+    let (splats, bin) = gltf_fixture();
+
+    let primitives = parse_gltf(&glb(&splats, &bin)).unwrap();
+    assert_eq!(primitives.len(), 1);
+    assert_eq!((primitives[0].mesh, primitives[0].primitive), (0, 0));
+
+    // positions stay in the primitive's space, the node transform is applied by the scene
+    let gaussians = &primitives[0].gaussians;
+    assert_eq!(gaussians.len(), 2);
+    assert_eq!(gaussians[0].position_visibility.position, [0.0, 0.0, 0.0]);
+    assert_eq!(gaussians[1].position_visibility.position, [0.0, 2.0, 0.0]);
+
+    let first = gaussians[0];
+    assert!(close(first.scale_opacity.scale[2], 0.3));
+    assert!(close(first.scale_opacity.opacity, 1.0));
+    assert!(close(0.5 + 0.2820948 * first.spherical_harmonic.get(0), 1.0));
+    assert!(close(0.5 + 0.2820948 * first.spherical_harmonic.get(2), 0.0));
+
+    let second = gaussians[1];
+    assert!(close(second.rotation.rotation[0].abs(), FRAC_1_SQRT_2));
+    assert!(close(second.rotation.rotation[3].abs(), FRAC_1_SQRT_2));
+    assert!(close(second.scale_opacity.opacity, 64.0 / 255.0));
+
+    // .gltf files with data uri buffers parse the same
+    let data_uri = format!(
+        r#""buffers": [{{ "byteLength": 88, "uri": "data:application/octet-stream;base64,{}" }}]"#,
+        base64::engine::general_purpose::STANDARD.encode(&bin),
+    );
+    let embedded = splats.replace(r#""buffers": [{ "byteLength": 88 }]"#, &data_uri);
+    let embedded = parse_gltf(embedded.as_bytes()).unwrap();
+    assert_eq!(embedded.len(), 1);
+    assert_eq!(embedded[0].gaussians, primitives[0].gaussians);
+
+    // a scene without the extension has no gaussian primitives, bevy_gltf loads it as is
+    let plain = splats.replace(r#""extensions": { "KHR_gaussian_splatting": {} }"#, r#""extras": {}"#);
+    assert!(parse_gltf(&glb(&plain, &bin)).unwrap().is_empty());
+
+    // untrusted counts are rejected before allocating, whether they overflow, mismatch or overrun the view
+    let overflowing = splats.replacen(r#""count": 2"#, r#""count": 4611686018427387904"#, 1);
+    assert!(parse_gltf(&glb(&overflowing, &bin)).is_err());
+
+    let mismatched = splats.replace(r#""count": 2, "type": "VEC4" },"#, r#""count": 3, "type": "VEC4" },"#);
+    assert!(parse_gltf(&glb(&mismatched, &bin)).is_err());
+
+    let overrun = splats.replace(r#""byteOffset": 24, "byteLength": 32"#, r#""byteOffset": 24, "byteLength": 16"#);
+    assert!(parse_gltf(&glb(&overrun, &bin)).is_err());
+}
+
+#[cfg(feature = "io_gltf")]
+#[test]
+fn test_glb_loader_selection() {
+    use bevy::{
+        prelude::*,
+        gltf::GltfPlugin,
+    };
+    use bevy_gaussian_splatting::io::{
+        gltf::GaussianGltfPlugin,
+        loader::GaussianCloudLoader,
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin::default(),
+        GltfPlugin::default(),
+        GaussianGltfPlugin,
+    ));
+    app.init_asset::<GaussianCloud>();
+    app.register_asset_loader(GaussianCloudLoader {
+        progress: default(),
+        transforms: default(),
+    });
+    app.finish();
+
+    let asset_server = app.world().resource::<AssetServer>().clone();
+    let loader = |path: &'static str| {
+        pollster::block_on(asset_server.get_path_asset_loader(path))
+            .unwrap()
+            .type_name()
+    };
+
+    // gltf files, labeled or not, reach the wrapping loader, which hands plain files to bevy_gltf
+    assert!(loader("scene.glb#Scene0").ends_with("GaussianGltfLoader"));
+    assert!(loader("scene.glb").ends_with("GaussianGltfLoader"));
+    assert!(loader("scene.gltf").ends_with("GaussianGltfLoader"));
+    assert!(loader("scene.ply").ends_with("GaussianCloudLoader"));
+}
+
+#[cfg(all(feature = "io_gltf", not(feature = "precompute_covariance_3d")))]
+#[test]
+fn test_gltf_scene() {
+    use bevy::{
+        prelude::*,
+        gltf::{
+            Gltf,
+            GltfPlugin,
+        },
+        scene::ScenePlugin,
+    };
+    use bevy_gaussian_splatting::io::gltf::{
+        GaussianGltfPlugin,
+        GltfGaussianCloud,
+    };
+
+    let directory = std::env::temp_dir().join(format!("gaussian_gltf_scene_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    // the splats read their buffer from a file next to the gltf
+    let (json, bin) = gltf_fixture();
+    let json = json.replace(r#""buffers": [{ "byteLength": 88 }]"#, r#""buffers": [{ "byteLength": 88, "uri": "scene.bin" }]"#);
+    // custom attributes are `_` prefixed once the extension is gone
+    let plain = json
+        .replace(r#""extensions": { "KHR_gaussian_splatting": {} }"#, r#""extras": {}"#)
+        .replace("KHR_gaussian_splatting:", "_");
+    std::fs::write(directory.join("scene.gltf"), json).unwrap();
+    std::fs::write(directory.join("plain.gltf"), plain).unwrap();
+    std::fs::write(directory.join("scene.bin"), &bin).unwrap();
+
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin {
+            file_path: directory.to_string_lossy().into_owned(),
+            ..default()
+        },
+        ScenePlugin,
+        GltfPlugin::default(),
+        GaussianGltfPlugin,
+    ));
+    app.init_asset::<GaussianCloud>();
+    app.init_asset::<Mesh>();
+    app.init_asset::<Image>();
+    app.init_asset::<StandardMaterial>();
+    app.finish();
+
+    let asset_server = app.world().resource::<AssetServer>().clone();
+    let gltf = asset_server.load::<Gltf>("scene.gltf");
+    let plain = asset_server.load::<Gltf>("plain.gltf");
+
+    for _ in 0..1000 {
+        app.update();
+
+        if asset_server.is_loaded_with_dependencies(&gltf) && asset_server.is_loaded_with_dependencies(&plain) {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert!(asset_server.is_loaded_with_dependencies(&gltf), "{:?}", asset_server.get_load_state(&gltf));
+    assert!(asset_server.is_loaded_with_dependencies(&plain), "{:?}", asset_server.get_load_state(&plain));
+
+    // without gaussian primitives bevy_gltf loads the file as is, scene included
+    let plain = app.world().resource::<Assets<Gltf>>().get(&plain).unwrap();
+    assert!(plain.default_scene.is_some());
+    assert_eq!(plain.meshes.len(), 2);
+
+    let cloud = asset_server.get_handle::<GaussianCloud>("scene.gltf#Mesh0/Primitive0/GaussianCloud").unwrap();
+    assert_eq!(app.world().resource::<Assets<GaussianCloud>>().get(&cloud).unwrap().len(), 2);
+
+    // the plain primitive sharing the mesh with the splats is loaded by bevy_gltf
+    assert!(asset_server.get_handle::<Mesh>("scene.gltf#Mesh0/Primitive0").is_some());
+
+    let gltf = app.world().resource::<Assets<Gltf>>().get(&gltf).unwrap();
+    let scene = gltf.default_scene.clone().unwrap();
+
+    let mut scenes = app.world_mut().resource_mut::<Assets<Scene>>();
+    let world = &mut scenes.get_mut(&scene).unwrap().world;
+
+    let mut clouds = world.query_filtered::<(&Parent, &Handle<GaussianCloud>), With<GltfGaussianCloud>>();
+    let (parent, handle) = clouds.single(world);
+    assert_eq!(handle.id(), cloud.id());
+
+    let node = world.entity(parent.get());
+    assert_eq!(node.get::<Name>().unwrap().as_str(), "splats");
+    assert_eq!(node.get::<Transform>().unwrap().translation, Vec3::new(1.0, 0.0, 0.0));
+
+    // both nodes carry one plain mesh
+    let mut meshes = world.query::<(&Parent, &Handle<Mesh>)>();
+    assert_eq!(meshes.iter(world).count(), 2);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,155 @@
+use bevy::{
+    asset::AssetId,
+    ecs::entity::Entity,
+    math::Vec3,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloudSettings,
+    GaussianTransparencyMode,
+    render::on_top_distance,
+    sort::{
+        SortEntry,
+        SortMode,
+        cache::GaussianCloudSortCache,
+        compare_back_to_front,
+    },
+};
+
+
+fn entries(indices: &[u32]) -> Vec<SortEntry> {
+    indices.iter()
+        .map(|&index| SortEntry {
+            key: 0,
+            index,
+        })
+        .collect()
+}
+
+#[test]
+fn test_sort_cache() {
+    let mut cache = GaussianCloudSortCache::default();
+    cache.capacity = 2;
+    cache.granularity = 1.0;
+    let cloud = AssetId::default();
+    let entity = Entity::from_raw(0);
+
+    let a = cache.key(entity, cloud, Vec3::new(0.1, 0.0, 0.0), None);
+    let b = cache.key(entity, cloud, Vec3::new(5.0, 0.0, 0.0), None);
+    let c = cache.key(entity, cloud, Vec3::new(9.0, 0.0, 0.0), None);
+
+    cache.insert(a.clone(), &entries(&[0, 1]));
+    cache.insert(b.clone(), &entries(&[1, 0]));
+
+    // poses within the same cell share an order
+    let nearby = cache.key(entity, cloud, Vec3::new(-0.2, 0.3, 0.0), None);
+    assert_eq!(cache.get(&nearby, 2), Some(entries(&[0, 1]).as_slice()));
+
+    // another entity drawing the same asset from the same cloud space pose sorts under its own transform
+    let other = cache.key(Entity::from_raw(1), cloud, Vec3::new(0.1, 0.0, 0.0), None);
+    assert!(cache.get(&other, 2).is_none());
+
+    // a was used more recently, so b is evicted
+    cache.insert(c.clone(), &entries(&[0, 1]));
+    assert!(cache.get(&b, 2).is_none());
+    assert!(cache.get(&a, 2).is_some());
+
+    // a resized cloud never reuses an old order
+    assert!(cache.get(&c, 3).is_none());
+
+    cache.invalidate_entity(entity);
+    assert!(cache.get(&a, 2).is_none());
+
+    cache.insert(a.clone(), &entries(&[0, 1]));
+    cache.invalidate(cloud);
+    assert!(cache.get(&a, 2).is_none());
+}
+
+#[test]
+fn test_sort_tie_break() {
+    let distance = |index: u32| -> f32 {
+        if index.is_multiple_of(2) { 4.0 } else { 1.0 }
+    };
+    let entries = |order: &[u32]| order.iter()
+        .map(|&index| SortEntry {
+            key: bytemuck::cast(distance(index)),
+            index,
+        })
+        .collect::<Vec<_>>();
+
+    let mut a = entries(&[3, 0, 5, 2, 1, 4]);
+    let mut b = entries(&[4, 1, 2, 5, 0, 3]);
+    a.sort_unstable_by(compare_back_to_front);
+    b.sort_unstable_by(compare_back_to_front);
+
+    // far before near, equal distances in index order regardless of the previous order
+    assert_eq!(a, b);
+    assert_eq!(a.iter().map(|entry| entry.index).collect::<Vec<_>>(), vec![0, 2, 4, 1, 3, 5]);
+}
+
+#[test]
+fn test_always_on_top_sorts() {
+    let mut settings = GaussianCloudSettings {
+        transparency_mode: GaussianTransparencyMode::Stochastic,
+        ..Default::default()
+    };
+    assert_eq!(settings.effective_sort_mode(), SortMode::None);
+
+    // without a depth test the splats can only be resolved by drawing them in order
+    settings.always_on_top = true;
+    assert_eq!(settings.effective_transparency_mode(), GaussianTransparencyMode::Sorted);
+    assert_eq!(settings.effective_sort_mode(), settings.sort_mode);
+}
+
+#[test]
+fn test_on_top_distance() {
+    // on top clouds draw after every regular item, whatever its depth and bias
+    assert!(on_top_distance(f32::MIN) > 1e20);
+
+    // and keep their depth and sort_bias order among each other
+    let keys = [-1000.0, -10.0, -10.0 + 0.01, -1.0, 0.0, 2.5, 1e6].map(on_top_distance);
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", keys);
+
+    // the float just below a key, where its outline draws, is above the next lower key
+    assert!(f32::from_bits(on_top_distance(-1.0).to_bits() - 1) > on_top_distance(-1.0 - 0.001));
+}
+
+#[test]
+fn test_draws_indirect() {
+    let mut settings = GaussianCloudSettings::default();
+    assert_eq!(settings.draws_indirect(), cfg!(not(feature = "webgl2")));
+
+    // a direct draw never reads the radix sort's count, so the sort must not write it
+    settings.use_indirect = false;
+    assert!(!settings.draws_indirect());
+
+    // tessellated splats draw more vertices than the shared args hold
+    settings.use_indirect = true;
+    settings.splat_tessellation = 8;
+    assert!(!settings.draws_indirect());
+}
+
+#[cfg(feature = "sort_temporal")]
+#[test]
+fn test_temporal_sort_converges() {
+    use bevy_gaussian_splatting::sort::temporal::refine_temporal_order;
+
+    // a reversed order is the worst case, every entry has to move back
+    let window_size = 4;
+    let depth = |index: u32| bytemuck::cast::<f32, u32>(index as f32);
+    let mut order = entries(&(0..64).collect::<Vec<_>>());
+
+    let mut written = Vec::new();
+    for frame in 0..window_size * window_size {
+        written.extend(refine_temporal_order(&mut order, window_size, frame, depth));
+    }
+
+    let indices = order.iter().map(|entry| entry.index).collect::<Vec<_>>();
+    assert_eq!(indices, (0..64).rev().collect::<Vec<_>>());
+    assert!(written.iter().all(|range| range.len() <= 2 * 64 / window_size as usize));
+
+    // a settled order has nothing to write
+    for frame in 0..window_size {
+        assert_eq!(refine_temporal_order(&mut order, window_size, frame, depth), None);
+    }
+}